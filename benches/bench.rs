@@ -52,7 +52,9 @@ fn benchmark<Crypto: HpkeCrypto + 'static>(c: &mut Criterion) {
                     let mut hpke = Hpke::<Crypto>::new(hpke_mode, kem_mode, kdf_mode, aead_mode);
                     let label = format!("{} {}", Crypto::name(), hpke);
                     let kp = hpke.generate_key_pair().unwrap();
-                    let enc = kp.public_key().as_slice();
+                    let enc =
+                        &EncapsulatedSecret::new(kem_mode, kp.public_key().as_slice().to_vec())
+                            .unwrap();
                     let kp_r = hpke.generate_key_pair().unwrap();
                     let sk_rm = kp_r.private_key();
                     let pk_rm = kp_r.public_key();
@@ -68,7 +68,9 @@ fn benchmark<Crypto: HpkeCrypto + 'static>() {
                     println!("{}", label);
 
                     let kp = hpke.generate_key_pair().unwrap();
-                    let enc = kp.public_key().as_slice();
+                    let enc =
+                        &EncapsulatedSecret::new(kem_mode, kp.public_key().as_slice().to_vec())
+                            .unwrap();
                     let kp_r = hpke.generate_key_pair().unwrap();
                     let sk_rm = kp_r.private_key();
                     let pk_rm = kp_r.public_key();
@@ -193,8 +195,8 @@ fn benchmark<Crypto: HpkeCrypto + 'static>() {
                     let mut ptxt = vec![0u8; AEAD_PAYLOAD];
                     rand::rng().fill_bytes(&mut ptxt);
 
-                    let mut enc = Vec::<u8>::new();
-                    let mut ctxt = Vec::<u8>::new();
+                    let mut enc = enc.clone();
+                    let mut ctxt = ctxts[0].clone();
                     let start = Instant::now();
                     for _ in 0..ITERATIONS {
                         let (new_enc, new_ctxt) = hpke
@@ -0,0 +1,307 @@
+#![doc = include_str!("../Readme.md")]
+
+use std::fmt::Display;
+
+use evercrypt::prelude::*;
+use hpke_rs_crypto::{
+    error::Error,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    CryptoRng, HpkeCrypto, HpkeTestRng, RngCore,
+};
+use rand_core::SeedableRng;
+
+/// The Evercrypt HPKE Provider
+#[derive(Debug)]
+pub struct HpkeEvercrypt {}
+
+/// The PRNG for the Evercrypt Provider.
+pub struct HpkeEvercryptPrng {
+    #[cfg(feature = "deterministic-prng")]
+    fake_rng: Vec<u8>,
+    rng: rand_chacha::ChaCha20Rng,
+}
+
+impl HpkeEvercryptPrng {
+    /// Create a PRNG seeded from the external `rng`.
+    ///
+    /// Use the adapters in `hpke_rs_crypto::rng` for RNGs implementing the
+    /// `rand_core` 0.6 traits.
+    pub fn from_rng(rng: &mut impl CryptoRng) -> Self {
+        #[cfg(feature = "deterministic-prng")]
+        {
+            let mut fake_rng = vec![0u8; 256];
+            rng.fill_bytes(&mut fake_rng);
+            HpkeEvercryptPrng {
+                fake_rng,
+                rng: rand_chacha::ChaCha20Rng::from_rng(rng),
+            }
+        }
+        #[cfg(not(feature = "deterministic-prng"))]
+        HpkeEvercryptPrng {
+            rng: rand_chacha::ChaCha20Rng::from_rng(rng),
+        }
+    }
+}
+
+impl HpkeCrypto for HpkeEvercrypt {
+    fn name() -> String {
+        "Evercrypt".into()
+    }
+
+    fn kdf_extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(hkdf_extract(hmac_mode(alg)?, salt, ikm))
+    }
+
+    fn kdf_expand(
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let mode = hmac_mode(alg)?;
+        if output_size > 255 * alg.digest_len() {
+            return Err(Error::HpkeInvalidOutputLength);
+        }
+        Ok(hkdf_expand(mode, prk, info, output_size))
+    }
+
+    fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        let mode = ecdh_mode(alg)?;
+        match mode {
+            EcdhMode::P256 => {
+                // Evercrypt expects the raw coordinates without the SEC1 tag
+                // and returns the full point, of which we only use the
+                // x-coordinate.
+                let pk = match pk.split_first() {
+                    Some((0x04, point)) => point,
+                    _ => return Err(Error::KemInvalidPublicKey),
+                };
+                ecdh_derive(mode, pk, sk).map_err(ecdh_error).map(|mut p| {
+                    p.truncate(alg.shared_secret_len());
+                    p
+                })
+            }
+            _ => ecdh_derive(mode, pk, sk).map_err(ecdh_error),
+        }
+    }
+
+    fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        let mode = ecdh_mode(alg)?;
+        ecdh_derive_base(mode, sk)
+            .map_err(ecdh_error)
+            .map(|p| match mode {
+                EcdhMode::P256 => nist_format_uncompressed(p),
+                _ => p,
+            })
+    }
+
+    fn kem_key_gen(
+        alg: KemAlgorithm,
+        prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let mode = ecdh_mode(alg)?;
+        let mut sk = vec![0u8; alg.private_key_len()];
+        loop {
+            prng.try_fill_test_bytes(&mut sk)?;
+            // X25519 clamps any string, P-256 rejects scalars that are zero
+            // or not less than the order.
+            if matches!(mode, EcdhMode::X25519) || p256_validate_sk(&sk).is_ok() {
+                break;
+            }
+        }
+        let pk = Self::secret_to_public(alg, &sk)?;
+        Ok((pk, sk))
+    }
+
+    fn kem_key_gen_derand(_alg: KemAlgorithm, _seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_encaps(
+        _alg: KemAlgorithm,
+        _pk_r: &[u8],
+        _prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_decaps(_alg: KemAlgorithm, _ct: &[u8], _sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn dh_validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::DhKemP256 => p256_validate_sk(sk)
+                .map_err(|_| Error::KemInvalidSecretKey)
+                .map(|sk| sk.to_vec()),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKem25519 => {
+                if pk.len() != 32 || pk.iter().all(|&b| b == 0) {
+                    return Err(Error::KemInvalidPublicKey);
+                }
+                Ok(())
+            }
+            KemAlgorithm::DhKemP256 => match pk.split_first() {
+                // Evercrypt expects the raw coordinates without the SEC1 tag.
+                Some((0x04, point)) => p256_validate_pk(point)
+                    .map(|_| ())
+                    .map_err(|_| Error::KemInvalidPublicKey),
+                _ => Err(Error::KemInvalidPublicKey),
+            },
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn aead_seal(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mode = aead_mode(alg)?;
+        if nonce.len() != alg.nonce_length() {
+            return Err(Error::AeadInvalidNonce);
+        }
+        let (mut ct, tag) = aead_encrypt(mode, key, msg, nonce, aad).map_err(aead_error)?;
+        ct.extend_from_slice(&tag);
+        Ok(ct)
+    }
+
+    fn aead_open(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mode = aead_mode(alg)?;
+        if nonce.len() != alg.nonce_length() {
+            return Err(Error::AeadInvalidNonce);
+        }
+        let boundary = msg
+            .len()
+            .checked_sub(alg.tag_length())
+            .ok_or(Error::AeadInvalidCiphertext)?;
+        let (ct, tag) = msg.split_at(boundary);
+        aead_decrypt(mode, key, ct, tag, nonce, aad).map_err(|_| Error::AeadOpenError)
+    }
+
+    type HpkePrng = HpkeEvercryptPrng;
+
+    fn prng() -> Self::HpkePrng {
+        #[cfg(feature = "deterministic-prng")]
+        {
+            use rand_core::TryRngCore;
+            let mut fake_rng = vec![0u8; 256];
+            rand_chacha::ChaCha20Rng::from_os_rng()
+                .try_fill_bytes(&mut fake_rng)
+                .unwrap();
+            HpkeEvercryptPrng {
+                fake_rng,
+                rng: rand_chacha::ChaCha20Rng::from_os_rng(),
+            }
+        }
+        #[cfg(not(feature = "deterministic-prng"))]
+        HpkeEvercryptPrng {
+            rng: rand_chacha::ChaCha20Rng::from_os_rng(),
+        }
+    }
+
+    hpke_rs_provider_macros::supports! {
+        kdf: [HkdfSha256, HkdfSha384, HkdfSha512],
+        kem: [DhKem25519, DhKemP256],
+        aead: [Aes128Gcm, Aes256Gcm, ChaCha20Poly1305, HpkeExport],
+    }
+}
+
+/// Prepend 0x04 for uncompressed NIST curve points.
+#[inline(always)]
+fn nist_format_uncompressed(mut pk: Vec<u8>) -> Vec<u8> {
+    let mut tmp = Vec::with_capacity(pk.len() + 1);
+    tmp.push(0x04);
+    tmp.append(&mut pk);
+    tmp
+}
+
+hpke_rs_provider_macros::algorithm_map! {
+    fn hmac_mode(KdfAlgorithm) -> HmacMode, else UnknownKdfAlgorithm {
+        HkdfSha256 => HmacMode::Sha256,
+        HkdfSha384 => HmacMode::Sha384,
+        HkdfSha512 => HmacMode::Sha512,
+    }
+
+    fn ecdh_mode(KemAlgorithm) -> EcdhMode, else UnknownKemAlgorithm {
+        DhKem25519 => EcdhMode::X25519,
+        DhKemP256 => EcdhMode::P256,
+    }
+
+    fn aead_mode(AeadAlgorithm) -> AeadMode, else UnknownAeadAlgorithm {
+        Aes128Gcm => AeadMode::Aes128Gcm,
+        Aes256Gcm => AeadMode::Aes256Gcm,
+        ChaCha20Poly1305 => AeadMode::Chacha20Poly1305,
+    }
+}
+
+hpke_rs_provider_macros::error_map! {
+    fn ecdh_error(EcdhError) => CryptoLibraryError("ECDH derive error");
+    fn aead_error(AeadError) => CryptoLibraryError("AEAD encrypt error");
+}
+
+impl RngCore for HpkeEvercryptPrng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for HpkeEvercryptPrng {}
+
+impl HpkeTestRng for HpkeEvercryptPrng {
+    type Error = Error;
+
+    #[cfg(feature = "deterministic-prng")]
+    fn try_fill_test_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        // Here we fake our randomness for testing.
+        if dest.len() > self.fake_rng.len() {
+            return Err(Error::InsufficientRandomness);
+        }
+        dest.clone_from_slice(&self.fake_rng.split_off(self.fake_rng.len() - dest.len()));
+        Ok(())
+    }
+    #[cfg(not(feature = "deterministic-prng"))]
+    fn try_fill_test_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        use rand_core::TryRngCore;
+        self.rng
+            .try_fill_bytes(dest)
+            .map_err(|_| Error::InsufficientRandomness)
+    }
+
+    #[cfg(feature = "deterministic-prng")]
+    fn seed(&mut self, seed: &[u8]) {
+        self.fake_rng = seed.to_vec();
+    }
+    #[cfg(not(feature = "deterministic-prng"))]
+    fn seed(&mut self, _: &[u8]) {}
+}
+
+impl Display for HpkeEvercrypt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Self::name())
+    }
+}
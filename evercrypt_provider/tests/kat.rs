@@ -0,0 +1,13 @@
+//! RFC 9180 known-answer tests.
+//!
+//! Test vectors for ciphersuites that Evercrypt doesn't support are skipped.
+
+use hpke_rs::test_util::kat::{kat, test_vectors};
+use hpke_rs_evercrypt::HpkeEvercrypt;
+
+#[test]
+fn kat_evercrypt() {
+    for test in test_vectors(include_str!("../../tests/test_vectors.json")) {
+        kat::<HpkeEvercrypt>(&test);
+    }
+}
@@ -4,19 +4,37 @@ use libfuzzer_sys::fuzz_target;
 use hpke_rs::prelude::*;
 use hpke_rs_crypto::types::*;
 
+const KEM: KemAlgorithm = KemAlgorithm::DhKemP256;
+const AEAD: AeadAlgorithm = AeadAlgorithm::Aes128Gcm;
+
 fuzz_target!(|data: &[u8]| {
     let mut hpke = Hpke::<hpke_rs_rust_crypto::HpkeRustCrypto>::new(
         HpkeMode::Base,
-        KemAlgorithm::DhKemP256,
+        KEM,
         KdfAlgorithm::HkdfSha256,
-        AeadAlgorithm::Aes128Gcm,
+        AEAD,
     );
 
     let pk_r = HpkePublicKey::new(data.to_vec());
-    let sk_r = HpkePrivateKey::new(data.to_vec());
     let info = b"HPKE self test info";
     let aad = b"HPKE self test aad";
     let plain_txt = b"HPKE self test plain text";
     let _ = hpke.seal(&pk_r, info, aad, plain_txt, None, None, None);
-    let _ = hpke.open(data, &sk_r, info, aad, data, None, None, None);
+
+    // Split the input into the encapsulated secret and the ciphertext, and
+    // open them with a valid private key.
+    if data.len() < KEM.encapsulated_key_len() {
+        return;
+    }
+    let (enc, ct) = data.split_at(KEM.encapsulated_key_len());
+    let (sk_r, _pk_r) = hpke
+        .derive_key_pair(b"HPKE fuzz receiver key")
+        .unwrap()
+        .into_keys();
+    if let (Ok(enc), Ok(ct)) = (
+        EncapsulatedSecret::new(KEM, enc.to_vec()),
+        Ciphertext::new(AEAD, ct.to_vec()),
+    ) {
+        let _ = hpke.open(&enc, &sk_r, info, aad, &ct, None, None, None);
+    }
 });
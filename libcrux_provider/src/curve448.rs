@@ -0,0 +1,42 @@
+//! X448 with the RustCrypto `x448` crate.
+//!
+//! libcrux doesn't implement X448 yet.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{error::Error, RngCore};
+use x448::{PublicKey, Secret};
+
+/// The length of keys and shared secrets.
+const LEN: usize = 56;
+
+pub(crate) fn dh(pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+    let sk = Secret::from_bytes(sk).ok_or(Error::KemInvalidSecretKey)?;
+    let pk = PublicKey::from_bytes(pk).ok_or(Error::KemInvalidPublicKey)?;
+    // Rejects the all-zero output of low order points.
+    sk.as_diffie_hellman(&pk)
+        .map(|dh| dh.as_bytes().to_vec())
+        .ok_or(Error::KemInvalidPublicKey)
+}
+
+pub(crate) fn secret_to_public(sk: &[u8]) -> Result<Vec<u8>, Error> {
+    let sk = Secret::from_bytes(sk).ok_or(Error::KemInvalidSecretKey)?;
+    Ok(PublicKey::from(&sk).as_bytes().to_vec())
+}
+
+/// Generate a key pair (public key, private key).
+pub(crate) fn key_gen(rng: &mut impl RngCore) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let mut sk = [0u8; LEN];
+    rng.fill_bytes(&mut sk);
+    let sk = Secret::from_bytes(&sk).ok_or(Error::KemInvalidSecretKey)?;
+    Ok((
+        PublicKey::from(&sk).as_bytes().to_vec(),
+        sk.as_bytes().to_vec(),
+    ))
+}
+
+pub(crate) fn validate_pk(pk: &[u8]) -> Result<(), Error> {
+    PublicKey::from_bytes(pk)
+        .map(|_| ())
+        .ok_or(Error::KemInvalidPublicKey)
+}
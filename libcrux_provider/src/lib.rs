@@ -2,17 +2,22 @@
 #![cfg_attr(not(test), no_std)]
 extern crate alloc;
 
-use alloc::{string::String, vec::Vec, format};
+use alloc::{format, string::String, vec::Vec};
 use core::fmt::Display;
 
 use hpke_rs_crypto::{
     error::Error,
     types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
-    CryptoRng, HpkeCrypto, HpkeTestRng,
+    CryptoRng, HpkeCrypto, HpkeTestRng, RngCore,
 };
 
+use aes_gcm::aead::{Aead, KeyInit, Nonce, Payload};
 use rand_core::SeedableRng;
 
+mod curve448;
+mod mlkem;
+mod nist_p384;
+
 /// The Libcrux HPKE Provider
 #[derive(Debug)]
 pub struct HpkeLibcrux {}
@@ -24,13 +29,35 @@ pub struct HpkeLibcruxPrng {
     rng: rand_chacha::ChaCha20Rng,
 }
 
+impl HpkeLibcruxPrng {
+    /// Create a PRNG seeded from the external `rng`.
+    ///
+    /// Use the adapters in `hpke_rs_crypto::rng` for RNGs implementing the
+    /// `rand_core` 0.6 traits.
+    pub fn from_rng(rng: &mut impl CryptoRng) -> Self {
+        #[cfg(feature = "deterministic-prng")]
+        {
+            let mut fake_rng = alloc::vec![0u8; 256];
+            rng.fill_bytes(&mut fake_rng);
+            HpkeLibcruxPrng {
+                fake_rng,
+                rng: rand_chacha::ChaCha20Rng::from_rng(rng),
+            }
+        }
+        #[cfg(not(feature = "deterministic-prng"))]
+        HpkeLibcruxPrng {
+            rng: rand_chacha::ChaCha20Rng::from_rng(rng),
+        }
+    }
+}
+
 impl HpkeCrypto for HpkeLibcrux {
     fn name() -> String {
         "Libcrux".into()
     }
 
     fn kdf_extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
-        let alg = kdf_algorithm_to_libcrux_hkdf_algorithm(alg);
+        let alg = kdf_algorithm_to_libcrux_hkdf_algorithm(alg)?;
         libcrux_hkdf::extract(alg, salt, ikm)
             .map_err(|e| Error::CryptoLibraryError(format!("KDF extract error: {:?}", e)))
     }
@@ -41,12 +68,17 @@ impl HpkeCrypto for HpkeLibcrux {
         info: &[u8],
         output_size: usize,
     ) -> Result<Vec<u8>, Error> {
-        let alg = kdf_algorithm_to_libcrux_hkdf_algorithm(alg);
+        let alg = kdf_algorithm_to_libcrux_hkdf_algorithm(alg)?;
         libcrux_hkdf::expand(alg, prk, info, output_size)
             .map_err(|e| Error::CryptoLibraryError(format!("KDF expand error: {:?}", e)))
     }
 
     fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::DhKemP384 => return nist_p384::dh(pk, sk),
+            KemAlgorithm::DhKem448 => return curve448::dh(pk, sk),
+            _ => (),
+        }
         let alg = kem_key_type_to_ecdh_alg(alg)?;
 
         libcrux_ecdh::derive(alg, pk, sk)
@@ -62,6 +94,11 @@ impl HpkeCrypto for HpkeLibcrux {
     }
 
     fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::DhKemP384 => return nist_p384::secret_to_public(sk),
+            KemAlgorithm::DhKem448 => return curve448::secret_to_public(sk),
+            _ => (),
+        }
         let alg = kem_key_type_to_ecdh_alg(alg)?;
 
         kem_ecdh_secret_to_public(alg, sk)
@@ -77,6 +114,13 @@ impl HpkeCrypto for HpkeLibcrux {
                     .map(|(sk, pk)| (pk.encode(), sk.encode()))
                     .map_err(|e| Error::CryptoLibraryError(format!("KEM key gen error: {:?}", e)))
             }
+            KemAlgorithm::MlKem768 | KemAlgorithm::MlKem1024 => {
+                let mut seed = [0u8; mlkem::SEED_LEN];
+                prng.fill_bytes(&mut seed);
+                Self::kem_key_gen_derand(alg, &seed)
+            }
+            KemAlgorithm::DhKemP384 => nist_p384::key_gen(prng),
+            KemAlgorithm::DhKem448 => curve448::key_gen(prng),
             other_alg => {
                 // ECDH only
                 let ecdh_alg = kem_key_type_to_ecdh_alg(other_alg)?;
@@ -92,6 +136,12 @@ impl HpkeCrypto for HpkeLibcrux {
     }
 
     fn kem_key_gen_derand(alg: KemAlgorithm, seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        match alg {
+            KemAlgorithm::X25519Kyber768Draft00 => return kyber768_key_gen_derand(seed),
+            KemAlgorithm::MlKem768 => return mlkem::ml_kem768::key_gen_derand(seed),
+            KemAlgorithm::MlKem1024 => return mlkem::ml_kem1024::key_gen_derand(seed),
+            _ => (),
+        }
         let alg = kem_key_type_to_libcrux_alg(alg)?;
 
         libcrux_kem::key_gen_derand(alg, seed)
@@ -104,6 +154,24 @@ impl HpkeCrypto for HpkeLibcrux {
         pk_r: &[u8],
         prng: &mut Self::HpkePrng,
     ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        match alg {
+            KemAlgorithm::X25519Kyber768Draft00 => {
+                let mut randomness = [0u8; libcrux_ml_kem::SHARED_SECRET_SIZE];
+                prng.fill_bytes(&mut randomness);
+                return kyber768_encaps(pk_r, randomness);
+            }
+            KemAlgorithm::MlKem768 => {
+                let mut randomness = [0u8; mlkem::ENCAPS_RANDOMNESS_LEN];
+                prng.fill_bytes(&mut randomness);
+                return mlkem::ml_kem768::encaps(pk_r, randomness);
+            }
+            KemAlgorithm::MlKem1024 => {
+                let mut randomness = [0u8; mlkem::ENCAPS_RANDOMNESS_LEN];
+                prng.fill_bytes(&mut randomness);
+                return mlkem::ml_kem1024::encaps(pk_r, randomness);
+            }
+            _ => (),
+        }
         let alg = kem_key_type_to_libcrux_alg(alg)?;
 
         let pk =
@@ -113,10 +181,34 @@ impl HpkeCrypto for HpkeLibcrux {
             .map(|(ss, ct)| (ss.encode(), ct.encode()))
     }
 
+    fn kem_encaps_derand(
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let ml_kem_randomness = || -> Result<[u8; mlkem::ENCAPS_RANDOMNESS_LEN], Error> {
+            randomness
+                .try_into()
+                .map_err(|_| Error::InsufficientRandomness)
+        };
+        match alg {
+            KemAlgorithm::X25519Kyber768Draft00 => kyber768_encaps(pk_r, ml_kem_randomness()?),
+            KemAlgorithm::MlKem768 => mlkem::ml_kem768::encaps(pk_r, ml_kem_randomness()?),
+            KemAlgorithm::MlKem1024 => mlkem::ml_kem1024::encaps(pk_r, ml_kem_randomness()?),
+            _ => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
     fn kem_decaps(alg: KemAlgorithm, ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::X25519Kyber768Draft00 => return kyber768_decaps(ct, sk_r),
+            KemAlgorithm::MlKem768 => return mlkem::ml_kem768::decaps(ct, sk_r),
+            KemAlgorithm::MlKem1024 => return mlkem::ml_kem1024::decaps(ct, sk_r),
+            _ => (),
+        }
         let alg = kem_key_type_to_libcrux_alg(alg)?;
 
-        let ct = libcrux_kem::Ct::decode(alg, ct).map_err(|_| Error::AeadInvalidCiphertext)?;
+        let ct = libcrux_kem::Ct::decode(alg, ct).map_err(|_| Error::KemInvalidCiphertext)?;
         let sk =
             libcrux_kem::PrivateKey::decode(alg, sk_r).map_err(|_| Error::KemInvalidSecretKey)?;
         ct.decapsulate(&sk)
@@ -129,6 +221,27 @@ impl HpkeCrypto for HpkeLibcrux {
             KemAlgorithm::DhKemP256 => libcrux_ecdh::p256::validate_scalar_slice(&sk)
                 .map_err(|e| Error::CryptoLibraryError(format!("ECDH invalid sk error: {:?}", e)))
                 .map(|sk| sk.0.to_vec()),
+            KemAlgorithm::DhKemP384 => nist_p384::validate_sk(sk),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKem25519 => {
+                if pk.len() != 32 || pk.iter().all(|&b| b == 0) {
+                    return Err(Error::KemInvalidPublicKey);
+                }
+                Ok(())
+            }
+            KemAlgorithm::DhKemP256 => match pk.split_first() {
+                // libcrux expects the raw coordinates without the SEC1 tag.
+                Some((0x04, point)) => libcrux_ecdh::p256::validate_point(point)
+                    .map_err(|_| Error::KemInvalidPublicKey),
+                _ => Err(Error::KemInvalidPublicKey),
+            },
+            KemAlgorithm::DhKemP384 => nist_p384::validate_pk(pk),
+            KemAlgorithm::DhKem448 => curve448::validate_pk(pk),
             _ => Err(Error::UnknownKemAlgorithm),
         }
     }
@@ -140,22 +253,12 @@ impl HpkeCrypto for HpkeLibcrux {
         aad: &[u8],
         msg: &[u8],
     ) -> Result<Vec<u8>, Error> {
-        // only chacha20poly1305 is supported
-        if !matches!(alg, AeadAlgorithm::ChaCha20Poly1305) {
-            return Err(Error::UnknownAeadAlgorithm);
+        match alg {
+            AeadAlgorithm::Aes128Gcm => aes_gcm_seal::<aes_gcm::Aes128Gcm>(key, nonce, aad, msg),
+            AeadAlgorithm::Aes256Gcm => aes_gcm_seal::<aes_gcm::Aes256Gcm>(key, nonce, aad, msg),
+            AeadAlgorithm::ChaCha20Poly1305 => chacha20poly1305_seal(key, nonce, aad, msg),
+            _ => Err(Error::UnknownAeadAlgorithm),
         }
-
-        let iv = <&[u8; 12]>::try_from(nonce).map_err(|_| Error::AeadInvalidNonce)?;
-
-        // TODO: instead, use key conversion from the libcrux-chacha20poly1305 crate, when available,
-        let key = <&[u8; 32]>::try_from(key)
-            .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
-
-        let mut msg_ctx: Vec<u8> = alloc::vec![0; msg.len() + 16];
-        libcrux_chacha20poly1305::encrypt(key, msg, &mut msg_ctx, aad, iv)
-            .map_err(|_| Error::CryptoLibraryError("Invalid configuration".into()))?;
-
-        Ok(msg_ctx)
     }
 
     fn aead_open(
@@ -165,33 +268,16 @@ impl HpkeCrypto for HpkeLibcrux {
         aad: &[u8],
         cipher_txt: &[u8],
     ) -> Result<Vec<u8>, Error> {
-        // only chacha20poly1305 is supported
-        if !matches!(alg, AeadAlgorithm::ChaCha20Poly1305) {
-            return Err(Error::UnknownAeadAlgorithm);
-        }
-        if cipher_txt.len() < 16 {
-            return Err(Error::AeadInvalidCiphertext);
+        match alg {
+            AeadAlgorithm::Aes128Gcm => {
+                aes_gcm_open::<aes_gcm::Aes128Gcm>(key, nonce, aad, cipher_txt)
+            }
+            AeadAlgorithm::Aes256Gcm => {
+                aes_gcm_open::<aes_gcm::Aes256Gcm>(key, nonce, aad, cipher_txt)
+            }
+            AeadAlgorithm::ChaCha20Poly1305 => chacha20poly1305_open(key, nonce, aad, cipher_txt),
+            _ => Err(Error::UnknownAeadAlgorithm),
         }
-
-        let boundary = cipher_txt.len() - 16;
-
-        let mut ptext = alloc::vec![0; boundary];
-
-        let iv = <&[u8; 12]>::try_from(nonce).map_err(|_| Error::AeadInvalidNonce)?;
-
-        // TODO: instead, use key conversion from the libcrux-chacha20poly1305 crate, when available,
-        let key = <&[u8; 32]>::try_from(key)
-            .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
-        libcrux_chacha20poly1305::decrypt(key, &mut ptext, cipher_txt, aad, iv).map_err(
-            |e| match e {
-                libcrux_chacha20poly1305::AeadError::InvalidCiphertext => {
-                    Error::CryptoLibraryError(format!("AEAD decryption error: {:?}", e))
-                }
-                _ => Error::CryptoLibraryError("Invalid configuration".into()),
-            },
-        )?;
-
-        Ok(ptext)
     }
 
     type HpkePrng = HpkeLibcruxPrng;
@@ -216,16 +302,21 @@ impl HpkeCrypto for HpkeLibcrux {
     }
 
     /// Returns an error if the KDF algorithm is not supported by this crypto provider.
-    fn supports_kdf(_: KdfAlgorithm) -> Result<(), Error> {
-        Ok(())
+    fn supports_kdf(alg: KdfAlgorithm) -> Result<(), Error> {
+        kdf_algorithm_to_libcrux_hkdf_algorithm(alg).map(|_| ())
     }
 
     /// Returns an error if the KEM algorithm is not supported by this crypto provider.
     fn supports_kem(alg: KemAlgorithm) -> Result<(), Error> {
         match alg {
-            KemAlgorithm::DhKem25519 | KemAlgorithm::DhKemP256 | KemAlgorithm::XWingDraft06 => {
-                Ok(())
-            }
+            KemAlgorithm::DhKem25519
+            | KemAlgorithm::DhKemP256
+            | KemAlgorithm::DhKemP384
+            | KemAlgorithm::DhKem448
+            | KemAlgorithm::XWingDraft06
+            | KemAlgorithm::X25519Kyber768Draft00
+            | KemAlgorithm::MlKem768
+            | KemAlgorithm::MlKem1024 => Ok(()),
             _ => Err(Error::UnknownKemAlgorithm),
         }
     }
@@ -233,10 +324,11 @@ impl HpkeCrypto for HpkeLibcrux {
     /// Returns an error if the AEAD algorithm is not supported by this crypto provider.
     fn supports_aead(alg: AeadAlgorithm) -> Result<(), Error> {
         match alg {
-            // Don't support Aes
-            AeadAlgorithm::Aes128Gcm | AeadAlgorithm::Aes256Gcm => Err(Error::UnknownAeadAlgorithm),
-            AeadAlgorithm::ChaCha20Poly1305 => Ok(()),
+            AeadAlgorithm::Aes128Gcm
+            | AeadAlgorithm::Aes256Gcm
+            | AeadAlgorithm::ChaCha20Poly1305 => Ok(()),
             AeadAlgorithm::HpkeExport => Ok(()),
+            _ => Err(Error::UnknownAeadAlgorithm),
         }
     }
 }
@@ -264,11 +356,14 @@ fn nist_format_uncompressed(mut pk: Vec<u8>) -> Vec<u8> {
 }
 
 #[inline(always)]
-fn kdf_algorithm_to_libcrux_hkdf_algorithm(alg: KdfAlgorithm) -> libcrux_hkdf::Algorithm {
+fn kdf_algorithm_to_libcrux_hkdf_algorithm(
+    alg: KdfAlgorithm,
+) -> Result<libcrux_hkdf::Algorithm, Error> {
     match alg {
-        KdfAlgorithm::HkdfSha256 => libcrux_hkdf::Algorithm::Sha256,
-        KdfAlgorithm::HkdfSha384 => libcrux_hkdf::Algorithm::Sha384,
-        KdfAlgorithm::HkdfSha512 => libcrux_hkdf::Algorithm::Sha512,
+        KdfAlgorithm::HkdfSha256 => Ok(libcrux_hkdf::Algorithm::Sha256),
+        KdfAlgorithm::HkdfSha384 => Ok(libcrux_hkdf::Algorithm::Sha384),
+        KdfAlgorithm::HkdfSha512 => Ok(libcrux_hkdf::Algorithm::Sha512),
+        _ => Err(Error::UnknownKdfAlgorithm),
     }
 }
 
@@ -282,6 +377,127 @@ fn kem_key_type_to_libcrux_alg(alg: KemAlgorithm) -> Result<libcrux_kem::Algorit
     }
 }
 
+fn chacha20poly1305_seal(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    msg: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let iv = <&[u8; 12]>::try_from(nonce).map_err(|_| Error::AeadInvalidNonce)?;
+
+    // TODO: instead, use key conversion from the libcrux-chacha20poly1305 crate, when available,
+    let key = <&[u8; 32]>::try_from(key)
+        .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
+
+    let mut msg_ctx: Vec<u8> = alloc::vec![0; msg.len() + 16];
+    libcrux_chacha20poly1305::encrypt(key, msg, &mut msg_ctx, aad, iv)
+        .map_err(|_| Error::CryptoLibraryError("Invalid configuration".into()))?;
+
+    Ok(msg_ctx)
+}
+
+fn chacha20poly1305_open(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    cipher_txt: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if cipher_txt.len() < 16 {
+        return Err(Error::AeadInvalidCiphertext);
+    }
+
+    let boundary = cipher_txt.len() - 16;
+
+    let mut ptext = alloc::vec![0; boundary];
+
+    let iv = <&[u8; 12]>::try_from(nonce).map_err(|_| Error::AeadInvalidNonce)?;
+
+    // TODO: instead, use key conversion from the libcrux-chacha20poly1305 crate, when available,
+    let key = <&[u8; 32]>::try_from(key)
+        .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
+    libcrux_chacha20poly1305::decrypt(key, &mut ptext, cipher_txt, aad, iv).map_err(
+        |e| match e {
+            libcrux_chacha20poly1305::AeadError::InvalidCiphertext => {
+                Error::CryptoLibraryError(format!("AEAD decryption error: {:?}", e))
+            }
+            _ => Error::CryptoLibraryError("Invalid configuration".into()),
+        },
+    )?;
+
+    Ok(ptext)
+}
+
+// libcrux has no portable AES-GCM yet, AES-GCM always uses the RustCrypto
+// implementation. See the RustCrypto fallbacks in the Readme.
+
+fn aes_gcm_seal<Cipher: Aead + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    msg: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if nonce.len() != 12 {
+        return Err(Error::AeadInvalidNonce);
+    }
+    let cipher = Cipher::new_from_slice(key)
+        .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
+    cipher
+        .encrypt(Nonce::<Cipher>::from_slice(nonce), Payload { msg, aad })
+        .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))
+}
+
+fn aes_gcm_open<Cipher: Aead + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    cipher_txt: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if nonce.len() != 12 {
+        return Err(Error::AeadInvalidNonce);
+    }
+    if cipher_txt.len() < 16 {
+        return Err(Error::AeadInvalidCiphertext);
+    }
+    let cipher = Cipher::new_from_slice(key)
+        .map_err(|_| Error::CryptoLibraryError("AEAD invalid key length".into()))?;
+    cipher
+        .decrypt(
+            Nonce::<Cipher>::from_slice(nonce),
+            Payload {
+                msg: cipher_txt,
+                aad,
+            },
+        )
+        .map_err(|_| Error::AeadOpenError)
+}
+
+// The Kyber768 component of X25519Kyber768Draft00.
+// hpke-rs computes the DHKEM(X25519) component.
+
+fn kyber768_key_gen_derand(seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let seed = seed.try_into().map_err(|_| Error::KemInvalidSecretKey)?;
+    let key_pair = libcrux_ml_kem::kyber768::generate_key_pair(seed);
+    Ok((key_pair.pk().to_vec(), key_pair.sk().to_vec()))
+}
+
+fn kyber768_encaps(
+    pk_r: &[u8],
+    randomness: [u8; libcrux_ml_kem::SHARED_SECRET_SIZE],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let pk = libcrux_ml_kem::mlkem768::MlKem768PublicKey::try_from(pk_r)
+        .map_err(|_| Error::KemInvalidPublicKey)?;
+    let (ct, ss) = libcrux_ml_kem::kyber768::encapsulate(&pk, randomness);
+    Ok((ss.to_vec(), ct.as_ref().to_vec()))
+}
+
+fn kyber768_decaps(ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+    let ct = libcrux_ml_kem::mlkem768::MlKem768Ciphertext::try_from(ct)
+        .map_err(|_| Error::KemInvalidCiphertext)?;
+    let sk = libcrux_ml_kem::mlkem768::MlKem768PrivateKey::try_from(sk_r)
+        .map_err(|_| Error::KemInvalidSecretKey)?;
+    Ok(libcrux_ml_kem::kyber768::decapsulate(&sk, &ct).to_vec())
+}
+
 #[inline(always)]
 fn kem_key_type_to_ecdh_alg(alg: KemAlgorithm) -> Result<libcrux_ecdh::Algorithm, Error> {
     match alg {
@@ -0,0 +1,79 @@
+//! ML-KEM (FIPS 203) with `libcrux-ml-kem`.
+//!
+//! Private keys are either the 64 byte seed `d || z` or the expanded
+//! decapsulation key.
+
+/// The length of the key generation seed.
+pub(crate) const SEED_LEN: usize = 64;
+
+/// The length of the randomness for the encapsulation.
+pub(crate) const ENCAPS_RANDOMNESS_LEN: usize = 32;
+
+macro_rules! implement_ml_kem {
+    ($name:ident, $module:ident, $public_key:ident, $private_key:ident, $ciphertext:ident) => {
+        pub(crate) mod $name {
+            use alloc::vec::Vec;
+
+            use hpke_rs_crypto::error::Error;
+            use libcrux_ml_kem::$module::{
+                decapsulate, encapsulate, generate_key_pair, validate_public_key, $ciphertext,
+                $private_key, $public_key,
+            };
+
+            use super::{ENCAPS_RANDOMNESS_LEN, SEED_LEN};
+
+            /// Derive the key pair (encapsulation key, decapsulation key) from
+            /// the `seed`.
+            pub(crate) fn key_gen_derand(seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+                let seed: [u8; SEED_LEN] =
+                    seed.try_into().map_err(|_| Error::KemInvalidSecretKey)?;
+                let key_pair = generate_key_pair(seed);
+                Ok((key_pair.pk().to_vec(), key_pair.sk().to_vec()))
+            }
+
+            /// Encapsulate to `pk_r` with the `randomness` (shared secret,
+            /// ciphertext).
+            pub(crate) fn encaps(
+                pk_r: &[u8],
+                randomness: [u8; ENCAPS_RANDOMNESS_LEN],
+            ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+                let pk = $public_key::try_from(pk_r).map_err(|_| Error::KemInvalidPublicKey)?;
+                // The coefficients must be reduced modulo q (FIPS 203, Section 7.2).
+                if !validate_public_key(&pk) {
+                    return Err(Error::KemInvalidPublicKey);
+                }
+                let (ct, ss) = encapsulate(&pk, randomness);
+                Ok((ss.to_vec(), ct.as_ref().to_vec()))
+            }
+
+            /// Decapsulate `ct` with the seed or decapsulation key `sk_r`.
+            pub(crate) fn decaps(ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+                let ct = $ciphertext::try_from(ct).map_err(|_| Error::KemInvalidCiphertext)?;
+                let sk = if sk_r.len() == SEED_LEN {
+                    key_gen_derand(sk_r)?.1
+                } else {
+                    sk_r.to_vec()
+                };
+                let sk = $private_key::try_from(sk.as_slice())
+                    .map_err(|_| Error::KemInvalidSecretKey)?;
+                // ML-KEM decapsulation rejects implicitly.
+                Ok(decapsulate(&sk, &ct).to_vec())
+            }
+        }
+    };
+}
+
+implement_ml_kem!(
+    ml_kem768,
+    mlkem768,
+    MlKem768PublicKey,
+    MlKem768PrivateKey,
+    MlKem768Ciphertext
+);
+implement_ml_kem!(
+    ml_kem1024,
+    mlkem1024,
+    MlKem1024PublicKey,
+    MlKem1024PrivateKey,
+    MlKem1024Ciphertext
+);
@@ -0,0 +1,56 @@
+//! DHKEM(P-384) with the RustCrypto `p384` crate.
+//!
+//! libcrux doesn't implement P-384 yet.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{error::Error, RngCore};
+use p384::{
+    elliptic_curve::{ecdh::diffie_hellman, sec1::ToEncodedPoint},
+    PublicKey, SecretKey,
+};
+
+/// The length of a private key.
+const SK_LEN: usize = 48;
+
+pub(crate) fn dh(pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+    let sk = SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
+    let pk = PublicKey::from_sec1_bytes(pk).map_err(|_| Error::KemInvalidPublicKey)?;
+    Ok(diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine())
+        .raw_secret_bytes()
+        .to_vec())
+}
+
+pub(crate) fn secret_to_public(sk: &[u8]) -> Result<Vec<u8>, Error> {
+    let sk = SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
+    Ok(encode(&sk))
+}
+
+/// Generate a key pair (public key, private key) with rejection sampling.
+pub(crate) fn key_gen(rng: &mut impl RngCore) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let mut candidate = [0u8; SK_LEN];
+    for _ in 0..u8::MAX {
+        rng.fill_bytes(&mut candidate);
+        if let Ok(sk) = SecretKey::from_slice(&candidate) {
+            return Ok((encode(&sk), candidate.to_vec()));
+        }
+    }
+    Err(Error::InsufficientRandomness)
+}
+
+pub(crate) fn validate_sk(sk: &[u8]) -> Result<Vec<u8>, Error> {
+    SecretKey::from_slice(sk)
+        .map(|_| sk.to_vec())
+        .map_err(|_| Error::KemInvalidSecretKey)
+}
+
+pub(crate) fn validate_pk(pk: &[u8]) -> Result<(), Error> {
+    PublicKey::from_sec1_bytes(pk)
+        .map(|_| ())
+        .map_err(|_| Error::KemInvalidPublicKey)
+}
+
+/// The uncompressed SEC1 encoding of the public key of `sk`.
+fn encode(sk: &SecretKey) -> Vec<u8> {
+    sk.public_key().to_encoded_point(false).as_bytes().to_vec()
+}
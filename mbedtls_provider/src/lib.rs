@@ -0,0 +1,359 @@
+#![doc = include_str!("../Readme.md")]
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Display;
+
+use hpke_rs_crypto::{
+    error::Error,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    CryptoRng, HpkeCrypto, HpkeTestRng, RngCore,
+};
+use psa_crypto::{
+    operations::{aead, key_agreement, key_management, mac, other},
+    types::{
+        algorithm::{
+            Aead, AeadWithDefaultLengthTag, Algorithm, FullLengthMac, Hash, KeyAgreement, Mac,
+            RawKeyAgreement,
+        },
+        key::{Attributes, EccFamily, Id, Lifetime, Policy, Type, UsageFlags},
+        status::Error as PsaError,
+    },
+};
+
+/// The mbedTLS HPKE Provider
+#[derive(Debug)]
+pub struct HpkeMbedTls {}
+
+/// The PRNG for the mbedTLS Provider, i.e. the PSA random generator.
+pub struct HpkeMbedTlsPrng {}
+
+/// A volatile PSA key that is destroyed on drop.
+struct Key(Id);
+
+impl Key {
+    /// Import the key `data` of the `key_type` and `bits` that may be used
+    /// for the `alg` with the `usage`.
+    fn import(
+        key_type: Type,
+        bits: usize,
+        usage: UsageFlags,
+        alg: impl Into<Algorithm>,
+        data: &[u8],
+    ) -> Result<Self, PsaError> {
+        psa_crypto::init()?;
+        key_management::import(attributes(key_type, bits, usage, alg), None, data).map(Self)
+    }
+
+    /// Generate a key of the `key_type` and `bits` that may be used for the
+    /// `alg` with the `usage`.
+    fn generate(
+        key_type: Type,
+        bits: usize,
+        usage: UsageFlags,
+        alg: impl Into<Algorithm>,
+    ) -> Result<Self, PsaError> {
+        psa_crypto::init()?;
+        key_management::generate(attributes(key_type, bits, usage, alg), None).map(Self)
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        // Safety: the key is volatile and not used after this.
+        let _ = unsafe { key_management::destroy(self.0) };
+    }
+}
+
+fn attributes(
+    key_type: Type,
+    bits: usize,
+    usage_flags: UsageFlags,
+    alg: impl Into<Algorithm>,
+) -> Attributes {
+    Attributes {
+        key_type,
+        bits,
+        lifetime: Lifetime::Volatile,
+        policy: Policy {
+            usage_flags,
+            permitted_algorithms: alg.into(),
+        },
+    }
+}
+
+/// Import the private key `sk` of the DH KEM `alg` for ECDH, with the
+/// `usage` in addition.
+fn key_pair(alg: KemAlgorithm, sk: &[u8], mut usage: UsageFlags) -> Result<Key, Error> {
+    let curve_family = ecc_family(alg)?;
+    if sk.len() != alg.private_key_len() {
+        return Err(Error::KemInvalidSecretKey);
+    }
+    usage.set_derive();
+    Key::import(
+        Type::EccKeyPair { curve_family },
+        8 * sk.len(),
+        usage,
+        KeyAgreement::Raw(RawKeyAgreement::Ecdh),
+        sk,
+    )
+    .map_err(invalid_secret_key)
+}
+
+/// Export the public key of the `key` of the DH KEM `alg`.
+fn public_key(alg: KemAlgorithm, key: &Key) -> Result<Vec<u8>, Error> {
+    let mut pk = vec![0u8; alg.public_key_len()];
+    let len = key_management::export_public(key.0, &mut pk).map_err(psa_error)?;
+    pk.truncate(len);
+    Ok(pk)
+}
+
+/// HMAC with the `hash` of the `data` with the `key`.
+fn hmac(hash: Hash, key: &[u8], data: &[u8], digest_len: usize) -> Result<Vec<u8>, Error> {
+    let mut usage = UsageFlags::default();
+    usage.set_sign_message();
+    let alg = Mac::FullLength(FullLengthMac::Hmac { hash_alg: hash });
+    let key = Key::import(Type::Hmac, 8 * key.len(), usage, alg, key).map_err(psa_error)?;
+    let mut tag = vec![0u8; digest_len];
+    let len = mac::compute_mac(key.0, alg, data, &mut tag).map_err(psa_error)?;
+    tag.truncate(len);
+    Ok(tag)
+}
+
+/// Import the AEAD `key` of the `alg` with the `usage`.
+fn aead_key(alg: AeadAlgorithm, key: &[u8], usage: UsageFlags) -> Result<(Key, Aead), Error> {
+    let aead = aead_algorithm(alg)?;
+    if key.len() != alg.key_length() {
+        return Err(Error::CryptoLibraryError("Invalid AEAD key length".into()));
+    }
+    let key = Key::import(Type::Aes, 8 * key.len(), usage, aead, key).map_err(psa_error)?;
+    Ok((key, aead))
+}
+
+impl HpkeCrypto for HpkeMbedTls {
+    fn name() -> String {
+        "mbedTLS".into()
+    }
+
+    fn kdf_extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+        let hash = kdf_hash(alg)?;
+        // An empty salt is a string of zeros of the hash length (RFC 5869),
+        // which yields the same HMAC but is a valid PSA key.
+        let zeros = vec![0u8; alg.digest_len()];
+        let salt = if salt.is_empty() { &zeros } else { salt };
+        hmac(hash, salt, ikm, alg.digest_len())
+    }
+
+    fn kdf_expand(
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let hash = kdf_hash(alg)?;
+        let digest_len = alg.digest_len();
+        if output_size > 255 * digest_len {
+            return Err(Error::HpkeInvalidOutputLength);
+        }
+
+        // T(i) = HMAC(PRK, T(i - 1) | info | i)
+        let mut okm = Vec::with_capacity(output_size + digest_len);
+        let mut t = Vec::new();
+        let mut counter = 1u8;
+        while okm.len() < output_size {
+            let mut input = t;
+            input.extend_from_slice(info);
+            input.push(counter);
+            t = hmac(hash, prk, &input, digest_len)?;
+            okm.extend_from_slice(&t);
+            counter = counter.wrapping_add(1);
+        }
+        okm.truncate(output_size);
+        Ok(okm)
+    }
+
+    fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        let sk = key_pair(alg, sk, UsageFlags::default())?;
+        let mut dh = vec![0u8; alg.private_key_len()];
+        // mbedTLS validates the peer's point.
+        let len = key_agreement::raw_key_agreement(RawKeyAgreement::Ecdh, sk.0, pk, &mut dh)
+            .map_err(|_| Error::KemInvalidPublicKey)?;
+        dh.truncate(len);
+        Ok(dh)
+    }
+
+    fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        public_key(alg, &key_pair(alg, sk, UsageFlags::default())?)
+    }
+
+    fn kem_key_gen(
+        alg: KemAlgorithm,
+        _prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let curve_family = ecc_family(alg)?;
+        let mut usage = UsageFlags::default();
+        usage.set_derive().set_export();
+        let key = Key::generate(
+            Type::EccKeyPair { curve_family },
+            8 * alg.private_key_len(),
+            usage,
+            KeyAgreement::Raw(RawKeyAgreement::Ecdh),
+        )
+        .map_err(psa_error)?;
+        let mut sk = vec![0u8; alg.private_key_len()];
+        let len = key_management::export(key.0, &mut sk).map_err(psa_error)?;
+        sk.truncate(len);
+        Ok((public_key(alg, &key)?, sk))
+    }
+
+    fn kem_key_gen_derand(_alg: KemAlgorithm, _seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_encaps(
+        _alg: KemAlgorithm,
+        _pk_r: &[u8],
+        _prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_decaps(_alg: KemAlgorithm, _ct: &[u8], _sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+        // No ciphersuite uses this.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn dh_validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        // The import rejects scalars that are zero or not less than the order.
+        key_pair(alg, sk, UsageFlags::default()).map(|_| sk.into())
+    }
+
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        let curve_family = ecc_family(alg)?;
+        // The import rejects points that are not on the curve.
+        Key::import(
+            Type::EccPublicKey { curve_family },
+            8 * alg.private_key_len(),
+            UsageFlags::default(),
+            KeyAgreement::Raw(RawKeyAgreement::Ecdh),
+            pk,
+        )
+        .map(|_| ())
+        .map_err(|_| Error::KemInvalidPublicKey)
+    }
+
+    fn aead_seal(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut usage = UsageFlags::default();
+        usage.set_encrypt();
+        let (key, aead) = aead_key(alg, key, usage)?;
+        let mut ct = vec![0u8; msg.len() + alg.tag_length()];
+        let len = aead::encrypt(key.0, aead, nonce, aad, msg, &mut ct).map_err(psa_error)?;
+        ct.truncate(len);
+        Ok(ct)
+    }
+
+    fn aead_open(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut usage = UsageFlags::default();
+        usage.set_decrypt();
+        let (key, aead) = aead_key(alg, key, usage)?;
+        let pt_len = msg
+            .len()
+            .checked_sub(alg.tag_length())
+            .ok_or(Error::AeadInvalidCiphertext)?;
+        let mut pt = vec![0u8; pt_len];
+        let len = aead::decrypt(key.0, aead, nonce, aad, msg, &mut pt)
+            .map_err(|_| Error::AeadOpenError)?;
+        pt.truncate(len);
+        Ok(pt)
+    }
+
+    type HpkePrng = HpkeMbedTlsPrng;
+
+    fn prng() -> Self::HpkePrng {
+        HpkeMbedTlsPrng {}
+    }
+
+    hpke_rs_provider_macros::supports! {
+        kdf: [HkdfSha256],
+        kem: [DhKemP256],
+        aead: [Aes128Gcm, Aes256Gcm, HpkeExport],
+    }
+}
+
+hpke_rs_provider_macros::algorithm_map! {
+    fn kdf_hash(KdfAlgorithm) -> Hash, else UnknownKdfAlgorithm {
+        HkdfSha256 => Hash::Sha256,
+    }
+
+    fn ecc_family(KemAlgorithm) -> EccFamily, else UnknownKemAlgorithm {
+        DhKemP256 => EccFamily::SecpR1,
+    }
+
+    fn aead_algorithm(AeadAlgorithm) -> Aead, else UnknownAeadAlgorithm {
+        Aes128Gcm => Aead::AeadWithDefaultLengthTag(AeadWithDefaultLengthTag::Gcm),
+        Aes256Gcm => Aead::AeadWithDefaultLengthTag(AeadWithDefaultLengthTag::Gcm),
+    }
+}
+
+hpke_rs_provider_macros::error_map! {
+    fn psa_error(PsaError) => CryptoLibraryError("PSA error");
+    fn invalid_secret_key(PsaError) => KemInvalidSecretKey;
+}
+
+impl RngCore for HpkeMbedTlsPrng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Panics if the PSA random generator fails, like the OS RNG of the other
+    /// providers.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        psa_crypto::init()
+            .and_then(|_| other::generate_random(dest))
+            .expect("The PSA random generator failed");
+    }
+}
+
+impl CryptoRng for HpkeMbedTlsPrng {}
+
+impl HpkeTestRng for HpkeMbedTlsPrng {
+    type Error = Error;
+
+    fn try_fill_test_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        psa_crypto::init()
+            .and_then(|_| other::generate_random(dest))
+            .map_err(|_| Error::InsufficientRandomness)
+    }
+
+    fn seed(&mut self, _: &[u8]) {}
+}
+
+impl Display for HpkeMbedTls {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", Self::name())
+    }
+}
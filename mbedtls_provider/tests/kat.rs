@@ -0,0 +1,13 @@
+//! RFC 9180 known-answer tests.
+//!
+//! Test vectors for ciphersuites that mbedTLS doesn't support are skipped.
+
+use hpke_rs::test_util::kat::{kat, test_vectors};
+use hpke_rs_mbedtls::HpkeMbedTls;
+
+#[test]
+fn kat_mbedtls() {
+    for test in test_vectors(include_str!("../../tests/test_vectors.json")) {
+        kat::<HpkeMbedTls>(&test);
+    }
+}
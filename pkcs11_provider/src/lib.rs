@@ -0,0 +1,178 @@
+#![doc = include_str!("../Readme.md")]
+
+use std::sync::Mutex;
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::{
+        elliptic_curve::{EcKdf, Ecdh1DeriveParams},
+        Mechanism,
+    },
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use hpke_rs_crypto::{
+    error::Error,
+    keystore::{KeyStore, KeyStoreProvider},
+    types::KemAlgorithm,
+    HpkeCrypto,
+};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use subtle::ConstantTimeEq;
+
+/// The PKCS#11 HPKE provider.
+///
+/// Private keys given as handles are used in the PKCS#11 token, everything
+/// else is done by the [`HpkeRustCrypto`] provider.
+pub type HpkePkcs11 = KeyStoreProvider<HpkeRustCrypto, Pkcs11KeyStore>;
+
+/// A [`KeyStore`] for the keys in a PKCS#11 token.
+///
+/// The handles of the keys are their `CKA_ID`s.
+/// Every private key needs a public key object with the same `CKA_ID`.
+#[derive(Debug)]
+pub struct Pkcs11KeyStore {
+    session: Mutex<Session>,
+}
+
+impl Pkcs11KeyStore {
+    /// Create a key store for an open, and if necessary logged in, `session`.
+    pub fn new(session: Session) -> Self {
+        Self {
+            session: Mutex::new(session),
+        }
+    }
+
+    /// Load the PKCS#11 `module`, e.g. `libsofthsm2.so`, and log into the
+    /// token with the `label` with the user `pin`.
+    pub fn open(module: &str, label: &str, pin: &str) -> Result<Self, Error> {
+        let pkcs11 = Pkcs11::new(module).map_err(library_error)?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(library_error)?;
+        let slot = pkcs11
+            .get_slots_with_token()
+            .map_err(library_error)?
+            .into_iter()
+            .find(|&slot| {
+                pkcs11
+                    .get_token_info(slot)
+                    .is_ok_and(|info| info.label() == label)
+            })
+            .ok_or_else(|| Error::CryptoLibraryError(format!("No PKCS#11 token {label}")))?;
+        let session = pkcs11.open_ro_session(slot).map_err(library_error)?;
+        session
+            .login(UserType::User, Some(&AuthPin::new(pin.into())))
+            .map_err(library_error)?;
+        Ok(Self::new(session))
+    }
+
+    /// Find the single object of the `class` with the `CKA_ID` `handle`.
+    fn find(
+        session: &Session,
+        class: ObjectClass,
+        alg: KemAlgorithm,
+        handle: &[u8],
+    ) -> Result<ObjectHandle, Error> {
+        let template = [
+            Attribute::Class(class),
+            Attribute::KeyType(key_type(alg)?),
+            Attribute::Id(handle.to_vec()),
+        ];
+        match session.find_objects(&template).map_err(library_error)?[..] {
+            [object] => Ok(object),
+            _ => Err(Error::KemInvalidSecretKey),
+        }
+    }
+}
+
+impl KeyStore for Pkcs11KeyStore {
+    fn name(&self) -> String {
+        "PKCS#11".into()
+    }
+
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        key_type(alg).map(|_| ())
+    }
+
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error> {
+        // Not all tokens validate the peer's point.
+        HpkeRustCrypto::dh_validate_pk(alg, pk)?;
+
+        let session = self.session.lock().map_err(|_| {
+            Error::CryptoLibraryError("The PKCS#11 session is poisoned".to_string())
+        })?;
+        let sk = Self::find(&session, ObjectClass::PRIVATE_KEY, alg, handle)?;
+        let template = [
+            Attribute::Class(ObjectClass::SECRET_KEY),
+            Attribute::KeyType(KeyType::GENERIC_SECRET),
+            Attribute::Token(false),
+            Attribute::Sensitive(false),
+            Attribute::Extractable(true),
+        ];
+        let params = Ecdh1DeriveParams::new(EcKdf::null(), pk);
+        let secret = session
+            .derive_key(&Mechanism::Ecdh1Derive(params), sk, &template)
+            .map_err(library_error)?;
+        let value = session.get_attributes(secret, &[AttributeType::Value]);
+        session.destroy_object(secret).map_err(library_error)?;
+
+        match value.map_err(library_error)?.pop() {
+            // Rejects the all-zero output of low order points, without
+            // branching on the bytes of the shared secret.
+            Some(Attribute::Value(dh)) if !bool::from(dh.ct_eq(&vec![0; dh.len()])) => Ok(dh),
+            Some(Attribute::Value(_)) => Err(Error::KemInvalidPublicKey),
+            _ => Err(Error::CryptoLibraryError(
+                "The PKCS#11 token didn't return the shared secret".to_string(),
+            )),
+        }
+    }
+
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error> {
+        let session = self.session.lock().map_err(|_| {
+            Error::CryptoLibraryError("The PKCS#11 session is poisoned".to_string())
+        })?;
+        let pk = Self::find(&session, ObjectClass::PUBLIC_KEY, alg, handle)?;
+        match session
+            .get_attributes(pk, &[AttributeType::EcPoint])
+            .map_err(library_error)?
+            .pop()
+        {
+            Some(Attribute::EcPoint(point)) => decode_ec_point(alg, &point),
+            _ => Err(Error::KemInvalidSecretKey),
+        }
+    }
+}
+
+/// The PKCS#11 key type for keys of the `alg`.
+fn key_type(alg: KemAlgorithm) -> Result<KeyType, Error> {
+    match alg {
+        KemAlgorithm::DhKemP256 => Ok(KeyType::EC),
+        KemAlgorithm::DhKem25519 => Ok(KeyType::EC_MONTGOMERY),
+        _ => Err(Error::UnknownKemAlgorithm),
+    }
+}
+
+/// Decode the `CKA_EC_POINT` of a public key.
+///
+/// The point should be a DER encoded OCTET STRING, but some tokens store the
+/// raw point for Montgomery curves.
+fn decode_ec_point(alg: KemAlgorithm, point: &[u8]) -> Result<Vec<u8>, Error> {
+    let pk_len = alg.public_key_len();
+    if point.len() == pk_len {
+        return Ok(point.to_vec());
+    }
+    // The public keys of the supported KEMs are shorter than 128 bytes, i.e.
+    // the DER length is in short form.
+    match point {
+        [0x04, len, pk @ ..] if usize::from(*len) == pk_len && pk.len() == pk_len => {
+            Ok(pk.to_vec())
+        }
+        _ => Err(Error::KemInvalidPublicKey),
+    }
+}
+
+fn library_error(e: cryptoki::error::Error) -> Error {
+    Error::CryptoLibraryError(format!("PKCS#11 error: {e}"))
+}
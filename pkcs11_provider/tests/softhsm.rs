@@ -0,0 +1,117 @@
+//! RFC 9180 known-answer tests with the receiver keys in a SoftHSM token.
+//!
+//! The tests need an initialized token with the label `hpke` and the user
+//! pin `1234`, and the path of the PKCS#11 module in `HPKE_PKCS11_MODULE`.
+//!
+//! ```text
+//! softhsm2-util --init-token --free --label hpke --pin 1234 --so-pin 1234
+//! HPKE_PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so cargo test
+//! ```
+//!
+//! The tests are skipped if `HPKE_PKCS11_MODULE` isn't set.
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    object::{Attribute, KeyType, ObjectClass},
+    session::{Session, UserType},
+    slot::Slot,
+    types::AuthPin,
+};
+use hpke_rs::{
+    test_util::kat::{kat_receiver, test_vectors},
+    HpkePrivateKey,
+};
+use hpke_rs_crypto::{types::KemAlgorithm, HpkeCrypto};
+use hpke_rs_pkcs11::{HpkePkcs11, Pkcs11KeyStore};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+
+const LABEL: &str = "hpke";
+const PIN: &str = "1234";
+
+/// The DER encoded OID of the P-256 curve, i.e. the `CKA_EC_PARAMS`.
+const P256_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Open a session with the token and import the key pair `(sk, pk)` as
+/// session objects with the `CKA_ID` `id`.
+fn import(pkcs11: &Pkcs11, slot: Slot, id: &[u8], sk: &[u8], pk: &[u8]) -> Session {
+    let session = pkcs11.open_rw_session(slot).unwrap();
+    session
+        .create_object(&[
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::Value(sk.to_vec()),
+            Attribute::Id(id.to_vec()),
+            Attribute::Token(false),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Derive(true),
+        ])
+        .unwrap();
+    // CKA_EC_POINT is the DER encoded OCTET STRING of the point.
+    let mut point = vec![0x04, pk.len() as u8];
+    point.extend_from_slice(pk);
+    session
+        .create_object(&[
+            Attribute::Class(ObjectClass::PUBLIC_KEY),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::EcParams(P256_PARAMS.to_vec()),
+            Attribute::EcPoint(point),
+            Attribute::Id(id.to_vec()),
+            Attribute::Token(false),
+        ])
+        .unwrap();
+    session
+}
+
+// SoftHSM can't import X25519 keys in all versions, so only the P-256 test
+// vectors are run.
+#[test]
+fn kat_softhsm() {
+    let module = match std::env::var("HPKE_PKCS11_MODULE") {
+        Ok(module) => module,
+        Err(_) => {
+            eprintln!("HPKE_PKCS11_MODULE isn't set, skipping the PKCS#11 tests");
+            return;
+        }
+    };
+
+    // A PKCS#11 module can only be initialized once per process.
+    let pkcs11 = Pkcs11::new(module).unwrap();
+    pkcs11.initialize(CInitializeArgs::OsThreads).unwrap();
+    let slot = pkcs11
+        .get_slots_with_token()
+        .unwrap()
+        .into_iter()
+        .find(|&slot| pkcs11.get_token_info(slot).unwrap().label() == LABEL)
+        .expect("No SoftHSM token with the label hpke");
+
+    // The login applies to all sessions with the token, as long as this
+    // session is open.
+    let login = pkcs11.open_ro_session(slot).unwrap();
+    login
+        .login(UserType::User, Some(&AuthPin::new(PIN.into())))
+        .unwrap();
+
+    let tests = test_vectors(include_str!("../../tests/test_vectors.json"));
+    let mut count = 0;
+    for test in tests {
+        let suite = test.suite();
+        if suite.kem != KemAlgorithm::DhKemP256
+            || HpkeRustCrypto::supports_kdf(suite.kdf).is_err()
+            || HpkeRustCrypto::supports_aead(suite.aead).is_err()
+        {
+            continue;
+        }
+
+        let id = format!("kat-{count}").into_bytes();
+        let sk = test.receiver_private_key();
+        let pk = test.receiver_public_key();
+        let session = import(&pkcs11, slot, &id, &sk, &pk);
+
+        let provider = HpkePkcs11::new(Pkcs11KeyStore::new(session));
+        kat_receiver(provider, &test, &HpkePrivateKey::from_handle(suite.kem, id));
+        count += 1;
+    }
+    assert!(count > 0);
+}
@@ -0,0 +1,159 @@
+//! # macOS Keychain
+//!
+//! Private keys in the macOS Keychain, including keys in the Secure Enclave.
+//!
+//! The handles are the application labels (`kSecAttrApplicationLabel`) of the
+//! keys, which are the SHA-1 hashes of the public keys unless set explicitly.
+//! The keys must be NIST P-256 keys (`kSecAttrKeyTypeECSECPrimeRandom`) that
+//! allow key exchange (`kSecAttrCanDerive`).
+
+use core_foundation::{
+    base::TCFType,
+    data::{CFData, CFDataRef},
+    dictionary::{CFDictionary, CFDictionaryRef},
+    error::{CFError, CFErrorRef},
+    string::CFString,
+};
+use hpke_rs_crypto::{
+    error::Error,
+    keystore::{KeyStore, KeyStoreProvider},
+    types::KemAlgorithm,
+    HpkeCrypto,
+};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use security_framework::{
+    item::{ItemClass, ItemSearchOptions, Reference, SearchResult},
+    key::SecKey,
+};
+use security_framework_sys::{
+    base::SecKeyRef,
+    item::{
+        kSecAttrKeyClass, kSecAttrKeyClassPublic, kSecAttrKeyType, kSecAttrKeyTypeECSECPrimeRandom,
+    },
+    key::{Algorithm, SecKeyAlgorithm},
+};
+
+use crate::library_error;
+
+#[link(name = "Security", kind = "framework")]
+extern "C" {
+    fn SecKeyCreateWithData(
+        key_data: CFDataRef,
+        attributes: CFDictionaryRef,
+        error: *mut CFErrorRef,
+    ) -> SecKeyRef;
+
+    fn SecKeyCopyKeyExchangeResult(
+        private_key: SecKeyRef,
+        algorithm: SecKeyAlgorithm,
+        public_key: SecKeyRef,
+        parameters: CFDictionaryRef,
+        error: *mut CFErrorRef,
+    ) -> CFDataRef;
+}
+
+/// The HPKE provider with private keys in the macOS Keychain.
+pub type HpkeKeychain = KeyStoreProvider<HpkeRustCrypto, KeychainKeyStore>;
+
+/// A [`KeyStore`] for the keys in the macOS Keychain.
+#[derive(Debug, Default)]
+pub struct KeychainKeyStore {}
+
+impl KeychainKeyStore {
+    /// Create a key store for the default keychains of the user.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Find the private key with the application label `handle`.
+    fn find(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<SecKey, Error> {
+        self.supports_kem(alg)?;
+        ItemSearchOptions::new()
+            .class(ItemClass::key())
+            .application_label(handle)
+            .load_refs(true)
+            .search()
+            .map_err(|_| Error::KemInvalidSecretKey)?
+            .into_iter()
+            .find_map(|result| match result {
+                SearchResult::Ref(Reference::Key(key)) => Some(key),
+                _ => None,
+            })
+            .ok_or(Error::KemInvalidSecretKey)
+    }
+}
+
+impl KeyStore for KeychainKeyStore {
+    fn name(&self) -> String {
+        "Keychain".into()
+    }
+
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKemP256 => Ok(()),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error> {
+        HpkeRustCrypto::dh_validate_pk(alg, pk)?;
+        let sk = self.find(alg, handle)?;
+
+        // Safety: the attribute names are constants of the Security framework.
+        let attributes = unsafe {
+            CFDictionary::from_CFType_pairs(&[
+                (
+                    CFString::wrap_under_get_rule(kSecAttrKeyType),
+                    CFString::wrap_under_get_rule(kSecAttrKeyTypeECSECPrimeRandom),
+                ),
+                (
+                    CFString::wrap_under_get_rule(kSecAttrKeyClass),
+                    CFString::wrap_under_get_rule(kSecAttrKeyClassPublic),
+                ),
+            ])
+        };
+        let pk = CFData::from_buffer(pk);
+        let parameters = CFDictionary::<CFString, CFString>::from_CFType_pairs(&[]);
+
+        let mut error = core::ptr::null_mut();
+        // Safety: all arguments are valid references that outlive the calls,
+        // and the results are checked for null before taking ownership.
+        unsafe {
+            let pk = SecKeyCreateWithData(
+                pk.as_concrete_TypeRef(),
+                attributes.as_concrete_TypeRef(),
+                &mut error,
+            );
+            if pk.is_null() {
+                drop(CFError::wrap_under_create_rule(error));
+                return Err(Error::KemInvalidPublicKey);
+            }
+            let pk = SecKey::wrap_under_create_rule(pk);
+
+            let dh = SecKeyCopyKeyExchangeResult(
+                sk.as_concrete_TypeRef(),
+                Algorithm::ECDHKeyExchangeStandard.into(),
+                pk.as_concrete_TypeRef(),
+                parameters.as_concrete_TypeRef(),
+                &mut error,
+            );
+            if dh.is_null() {
+                return Err(library_error(
+                    "Keychain",
+                    CFError::wrap_under_create_rule(error),
+                ));
+            }
+            Ok(CFData::wrap_under_create_rule(dh).to_vec())
+        }
+    }
+
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error> {
+        // The external representation of an EC public key is the
+        // uncompressed SEC1 point.
+        self.find(alg, handle)?
+            .public_key()
+            .and_then(|pk| pk.external_representation())
+            .map(|pk| pk.to_vec())
+            .ok_or(Error::KemInvalidSecretKey)
+    }
+}
@@ -0,0 +1,102 @@
+//! # Linux Keyring (At Rest Only)
+//!
+//! Private keys stored in the Linux kernel keyring, see `keyrings(7)`.
+//!
+//! The handles are the descriptions of `user` keys whose payloads are the
+//! serialized private keys.
+//!
+//! **Note** that unlike the other key stores of this crate the keyring doesn't
+//! compute the ECDH.
+//! The private key is read into the memory of this process for every
+//! operation and zeroized afterwards, i.e. it is only protected at rest.
+
+use hpke_rs_crypto::{
+    error::Error,
+    keystore::{KeyStore, KeyStoreProvider},
+    types::KemAlgorithm,
+    HpkeCrypto,
+};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use linux_keyutils::{KeyRing, KeyRingIdentifier};
+use zeroize::Zeroizing;
+
+use crate::library_error;
+
+/// The HPKE provider with private keys in the Linux kernel keyring.
+pub type HpkeKeyring = KeyStoreProvider<HpkeRustCrypto, KeyringKeyStore>;
+
+/// A [`KeyStore`] for the `user` keys in a Linux kernel keyring.
+#[derive(Debug)]
+pub struct KeyringKeyStore {
+    keyring: KeyRing,
+}
+
+impl KeyringKeyStore {
+    /// Create a key store for the `keyring`.
+    ///
+    /// Keys are searched recursively in the keyrings linked to it.
+    pub fn new(keyring: KeyRing) -> Self {
+        Self { keyring }
+    }
+
+    /// Create a key store for the keyring of the user, see `user-keyring(7)`.
+    pub fn user() -> Result<Self, Error> {
+        let keyring = KeyRing::from_special_id(KeyRingIdentifier::User, false)
+            .map_err(|e| library_error("Keyring", e))?;
+        Ok(Self::new(keyring))
+    }
+
+    /// Store the serialized private key `sk` with the `description`, which
+    /// is the handle of the key.
+    ///
+    /// An existing key with the same description is replaced.
+    pub fn insert(&self, description: &str, sk: &[u8]) -> Result<(), Error> {
+        self.keyring
+            .add_key(description, sk)
+            .map(|_| ())
+            .map_err(|e| library_error("Keyring", e))
+    }
+
+    /// Read the private key `handle` for the `alg` into a zeroizing buffer.
+    fn read(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let description = core::str::from_utf8(handle).map_err(|_| Error::KemInvalidSecretKey)?;
+        let key = self
+            .keyring
+            .search(description)
+            .map_err(|_| Error::KemInvalidSecretKey)?;
+        let mut sk = Zeroizing::new(vec![0u8; alg.private_key_len()]);
+        let len = key
+            .read(&mut *sk)
+            .map_err(|e| library_error("Keyring", e))?;
+        if len != sk.len() {
+            return Err(Error::KemInvalidSecretKey);
+        }
+        Ok(sk)
+    }
+}
+
+impl KeyStore for KeyringKeyStore {
+    fn name(&self) -> String {
+        "Linux keyring".into()
+    }
+
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKemP256
+            | KemAlgorithm::DhKemP384
+            | KemAlgorithm::DhKemP521
+            | KemAlgorithm::DhKemK256
+            | KemAlgorithm::DhKem25519
+            | KemAlgorithm::DhKem448 => HpkeRustCrypto::supports_kem(alg),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error> {
+        HpkeRustCrypto::dh(alg, pk, &self.read(alg, handle)?)
+    }
+
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error> {
+        HpkeRustCrypto::secret_to_public(alg, &self.read(alg, handle)?)
+    }
+}
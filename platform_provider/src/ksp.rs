@@ -0,0 +1,198 @@
+//! # Windows Key Storage Provider
+//!
+//! Private keys in a CNG key storage provider, e.g. the
+//! Microsoft Platform Crypto Provider, which keeps the keys in the TPM.
+//!
+//! The handles are the names of persisted `ECDH_P256` keys as UTF-8.
+
+use hpke_rs_crypto::{
+    error::Error,
+    keystore::{KeyStore, KeyStoreProvider},
+    types::KemAlgorithm,
+    HpkeCrypto,
+};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use windows_sys::{
+    core::PCWSTR,
+    Win32::Security::Cryptography::{
+        NCryptDeriveKey, NCryptExportKey, NCryptFreeObject, NCryptImportKey, NCryptOpenKey,
+        NCryptOpenStorageProvider, NCryptSecretAgreement, BCRYPT_ECCKEY_BLOB,
+        BCRYPT_ECCPUBLIC_BLOB, BCRYPT_ECDH_PUBLIC_P256_MAGIC, BCRYPT_KDF_RAW_SECRET,
+        MS_KEY_STORAGE_PROVIDER, MS_PLATFORM_CRYPTO_PROVIDER, NCRYPT_SILENT_FLAG,
+    },
+};
+
+use crate::{library_error, p256_coordinates, p256_point, P256_COORDINATE_LEN};
+
+/// The HPKE provider with private keys in a Windows key storage provider.
+pub type HpkeKsp = KeyStoreProvider<HpkeRustCrypto, KspKeyStore>;
+
+/// The length of a `BCRYPT_ECCKEY_BLOB` header.
+const BLOB_HEADER_LEN: usize = core::mem::size_of::<BCRYPT_ECCKEY_BLOB>();
+
+/// An NCrypt handle that is freed on drop.
+struct Handle(usize);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            // Safety: the handle was returned by NCrypt and is freed once.
+            unsafe { NCryptFreeObject(self.0) };
+        }
+    }
+}
+
+/// A [`KeyStore`] for the persisted keys of a Windows key storage provider.
+#[derive(Debug)]
+pub struct KspKeyStore {
+    provider: usize,
+}
+
+impl KspKeyStore {
+    /// Open the Microsoft Platform Crypto Provider, i.e. the TPM.
+    pub fn platform() -> Result<Self, Error> {
+        Self::open(MS_PLATFORM_CRYPTO_PROVIDER)
+    }
+
+    /// Open the Microsoft Software Key Storage Provider.
+    pub fn software() -> Result<Self, Error> {
+        Self::open(MS_KEY_STORAGE_PROVIDER)
+    }
+
+    fn open(name: PCWSTR) -> Result<Self, Error> {
+        let mut provider = 0;
+        // Safety: `name` is a valid null terminated wide string.
+        check(unsafe { NCryptOpenStorageProvider(&mut provider, name, 0) })?;
+        Ok(Self { provider })
+    }
+
+    /// Open the persisted key with the name `handle`.
+    fn key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Handle, Error> {
+        self.supports_kem(alg)?;
+        let name = core::str::from_utf8(handle).map_err(|_| Error::KemInvalidSecretKey)?;
+        let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+        let mut key = Handle(0);
+        // Safety: `name` is a null terminated wide string.
+        let status = unsafe {
+            NCryptOpenKey(
+                self.provider,
+                &mut key.0,
+                name.as_ptr(),
+                0,
+                NCRYPT_SILENT_FLAG,
+            )
+        };
+        check(status).map_err(|_| Error::KemInvalidSecretKey)?;
+        Ok(key)
+    }
+}
+
+impl Drop for KspKeyStore {
+    fn drop(&mut self) {
+        // Safety: the provider handle was opened in `open` and is freed once.
+        unsafe { NCryptFreeObject(self.provider) };
+    }
+}
+
+impl KeyStore for KspKeyStore {
+    fn name(&self) -> String {
+        "Windows KSP".into()
+    }
+
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKemP256 => Ok(()),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error> {
+        HpkeRustCrypto::dh_validate_pk(alg, pk)?;
+        let sk = self.key(alg, handle)?;
+
+        let (x, y) = p256_coordinates(pk)?;
+        let mut blob = Vec::with_capacity(BLOB_HEADER_LEN + 2 * P256_COORDINATE_LEN);
+        blob.extend_from_slice(&BCRYPT_ECDH_PUBLIC_P256_MAGIC.to_le_bytes());
+        blob.extend_from_slice(&(P256_COORDINATE_LEN as u32).to_le_bytes());
+        blob.extend_from_slice(x);
+        blob.extend_from_slice(y);
+
+        let mut pk = Handle(0);
+        let mut secret = Handle(0);
+        let mut dh = vec![0u8; P256_COORDINATE_LEN];
+        let mut dh_len = 0;
+        // Safety: all buffers are valid for the given lengths and the handles
+        // are freed on drop.
+        unsafe {
+            check(NCryptImportKey(
+                self.provider,
+                0,
+                BCRYPT_ECCPUBLIC_BLOB,
+                core::ptr::null(),
+                &mut pk.0,
+                blob.as_ptr(),
+                blob.len() as u32,
+                NCRYPT_SILENT_FLAG,
+            ))
+            .map_err(|_| Error::KemInvalidPublicKey)?;
+            check(NCryptSecretAgreement(
+                sk.0,
+                pk.0,
+                &mut secret.0,
+                NCRYPT_SILENT_FLAG,
+            ))?;
+            check(NCryptDeriveKey(
+                secret.0,
+                BCRYPT_KDF_RAW_SECRET,
+                core::ptr::null(),
+                dh.as_mut_ptr(),
+                dh.len() as u32,
+                &mut dh_len,
+                0,
+            ))?;
+        }
+        if dh_len as usize != dh.len() {
+            return Err(Error::CryptoLibraryError(
+                "The KSP returned a truncated shared secret".to_string(),
+            ));
+        }
+        // The raw secret is little endian.
+        dh.reverse();
+        Ok(dh)
+    }
+
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error> {
+        let sk = self.key(alg, handle)?;
+        let mut blob = vec![0u8; BLOB_HEADER_LEN + 2 * P256_COORDINATE_LEN];
+        let mut blob_len = 0;
+        // Safety: the buffer is valid for the given length.
+        check(unsafe {
+            NCryptExportKey(
+                sk.0,
+                0,
+                BCRYPT_ECCPUBLIC_BLOB,
+                core::ptr::null(),
+                blob.as_mut_ptr(),
+                blob.len() as u32,
+                &mut blob_len,
+                NCRYPT_SILENT_FLAG,
+            )
+        })?;
+        match blob.get(BLOB_HEADER_LEN..blob_len as usize) {
+            Some(xy) if xy.len() == 2 * P256_COORDINATE_LEN => {
+                let (x, y) = xy.split_at(P256_COORDINATE_LEN);
+                p256_point(x, y)
+            }
+            _ => Err(Error::KemInvalidSecretKey),
+        }
+    }
+}
+
+/// Map an NCrypt status to an error.
+fn check(status: i32) -> Result<(), Error> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(library_error("NCrypt", format!("{status:#010x}")))
+    }
+}
@@ -0,0 +1,53 @@
+#![doc = include_str!("../Readme.md")]
+
+use hpke_rs_crypto::error::Error;
+
+#[cfg(all(feature = "keychain", target_os = "macos"))]
+pub mod keychain;
+#[cfg(all(feature = "keyring-at-rest", target_os = "linux"))]
+pub mod keyring;
+#[cfg(all(feature = "ksp", windows))]
+pub mod ksp;
+#[cfg(feature = "tpm")]
+pub mod tpm;
+
+/// The length of a P-256 coordinate.
+#[cfg(any(feature = "tpm", all(feature = "ksp", windows)))]
+const P256_COORDINATE_LEN: usize = 32;
+
+/// Left-pad the big endian P-256 `coordinate`, which key stores may return
+/// without leading zeros.
+#[cfg(any(feature = "tpm", all(feature = "ksp", windows)))]
+fn p256_coordinate(coordinate: &[u8]) -> Result<Vec<u8>, Error> {
+    let offset = P256_COORDINATE_LEN
+        .checked_sub(coordinate.len())
+        .ok_or(Error::KemInvalidPublicKey)?;
+    let mut padded = vec![0u8; P256_COORDINATE_LEN];
+    padded[offset..].copy_from_slice(coordinate);
+    Ok(padded)
+}
+
+/// Encode the P-256 point (`x`, `y`) in the uncompressed SEC1 form.
+#[cfg(any(feature = "tpm", all(feature = "ksp", windows)))]
+fn p256_point(x: &[u8], y: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut point = vec![0x04];
+    point.extend_from_slice(&p256_coordinate(x)?);
+    point.extend_from_slice(&p256_coordinate(y)?);
+    Ok(point)
+}
+
+/// Split the uncompressed SEC1 P-256 point `pk` into its coordinates.
+#[cfg(any(feature = "tpm", all(feature = "ksp", windows)))]
+fn p256_coordinates(pk: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    match pk {
+        [0x04, xy @ ..] if xy.len() == 2 * P256_COORDINATE_LEN => {
+            Ok(xy.split_at(P256_COORDINATE_LEN))
+        }
+        _ => Err(Error::KemInvalidPublicKey),
+    }
+}
+
+#[allow(dead_code)]
+fn library_error(name: &str, e: impl core::fmt::Display) -> Error {
+    Error::CryptoLibraryError(format!("{name} error: {e}"))
+}
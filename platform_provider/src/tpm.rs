@@ -0,0 +1,106 @@
+//! # TPM 2.0
+//!
+//! Private keys in a TPM 2.0, using the TPM Software Stack.
+//!
+//! The handles are the persistent handles of the keys, e.g. `0x81010001`, as
+//! 4 big endian bytes.
+//! The keys must be unrestricted ECC decryption keys on the NIST P-256 curve
+//! with an empty authorization value, e.g. created with
+//! `tpm2_createprimary -C o -G ecc256:ecdh -a "fixedtpm|fixedparent|sensitivedataorigin|userwithauth|decrypt"`
+//! and made persistent with `tpm2_evictcontrol`.
+
+use hpke_rs_crypto::{
+    error::Error,
+    keystore::{KeyStore, KeyStoreProvider},
+    types::KemAlgorithm,
+    HpkeCrypto,
+};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use tss_esapi::{
+    handles::{KeyHandle, PersistentTpmHandle, TpmHandle},
+    structures::{EccParameter, EccPoint, Public},
+    Context, TctiNameConf,
+};
+
+use crate::{library_error, p256_coordinate, p256_coordinates, p256_point};
+
+/// The HPKE provider with private keys in a TPM 2.0.
+pub type HpkeTpm = KeyStoreProvider<HpkeRustCrypto, TpmKeyStore>;
+
+/// A [`KeyStore`] for the persistent keys in a TPM 2.0.
+#[derive(Debug)]
+pub struct TpmKeyStore {
+    // The TSS context is not `Send`, such that a context is created for every
+    // operation.
+    tcti: TctiNameConf,
+}
+
+impl TpmKeyStore {
+    /// Create a key store for the TPM at the `tcti`, e.g. `device:/dev/tpmrm0`.
+    pub fn new(tcti: TctiNameConf) -> Self {
+        Self { tcti }
+    }
+
+    /// Create a key store for the TPM configured in the `TPM2TOOLS_TCTI`,
+    /// `TCTI`, or `TEST_TCTI` environment variable.
+    pub fn from_environment() -> Result<Self, Error> {
+        let tcti = TctiNameConf::from_environment_variable().map_err(tpm_error)?;
+        Ok(Self::new(tcti))
+    }
+
+    /// Open a context and load the key with the persistent `handle`.
+    fn load(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<(Context, KeyHandle), Error> {
+        self.supports_kem(alg)?;
+        let handle = <[u8; 4]>::try_from(handle).map_err(|_| Error::KemInvalidSecretKey)?;
+        let handle = PersistentTpmHandle::new(u32::from_be_bytes(handle))
+            .map_err(|_| Error::KemInvalidSecretKey)?;
+
+        let mut context = Context::new(self.tcti.clone()).map_err(tpm_error)?;
+        let key = context
+            .tr_from_tpm_public(TpmHandle::Persistent(handle))
+            .map_err(|_| Error::KemInvalidSecretKey)?;
+        Ok((context, key.into()))
+    }
+}
+
+impl KeyStore for TpmKeyStore {
+    fn name(&self) -> String {
+        "TPM 2.0".into()
+    }
+
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKemP256 => Ok(()),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error> {
+        HpkeRustCrypto::dh_validate_pk(alg, pk)?;
+        let (x, y) = p256_coordinates(pk)?;
+        let point = EccPoint::new(
+            EccParameter::try_from(x).map_err(tpm_error)?,
+            EccParameter::try_from(y).map_err(tpm_error)?,
+        );
+
+        let (mut context, key) = self.load(alg, handle)?;
+        let z = context
+            .execute_with_nullauth_session(|context| context.ecdh_z_gen(key, point))
+            .map_err(tpm_error)?;
+        p256_coordinate(z.x().value())
+    }
+
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error> {
+        let (mut context, key) = self.load(alg, handle)?;
+        match context.read_public(key).map_err(tpm_error)? {
+            (Public::Ecc { unique, .. }, _, _) => {
+                p256_point(unique.x().value(), unique.y().value())
+            }
+            _ => Err(Error::KemInvalidSecretKey),
+        }
+    }
+}
+
+fn tpm_error(e: tss_esapi::Error) -> Error {
+    library_error("TPM", e)
+}
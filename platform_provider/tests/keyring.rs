@@ -0,0 +1,38 @@
+//! RFC 9180 known-answer tests with the receiver keys in the Linux kernel
+//! keyring.
+#![cfg(all(feature = "keyring-at-rest", target_os = "linux"))]
+
+use hpke_rs::{
+    test_util::kat::{kat_receiver, test_vectors},
+    HpkePrivateKey,
+};
+use hpke_rs_crypto::HpkeCrypto;
+use hpke_rs_platform::keyring::{HpkeKeyring, KeyringKeyStore};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+use linux_keyutils::{KeyRing, KeyRingIdentifier};
+
+#[test]
+fn kat_keyring() {
+    // Use the keyring of this process, such that the keys of the test aren't
+    // left in the keyring of the user.
+    let keyring = KeyRing::from_special_id(KeyRingIdentifier::Process, true).unwrap();
+
+    let tests = test_vectors(include_str!("../../tests/test_vectors.json"));
+    for (i, test) in tests.iter().enumerate() {
+        let suite = test.suite();
+        if HpkeRustCrypto::supports_kem(suite.kem).is_err()
+            || HpkeRustCrypto::supports_kdf(suite.kdf).is_err()
+            || HpkeRustCrypto::supports_aead(suite.aead).is_err()
+        {
+            continue;
+        }
+
+        let store = KeyringKeyStore::new(keyring);
+        let description = format!("hpke-rs-kat-{i}");
+        store
+            .insert(&description, &test.receiver_private_key())
+            .unwrap();
+        let sk_r = HpkePrivateKey::from_handle(suite.kem, description.into_bytes());
+        kat_receiver(HpkeKeyring::new(store), test, &sk_r);
+    }
+}
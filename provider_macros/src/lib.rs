@@ -0,0 +1,176 @@
+#![doc = include_str!("../Readme.md")]
+#![no_std]
+
+extern crate alloc;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::format;
+    pub use hpke_rs_crypto;
+}
+
+/// Generate the `supports_kdf`, `supports_kem`, and `supports_aead` functions
+/// of an [`HpkeCrypto`](hpke_rs_crypto::HpkeCrypto) implementation.
+///
+/// Each list holds the names of the supported algorithm variants.
+/// All other algorithms are rejected with the `Unknown*Algorithm` error.
+///
+/// ```
+/// # use hpke_rs_crypto::{error::Error, types::*};
+/// struct Provider;
+///
+/// impl Provider {
+///     hpke_rs_provider_macros::supports! {
+///         kdf: [HkdfSha256, HkdfSha384, HkdfSha512],
+///         kem: [DhKem25519],
+///         aead: [ChaCha20Poly1305, HpkeExport],
+///     }
+/// }
+///
+/// assert!(Provider::supports_kem(KemAlgorithm::DhKem25519).is_ok());
+/// assert!(Provider::supports_kem(KemAlgorithm::DhKemP256).is_err());
+/// assert!(Provider::supports_aead(AeadAlgorithm::Aes128Gcm).is_err());
+/// ```
+#[macro_export]
+macro_rules! supports {
+    (
+        kdf: [$($kdf:ident),+ $(,)?],
+        kem: [$($kem:ident),+ $(,)?],
+        aead: [$($aead:ident),+ $(,)?] $(,)?
+    ) => {
+        /// Returns an error if the KDF algorithm is not supported by this crypto provider.
+        fn supports_kdf(
+            alg: $crate::__private::hpke_rs_crypto::types::KdfAlgorithm,
+        ) -> Result<(), $crate::__private::hpke_rs_crypto::error::Error> {
+            match alg {
+                $($crate::__private::hpke_rs_crypto::types::KdfAlgorithm::$kdf)|+ => Ok(()),
+                #[allow(unreachable_patterns)]
+                _ => Err($crate::__private::hpke_rs_crypto::error::Error::UnknownKdfAlgorithm),
+            }
+        }
+
+        /// Returns an error if the KEM algorithm is not supported by this crypto provider.
+        fn supports_kem(
+            alg: $crate::__private::hpke_rs_crypto::types::KemAlgorithm,
+        ) -> Result<(), $crate::__private::hpke_rs_crypto::error::Error> {
+            match alg {
+                $($crate::__private::hpke_rs_crypto::types::KemAlgorithm::$kem)|+ => Ok(()),
+                #[allow(unreachable_patterns)]
+                _ => Err($crate::__private::hpke_rs_crypto::error::Error::UnknownKemAlgorithm),
+            }
+        }
+
+        /// Returns an error if the AEAD algorithm is not supported by this crypto provider.
+        fn supports_aead(
+            alg: $crate::__private::hpke_rs_crypto::types::AeadAlgorithm,
+        ) -> Result<(), $crate::__private::hpke_rs_crypto::error::Error> {
+            match alg {
+                $($crate::__private::hpke_rs_crypto::types::AeadAlgorithm::$aead)|+ => Ok(()),
+                #[allow(unreachable_patterns)]
+                _ => Err($crate::__private::hpke_rs_crypto::error::Error::UnknownAeadAlgorithm),
+            }
+        }
+    };
+}
+
+/// Generate functions mapping HPKE algorithm identifiers to the algorithm
+/// types of a crypto library.
+///
+/// Each function maps the listed variants of the HPKE algorithm type
+/// (`KdfAlgorithm`, `KemAlgorithm`, or `AeadAlgorithm`) and rejects all
+/// others with the given error variant.
+///
+/// ```
+/// # use hpke_rs_crypto::{error::Error, types::*};
+/// #[derive(Debug, PartialEq)]
+/// enum Curve {
+///     X25519,
+///     P256,
+/// }
+///
+/// hpke_rs_provider_macros::algorithm_map! {
+///     /// Map the KEM to its curve.
+///     fn kem_to_curve(KemAlgorithm) -> Curve, else UnknownKemAlgorithm {
+///         DhKem25519 => Curve::X25519,
+///         DhKemP256 => Curve::P256,
+///     }
+/// }
+///
+/// assert_eq!(kem_to_curve(KemAlgorithm::DhKemP256).unwrap(), Curve::P256);
+/// assert!(kem_to_curve(KemAlgorithm::DhKemP384).is_err());
+/// ```
+#[macro_export]
+macro_rules! algorithm_map {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($alg:ident) -> $out:ty, else $error:ident {
+            $($variant:ident => $value:expr),+ $(,)?
+        }
+    )+) => {$(
+        $(#[$meta])*
+        #[inline(always)]
+        $vis fn $name(
+            alg: $crate::__private::hpke_rs_crypto::types::$alg,
+        ) -> Result<$out, $crate::__private::hpke_rs_crypto::error::Error> {
+            match alg {
+                $($crate::__private::hpke_rs_crypto::types::$alg::$variant => Ok($value),)+
+                #[allow(unreachable_patterns)]
+                _ => Err($crate::__private::hpke_rs_crypto::error::Error::$error),
+            }
+        }
+    )+};
+}
+
+/// Generate functions converting crypto library errors into HPKE crypto
+/// errors.
+///
+/// A conversion either maps to a unit variant of the HPKE crypto error, or
+/// to a `CryptoLibraryError` with the given message and the debug
+/// representation of the library error.
+///
+/// ```
+/// # use hpke_rs_crypto::error::Error;
+/// #[derive(Debug)]
+/// struct LibError;
+///
+/// hpke_rs_provider_macros::error_map! {
+///     fn aead_error(LibError) => AeadOpenError;
+///     fn ecdh_error(LibError) => CryptoLibraryError("ECDH error");
+/// }
+///
+/// assert!(matches!(aead_error(LibError), Error::AeadOpenError));
+/// assert!(matches!(
+///     ecdh_error(LibError),
+///     Error::CryptoLibraryError(msg) if msg == "ECDH error: LibError"
+/// ));
+/// ```
+#[macro_export]
+macro_rules! error_map {
+    () => {};
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($from:ty) => CryptoLibraryError($msg:literal);
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        #[inline(always)]
+        $vis fn $name(e: $from) -> $crate::__private::hpke_rs_crypto::error::Error {
+            $crate::__private::hpke_rs_crypto::error::Error::CryptoLibraryError(
+                $crate::__private::format!("{}: {:?}", $msg, e),
+            )
+        }
+        $crate::error_map! { $($rest)* }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($from:ty) => $error:ident;
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        #[inline(always)]
+        $vis fn $name(_: $from) -> $crate::__private::hpke_rs_crypto::error::Error {
+            $crate::__private::hpke_rs_crypto::error::Error::$error
+        }
+        $crate::error_map! { $($rest)* }
+    };
+}
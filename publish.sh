@@ -6,11 +6,29 @@ set -e
 # "hpke-rs-crypto
 cd traits && cargo publish $@ && cd -
 
+# hpke-rs-provider-macros
+cd provider_macros && cargo publish $@ && cd -
+
 # hpke-rs-libcrux
 cd libcrux_provider && cargo publish $@ && cd -
 
 # hpke-rs-rust-crypto
 cd rust_crypto_provider && cargo publish $@ && cd -
 
+# hpke-rs-mbedtls
+cd mbedtls_provider && cargo publish $@ && cd -
+
+# hpke-rs-pkcs11
+cd pkcs11_provider && cargo publish $@ && cd -
+
+# hpke-rs-platform
+cd platform_provider && cargo publish $@ && cd -
+
+# hpke-rs-evercrypt
+cd evercrypt_provider && cargo publish $@ && cd -
+
+# hpke-rs-webcrypto
+cd webcrypto_provider && cargo publish $@ && cd -
+
 # hpke-rs
 cargo publish $@
@@ -1,9 +1,10 @@
 use alloc::{format, vec::Vec};
 
 use aes_gcm::{Aes128Gcm as RC_Aes128Gcm, Aes256Gcm as RC_Aes256Gcm};
+use chacha20poly1305::aead::generic_array::{typenum::Unsigned, GenericArray};
 use chacha20poly1305::{
-    aead::{Aead, KeyInit, Payload},
-    ChaCha20Poly1305 as RC_ChaCha20Poly1305,
+    aead::{Aead, AeadCore, AeadInPlace, KeyInit, Payload},
+    ChaCha20Poly1305 as RC_ChaCha20Poly1305, XChaCha20Poly1305 as RC_XChaCha20Poly1305,
 };
 use hpke_rs_crypto::{error::Error, types::AeadAlgorithm, HpkeCrypto};
 
@@ -17,7 +18,7 @@ macro_rules! implement_aead {
             aad: &[u8],
             msg: &[u8],
         ) -> Result<Vec<u8>, Error> {
-            if nonce.len() != 12 {
+            if nonce.len() != <$algorithm as AeadCore>::NonceSize::USIZE {
                 return Err(Error::AeadInvalidNonce);
             }
 
@@ -39,7 +40,7 @@ macro_rules! implement_aead {
                 return Err(Error::AeadInvalidNonce);
             }
             let tag_length = HpkeRustCrypto::aead_tag_length(alg);
-            if msg.len() <= tag_length {
+            if msg.len() < tag_length {
                 return Err(Error::AeadInvalidCiphertext);
             }
 
@@ -61,3 +62,137 @@ implement_aead!(
     ChaCha20Poly1305,
     RC_ChaCha20Poly1305
 );
+implement_aead!(
+    xchacha_seal,
+    xchacha_open,
+    XChaCha20Poly1305,
+    RC_XChaCha20Poly1305
+);
+
+/// Encrypt `buffer` in place with the `Cipher` and return the detached tag.
+pub(crate) fn seal_in_place<Cipher: AeadInPlace + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    buffer: &mut [u8],
+) -> Result<Vec<u8>, Error> {
+    if nonce.len() != Cipher::NonceSize::USIZE {
+        return Err(Error::AeadInvalidNonce);
+    }
+    let cipher = Cipher::new_from_slice(key)
+        .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))?;
+    cipher
+        .encrypt_in_place_detached(GenericArray::from_slice(nonce), aad, buffer)
+        .map(|tag| tag.to_vec())
+        .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))
+}
+
+/// Decrypt `buffer` in place with the `Cipher` and the detached `tag`.
+pub(crate) fn open_in_place<Cipher: AeadInPlace + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), Error> {
+    if nonce.len() != Cipher::NonceSize::USIZE {
+        return Err(Error::AeadInvalidNonce);
+    }
+    if tag.len() != <Cipher as AeadCore>::TagSize::USIZE {
+        return Err(Error::AeadInvalidCiphertext);
+    }
+    let cipher = Cipher::new_from_slice(key)
+        .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))?;
+    cipher
+        .decrypt_in_place_detached(
+            GenericArray::from_slice(nonce),
+            aad,
+            buffer,
+            GenericArray::from_slice(tag),
+        )
+        .map_err(|_| Error::AeadOpenError)
+}
+
+/// AEGIS with 128 bit tags.
+///
+/// The `aegis` crate has its own API, so these don't go through the RustCrypto
+/// AEAD traits.
+macro_rules! implement_aegis {
+    ($name:ident, $module:ident, $cipher:ident) => {
+        pub(crate) mod $name {
+            use alloc::{format, vec::Vec};
+
+            use aegis::$module::$cipher;
+            use hpke_rs_crypto::error::Error;
+
+            /// The length of the tag.
+            const TAG_LEN: usize = 16;
+
+            fn cipher(key: &[u8], nonce: &[u8]) -> Result<$cipher<TAG_LEN>, Error> {
+                let key = key.try_into().map_err(|_| {
+                    Error::CryptoLibraryError(format!(
+                        "AEAD error: invalid key length {}",
+                        key.len()
+                    ))
+                })?;
+                let nonce = nonce.try_into().map_err(|_| Error::AeadInvalidNonce)?;
+                Ok($cipher::new(key, nonce))
+            }
+
+            /// Encrypt `buffer` in place and return the detached tag.
+            pub(crate) fn seal_in_place(
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                buffer: &mut [u8],
+            ) -> Result<Vec<u8>, Error> {
+                Ok(cipher(key, nonce)?.encrypt_in_place(buffer, aad).to_vec())
+            }
+
+            /// Decrypt `buffer` in place with the detached `tag`.
+            pub(crate) fn open_in_place(
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                buffer: &mut [u8],
+                tag: &[u8],
+            ) -> Result<(), Error> {
+                let tag = tag.try_into().map_err(|_| Error::AeadInvalidCiphertext)?;
+                cipher(key, nonce)?
+                    .decrypt_in_place(buffer, tag, aad)
+                    .map_err(|_| Error::AeadOpenError)
+            }
+
+            pub(crate) fn seal(
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Error> {
+                let mut ctxt = msg.to_vec();
+                let tag = seal_in_place(key, nonce, aad, &mut ctxt)?;
+                ctxt.extend_from_slice(&tag);
+                Ok(ctxt)
+            }
+
+            pub(crate) fn open(
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Error> {
+                let ctxt_len = msg
+                    .len()
+                    .checked_sub(TAG_LEN)
+                    .ok_or(Error::AeadInvalidCiphertext)?;
+                let (ctxt, tag) = msg.split_at(ctxt_len);
+                let mut ptxt = ctxt.to_vec();
+                open_in_place(key, nonce, aad, &mut ptxt, tag)?;
+                Ok(ptxt)
+            }
+        }
+    };
+}
+
+implement_aegis!(aegis128l, aegis128l, Aegis128L);
+implement_aegis!(aegis256, aegis256, Aegis256);
@@ -0,0 +1,646 @@
+//! # Generic RustCrypto Provider
+//!
+//! [`RustCryptoProvider`] implements [`HpkeCrypto`] for any combination of
+//! primitives implementing the RustCrypto traits:
+//! - KDFs are HKDF over a hash function implementing [`Digest`] ([`Kdf`]),
+//! - AEADs implement [`aead::Aead`] and [`KeyInit`] ([`Aead`]),
+//! - DH KEMs use an elliptic curve implementing [`CurveArithmetic`] with
+//!   SEC1 point encodings ([`DhKem`]).
+//!
+//! Each primitive is described by a small marker type that names its HPKE
+//! algorithm identifier.
+//! A provider can hold several primitives of each kind as a tuple:
+//!
+//! ```
+//! use hpke_rs_rust_crypto::generic::*;
+//!
+//! type Provider = RustCryptoProvider<
+//!     (DhKemP256, DhKemK256),
+//!     (HkdfSha256, HkdfSha384),
+//!     (Aes128Gcm, ChaCha20Poly1305),
+//! >;
+//! ```
+//!
+//! New primitives only need a marker type.
+//! For example, for the curve of the `p256` crate:
+//!
+//! ```
+//! use hpke_rs_crypto::{types::KemAlgorithm, HpkeCrypto};
+//! use hpke_rs_rust_crypto::generic::*;
+//!
+//! struct MyDhKem;
+//!
+//! impl DhKem for MyDhKem {
+//!     const ALG: KemAlgorithm = KemAlgorithm::DhKemP256;
+//!     type Curve = p256::NistP256;
+//! }
+//!
+//! type Provider = RustCryptoProvider<MyDhKem, HkdfSha256, Aes128Gcm>;
+//! assert!(Provider::supports_kem(KemAlgorithm::DhKemP256).is_ok());
+//! ```
+//!
+//! The export-only AEAD is always supported.
+//! Note that X25519 and X448 don't implement the elliptic curve traits and
+//! are not supported by this provider.
+
+use alloc::{format, string::String, vec, vec::Vec};
+use core::marker::PhantomData;
+
+use aead::{AeadCore, KeyInit, Payload};
+use digest::{core_api::BlockSizeUser, Digest};
+use elliptic_curve::{
+    ecdh::diffie_hellman,
+    generic_array::typenum::Unsigned,
+    sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+    AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey, SecretKey,
+};
+use hkdf::SimpleHkdf;
+use hpke_rs_crypto::{
+    error::Error,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+use rand_core::CryptoRngCore;
+
+use crate::{HpkeRustCrypto, HpkeRustCryptoPrng};
+
+/// An HKDF over a RustCrypto hash function.
+pub trait Kdf {
+    /// The HPKE algorithm identifier.
+    const ALG: KdfAlgorithm;
+
+    /// The hash function.
+    type Hash: Digest + BlockSizeUser + Clone;
+}
+
+/// A RustCrypto AEAD.
+pub trait Aead {
+    /// The HPKE algorithm identifier.
+    const ALG: AeadAlgorithm;
+
+    /// The AEAD implementation.
+    type Cipher: aead::Aead + KeyInit;
+}
+
+/// A DH KEM over a RustCrypto elliptic curve.
+pub trait DhKem {
+    /// The HPKE algorithm identifier.
+    const ALG: KemAlgorithm;
+
+    /// The elliptic curve.
+    type Curve: CurveArithmetic;
+}
+
+/// A set of KDFs, i.e. a [`Kdf`] or a tuple of KDF sets.
+pub trait KdfSet {
+    /// Returns `true` if the set contains `alg`.
+    fn contains(alg: KdfAlgorithm) -> bool;
+
+    /// HKDF extract with `alg`.
+    fn extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// HKDF expand with `alg`.
+    fn expand(
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// A set of AEADs, i.e. an [`Aead`] or a tuple of AEAD sets.
+pub trait AeadSet {
+    /// Returns `true` if the set contains `alg`.
+    fn contains(alg: AeadAlgorithm) -> bool;
+
+    /// AEAD encrypt with `alg`.
+    fn seal(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// AEAD decrypt with `alg`.
+    fn open(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// A set of DH KEMs, i.e. a [`DhKem`] or a tuple of DH KEM sets.
+pub trait DhKemSet {
+    /// Returns `true` if the set contains `alg`.
+    fn contains(alg: KemAlgorithm) -> bool;
+
+    /// Diffie-Hellman with `alg`.
+    fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Compute the public key for `sk` with `alg`.
+    fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Generate a key pair (public key, private key) for `alg`.
+    fn key_gen(
+        alg: KemAlgorithm,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error>;
+
+    /// Validate the private key `sk` for `alg`.
+    fn validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Validate the public key `pk` for `alg`.
+    fn validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error>;
+
+    /// Encode the public key `pk` for `alg` in the `compressed` or
+    /// uncompressed SEC1 form.
+    fn encode_pk(alg: KemAlgorithm, pk: &[u8], compressed: bool) -> Result<Vec<u8>, Error>;
+}
+
+impl<K: Kdf> KdfSet for K {
+    fn contains(alg: KdfAlgorithm) -> bool {
+        alg == K::ALG
+    }
+
+    fn extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKdfAlgorithm);
+        }
+        Ok(SimpleHkdf::<K::Hash>::extract(Some(salt), ikm)
+            .0
+            .as_slice()
+            .into())
+    }
+
+    fn expand(
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKdfAlgorithm);
+        }
+        let hkdf =
+            SimpleHkdf::<K::Hash>::from_prk(prk).map_err(|_| Error::HpkeInvalidOutputLength)?;
+        let mut okm = vec![0u8; output_size];
+        hkdf.expand(info, &mut okm)
+            .map_err(|_| Error::HpkeInvalidOutputLength)?;
+        Ok(okm)
+    }
+}
+
+impl<A: Aead> AeadSet for A {
+    fn contains(alg: AeadAlgorithm) -> bool {
+        alg == A::ALG
+    }
+
+    fn seal(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if alg != A::ALG {
+            return Err(Error::UnknownAeadAlgorithm);
+        }
+        if nonce.len() != <A::Cipher as AeadCore>::NonceSize::USIZE {
+            return Err(Error::AeadInvalidNonce);
+        }
+        let cipher = A::Cipher::new_from_slice(key)
+            .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))?;
+        aead::Aead::encrypt(&cipher, nonce.into(), Payload { msg, aad })
+            .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))
+    }
+
+    fn open(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        if alg != A::ALG {
+            return Err(Error::UnknownAeadAlgorithm);
+        }
+        if nonce.len() != <A::Cipher as AeadCore>::NonceSize::USIZE {
+            return Err(Error::AeadInvalidNonce);
+        }
+        if msg.len() < <A::Cipher as AeadCore>::TagSize::USIZE {
+            return Err(Error::AeadInvalidCiphertext);
+        }
+        let cipher = A::Cipher::new_from_slice(key)
+            .map_err(|e| Error::CryptoLibraryError(format!("AEAD error: {:?}", e)))?;
+        aead::Aead::decrypt(&cipher, nonce.into(), Payload { msg, aad })
+            .map_err(|_| Error::AeadOpenError)
+    }
+}
+
+impl<K: DhKem> DhKemSet for K
+where
+    FieldBytesSize<K::Curve>: ModulusSize,
+    AffinePoint<K::Curve>: FromEncodedPoint<K::Curve> + ToEncodedPoint<K::Curve>,
+{
+    fn contains(alg: KemAlgorithm) -> bool {
+        alg == K::ALG
+    }
+
+    fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        let sk = SecretKey::<K::Curve>::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
+        let pk =
+            PublicKey::<K::Curve>::from_sec1_bytes(pk).map_err(|_| Error::KemInvalidPublicKey)?;
+        Ok(diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine())
+            .raw_secret_bytes()
+            .as_slice()
+            .into())
+    }
+
+    fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        let sk = SecretKey::<K::Curve>::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
+        Ok(sk.public_key().to_encoded_point(false).as_bytes().into())
+    }
+
+    fn key_gen(
+        alg: KemAlgorithm,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        let sk = SecretKey::<K::Curve>::random(rng);
+        let pk = sk.public_key().to_encoded_point(false).as_bytes().into();
+        let sk = sk.to_bytes().as_slice().into();
+        Ok((pk, sk))
+    }
+
+    fn validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        SecretKey::<K::Curve>::from_slice(sk)
+            .map_err(|_| Error::KemInvalidSecretKey)
+            .map(|_| sk.into())
+    }
+
+    fn validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        PublicKey::<K::Curve>::from_sec1_bytes(pk)
+            .map_err(|_| Error::KemInvalidPublicKey)
+            .map(|_| ())
+    }
+
+    fn encode_pk(alg: KemAlgorithm, pk: &[u8], compressed: bool) -> Result<Vec<u8>, Error> {
+        if alg != K::ALG {
+            return Err(Error::UnknownKemAlgorithm);
+        }
+        PublicKey::<K::Curve>::from_sec1_bytes(pk)
+            .map_err(|_| Error::KemInvalidPublicKey)
+            .map(|pk| pk.to_encoded_point(compressed).as_bytes().into())
+    }
+}
+
+macro_rules! implement_tuple_sets {
+    ($($t:ident),+) => {
+        impl<$($t: KdfSet),+> KdfSet for ($($t,)+) {
+            fn contains(alg: KdfAlgorithm) -> bool {
+                $($t::contains(alg))||+
+            }
+
+            fn extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::extract(alg, salt, ikm);
+                })+
+                Err(Error::UnknownKdfAlgorithm)
+            }
+
+            fn expand(
+                alg: KdfAlgorithm,
+                prk: &[u8],
+                info: &[u8],
+                output_size: usize,
+            ) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::expand(alg, prk, info, output_size);
+                })+
+                Err(Error::UnknownKdfAlgorithm)
+            }
+        }
+
+        impl<$($t: AeadSet),+> AeadSet for ($($t,)+) {
+            fn contains(alg: AeadAlgorithm) -> bool {
+                $($t::contains(alg))||+
+            }
+
+            fn seal(
+                alg: AeadAlgorithm,
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::seal(alg, key, nonce, aad, msg);
+                })+
+                Err(Error::UnknownAeadAlgorithm)
+            }
+
+            fn open(
+                alg: AeadAlgorithm,
+                key: &[u8],
+                nonce: &[u8],
+                aad: &[u8],
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::open(alg, key, nonce, aad, msg);
+                })+
+                Err(Error::UnknownAeadAlgorithm)
+            }
+        }
+
+        impl<$($t: DhKemSet),+> DhKemSet for ($($t,)+) {
+            fn contains(alg: KemAlgorithm) -> bool {
+                $($t::contains(alg))||+
+            }
+
+            fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::dh(alg, pk, sk);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+
+            fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::secret_to_public(alg, sk);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+
+            fn key_gen(
+                alg: KemAlgorithm,
+                rng: &mut impl CryptoRngCore,
+            ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+                $(if $t::contains(alg) {
+                    return $t::key_gen(alg, rng);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+
+            fn validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::validate_sk(alg, sk);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+
+            fn validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+                $(if $t::contains(alg) {
+                    return $t::validate_pk(alg, pk);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+
+            fn encode_pk(
+                alg: KemAlgorithm,
+                pk: &[u8],
+                compressed: bool,
+            ) -> Result<Vec<u8>, Error> {
+                $(if $t::contains(alg) {
+                    return $t::encode_pk(alg, pk, compressed);
+                })+
+                Err(Error::UnknownKemAlgorithm)
+            }
+        }
+    };
+}
+
+implement_tuple_sets!(A);
+implement_tuple_sets!(A, B);
+implement_tuple_sets!(A, B, C);
+implement_tuple_sets!(A, B, C, D);
+
+/// HKDF-SHA256
+#[derive(Debug)]
+pub struct HkdfSha256;
+
+impl Kdf for HkdfSha256 {
+    const ALG: KdfAlgorithm = KdfAlgorithm::HkdfSha256;
+    type Hash = sha2::Sha256;
+}
+
+/// HKDF-SHA384
+#[derive(Debug)]
+pub struct HkdfSha384;
+
+impl Kdf for HkdfSha384 {
+    const ALG: KdfAlgorithm = KdfAlgorithm::HkdfSha384;
+    type Hash = sha2::Sha384;
+}
+
+/// HKDF-SHA512
+#[derive(Debug)]
+pub struct HkdfSha512;
+
+impl Kdf for HkdfSha512 {
+    const ALG: KdfAlgorithm = KdfAlgorithm::HkdfSha512;
+    type Hash = sha2::Sha512;
+}
+
+/// AES-128-GCM
+#[derive(Debug)]
+pub struct Aes128Gcm;
+
+impl Aead for Aes128Gcm {
+    const ALG: AeadAlgorithm = AeadAlgorithm::Aes128Gcm;
+    type Cipher = aes_gcm::Aes128Gcm;
+}
+
+/// AES-256-GCM
+#[derive(Debug)]
+pub struct Aes256Gcm;
+
+impl Aead for Aes256Gcm {
+    const ALG: AeadAlgorithm = AeadAlgorithm::Aes256Gcm;
+    type Cipher = aes_gcm::Aes256Gcm;
+}
+
+/// ChaCha20Poly1305
+#[derive(Debug)]
+pub struct ChaCha20Poly1305;
+
+impl Aead for ChaCha20Poly1305 {
+    const ALG: AeadAlgorithm = AeadAlgorithm::ChaCha20Poly1305;
+    type Cipher = chacha20poly1305::ChaCha20Poly1305;
+}
+
+/// DHKEM(P-256, HKDF-SHA256)
+#[derive(Debug)]
+pub struct DhKemP256;
+
+impl DhKem for DhKemP256 {
+    const ALG: KemAlgorithm = KemAlgorithm::DhKemP256;
+    type Curve = p256::NistP256;
+}
+
+/// DHKEM(secp256k1, HKDF-SHA256)
+#[derive(Debug)]
+pub struct DhKemK256;
+
+impl DhKem for DhKemK256 {
+    const ALG: KemAlgorithm = KemAlgorithm::DhKemK256;
+    type Curve = k256::Secp256k1;
+}
+
+/// DHKEM(P-521, HKDF-SHA512)
+#[derive(Debug)]
+pub struct DhKemP521;
+
+impl DhKem for DhKemP521 {
+    const ALG: KemAlgorithm = KemAlgorithm::DhKemP521;
+    type Curve = p521::NistP521;
+}
+
+/// An HPKE crypto provider assembled from RustCrypto primitives.
+///
+/// See the [module documentation](self) for details.
+pub struct RustCryptoProvider<Kem, Kdf, Aead> {
+    phantom: PhantomData<Components<Kem, Kdf, Aead>>,
+}
+
+/// The components of a [`RustCryptoProvider`], which are only used at the
+/// type level and therefore don't affect `Send` and `Sync`.
+type Components<Kem, Kdf, Aead> = fn() -> (Kem, Kdf, Aead);
+
+impl<Kem, Kdf, Aead> core::fmt::Debug for RustCryptoProvider<Kem, Kdf, Aead> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RustCryptoProvider").finish()
+    }
+}
+
+impl<Kem: DhKemSet, Kdf: KdfSet, Aead: AeadSet> HpkeCrypto for RustCryptoProvider<Kem, Kdf, Aead> {
+    type HpkePrng = HpkeRustCryptoPrng;
+
+    fn name() -> String {
+        "RustCrypto (generic)".into()
+    }
+
+    fn supports_kdf(alg: KdfAlgorithm) -> Result<(), Error> {
+        if Kdf::contains(alg) {
+            Ok(())
+        } else {
+            Err(Error::UnknownKdfAlgorithm)
+        }
+    }
+
+    fn supports_kem(alg: KemAlgorithm) -> Result<(), Error> {
+        if Kem::contains(alg) {
+            Ok(())
+        } else {
+            Err(Error::UnknownKemAlgorithm)
+        }
+    }
+
+    fn supports_aead(alg: AeadAlgorithm) -> Result<(), Error> {
+        if alg == AeadAlgorithm::HpkeExport || Aead::contains(alg) {
+            Ok(())
+        } else {
+            Err(Error::UnknownAeadAlgorithm)
+        }
+    }
+
+    fn prng() -> Self::HpkePrng {
+        HpkeRustCrypto::prng()
+    }
+
+    fn kdf_extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+        Kdf::extract(alg, salt, ikm)
+    }
+
+    fn kdf_expand(
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        Kdf::expand(alg, prk, info, output_size)
+    }
+
+    fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        Kem::dh(alg, pk, sk)
+    }
+
+    fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        Kem::secret_to_public(alg, sk)
+    }
+
+    fn kem_key_gen(
+        alg: KemAlgorithm,
+        prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        Kem::key_gen(alg, &mut prng.rng)
+    }
+
+    fn kem_key_gen_derand(_alg: KemAlgorithm, _seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // Only DH KEMs are supported.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_encaps(
+        _alg: KemAlgorithm,
+        _pk_r: &[u8],
+        _prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        // Only DH KEMs are supported.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn kem_decaps(_alg: KemAlgorithm, _ct: &[u8], _sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+        // Only DH KEMs are supported.
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    fn dh_validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        Kem::validate_sk(alg, sk)
+    }
+
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        Kem::validate_pk(alg, pk)
+    }
+
+    fn dh_encode_pk(alg: KemAlgorithm, pk: &[u8], compressed: bool) -> Result<Vec<u8>, Error> {
+        Kem::encode_pk(alg, pk, compressed)
+    }
+
+    fn aead_seal(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        Aead::seal(alg, key, nonce, aad, msg)
+    }
+
+    fn aead_open(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        Aead::open(alg, key, nonce, aad, msg)
+    }
+}
@@ -0,0 +1,109 @@
+//! KMAC256 (NIST SP 800-185) as HPKE KDF.
+//!
+//! - `Extract(salt, ikm) = KMAC256(salt, ikm, 512, "HPKE Extract")`
+//! - `Expand(prk, info, L) = KMAC256(prk, info, 8 * L, "HPKE Expand")`
+
+use alloc::{vec, vec::Vec};
+
+use hpke_rs_crypto::error::Error;
+use sha3::{
+    digest::{core_api::CoreWrapper, ExtendableOutput, Update},
+    CShake256Core,
+};
+
+/// The output length of the extract step (`Nh`).
+const EXTRACT_LEN: usize = 64;
+
+/// The rate of cSHAKE256 in bytes.
+const RATE: usize = 136;
+
+/// Encode `x` with its length prefixed (`left_encode`) or appended
+/// (`right_encode`).
+fn encode(x: u64, left: bool) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|&&b| b == 0).count();
+    let value = bytes.get(skip..).unwrap_or_default();
+    let len = [u8::try_from(value.len()).unwrap_or_default()];
+    if left {
+        [&len[..], value].concat()
+    } else {
+        [value, &len[..]].concat()
+    }
+}
+
+/// Get the length of `bytes` in bits.
+fn bit_len(bytes: usize) -> Result<u64, Error> {
+    u64::try_from(bytes)
+        .ok()
+        .and_then(|len| len.checked_mul(8))
+        .ok_or(Error::HpkeInvalidOutputLength)
+}
+
+/// Compute `output_size` bytes of `KMAC256(key, data, 8 * output_size, customization)`.
+fn kmac256(
+    key: &[u8],
+    data: &[u8],
+    output_size: usize,
+    customization: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let mut kmac = CoreWrapper::from_core(CShake256Core::new_with_function_name(
+        b"KMAC",
+        customization,
+    ));
+
+    // bytepad(encode_string(key), RATE)
+    let mut padded = encode(RATE as u64, true);
+    padded.extend_from_slice(&encode(bit_len(key.len())?, true));
+    padded.extend_from_slice(key);
+    padded.resize(padded.len().next_multiple_of(RATE), 0);
+    kmac.update(&padded);
+
+    kmac.update(data);
+    kmac.update(&encode(bit_len(output_size)?, false));
+    let mut out = vec![0u8; output_size];
+    kmac.finalize_xof_into(&mut out);
+    Ok(out)
+}
+
+pub(crate) fn extract(salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
+    kmac256(salt, ikm, EXTRACT_LEN, b"HPKE Extract")
+}
+
+pub(crate) fn expand(prk: &[u8], info: &[u8], output_size: usize) -> Result<Vec<u8>, Error> {
+    kmac256(prk, info, output_size, b"HPKE Expand")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::kmac256;
+
+    // KMAC256 samples #4 and #5 of NIST SP 800-185.
+    #[test]
+    fn kmac256_kat() {
+        let key: Vec<u8> = (0x40..0x60).collect();
+        let customization = b"My Tagged Application";
+
+        let expected = [
+            0x20, 0xc5, 0x70, 0xc3, 0x13, 0x46, 0xf7, 0x03, 0xc9, 0xac, 0x36, 0xc6, 0x1c, 0x03,
+            0xcb, 0x64, 0xc3, 0x97, 0x0d, 0x0c, 0xfc, 0x78, 0x7e, 0x9b, 0x79, 0x59, 0x9d, 0x27,
+            0x3a, 0x68, 0xd2, 0xf7, 0xf6, 0x9d, 0x4c, 0xc3, 0xde, 0x9d, 0x10, 0x4a, 0x35, 0x16,
+            0x89, 0xf2, 0x7c, 0xf6, 0xf5, 0x95, 0x1f, 0x01, 0x03, 0xf3, 0x3f, 0x4f, 0x24, 0x87,
+            0x10, 0x24, 0xd9, 0xc2, 0x77, 0x73, 0xa8, 0xdd,
+        ];
+        let out = kmac256(&key, &[0x00, 0x01, 0x02, 0x03], 64, customization).unwrap();
+        assert_eq!(out, expected);
+
+        let data: Vec<u8> = (0..200).collect();
+        let expected = [
+            0xb5, 0x86, 0x18, 0xf7, 0x1f, 0x92, 0xe1, 0xd5, 0x6c, 0x1b, 0x8c, 0x55, 0xdd, 0xd7,
+            0xcd, 0x18, 0x8b, 0x97, 0xb4, 0xca, 0x4d, 0x99, 0x83, 0x1e, 0xb2, 0x69, 0x9a, 0x83,
+            0x7d, 0xa2, 0xe4, 0xd9, 0x70, 0xfb, 0xac, 0xfd, 0xe5, 0x00, 0x33, 0xae, 0xa5, 0x85,
+            0xf1, 0xa2, 0x70, 0x85, 0x10, 0xc3, 0x2d, 0x07, 0x88, 0x08, 0x01, 0xbd, 0x18, 0x28,
+            0x98, 0xfe, 0x47, 0x68, 0x76, 0xfc, 0x89, 0x65,
+        ];
+        let out = kmac256(&key, &data, 64, customization).unwrap();
+        assert_eq!(out, expected);
+    }
+}
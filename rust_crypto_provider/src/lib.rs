@@ -21,11 +21,21 @@ use k256::{
     PublicKey as k256PublicKey, SecretKey as k256SecretKey,
 };
 
+use p521::{
+    elliptic_curve::ecdh::diffie_hellman as p521diffie_hellman, PublicKey as p521PublicKey,
+    SecretKey as p521SecretKey,
+};
+
 use rand_core::SeedableRng;
 use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret as X25519StaticSecret};
+use x448::{PublicKey as X448PublicKey, Secret as X448Secret};
 
 mod aead;
+pub mod generic;
 mod hkdf;
+mod kmac;
+mod mlkem;
+mod xwing;
 use crate::aead::*;
 use crate::hkdf::*;
 
@@ -40,17 +50,53 @@ pub struct HpkeRustCryptoPrng {
     fake_rng: Vec<u8>,
 }
 
+impl HpkeRustCryptoPrng {
+    /// Create a PRNG seeded from the external `rng`.
+    ///
+    /// Use the adapters in `hpke_rs_crypto::rng` for RNGs implementing the
+    /// `rand_core` 0.6 traits.
+    pub fn from_rng(rng: &mut impl CryptoRng) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        #[cfg(feature = "deterministic-prng")]
+        {
+            let mut fake_rng = alloc::vec![0u8; 256];
+            rng.fill_bytes(&mut fake_rng);
+            HpkeRustCryptoPrng {
+                fake_rng,
+                rng: rand_chacha::ChaCha20Rng::from_seed(seed),
+            }
+        }
+        #[cfg(not(feature = "deterministic-prng"))]
+        HpkeRustCryptoPrng {
+            rng: rand_chacha::ChaCha20Rng::from_seed(seed),
+        }
+    }
+
+    /// Draw the randomness of a KEM encapsulation.
+    ///
+    /// This uses the test bytes such that KATs can inject it.
+    fn encaps_randomness<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut randomness = [0u8; N];
+        self.try_fill_test_bytes(&mut randomness)
+            .map_err(|_| Error::InsufficientRandomness)?;
+        Ok(randomness)
+    }
+}
+
 impl HpkeCrypto for HpkeRustCrypto {
     fn name() -> String {
         "RustCrypto".into()
     }
 
     fn kdf_extract(alg: KdfAlgorithm, salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, Error> {
-        Ok(match alg {
-            KdfAlgorithm::HkdfSha256 => sha256_extract(salt, ikm),
-            KdfAlgorithm::HkdfSha384 => sha384_extract(salt, ikm),
-            KdfAlgorithm::HkdfSha512 => sha512_extract(salt, ikm),
-        })
+        match alg {
+            KdfAlgorithm::HkdfSha256 => Ok(sha256_extract(salt, ikm)),
+            KdfAlgorithm::HkdfSha384 => Ok(sha384_extract(salt, ikm)),
+            KdfAlgorithm::HkdfSha512 => Ok(sha512_extract(salt, ikm)),
+            KdfAlgorithm::Kmac256 => kmac::extract(salt, ikm),
+            _ => Err(Error::UnknownKdfAlgorithm),
+        }
     }
 
     fn kdf_expand(
@@ -63,6 +109,8 @@ impl HpkeCrypto for HpkeRustCrypto {
             KdfAlgorithm::HkdfSha256 => sha256_expand(prk, info, output_size),
             KdfAlgorithm::HkdfSha384 => sha384_expand(prk, info, output_size),
             KdfAlgorithm::HkdfSha512 => sha512_expand(prk, info, output_size),
+            KdfAlgorithm::Kmac256 => kmac::expand(prk, info, output_size),
+            _ => Err(Error::UnknownKdfAlgorithm),
         }
     }
 
@@ -85,6 +133,14 @@ impl HpkeCrypto for HpkeRustCrypto {
                     .as_bytes()
                     .to_vec())
             }
+            KemAlgorithm::DhKem448 => {
+                let sk = X448Secret::from_bytes(sk).ok_or(Error::KemInvalidSecretKey)?;
+                let pk = X448PublicKey::from_bytes(pk).ok_or(Error::KemInvalidPublicKey)?;
+                // Rejects the all-zero output of low order points.
+                sk.as_diffie_hellman(&pk)
+                    .map(|dh| dh.as_bytes().to_vec())
+                    .ok_or(Error::KemInvalidPublicKey)
+            }
             KemAlgorithm::DhKemP256 => {
                 let sk = p256SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
                 let pk =
@@ -103,27 +159,77 @@ impl HpkeCrypto for HpkeRustCrypto {
                     .as_slice()
                     .into())
             }
+            KemAlgorithm::DhKemP521 => {
+                let sk = p521_secret_key(sk)?;
+                let pk =
+                    p521PublicKey::from_sec1_bytes(pk).map_err(|_| Error::KemInvalidPublicKey)?;
+                Ok(p521diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine())
+                    .raw_secret_bytes()
+                    .as_slice()
+                    .into())
+            }
             _ => Err(Error::UnknownKemAlgorithm),
         }
     }
 
-    fn kem_key_gen_derand(_alg: KemAlgorithm, _seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
-        // No ciphersuite uses this.
-        return Err(Error::UnsupportedKemOperation);
+    fn kem_key_gen_derand(alg: KemAlgorithm, seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        match alg {
+            KemAlgorithm::XWingDraft06 => xwing::key_gen_derand(seed),
+            KemAlgorithm::MlKem512 => mlkem::ml_kem512::key_gen_derand(seed),
+            KemAlgorithm::MlKem768 => mlkem::ml_kem768::key_gen_derand(seed),
+            KemAlgorithm::MlKem1024 => mlkem::ml_kem1024::key_gen_derand(seed),
+            _ => Err(Error::UnsupportedKemOperation),
+        }
     }
 
     fn kem_encaps(
-        _alg: KemAlgorithm,
-        _pk_r: &[u8],
-        _prng: &mut Self::HpkePrng,
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        match alg {
+            KemAlgorithm::XWingDraft06 => {
+                let randomness = prng.encaps_randomness::<{ xwing::ENCAPS_RANDOMNESS_LEN }>()?;
+                xwing::encaps(pk_r, &randomness)
+            }
+            KemAlgorithm::MlKem512 => {
+                let randomness = prng.encaps_randomness::<{ mlkem::ENCAPS_RANDOMNESS_LEN }>()?;
+                mlkem::ml_kem512::encaps(pk_r, &randomness)
+            }
+            KemAlgorithm::MlKem768 => {
+                let randomness = prng.encaps_randomness::<{ mlkem::ENCAPS_RANDOMNESS_LEN }>()?;
+                mlkem::ml_kem768::encaps(pk_r, &randomness)
+            }
+            KemAlgorithm::MlKem1024 => {
+                let randomness = prng.encaps_randomness::<{ mlkem::ENCAPS_RANDOMNESS_LEN }>()?;
+                mlkem::ml_kem1024::encaps(pk_r, &randomness)
+            }
+            _ => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
+    fn kem_encaps_derand(
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        randomness: &[u8],
     ) -> Result<(Vec<u8>, Vec<u8>), Error> {
-        // No ciphersuite uses this.
-        return Err(Error::UnsupportedKemOperation);
+        match alg {
+            KemAlgorithm::XWingDraft06 => xwing::encaps(pk_r, randomness),
+            KemAlgorithm::MlKem512 => mlkem::ml_kem512::encaps(pk_r, randomness),
+            KemAlgorithm::MlKem768 => mlkem::ml_kem768::encaps(pk_r, randomness),
+            KemAlgorithm::MlKem1024 => mlkem::ml_kem1024::encaps(pk_r, randomness),
+            _ => Err(Error::UnsupportedKemOperation),
+        }
     }
 
-    fn kem_decaps(_alg: KemAlgorithm, _ct: &[u8], _sk_r: &[u8]) -> Result<Vec<u8>, Error> {
-        // No ciphersuite uses this.
-        return Err(Error::UnsupportedKemOperation);
+    fn kem_decaps(alg: KemAlgorithm, ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::XWingDraft06 => xwing::decaps(ct, sk_r),
+            KemAlgorithm::MlKem512 => mlkem::ml_kem512::decaps(ct, sk_r),
+            KemAlgorithm::MlKem768 => mlkem::ml_kem768::decaps(ct, sk_r),
+            KemAlgorithm::MlKem1024 => mlkem::ml_kem1024::decaps(ct, sk_r),
+            _ => Err(Error::UnsupportedKemOperation),
+        }
     }
 
     fn secret_to_public(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
@@ -137,6 +243,10 @@ impl HpkeCrypto for HpkeRustCrypto {
                 let sk = X25519StaticSecret::from(sk_array);
                 Ok(X25519PublicKey::from(&sk).as_bytes().to_vec())
             }
+            KemAlgorithm::DhKem448 => {
+                let sk = X448Secret::from_bytes(sk).ok_or(Error::KemInvalidSecretKey)?;
+                Ok(X448PublicKey::from(&sk).as_bytes().to_vec())
+            }
             KemAlgorithm::DhKemP256 => {
                 let sk = p256SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
                 Ok(sk.public_key().to_encoded_point(false).as_bytes().into())
@@ -145,6 +255,10 @@ impl HpkeCrypto for HpkeRustCrypto {
                 let sk = k256SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)?;
                 Ok(sk.public_key().to_encoded_point(false).as_bytes().into())
             }
+            KemAlgorithm::DhKemP521 => {
+                let sk = p521_secret_key(sk)?;
+                Ok(sk.public_key().to_encoded_point(false).as_bytes().into())
+            }
             _ => Err(Error::UnsupportedKemOperation),
         }
     }
@@ -161,6 +275,13 @@ impl HpkeCrypto for HpkeRustCrypto {
                 let sk = sk.to_bytes().to_vec();
                 Ok((pk, sk))
             }
+            KemAlgorithm::DhKem448 => {
+                let mut sk = alloc::vec![0u8; alg.private_key_len()];
+                rand_core::RngCore::fill_bytes(&mut *rng, &mut sk);
+                let sk = X448Secret::from_bytes(&sk).ok_or(Error::KemInvalidSecretKey)?;
+                let pk = X448PublicKey::from(&sk).as_bytes().to_vec();
+                Ok((pk, sk.as_bytes().to_vec()))
+            }
             KemAlgorithm::DhKemP256 => {
                 let sk = p256SecretKey::random(&mut *rng);
                 let pk = sk.public_key().to_encoded_point(false).as_bytes().into();
@@ -173,6 +294,20 @@ impl HpkeCrypto for HpkeRustCrypto {
                 let sk = sk.to_bytes().as_slice().into();
                 Ok((pk, sk))
             }
+            KemAlgorithm::DhKemP521 => {
+                let sk = p521SecretKey::random(&mut *rng);
+                let pk = sk.public_key().to_encoded_point(false).as_bytes().into();
+                let sk = sk.to_bytes().as_slice().into();
+                Ok((pk, sk))
+            }
+            KemAlgorithm::XWingDraft06
+            | KemAlgorithm::MlKem512
+            | KemAlgorithm::MlKem768
+            | KemAlgorithm::MlKem1024 => {
+                let mut seed = alloc::vec![0u8; alg.seed_len().unwrap_or(alg.private_key_len())];
+                rand_core::RngCore::fill_bytes(&mut *rng, &mut seed);
+                Self::kem_key_gen_derand(alg, &seed)
+            }
             _ => Err(Error::UnknownKemAlgorithm),
         }
     }
@@ -185,10 +320,50 @@ impl HpkeCrypto for HpkeRustCrypto {
             KemAlgorithm::DhKemK256 => k256SecretKey::from_slice(sk)
                 .map_err(|_| Error::KemInvalidSecretKey)
                 .map(|_| sk.into()),
+            KemAlgorithm::DhKemP521 => p521_secret_key(sk).map(|_| sk.into()),
+            _ => Err(Error::UnknownKemAlgorithm),
+        }
+    }
+
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        match alg {
+            KemAlgorithm::DhKem25519 => {
+                if pk.len() != 32 || pk.iter().all(|&b| b == 0) {
+                    return Err(Error::KemInvalidPublicKey);
+                }
+                Ok(())
+            }
+            KemAlgorithm::DhKem448 => X448PublicKey::from_bytes(pk)
+                .map(|_| ())
+                .ok_or(Error::KemInvalidPublicKey),
+            KemAlgorithm::DhKemP256 => p256PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|_| ()),
+            KemAlgorithm::DhKemK256 => k256PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|_| ()),
+            KemAlgorithm::DhKemP521 => p521PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|_| ()),
             _ => Err(Error::UnknownKemAlgorithm),
         }
     }
 
+    fn dh_encode_pk(alg: KemAlgorithm, pk: &[u8], compressed: bool) -> Result<Vec<u8>, Error> {
+        match alg {
+            KemAlgorithm::DhKemP256 => p256PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|pk| pk.to_encoded_point(compressed).as_bytes().into()),
+            KemAlgorithm::DhKemK256 => k256PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|pk| pk.to_encoded_point(compressed).as_bytes().into()),
+            KemAlgorithm::DhKemP521 => p521PublicKey::from_sec1_bytes(pk)
+                .map_err(|_| Error::KemInvalidPublicKey)
+                .map(|pk| pk.to_encoded_point(compressed).as_bytes().into()),
+            _ => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
     fn aead_seal(
         alg: AeadAlgorithm,
         key: &[u8],
@@ -200,7 +375,10 @@ impl HpkeCrypto for HpkeRustCrypto {
             AeadAlgorithm::Aes128Gcm => aes128_seal(key, nonce, aad, msg),
             AeadAlgorithm::Aes256Gcm => aes256_seal(key, nonce, aad, msg),
             AeadAlgorithm::ChaCha20Poly1305 => chacha_seal(key, nonce, aad, msg),
-            AeadAlgorithm::HpkeExport => Err(Error::UnknownAeadAlgorithm),
+            AeadAlgorithm::XChaCha20Poly1305 => xchacha_seal(key, nonce, aad, msg),
+            AeadAlgorithm::Aegis128L => aegis128l::seal(key, nonce, aad, msg),
+            AeadAlgorithm::Aegis256 => aegis256::seal(key, nonce, aad, msg),
+            _ => Err(Error::UnknownAeadAlgorithm),
         }
     }
 
@@ -215,7 +393,63 @@ impl HpkeCrypto for HpkeRustCrypto {
             AeadAlgorithm::Aes128Gcm => aes128_open(alg, key, nonce, aad, msg),
             AeadAlgorithm::Aes256Gcm => aes256_open(alg, key, nonce, aad, msg),
             AeadAlgorithm::ChaCha20Poly1305 => chacha_open(alg, key, nonce, aad, msg),
-            AeadAlgorithm::HpkeExport => Err(Error::UnknownAeadAlgorithm),
+            AeadAlgorithm::XChaCha20Poly1305 => xchacha_open(alg, key, nonce, aad, msg),
+            AeadAlgorithm::Aegis128L => aegis128l::open(key, nonce, aad, msg),
+            AeadAlgorithm::Aegis256 => aegis256::open(key, nonce, aad, msg),
+            _ => Err(Error::UnknownAeadAlgorithm),
+        }
+    }
+
+    fn aead_seal_in_place_detached(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Vec<u8>, Error> {
+        match alg {
+            AeadAlgorithm::Aes128Gcm => {
+                seal_in_place::<aes_gcm::Aes128Gcm>(key, nonce, aad, buffer)
+            }
+            AeadAlgorithm::Aes256Gcm => {
+                seal_in_place::<aes_gcm::Aes256Gcm>(key, nonce, aad, buffer)
+            }
+            AeadAlgorithm::ChaCha20Poly1305 => {
+                seal_in_place::<chacha20poly1305::ChaCha20Poly1305>(key, nonce, aad, buffer)
+            }
+            AeadAlgorithm::XChaCha20Poly1305 => {
+                seal_in_place::<chacha20poly1305::XChaCha20Poly1305>(key, nonce, aad, buffer)
+            }
+            AeadAlgorithm::Aegis128L => aegis128l::seal_in_place(key, nonce, aad, buffer),
+            AeadAlgorithm::Aegis256 => aegis256::seal_in_place(key, nonce, aad, buffer),
+            _ => Err(Error::UnknownAeadAlgorithm),
+        }
+    }
+
+    fn aead_open_in_place_detached(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        match alg {
+            AeadAlgorithm::Aes128Gcm => {
+                open_in_place::<aes_gcm::Aes128Gcm>(key, nonce, aad, buffer, tag)
+            }
+            AeadAlgorithm::Aes256Gcm => {
+                open_in_place::<aes_gcm::Aes256Gcm>(key, nonce, aad, buffer, tag)
+            }
+            AeadAlgorithm::ChaCha20Poly1305 => {
+                open_in_place::<chacha20poly1305::ChaCha20Poly1305>(key, nonce, aad, buffer, tag)
+            }
+            AeadAlgorithm::XChaCha20Poly1305 => {
+                open_in_place::<chacha20poly1305::XChaCha20Poly1305>(key, nonce, aad, buffer, tag)
+            }
+            AeadAlgorithm::Aegis128L => aegis128l::open_in_place(key, nonce, aad, buffer, tag),
+            AeadAlgorithm::Aegis256 => aegis256::open_in_place(key, nonce, aad, buffer, tag),
+            _ => Err(Error::UnknownAeadAlgorithm),
         }
     }
 
@@ -237,28 +471,39 @@ impl HpkeCrypto for HpkeRustCrypto {
         }
     }
 
-    /// Returns an error if the KDF algorithm is not supported by this crypto provider.
-    fn supports_kdf(_: KdfAlgorithm) -> Result<(), Error> {
-        Ok(())
-    }
-
-    /// Returns an error if the KEM algorithm is not supported by this crypto provider.
-    fn supports_kem(alg: KemAlgorithm) -> Result<(), Error> {
-        match alg {
-            KemAlgorithm::DhKem25519 | KemAlgorithm::DhKemP256 | KemAlgorithm::DhKemK256 => Ok(()),
-            _ => Err(Error::UnknownKemAlgorithm),
-        }
+    hpke_rs_provider_macros::supports! {
+        kdf: [HkdfSha256, HkdfSha384, HkdfSha512, Kmac256],
+        kem: [
+            DhKem25519,
+            DhKem448,
+            DhKemP256,
+            DhKemK256,
+            DhKemP521,
+            XWingDraft06,
+            MlKem512,
+            MlKem768,
+            MlKem1024,
+        ],
+        aead: [
+            Aes128Gcm,
+            Aes256Gcm,
+            ChaCha20Poly1305,
+            XChaCha20Poly1305,
+            Aegis128L,
+            Aegis256,
+            HpkeExport,
+        ],
     }
+}
 
-    /// Returns an error if the AEAD algorithm is not supported by this crypto provider.
-    fn supports_aead(alg: AeadAlgorithm) -> Result<(), Error> {
-        match alg {
-            AeadAlgorithm::Aes128Gcm
-            | AeadAlgorithm::Aes256Gcm
-            | AeadAlgorithm::ChaCha20Poly1305
-            | AeadAlgorithm::HpkeExport => Ok(()),
-        }
+/// Parse a P-521 private key of exactly 66 bytes.
+///
+/// `SecretKey::from_slice` would zero-pad shorter slices.
+fn p521_secret_key(sk: &[u8]) -> Result<p521SecretKey, Error> {
+    if sk.len() != KemAlgorithm::DhKemP521.private_key_len() {
+        return Err(Error::KemInvalidSecretKey);
     }
+    p521SecretKey::from_slice(sk).map_err(|_| Error::KemInvalidSecretKey)
 }
 
 // We need to implement the old and new traits here because the crytpo uses the
@@ -0,0 +1,107 @@
+//! ML-KEM (FIPS 203) as HPKE KEM.
+//!
+//! Private keys are either the 64 byte seed `d || z` or the expanded
+//! decapsulation key.
+
+/// The length of the key generation seed.
+const SEED_LEN: usize = 64;
+
+/// The length of the randomness for the encapsulation.
+pub(crate) const ENCAPS_RANDOMNESS_LEN: usize = 32;
+
+/// The modulus `q` of ML-KEM.
+const Q: u16 = 3329;
+
+/// Check that the 12 bit coefficients of the encapsulation key `ek` are
+/// reduced modulo `q` (FIPS 203, Section 7.2).
+fn valid_encapsulation_key(ek: &[u8]) -> bool {
+    // The encoded polynomials are followed by the 32 byte seed rho.
+    let polynomials = &ek[..ek.len().saturating_sub(32)];
+    polynomials.chunks_exact(3).all(|c| {
+        let c0 = u16::from(c[0]) | (u16::from(c[1] & 0x0f) << 8);
+        let c1 = u16::from(c[1] >> 4) | (u16::from(c[2]) << 4);
+        c0 < Q && c1 < Q
+    })
+}
+
+macro_rules! implement_ml_kem {
+    ($name:ident, $kem:ident) => {
+        pub(crate) mod $name {
+            use alloc::{format, vec::Vec};
+
+            use hpke_rs_crypto::error::Error;
+            use ml_kem::{
+                kem::Decapsulate, $kem, EncapsulateDeterministic, EncodedSizeUser, KemCore,
+            };
+
+            use super::{valid_encapsulation_key, ENCAPS_RANDOMNESS_LEN, SEED_LEN};
+
+            type DecapsulationKey = <$kem as KemCore>::DecapsulationKey;
+            type EncapsulationKey = <$kem as KemCore>::EncapsulationKey;
+
+            fn from_seed(seed: &[u8]) -> Result<(DecapsulationKey, EncapsulationKey), Error> {
+                if seed.len() != SEED_LEN {
+                    return Err(Error::KemInvalidSecretKey);
+                }
+                let (d, z) = seed.split_at(32);
+                Ok($kem::generate_deterministic(
+                    d.try_into().map_err(|_| Error::KemInvalidSecretKey)?,
+                    z.try_into().map_err(|_| Error::KemInvalidSecretKey)?,
+                ))
+            }
+
+            /// Derive the key pair (encapsulation key, decapsulation key) from
+            /// the `seed`.
+            pub(crate) fn key_gen_derand(seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+                let (dk, ek) = from_seed(seed)?;
+                Ok((ek.as_bytes().to_vec(), dk.as_bytes().to_vec()))
+            }
+
+            /// Encapsulate to `pk_r` with the `randomness` (shared secret,
+            /// ciphertext).
+            pub(crate) fn encaps(
+                pk_r: &[u8],
+                randomness: &[u8],
+            ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+                if randomness.len() != ENCAPS_RANDOMNESS_LEN {
+                    return Err(Error::InsufficientRandomness);
+                }
+                if !valid_encapsulation_key(pk_r) {
+                    return Err(Error::KemInvalidPublicKey);
+                }
+                let ek = EncapsulationKey::from_bytes(
+                    pk_r.try_into().map_err(|_| Error::KemInvalidPublicKey)?,
+                );
+                let (ct, ss) = ek
+                    .encapsulate_deterministic(
+                        randomness
+                            .try_into()
+                            .map_err(|_| Error::InsufficientRandomness)?,
+                    )
+                    .map_err(|e| {
+                        Error::CryptoLibraryError(format!("ML-KEM encaps error: {:?}", e))
+                    })?;
+                Ok((ss.to_vec(), ct.to_vec()))
+            }
+
+            /// Decapsulate `ct` with the seed or decapsulation key `sk_r`.
+            pub(crate) fn decaps(ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+                let dk = if sk_r.len() == SEED_LEN {
+                    from_seed(sk_r)?.0
+                } else {
+                    DecapsulationKey::from_bytes(
+                        sk_r.try_into().map_err(|_| Error::KemInvalidSecretKey)?,
+                    )
+                };
+                // ML-KEM decapsulation rejects implicitly.
+                dk.decapsulate(ct.try_into().map_err(|_| Error::KemInvalidCiphertext)?)
+                    .map(|ss| ss.to_vec())
+                    .map_err(|e| Error::CryptoLibraryError(format!("ML-KEM decaps error: {:?}", e)))
+            }
+        }
+    };
+}
+
+implement_ml_kem!(ml_kem512, MlKem512);
+implement_ml_kem!(ml_kem768, MlKem768);
+implement_ml_kem!(ml_kem1024, MlKem1024);
@@ -0,0 +1,135 @@
+//! X-Wing (draft-connolly-cfrg-xwing-kem-06) from ML-KEM-768 and X25519.
+//!
+//! Private keys are the 32 byte seed, as in the draft and the libcrux
+//! provider, and are expanded on use.
+
+use alloc::{format, vec::Vec};
+
+use hpke_rs_crypto::error::Error;
+use ml_kem::{kem::Decapsulate, EncapsulateDeterministic, EncodedSizeUser, KemCore, MlKem768};
+use sha3::{digest::ExtendableOutput, Digest, Sha3_256, Shake256};
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret as X25519StaticSecret};
+
+type DecapsulationKey = <MlKem768 as KemCore>::DecapsulationKey;
+type EncapsulationKey = <MlKem768 as KemCore>::EncapsulationKey;
+
+const SEED_LEN: usize = 32;
+const X25519_LEN: usize = 32;
+const ML_KEM_EK_LEN: usize = 1184;
+const ML_KEM_CT_LEN: usize = 1088;
+
+/// The length of the randomness for the encapsulation.
+pub(crate) const ENCAPS_RANDOMNESS_LEN: usize = 64;
+
+/// `\.//^\`
+const X_WING_LABEL: &[u8] = b"\\.//^\\";
+
+struct ExpandedKey {
+    sk_m: DecapsulationKey,
+    sk_x: X25519StaticSecret,
+    pk_x: X25519PublicKey,
+}
+
+impl ExpandedKey {
+    /// Expand the key generation `seed`.
+    ///
+    /// Returns the expanded key and the ML-KEM encapsulation key.
+    fn from_seed(seed: &[u8]) -> Result<(Self, EncapsulationKey), Error> {
+        if seed.len() != SEED_LEN {
+            return Err(Error::KemInvalidSecretKey);
+        }
+        let mut expanded = [0u8; 96];
+        Shake256::digest_xof(seed, &mut expanded);
+        let (d, z) = expanded[..64].split_at(32);
+        let (sk_m, pk_m) = MlKem768::generate_deterministic(
+            d.try_into().map_err(|_| Error::KemInvalidSecretKey)?,
+            z.try_into().map_err(|_| Error::KemInvalidSecretKey)?,
+        );
+        let sk_x = x25519_secret(&expanded[64..])?;
+        expanded.fill(0);
+        let pk_x = X25519PublicKey::from(&sk_x);
+        Ok((Self { sk_m, sk_x, pk_x }, pk_m))
+    }
+}
+
+/// Derive the key pair (public key, private key) from the `seed`.
+///
+/// The private key is the `seed`.
+pub(crate) fn key_gen_derand(seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (sk, pk_m) = ExpandedKey::from_seed(seed)?;
+    let mut pk = pk_m.as_bytes().to_vec();
+    pk.extend_from_slice(sk.pk_x.as_bytes());
+    Ok((pk, seed.to_vec()))
+}
+
+/// Encapsulate to `pk_r` with the `randomness` (shared secret, ciphertext).
+pub(crate) fn encaps(pk_r: &[u8], randomness: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    if pk_r.len() != ML_KEM_EK_LEN + X25519_LEN {
+        return Err(Error::KemInvalidPublicKey);
+    }
+    if randomness.len() != ENCAPS_RANDOMNESS_LEN {
+        return Err(Error::InsufficientRandomness);
+    }
+    let (pk_m, pk_x) = pk_r.split_at(ML_KEM_EK_LEN);
+    let pk_m =
+        EncapsulationKey::from_bytes(pk_m.try_into().map_err(|_| Error::KemInvalidPublicKey)?);
+    let pk_x = x25519_public(pk_x).ok_or(Error::KemInvalidPublicKey)?;
+
+    let (m, ek_x) = randomness.split_at(32);
+    let (ct_m, ss_m) = pk_m
+        .encapsulate_deterministic(m.try_into().map_err(|_| Error::InsufficientRandomness)?)
+        .map_err(|e| Error::CryptoLibraryError(format!("ML-KEM encaps error: {:?}", e)))?;
+    let ek_x = x25519_secret(ek_x)?;
+    let ct_x = X25519PublicKey::from(&ek_x);
+    let ss_x = ek_x.diffie_hellman(&pk_x);
+
+    let ss = combiner(&ss_m, ss_x.as_bytes(), ct_x.as_bytes(), pk_x.as_bytes());
+    let mut ct = ct_m.to_vec();
+    ct.extend_from_slice(ct_x.as_bytes());
+    Ok((ss, ct))
+}
+
+/// Decapsulate `ct` with the private key (seed) `sk_r`.
+pub(crate) fn decaps(ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+    if ct.len() != ML_KEM_CT_LEN + X25519_LEN {
+        return Err(Error::KemInvalidCiphertext);
+    }
+    let (sk, _) = ExpandedKey::from_seed(sk_r)?;
+    let (ct_m, ct_x) = ct.split_at(ML_KEM_CT_LEN);
+    // ML-KEM decapsulation rejects implicitly.
+    let ss_m = sk
+        .sk_m
+        .decapsulate(ct_m.try_into().map_err(|_| Error::KemInvalidCiphertext)?)
+        .map_err(|e| Error::CryptoLibraryError(format!("ML-KEM decaps error: {:?}", e)))?;
+    let ct_x = x25519_public(ct_x).ok_or(Error::KemInvalidCiphertext)?;
+    let ss_x = sk.sk_x.diffie_hellman(&ct_x);
+
+    Ok(combiner(
+        &ss_m,
+        ss_x.as_bytes(),
+        ct_x.as_bytes(),
+        sk.pk_x.as_bytes(),
+    ))
+}
+
+/// `SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)`
+fn combiner(ss_m: &[u8], ss_x: &[u8], ct_x: &[u8], pk_x: &[u8]) -> Vec<u8> {
+    Sha3_256::new()
+        .chain_update(ss_m)
+        .chain_update(ss_x)
+        .chain_update(ct_x)
+        .chain_update(pk_x)
+        .chain_update(X_WING_LABEL)
+        .finalize()
+        .to_vec()
+}
+
+fn x25519_secret(sk: &[u8]) -> Result<X25519StaticSecret, Error> {
+    let sk: [u8; X25519_LEN] = sk.try_into().map_err(|_| Error::KemInvalidSecretKey)?;
+    Ok(X25519StaticSecret::from(sk))
+}
+
+fn x25519_public(pk: &[u8]) -> Option<X25519PublicKey> {
+    let pk: [u8; X25519_LEN] = pk.try_into().ok()?;
+    Some(X25519PublicKey::from(pk))
+}
@@ -0,0 +1,179 @@
+//! # RustCrypto AEAD Traits
+//!
+//! [`AeadContext`] wraps an HPKE [`Context`] and implements the [`aead`]
+//! crate traits, such that code that is generic over [`aead::AeadInPlace`]
+//! (or [`aead::Aead`]) can use an HPKE context as its cipher.
+//!
+//! The `aead` traits take an explicit nonce for every operation and
+//! `&self`, while an HPKE context derives the nonce from its sequence number.
+//! The wrapper therefore expects the nonce argument to be the **sequence
+//! number** of the next message, encoded as a big-endian integer of `Nn`
+//! bytes:
+//!
+//! ```text
+//! nonce = xor(base_nonce, I2OSP(seq, Nn))
+//! ```
+//!
+//! [`AeadContext::next_nonce`] returns this nonce, and
+//! [`AeadContext::sequence_nonce`] encodes any sequence number.
+//! Every other nonce is rejected.
+//! Sealing and opening go through the wrapped
+//! [`SenderContext`] and [`ReceiverContext`], such that the
+//! [message limit](Context::set_message_limit) and the
+//! [transcript](Context::enable_transcript) apply, and the sequence number
+//! is incremented.
+//!
+//! A wrapped [`SenderContext`] can only encrypt, and a wrapped
+//! [`ReceiverContext`] can only decrypt.
+
+use core::cell::RefCell;
+
+use aead::{
+    consts::{U0, U12, U16},
+    generic_array::GenericArray,
+    AeadCore, AeadInPlace, Nonce, Tag,
+};
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{Context, HpkeError, ReceiverContext, SenderContext};
+
+/// The nonce length of all HPKE AEADs.
+const NONCE_LEN: usize = 12;
+
+/// The tag length of all HPKE AEADs.
+const TAG_LEN: usize = 16;
+
+/// The wrapped context.
+#[derive(Debug)]
+enum Role<Crypto: 'static + HpkeCrypto> {
+    Sender(SenderContext<Crypto>),
+    Receiver(ReceiverContext<Crypto>),
+}
+
+/// An HPKE [`SenderContext`] or [`ReceiverContext`] implementing the
+/// [`aead`] crate traits.
+///
+/// See the [module documentation](self) for the nonce handling.
+#[derive(Debug)]
+pub struct AeadContext<Crypto: 'static + HpkeCrypto> {
+    role: RefCell<Role<Crypto>>,
+}
+
+impl<Crypto: HpkeCrypto> AeadContext<Crypto> {
+    /// Wrap the sender `context` for encryption.
+    ///
+    /// Returns an [`HpkeError::ExportOnlyMode`] for export-only contexts, and
+    /// an [`HpkeError::InvalidConfig`] for AEADs with other nonce or tag
+    /// lengths.
+    pub fn sender(context: SenderContext<Crypto>) -> Result<Self, HpkeError> {
+        check_context(&context)?;
+        Ok(Self {
+            role: RefCell::new(Role::Sender(context)),
+        })
+    }
+
+    /// Wrap the receiver `context` for decryption.
+    ///
+    /// See [`AeadContext::sender`] for the errors.
+    pub fn receiver(context: ReceiverContext<Crypto>) -> Result<Self, HpkeError> {
+        check_context(&context)?;
+        Ok(Self {
+            role: RefCell::new(Role::Receiver(context)),
+        })
+    }
+
+    /// Encode the sequence number `seq` as nonce for the `aead` traits.
+    pub fn sequence_nonce(seq: u64) -> Nonce<Self> {
+        let mut nonce = Nonce::<Self>::default();
+        for (n, s) in nonce.iter_mut().rev().zip(seq.to_be_bytes().iter().rev()) {
+            *n = *s;
+        }
+        nonce
+    }
+
+    /// The nonce for the next message.
+    pub fn next_nonce(&self) -> Nonce<Self> {
+        let role = self.role.borrow();
+        let context: &Context<Crypto> = match &*role {
+            Role::Sender(context) => context,
+            Role::Receiver(context) => context,
+        };
+        Self::sequence_nonce(u64::from(context.sequence_number))
+    }
+
+    /// Unwrap the sender context.
+    ///
+    /// Returns `None` if this wraps a receiver context.
+    pub fn into_sender(self) -> Option<SenderContext<Crypto>> {
+        match self.role.into_inner() {
+            Role::Sender(context) => Some(context),
+            Role::Receiver(_) => None,
+        }
+    }
+
+    /// Unwrap the receiver context.
+    ///
+    /// Returns `None` if this wraps a sender context.
+    pub fn into_receiver(self) -> Option<ReceiverContext<Crypto>> {
+        match self.role.into_inner() {
+            Role::Receiver(context) => Some(context),
+            Role::Sender(_) => None,
+        }
+    }
+}
+
+/// Check that the AEAD of the `context` fits the `aead` traits.
+fn check_context<Crypto: HpkeCrypto>(context: &Context<Crypto>) -> Result<(), HpkeError> {
+    context.check_aead()?;
+    let aead = context.suite.aead;
+    if Crypto::aead_nonce_length(aead) != NONCE_LEN || Crypto::aead_tag_length(aead) != TAG_LEN {
+        return Err(HpkeError::InvalidConfig);
+    }
+    Ok(())
+}
+
+impl<Crypto: HpkeCrypto> AeadCore for AeadContext<Crypto> {
+    type NonceSize = U12;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl<Crypto: HpkeCrypto> AeadInPlace for AeadContext<Crypto> {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        if *nonce != self.next_nonce() {
+            return Err(aead::Error);
+        }
+        let mut role = self.role.try_borrow_mut().map_err(|_| aead::Error)?;
+        let Role::Sender(context) = &mut *role else {
+            return Err(aead::Error);
+        };
+        let tag = context
+            .seal_in_place_detached(buffer, associated_data)
+            .map_err(|_| aead::Error)?;
+        GenericArray::from_exact_iter(tag).ok_or(aead::Error)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        if *nonce != self.next_nonce() {
+            return Err(aead::Error);
+        }
+        let mut role = self.role.try_borrow_mut().map_err(|_| aead::Error)?;
+        let Role::Receiver(context) = &mut *role else {
+            return Err(aead::Error);
+        };
+        context
+            .open_in_place_detached(buffer, associated_data, tag)
+            .map_err(|_| aead::Error)
+    }
+}
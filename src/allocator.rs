@@ -0,0 +1,76 @@
+//! # Caller-Supplied Allocators
+//!
+//! [`Context::seal_in`] and [`Context::open_in`] return their output in a
+//! [`Vec`] allocated with a caller-supplied [`Allocator`], e.g. an arena or
+//! bump allocator, with the `allocator-api2` feature.
+//!
+//! The allocator API of the standard library is nightly only, which this
+//! crate doesn't use.
+//! The [`Allocator`] trait and the [`Vec`] are the stable equivalents from
+//! the [`allocator_api2`] crate.
+//!
+//! The message is sealed and opened in place in the output buffer, such that
+//! it is never copied into a buffer of the global allocator.
+//! Like [`Context::seal_in_place_detached`] this fails with an
+//! [`HpkeError::InvalidConfig`] for contexts with a padding policy.
+//!
+//! **Note** that the key schedule and the crypto providers still allocate
+//! small intermediate buffers, e.g. the nonce and the tag, with the global
+//! allocator, because the [`HpkeCrypto`] trait returns `Vec`s.
+
+use allocator_api2::{alloc::Allocator, vec::Vec};
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{Ciphertext, Context, HpkeError};
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Seal `plain_txt` with `aad` like [`Context::seal`] and return the
+    /// ciphertext allocated with `alloc`.
+    pub fn seal_in<A: Allocator>(
+        &mut self,
+        aad: &[u8],
+        plain_txt: &[u8],
+        alloc: A,
+    ) -> Result<Vec<u8, A>, HpkeError> {
+        let ct_len = plain_txt
+            .len()
+            .checked_add(self.suite.aead.tag_length())
+            .ok_or(HpkeError::InputTooLong)?;
+        let mut ctxt = Vec::with_capacity_in(ct_len, alloc);
+        ctxt.extend_from_slice(plain_txt);
+        match self.seal_in_place_detached(&mut ctxt, aad) {
+            Ok(tag) => {
+                ctxt.extend_from_slice(&tag);
+                Ok(ctxt)
+            }
+            Err(e) => {
+                ctxt.fill(0);
+                Err(e)
+            }
+        }
+    }
+
+    /// Open `cipher_txt` with `aad` like [`Context::open`] and return the
+    /// plain text allocated with `alloc`.
+    pub fn open_in<A: Allocator>(
+        &mut self,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+        alloc: A,
+    ) -> Result<Vec<u8, A>, HpkeError> {
+        if cipher_txt.aead != self.suite.aead {
+            return Err(HpkeError::InvalidInput);
+        }
+        let (ct, tag) = cipher_txt.as_slice().split_at(cipher_txt.plaintext_len());
+        let mut ptxt = Vec::with_capacity_in(ct.len(), alloc);
+        ptxt.extend_from_slice(ct);
+        match self.open_in_place_detached(&mut ptxt, aad, tag) {
+            Ok(()) => Ok(ptxt),
+            Err(e) => {
+                ptxt.fill(0);
+                Err(e)
+            }
+        }
+    }
+}
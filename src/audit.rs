@@ -0,0 +1,113 @@
+//! # Key-Usage Auditing
+//!
+//! A [`KeyUsageAuditor`] attached to an [`HpkePrivateKey`] with
+//! [`HpkePrivateKey::with_auditor`] is notified whenever the key is used
+//!
+//! - to decapsulate an encapsulated secret, in any mode, or
+//! - to encapsulate in the Auth and AuthPSK modes.
+//!
+//! Every [`KeyUsageEvent`] carries the [fingerprint](HpkePrivateKey::fingerprint)
+//! of the key, the operation, and, with the `std` feature, the time of use.
+//! The auditor is invoked before the operation, such that failed operations
+//! are recorded as well.
+//!
+//! The auditor is not serialized with the key, and not compared when
+//! comparing keys.
+
+use alloc::sync::Arc;
+use core::time::Duration;
+
+use hpke_rs_crypto::types::KemAlgorithm;
+use zeroize::Zeroize;
+
+use crate::{util, HpkePrivateKey};
+
+const FINGERPRINT_LABEL: &[u8] = b"hpke-rs key fingerprint";
+
+/// A receiver of [`KeyUsageEvent`]s.
+pub trait KeyUsageAuditor: Send + Sync {
+    /// Called when the audited key is used.
+    fn key_used(&self, event: &KeyUsageEvent);
+}
+
+/// An operation performed with a private key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOperation {
+    /// Decapsulation by the receiver.
+    Decapsulation,
+
+    /// Auth or AuthPSK mode encapsulation by the sender.
+    AuthEncapsulation,
+}
+
+/// The use of an audited private key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyUsageEvent {
+    /// The fingerprint of the private key.
+    pub fingerprint: [u8; 32],
+
+    /// The KEM the key was used with.
+    pub kem: KemAlgorithm,
+
+    /// The operation performed with the key.
+    pub operation: KeyOperation,
+
+    /// The time of use since the UNIX epoch.
+    ///
+    /// This is `None` without the `std` feature.
+    pub timestamp: Option<Duration>,
+}
+
+/// The fingerprint of the private key bytes `sk`.
+///
+/// ```text
+/// fingerprint = SHA3-256("hpke-rs key fingerprint" || sk)
+/// ```
+pub(crate) fn fingerprint(sk: &[u8]) -> [u8; 32] {
+    let mut input = util::concat(&[FINGERPRINT_LABEL, sk]);
+    let fingerprint = libcrux_sha3::sha256(&input);
+    input.zeroize();
+    fingerprint
+}
+
+#[cfg(feature = "std")]
+fn now() -> Option<Duration> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+}
+
+#[cfg(not(feature = "std"))]
+fn now() -> Option<Duration> {
+    None
+}
+
+impl HpkePrivateKey {
+    /// Attach the `auditor` to this key.
+    ///
+    /// A previously attached auditor is replaced.
+    pub fn with_auditor(mut self, auditor: Arc<dyn KeyUsageAuditor>) -> Self {
+        self.auditor = Some(auditor);
+        self
+    }
+
+    /// Get the fingerprint of this key.
+    ///
+    /// The fingerprint identifies the key in audit trails without revealing
+    /// it.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        fingerprint(&self.value)
+    }
+
+    /// Notify the auditor, if any, about the `operation` with the `kem`.
+    pub(crate) fn audit(&self, kem: KemAlgorithm, operation: KeyOperation) {
+        if let Some(auditor) = &self.auditor {
+            auditor.key_used(&KeyUsageEvent {
+                fingerprint: self.fingerprint(),
+                kem,
+                operation,
+                timestamp: now(),
+            });
+        }
+    }
+}
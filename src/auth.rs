@@ -0,0 +1,151 @@
+//! # Usage-Tagged Keys for the Auth Modes
+//!
+//! In the Auth and AuthPSK modes a private key plays one of two roles:
+//!
+//! - the long-term key of a receiver, used to decapsulate, or
+//! - the key of a sender, used to authenticate the encapsulation.
+//!
+//! Deployments usually mandate separate keys for the two roles.
+//! [`ReceiverKey`] and [`SenderAuthKey`] tag a private key with its intended
+//! usage, and the Auth APIs in this module only accept the key of the right
+//! role, e.g. [`Hpke::setup_sender_auth`] takes a [`SenderAuthKey`].
+//! Reusing a receiver key as sender key therefore requires explicitly
+//! re-tagging it.
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, Mode,
+    Plaintext, ReceiverContext, SenderContext,
+};
+
+/// A private key for authenticating encapsulations in the Auth and AuthPSK
+/// modes.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "hazmat", derive(Clone))]
+pub struct SenderAuthKey(HpkePrivateKey);
+
+impl SenderAuthKey {
+    /// Tag the private key `sk` as sender authentication key.
+    pub fn new(sk: HpkePrivateKey) -> Self {
+        Self(sk)
+    }
+
+    /// Get the private key.
+    pub fn private_key(&self) -> &HpkePrivateKey {
+        &self.0
+    }
+
+    /// Remove the usage tag.
+    pub fn into_private_key(self) -> HpkePrivateKey {
+        self.0
+    }
+}
+
+/// A private key of a receiver.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "hazmat", derive(Clone))]
+pub struct ReceiverKey(HpkePrivateKey);
+
+impl ReceiverKey {
+    /// Tag the private key `sk` as receiver key.
+    pub fn new(sk: HpkePrivateKey) -> Self {
+        Self(sk)
+    }
+
+    /// Get the private key.
+    pub fn private_key(&self) -> &HpkePrivateKey {
+        &self.0
+    }
+
+    /// Remove the usage tag.
+    pub fn into_private_key(self) -> HpkePrivateKey {
+        self.0
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Returns an [`HpkeError::InvalidConfig`] outside of the Auth and AuthPSK
+    /// modes.
+    fn check_auth_mode(&self) -> Result<(), HpkeError> {
+        match self.mode {
+            Mode::Auth | Mode::AuthPsk => Ok(()),
+            Mode::Base | Mode::Psk => Err(HpkeError::InvalidConfig),
+        }
+    }
+
+    /// Set up an HPKE sender in the Auth or AuthPSK mode.
+    ///
+    /// This is [`Hpke::setup_sender`] with the sender authentication key
+    /// `sk_s`.
+    /// Returns an [`HpkeError::InvalidConfig`] in the Base and PSK modes.
+    pub fn setup_sender_auth(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: &SenderAuthKey,
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
+        self.check_auth_mode()?;
+        self.setup_sender(pk_r, info, psk, psk_id, Some(sk_s.private_key()))
+    }
+
+    /// Set up an HPKE receiver in the Auth or AuthPSK mode.
+    ///
+    /// This is [`Hpke::setup_receiver`] with the receiver key `sk_r` and the
+    /// public key `pk_s` of the sender.
+    /// Returns an [`HpkeError::InvalidConfig`] in the Base and PSK modes.
+    pub fn setup_receiver_auth(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &ReceiverKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: &HpkePublicKey,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        self.check_auth_mode()?;
+        self.setup_receiver(enc, sk_r.private_key(), info, psk, psk_id, Some(pk_s))
+    }
+
+    /// Single shot API to encrypt the bytes in `plain_txt` to the public key
+    /// `pk_r` in the Auth or AuthPSK mode.
+    ///
+    /// See [`Hpke::setup_sender_auth`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal_auth(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: &SenderAuthKey,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        let (enc, mut context) = self.setup_sender_auth(pk_r, info, psk, psk_id, sk_s)?;
+        let ctxt = context.seal(aad, plain_txt)?;
+        Ok((enc, ctxt))
+    }
+
+    /// Single shot API to decrypt the bytes in `ct` in the Auth or AuthPSK
+    /// mode.
+    ///
+    /// See [`Hpke::setup_receiver_auth`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_auth(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &ReceiverKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: &HpkePublicKey,
+    ) -> Result<Plaintext, HpkeError> {
+        let mut context = self.setup_receiver_auth(enc, sk_r, info, psk, psk_id, pk_s)?;
+        context.open(aad, ct)
+    }
+}
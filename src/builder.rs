@@ -0,0 +1,366 @@
+//! # Typestate Builder
+//!
+//! [`Hpke::setup_sender`] and friends take the PSK and the sender key as
+//! `Option`s and only check at runtime that they match the [`Mode`].
+//! An [`HpkeBuilder`] instead tracks the key material in its type, such that
+//! the mode follows from the material and missing material doesn't compile:
+//!
+//! - [`Hpke::base`] starts in the Base mode,
+//! - [`HpkeBuilder::with_psk`] switches to the PSK or AuthPSK mode, and
+//! - [`HpkeBuilder::with_sender_key`] (sender) or
+//!   [`HpkeBuilder::with_sender_public_key`] (receiver) switches to the Auth
+//!   or AuthPSK mode.
+//!
+//! A builder with a sender key can only set up senders, and a builder with
+//! the public key of a sender can only set up receivers.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let (kem, kdf, aead) = (
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (psk, psk_id) = ([0x42; 32], b"psk id");
+//! let mut keys = Hpke::<HpkeRustCrypto>::new(Mode::Base, kem, kdf, aead);
+//! let (sk_r, pk_r) = keys.generate_key_pair()?.into_keys();
+//! let (sk_s, pk_s) = keys.generate_key_pair()?.into_keys();
+//!
+//! let mut sender = Hpke::<HpkeRustCrypto>::base(kem, kdf, aead)
+//!     .with_psk(&psk, psk_id)
+//!     .with_sender_key(&sk_s);
+//! assert_eq!(sender.mode(), Mode::AuthPsk);
+//! let (enc, ct) = sender.seal(&pk_r, b"info", b"aad", b"message")?;
+//!
+//! let receiver = Hpke::<HpkeRustCrypto>::base(kem, kdf, aead)
+//!     .with_psk(&psk, psk_id)
+//!     .with_sender_public_key(&pk_s);
+//! assert_eq!(receiver.open(&enc, &sk_r, b"info", b"aad", &ct)?, b"message");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+
+use crate::{
+    Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, Mode,
+    Plaintext, ReceiverContext, SenderContext,
+};
+
+mod private {
+    use crate::{HpkePrivateKey, HpkePublicKey};
+
+    /// The PSK material of a builder.
+    pub trait PskState {
+        fn psk(&self) -> (Option<&[u8]>, Option<&[u8]>);
+    }
+
+    /// The authentication material of a builder for senders.
+    pub trait SenderAuthState {
+        fn sk_s(&self) -> Option<&HpkePrivateKey>;
+    }
+
+    /// The authentication material of a builder for receivers.
+    pub trait ReceiverAuthState {
+        fn pk_s(&self) -> Option<&HpkePublicKey>;
+    }
+}
+
+use private::{PskState, ReceiverAuthState, SenderAuthState};
+
+/// Builder state without a PSK.
+#[derive(Debug, Clone, Copy)]
+pub struct NoPsk;
+
+/// Builder state with a PSK and its id.
+#[derive(Clone, Copy)]
+pub struct WithPsk<'a> {
+    psk: &'a [u8],
+    psk_id: &'a [u8],
+}
+
+/// Builder state without sender authentication.
+#[derive(Debug, Clone, Copy)]
+pub struct NoAuth;
+
+/// Builder state with the private key of the sender.
+#[derive(Debug, Clone, Copy)]
+pub struct SenderKey<'a>(&'a HpkePrivateKey);
+
+/// Builder state with the public key of the sender.
+#[derive(Debug, Clone, Copy)]
+pub struct SenderPublicKey<'a>(&'a HpkePublicKey);
+
+impl core::fmt::Debug for WithPsk<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WithPsk")
+            .field("psk", &"***")
+            .field("psk_id", &self.psk_id)
+            .finish()
+    }
+}
+
+impl PskState for NoPsk {
+    fn psk(&self) -> (Option<&[u8]>, Option<&[u8]>) {
+        (None, None)
+    }
+}
+
+impl PskState for WithPsk<'_> {
+    fn psk(&self) -> (Option<&[u8]>, Option<&[u8]>) {
+        (Some(self.psk), Some(self.psk_id))
+    }
+}
+
+impl SenderAuthState for NoAuth {
+    fn sk_s(&self) -> Option<&HpkePrivateKey> {
+        None
+    }
+}
+
+impl SenderAuthState for SenderKey<'_> {
+    fn sk_s(&self) -> Option<&HpkePrivateKey> {
+        Some(self.0)
+    }
+}
+
+impl ReceiverAuthState for NoAuth {
+    fn pk_s(&self) -> Option<&HpkePublicKey> {
+        None
+    }
+}
+
+impl ReceiverAuthState for SenderPublicKey<'_> {
+    fn pk_s(&self) -> Option<&HpkePublicKey> {
+        Some(self.0)
+    }
+}
+
+/// An HPKE configuration with the key material for its mode.
+///
+/// See the [module documentation](self).
+pub struct HpkeBuilder<Crypto: 'static + HpkeCrypto, P, A> {
+    hpke: Hpke<Crypto>,
+    psk: P,
+    auth: A,
+}
+
+impl<Crypto: HpkeCrypto, P: core::fmt::Debug, A: core::fmt::Debug> core::fmt::Debug
+    for HpkeBuilder<Crypto, P, A>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HpkeBuilder")
+            .field("suite", &self.hpke.suite())
+            .field("psk", &self.psk)
+            .field("auth", &self.auth)
+            .finish()
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Start an [`HpkeBuilder`] in the Base mode.
+    pub fn base(
+        kem_id: KemAlgorithm,
+        kdf_id: KdfAlgorithm,
+        aead_id: AeadAlgorithm,
+    ) -> HpkeBuilder<Crypto, NoPsk, NoAuth> {
+        HpkeBuilder {
+            hpke: Hpke::new(Mode::Base, kem_id, kdf_id, aead_id),
+            psk: NoPsk,
+            auth: NoAuth,
+        }
+    }
+}
+
+impl<Crypto: HpkeCrypto, P, A> HpkeBuilder<Crypto, P, A> {
+    /// Get the HPKE configuration.
+    pub fn hpke(&self) -> &Hpke<Crypto> {
+        &self.hpke
+    }
+
+    /// Get the HPKE configuration mutably, e.g. to generate key pairs.
+    pub fn hpke_mut(&mut self) -> &mut Hpke<Crypto> {
+        &mut self.hpke
+    }
+
+    /// Get the mode that follows from the key material.
+    pub fn mode(&self) -> Mode {
+        self.hpke.mode
+    }
+}
+
+impl<Crypto: HpkeCrypto, A> HpkeBuilder<Crypto, NoPsk, A> {
+    /// Add the `psk` with its id `psk_id`.
+    ///
+    /// This switches from the Base to the PSK mode, or from the Auth to the
+    /// AuthPSK mode.
+    pub fn with_psk<'a>(
+        self,
+        psk: &'a [u8],
+        psk_id: &'a [u8],
+    ) -> HpkeBuilder<Crypto, WithPsk<'a>, A> {
+        let mut hpke = self.hpke;
+        hpke.mode = match hpke.mode {
+            Mode::Base => Mode::Psk,
+            _ => Mode::AuthPsk,
+        };
+        HpkeBuilder {
+            hpke,
+            psk: WithPsk { psk, psk_id },
+            auth: self.auth,
+        }
+    }
+}
+
+impl<Crypto: HpkeCrypto, P> HpkeBuilder<Crypto, P, NoAuth> {
+    /// Add the authentication material `auth` and switch the mode.
+    fn with_auth<A>(self, auth: A) -> HpkeBuilder<Crypto, P, A> {
+        let mut hpke = self.hpke;
+        hpke.mode = match hpke.mode {
+            Mode::Base => Mode::Auth,
+            _ => Mode::AuthPsk,
+        };
+        HpkeBuilder {
+            hpke,
+            psk: self.psk,
+            auth,
+        }
+    }
+
+    /// Add the private key `sk_s` of the sender for setting up senders.
+    ///
+    /// This switches from the Base to the Auth mode, or from the PSK to the
+    /// AuthPSK mode.
+    pub fn with_sender_key(self, sk_s: &HpkePrivateKey) -> HpkeBuilder<Crypto, P, SenderKey<'_>> {
+        self.with_auth(SenderKey(sk_s))
+    }
+
+    /// Add the public key `pk_s` of the sender for setting up receivers.
+    ///
+    /// This switches from the Base to the Auth mode, or from the PSK to the
+    /// AuthPSK mode.
+    pub fn with_sender_public_key(
+        self,
+        pk_s: &HpkePublicKey,
+    ) -> HpkeBuilder<Crypto, P, SenderPublicKey<'_>> {
+        self.with_auth(SenderPublicKey(pk_s))
+    }
+}
+
+impl<Crypto: HpkeCrypto, P: PskState, A: SenderAuthState> HpkeBuilder<Crypto, P, A> {
+    /// Set up an HPKE sender for the public key `pk_r` of the receiver.
+    ///
+    /// See [`Hpke::setup_sender`].
+    pub fn setup_sender(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke
+            .setup_sender(pk_r, info, psk, psk_id, self.auth.sk_s())
+    }
+
+    /// Single shot API to encrypt the bytes in `plain_txt` to the public key
+    /// `pk_r`.
+    ///
+    /// See [`Hpke::seal`].
+    pub fn seal(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke
+            .seal(pk_r, info, aad, plain_txt, psk, psk_id, self.auth.sk_s())
+    }
+
+    /// Single shot API to derive an exporter secret for the receiver with
+    /// the public key `pk_r`.
+    ///
+    /// See [`Hpke::send_export`].
+    pub fn send_export(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        exporter_context: &[u8],
+        length: usize,
+    ) -> Result<(EncapsulatedSecret, Vec<u8>), HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke.send_export(
+            pk_r,
+            info,
+            psk,
+            psk_id,
+            self.auth.sk_s(),
+            exporter_context,
+            length,
+        )
+    }
+}
+
+impl<Crypto: HpkeCrypto, P: PskState, A: ReceiverAuthState> HpkeBuilder<Crypto, P, A> {
+    /// Set up an HPKE receiver with the private key `sk_r`.
+    ///
+    /// See [`Hpke::setup_receiver`].
+    pub fn setup_receiver(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke
+            .setup_receiver(enc, sk_r, info, psk, psk_id, self.auth.pk_s())
+    }
+
+    /// Single shot API to decrypt the bytes in `ct` with the private key
+    /// `sk_r`.
+    ///
+    /// See [`Hpke::open`].
+    pub fn open(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke
+            .open(enc, sk_r, info, aad, ct, psk, psk_id, self.auth.pk_s())
+    }
+
+    /// Single shot API to derive an exporter secret with the private key
+    /// `sk_r`.
+    ///
+    /// See [`Hpke::receiver_export`].
+    pub fn receiver_export(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        exporter_context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, HpkeError> {
+        let (psk, psk_id) = self.psk.psk();
+        self.hpke.receiver_export(
+            enc,
+            sk_r,
+            info,
+            psk,
+            psk_id,
+            self.auth.pk_s(),
+            exporter_context,
+            length,
+        )
+    }
+}
@@ -0,0 +1,219 @@
+//! # Group Channels
+//!
+//! Derive independent channels from a single HPKE [`Context`], e.g. one per
+//! group member or topic in a fan-out messaging pattern.
+//!
+//! Each channel is a [`SenderContext`] or a [`ReceiverContext`] with its own
+//! key, base nonce, exporter secret, and sequence number.
+//! The sender derives its channels with [`Context::derive_sender_channel`],
+//! the receiver with [`Context::derive_receiver_channel`], such that a
+//! channel can't both seal and open.
+//! The channel secrets are derived from the exporter secret of the parent
+//! context with the channel label as part of the exporter context:
+//!
+//! ```text
+//! channel_context(label, purpose) =
+//!     "hpke-rs channel" || I2OSP(len(label), 2) || label || purpose
+//! key             = Export(channel_context(label, "key"), Nk)
+//! base_nonce      = Export(channel_context(label, "base_nonce"), Nn)
+//! exporter_secret = Export(channel_context(label, "exp"), Nh)
+//! ```
+//!
+//! Sender and receiver derive the same channel from the same label.
+//! Messages in different channels use different keys, so their sequence
+//! numbers are independent.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{util, Ciphertext, Context, HpkeError, Plaintext, ReceiverContext, SenderContext};
+
+const CHANNEL_LABEL: &[u8] = b"hpke-rs channel";
+
+/// Build the exporter context for the channel `label` and `purpose`.
+fn channel_context(label: &[u8], purpose: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let label_len = u16::try_from(label.len()).map_err(|_| HpkeError::InvalidInput)?;
+    Ok(util::concat(&[
+        CHANNEL_LABEL,
+        &label_len.to_be_bytes(),
+        label,
+        purpose,
+    ]))
+}
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Derive the sender side of the channel with the given `label` from this
+    /// context.
+    ///
+    /// The returned context starts with sequence number `0`.
+    /// Labels must be at most `u16::MAX` bytes long.
+    ///
+    /// **Note** that deriving the same channel twice yields the same key and
+    /// base nonce.
+    /// Don't seal with more than one context for the same channel, or use
+    /// [`GroupChannels`], which derives each channel only once.
+    pub fn derive_sender_channel(&self, label: &[u8]) -> Result<SenderContext<Crypto>, HpkeError> {
+        self.derive_channel(label).map(SenderContext::new)
+    }
+
+    /// Derive the receiver side of the channel with the given `label` from
+    /// this context.
+    ///
+    /// See [`Context::derive_sender_channel`].
+    pub fn derive_receiver_channel(
+        &self,
+        label: &[u8],
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        self.derive_channel(label).map(ReceiverContext::new)
+    }
+
+    /// Derive the channel with the given `label` from this context.
+    fn derive_channel(&self, label: &[u8]) -> Result<Context<Crypto>, HpkeError> {
+        let aead = self.suite.aead;
+        let key = self.export(
+            &channel_context(label, b"key")?,
+            Crypto::aead_key_length(aead),
+        )?;
+        let nonce = self.export(
+            &channel_context(label, b"base_nonce")?,
+            Crypto::aead_nonce_length(aead),
+        )?;
+        let exporter_secret = self.export(
+            &channel_context(label, b"exp")?,
+            Crypto::kdf_digest_length(self.suite.kdf),
+        )?;
+        Ok(Context {
+            key,
+            nonce,
+            exporter_secret,
+            sequence_number: 0,
+            message_limit: self.message_limit,
+            suite: self.suite,
+            transcript: None,
+            padding: None,
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// A set of channels derived from one HPKE context.
+///
+/// Channels are derived on first use and kept for the lifetime of the set
+/// such that their sequence numbers are never reset.
+/// The `Channel` is either a [`SenderContext`], see
+/// [`GroupChannels::new_sender`], or a [`ReceiverContext`], see
+/// [`GroupChannels::new_receiver`].
+pub struct GroupChannels<Crypto: 'static + HpkeCrypto, Channel> {
+    context: Context<Crypto>,
+    channels: BTreeMap<Vec<u8>, Channel>,
+}
+
+/// The channels of a sender.
+pub type SenderChannels<Crypto> = GroupChannels<Crypto, SenderContext<Crypto>>;
+
+/// The channels of a receiver.
+pub type ReceiverChannels<Crypto> = GroupChannels<Crypto, ReceiverContext<Crypto>>;
+
+impl<Crypto: HpkeCrypto, Channel> core::fmt::Debug for GroupChannels<Crypto, Channel> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GroupChannels")
+            .field("context", &self.context)
+            .field("channels", &self.channels.len())
+            .finish()
+    }
+}
+
+impl<Crypto: HpkeCrypto, Channel> GroupChannels<Crypto, Channel> {
+    /// Create a new set of channels for the `context`.
+    ///
+    /// Only its exporter secret is kept, such that the set can't seal or open
+    /// with the key of the `context`.
+    fn new(context: &Context<Crypto>) -> Self {
+        Self {
+            context: Context {
+                key: Vec::new(),
+                nonce: Vec::new(),
+                exporter_secret: context.exporter_secret.clone(),
+                sequence_number: 0,
+                message_limit: context.message_limit,
+                suite: context.suite,
+                transcript: None,
+                padding: None,
+                phantom: PhantomData,
+            },
+            channels: BTreeMap::new(),
+        }
+    }
+
+    /// Get the channel with the given `label`, deriving it with `derive` if
+    /// necessary.
+    fn channel_with(
+        &mut self,
+        label: &[u8],
+        derive: impl FnOnce(&Context<Crypto>, &[u8]) -> Result<Channel, HpkeError>,
+    ) -> Result<&mut Channel, HpkeError> {
+        if !self.channels.contains_key(label) {
+            let channel = derive(&self.context, label)?;
+            self.channels.insert(label.to_vec(), channel);
+        }
+        self.channels.get_mut(label).ok_or(HpkeError::InvalidInput)
+    }
+
+    /// The number of derived channels.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns `true` if no channel has been derived.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+}
+
+impl<Crypto: HpkeCrypto> SenderChannels<Crypto> {
+    /// Create a new set of sender channels for the `context`, e.g. a
+    /// [`SenderContext`].
+    pub fn new_sender(context: &Context<Crypto>) -> Self {
+        Self::new(context)
+    }
+
+    /// Get the channel with the given `label`, deriving it if necessary.
+    pub fn channel(&mut self, label: &[u8]) -> Result<&mut SenderContext<Crypto>, HpkeError> {
+        self.channel_with(label, Context::derive_sender_channel)
+    }
+
+    /// Seal `plain_txt` with `aad` on the channel with the given `label`.
+    pub fn seal(
+        &mut self,
+        label: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<Ciphertext, HpkeError> {
+        self.channel(label)?.seal(aad, plain_txt)
+    }
+}
+
+impl<Crypto: HpkeCrypto> ReceiverChannels<Crypto> {
+    /// Create a new set of receiver channels for the `context`, e.g. a
+    /// [`ReceiverContext`].
+    pub fn new_receiver(context: &Context<Crypto>) -> Self {
+        Self::new(context)
+    }
+
+    /// Get the channel with the given `label`, deriving it if necessary.
+    pub fn channel(&mut self, label: &[u8]) -> Result<&mut ReceiverContext<Crypto>, HpkeError> {
+        self.channel_with(label, Context::derive_receiver_channel)
+    }
+
+    /// Open `cipher_txt` with `aad` on the channel with the given `label`.
+    pub fn open(
+        &mut self,
+        label: &[u8],
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        self.channel(label)?.open(aad, cipher_txt)
+    }
+}
@@ -0,0 +1,129 @@
+//! # HPKE Configurations
+//!
+//! An [`HpkeConfig`] describes how to encrypt to a recipient: the recipient
+//! public key, its KEM, and the symmetric algorithms (KDF and AEAD) the
+//! recipient supports, in order of preference.
+//! This is the information published by e.g. OHTTP gateways and ECH servers.
+//!
+//! [`Hpke::seal_to_config`] selects the first symmetric suite in the config
+//! that is supported by the crypto provider and encrypts to the recipient in
+//! one call.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+
+use crate::{Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePublicKey, HpkeSuite, Mode};
+
+/// A symmetric HPKE suite, i.e. a KDF and an AEAD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymmetricSuite {
+    /// The KDF.
+    pub kdf: KdfAlgorithm,
+
+    /// The AEAD.
+    pub aead: AeadAlgorithm,
+}
+
+/// The HPKE configuration of a recipient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpkeConfig {
+    kem: KemAlgorithm,
+    public_key: HpkePublicKey,
+    symmetric_suites: Vec<SymmetricSuite>,
+}
+
+impl HpkeConfig {
+    /// Create a new configuration for the recipient `public_key` of the `kem`.
+    ///
+    /// The `symmetric_suites` are in order of preference.
+    pub fn new(
+        kem: KemAlgorithm,
+        public_key: HpkePublicKey,
+        symmetric_suites: Vec<SymmetricSuite>,
+    ) -> Self {
+        Self {
+            kem,
+            public_key,
+            symmetric_suites,
+        }
+    }
+
+    /// Get the KEM.
+    pub fn kem(&self) -> KemAlgorithm {
+        self.kem
+    }
+
+    /// Get the recipient public key.
+    pub fn public_key(&self) -> &HpkePublicKey {
+        &self.public_key
+    }
+
+    /// Get the symmetric suites in order of preference.
+    pub fn symmetric_suites(&self) -> &[SymmetricSuite] {
+        &self.symmetric_suites
+    }
+
+    /// Validate the configuration.
+    ///
+    /// Returns an error if
+    /// - the public key doesn't have the length required by the KEM
+    ///   ([`HpkeError::InvalidKeyLength`]),
+    /// - there are no symmetric suites ([`HpkeError::InvalidConfig`]).
+    pub fn validate(&self) -> Result<(), HpkeError> {
+        let expected = self.kem.public_key_len();
+        if self.public_key.as_slice().len() != expected {
+            return Err(HpkeError::InvalidKeyLength {
+                expected,
+                got: self.public_key.as_slice().len(),
+            });
+        }
+        if self.symmetric_suites.is_empty() {
+            return Err(HpkeError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Select the first suite of this configuration in `mode` that is
+    /// supported by the `Crypto` provider.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if the KEM or none of
+    /// the symmetric suites is supported.
+    pub fn select_suite<Crypto: HpkeCrypto>(&self, mode: Mode) -> Result<HpkeSuite, HpkeError> {
+        let mut error = HpkeError::InvalidConfig;
+        for symmetric in self.symmetric_suites.iter() {
+            let suite = HpkeSuite::new(mode, self.kem, symmetric.kdf, symmetric.aead);
+            match suite.check_support::<Crypto>() {
+                Ok(()) => return Ok(suite),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Encrypt `plain_txt` to the recipient described by `config` in the base
+    /// mode.
+    ///
+    /// The configuration is validated and the first of its symmetric suites
+    /// that is supported by the `Crypto` provider is used.
+    ///
+    /// Returns the selected suite, which the recipient needs to decrypt,
+    /// together with the encapsulated secret and the ciphertext.
+    pub fn seal_to_config(
+        config: &HpkeConfig,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<(HpkeSuite, EncapsulatedSecret, Ciphertext), HpkeError> {
+        config.validate()?;
+        let suite = config.select_suite::<Crypto>(Mode::Base)?;
+        let mut hpke = suite.into_hpke::<Crypto>();
+        let (enc, ct) = hpke.seal(&config.public_key, info, aad, plain_txt, None, None, None)?;
+        Ok((suite, enc, ct))
+    }
+}
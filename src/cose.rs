@@ -0,0 +1,335 @@
+//! # COSE Keys
+//!
+//! Conversions between HPKE keys and COSE_Key structures (RFC 9052), e.g. to
+//! exchange HPKE recipient keys in COSE or CWT based systems.
+//!
+//! - X25519 and X448 keys are `OKP` keys (RFC 9053).
+//! - Keys of the NIST curves and secp256k1 are `EC2` keys (RFC 9053,
+//!   RFC 8812). Compressed points use the sign bit of y instead of the
+//!   coordinate.
+//!
+//! [`CoseKey`] is encoded as CBOR map with the labels of RFC 9052 in
+//! deterministic order. Unknown labels, e.g. `alg` or `key_ops`, are ignored
+//! when decoding.
+//!
+//! ```
+//! use hpke_rs::{cose::CoseKey, hpke_types::*, Hpke, HpkePublicKey, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let kem = KemAlgorithm::DhKem25519;
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     kem,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let key_pair = hpke.generate_key_pair()?;
+//! let cbor = key_pair.public_key().to_cose_key(kem)?.to_cbor()?;
+//! let pk_r = HpkePublicKey::from_cose_key(&CoseKey::from_cbor(&cbor)?)?;
+//! assert_eq!(&pk_r, key_pair.public_key());
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use ciborium::Value;
+use hpke_rs_crypto::types::KemAlgorithm;
+use zeroize::Zeroize;
+
+use crate::{util, HpkeError, HpkeKeyPair, HpkePrivateKey, HpkePublicKey};
+
+/// The `kty` of X25519 and X448 keys.
+pub const KTY_OKP: i64 = 1;
+
+/// The `kty` of keys on the NIST curves and secp256k1.
+pub const KTY_EC2: i64 = 2;
+
+/// The label of the key type.
+const LABEL_KTY: i64 = 1;
+
+/// The label of the key identifier.
+const LABEL_KID: i64 = 2;
+
+/// The label of the curve.
+const LABEL_CRV: i64 = -1;
+
+/// The label of the public key (`OKP`) or x coordinate (`EC2`).
+const LABEL_X: i64 = -2;
+
+/// The label of the y coordinate.
+const LABEL_Y: i64 = -3;
+
+/// The label of the private key.
+const LABEL_D: i64 = -4;
+
+/// The y coordinate of an `EC2` key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CoseKeyY {
+    /// The coordinate of an uncompressed point.
+    Coordinate(Vec<u8>),
+
+    /// The sign bit of a compressed point.
+    Sign(bool),
+}
+
+/// A COSE_Key of an HPKE KEM.
+///
+/// The private key `d` is zeroized on drop.
+#[derive(Clone, PartialEq, Eq, Zeroize)]
+#[zeroize(drop)]
+pub struct CoseKey {
+    /// The key type, [`KTY_OKP`] or [`KTY_EC2`].
+    pub kty: i64,
+
+    /// The optional key identifier.
+    pub kid: Option<Vec<u8>>,
+
+    /// The curve, e.g. `4` for X25519 or `1` for P-256.
+    pub crv: i64,
+
+    /// The public key (`OKP`) or its x coordinate (`EC2`).
+    pub x: Vec<u8>,
+
+    /// The y coordinate of `EC2` keys.
+    #[zeroize(skip)]
+    pub y: Option<CoseKeyY>,
+
+    /// The private key.
+    pub d: Option<Vec<u8>>,
+}
+
+impl core::fmt::Debug for CoseKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CoseKey")
+            .field("kty", &self.kty)
+            .field("kid", &self.kid)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &self.d.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// Set the `slot` of a label to `value`.
+///
+/// Returns an [`HpkeError::InvalidInput`] if the label is duplicated.
+fn set<T>(slot: &mut Option<T>, value: Option<T>) -> Result<(), HpkeError> {
+    let value = value.ok_or(HpkeError::InvalidInput)?;
+    if slot.replace(value).is_some() {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(())
+}
+
+impl CoseKey {
+    /// Decode a CBOR encoded COSE_Key.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed,
+    /// a label is duplicated or has a value of the wrong type, or the key
+    /// type, curve or public key is missing.
+    pub fn from_cbor(cbor: &[u8]) -> Result<Self, HpkeError> {
+        let mut reader = cbor;
+        let value: Value =
+            ciborium::from_reader(&mut reader).map_err(|_| HpkeError::InvalidInput)?;
+        if !reader.is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let Value::Map(entries) = value else {
+            return Err(HpkeError::InvalidInput);
+        };
+
+        let integer = |value: Value| {
+            value
+                .as_integer()
+                .and_then(|value| i64::try_from(value).ok())
+        };
+        let (mut kty, mut kid, mut crv, mut x, mut y, mut d) = (None, None, None, None, None, None);
+        for (label, value) in entries {
+            let Some(label) = integer(label) else {
+                continue;
+            };
+            match label {
+                LABEL_KTY => set(&mut kty, integer(value))?,
+                LABEL_KID => set(&mut kid, value.into_bytes().ok())?,
+                LABEL_CRV => set(&mut crv, integer(value))?,
+                LABEL_X => set(&mut x, value.into_bytes().ok())?,
+                LABEL_Y => {
+                    let value = match value {
+                        Value::Bytes(y) => Some(CoseKeyY::Coordinate(y)),
+                        Value::Bool(sign) => Some(CoseKeyY::Sign(sign)),
+                        _ => None,
+                    };
+                    set(&mut y, value)?
+                }
+                LABEL_D => set(&mut d, value.into_bytes().ok())?,
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            kty: kty.ok_or(HpkeError::InvalidInput)?,
+            kid,
+            crv: crv.ok_or(HpkeError::InvalidInput)?,
+            x: x.ok_or(HpkeError::InvalidInput)?,
+            y,
+            d,
+        })
+    }
+
+    /// Encode this COSE_Key as CBOR.
+    ///
+    /// The encoding contains the private key `d` in plain text, if there is
+    /// one, and should be zeroized after use.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, HpkeError> {
+        let label = |label: i64| Value::Integer(label.into());
+        let mut entries = Vec::new();
+        entries.push((label(LABEL_KTY), label(self.kty)));
+        if let Some(kid) = &self.kid {
+            entries.push((label(LABEL_KID), Value::Bytes(kid.clone())));
+        }
+        entries.push((label(LABEL_CRV), label(self.crv)));
+        entries.push((label(LABEL_X), Value::Bytes(self.x.clone())));
+        match &self.y {
+            Some(CoseKeyY::Coordinate(y)) => {
+                entries.push((label(LABEL_Y), Value::Bytes(y.clone())));
+            }
+            Some(CoseKeyY::Sign(sign)) => entries.push((label(LABEL_Y), Value::Bool(*sign))),
+            None => (),
+        }
+        if let Some(d) = &self.d {
+            entries.push((label(LABEL_D), Value::Bytes(d.clone())));
+        }
+
+        let mut cbor = Vec::new();
+        let mut value = Value::Map(entries);
+        let result = ciborium::into_writer(&value, &mut cbor);
+        if let Value::Map(entries) = &mut value {
+            for (_, value) in entries {
+                if let Value::Bytes(bytes) = value {
+                    bytes.zeroize();
+                }
+            }
+        }
+        result.map_err(|_| HpkeError::InvalidInput)?;
+        Ok(cbor)
+    }
+}
+
+/// Get the `kty` and `crv` of the `kem`.
+fn key_type(kem: KemAlgorithm) -> Option<(i64, i64)> {
+    match kem {
+        KemAlgorithm::DhKem25519 => Some((KTY_OKP, 4)),
+        KemAlgorithm::DhKem448 => Some((KTY_OKP, 5)),
+        KemAlgorithm::DhKemP256 => Some((KTY_EC2, 1)),
+        KemAlgorithm::DhKemP384 => Some((KTY_EC2, 2)),
+        KemAlgorithm::DhKemP521 => Some((KTY_EC2, 3)),
+        KemAlgorithm::DhKemK256 => Some((KTY_EC2, 8)),
+        _ => None,
+    }
+}
+
+/// Get the KEM of the `kty` and `crv`.
+///
+/// Returns an [`HpkeError::InvalidInput`] for unknown key types and curves
+/// that don't match the key type.
+fn kem_from_key_type(kty: i64, crv: i64) -> Result<KemAlgorithm, HpkeError> {
+    [
+        KemAlgorithm::DhKem25519,
+        KemAlgorithm::DhKem448,
+        KemAlgorithm::DhKemP256,
+        KemAlgorithm::DhKemP384,
+        KemAlgorithm::DhKemP521,
+        KemAlgorithm::DhKemK256,
+    ]
+    .into_iter()
+    .find(|&kem| key_type(kem) == Some((kty, crv)))
+    .ok_or(HpkeError::InvalidInput)
+}
+
+impl HpkePublicKey {
+    /// Convert a COSE_Key into a public key.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `key` is malformed or
+    /// not a key of a KEM of this crate, and an
+    /// [`HpkeError::InvalidKeyLength`] if the key doesn't have the length
+    /// required by the KEM.
+    pub fn from_cose_key(key: &CoseKey) -> Result<Self, HpkeError> {
+        let kem = kem_from_key_type(key.kty, key.crv)?;
+        let value = match &key.y {
+            None if key.kty == KTY_OKP => key.x.clone(),
+            Some(_) if key.x.len() != kem.private_key_len() => return Err(HpkeError::InvalidInput),
+            Some(CoseKeyY::Coordinate(y)) if key.kty == KTY_EC2 => {
+                if y.len() != kem.private_key_len() {
+                    return Err(HpkeError::InvalidInput);
+                }
+                util::concat(&[&[0x04], &key.x, y])
+            }
+            Some(CoseKeyY::Sign(sign)) if key.kty == KTY_EC2 => {
+                util::concat(&[&[0x02 | u8::from(*sign)], &key.x])
+            }
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        Self::new_checked(kem, value)
+    }
+
+    /// Convert this public key for the `kem` into a COSE_Key.
+    ///
+    /// Compressed keys keep the sign bit of y only.
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM
+    /// or the `kem` has no COSE key type, and an [`HpkeError::InvalidInput`]
+    /// if the key doesn't have the length required by the `kem`.
+    pub fn to_cose_key(&self, kem: KemAlgorithm) -> Result<CoseKey, HpkeError> {
+        self.check_kem(kem)?;
+        let (kty, crv) = key_type(kem).ok_or(HpkeError::InvalidConfig)?;
+        let (x, y) = match self.value.split_first() {
+            Some((0x04, point)) if kty == KTY_EC2 && self.value.len() == kem.public_key_len() => {
+                let (x, y) = point
+                    .split_at_checked(kem.private_key_len())
+                    .ok_or(HpkeError::InvalidInput)?;
+                (x, Some(CoseKeyY::Coordinate(y.to_vec())))
+            }
+            Some((&tag, x)) if kty == KTY_EC2 && self.is_compressed(kem) => {
+                (x, Some(CoseKeyY::Sign(tag == 0x03)))
+            }
+            _ if kty == KTY_OKP && self.value.len() == kem.public_key_len() => {
+                (self.value.as_slice(), None)
+            }
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        Ok(CoseKey {
+            kty,
+            kid: None,
+            crv,
+            x: x.to_vec(),
+            y,
+            d: None,
+        })
+    }
+}
+
+impl HpkeKeyPair {
+    /// Convert a COSE_Key with its private key `d` into a key pair.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `key` has no private
+    /// key. See [`HpkePublicKey::from_cose_key`] for the other errors.
+    pub fn from_cose_key(key: &CoseKey) -> Result<Self, HpkeError> {
+        let public_key = HpkePublicKey::from_cose_key(key)?;
+        let d = key.d.as_ref().ok_or(HpkeError::InvalidInput)?;
+        let kem = kem_from_key_type(key.kty, key.crv)?;
+        let private_key = HpkePrivateKey::new_checked(kem, d.clone())?;
+        Ok(Self::from_keys(private_key, public_key))
+    }
+
+    /// Convert this key pair for the `kem` into a COSE_Key with the private
+    /// key `d`.
+    ///
+    /// See [`HpkePublicKey::to_cose_key`].
+    #[cfg(feature = "hazmat")]
+    pub fn to_cose_key(&self, kem: KemAlgorithm) -> Result<CoseKey, HpkeError> {
+        self.private_key().check_kem(kem)?;
+        let mut key = self.public_key().to_cose_key(kem)?;
+        key.d = Some(self.private_key().bytes()?.to_vec());
+        Ok(key)
+    }
+}
@@ -0,0 +1,502 @@
+//! # COSE-HPKE
+//!
+//! HPKE for COSE (draft-ietf-cose-hpke) in its two modes:
+//!
+//! - **Integrated encryption** encrypts the plaintext with HPKE directly into
+//!   a `COSE_Encrypt0` structure, see [`seal_encrypt0`] and [`open_encrypt0`].
+//! - **Key encryption** encrypts the plaintext with a random content
+//!   encryption key (CEK) into a `COSE_Encrypt` structure, and the CEK with
+//!   HPKE to each recipient, see [`seal_encrypt`] and [`open_encrypt`].
+//!
+//! The HPKE algorithm of the [`Hpke`] configuration is identified by the
+//! [`algorithm`] in the protected header, and the encapsulated key is the
+//! `ek` [`HEADER_EK`] in the unprotected header.
+//! The HPKE `aad` is the `Enc_structure` (RFC 9052) of the layer, i.e. it
+//! covers the protected header and the `external_aad`, and the HPKE `info`
+//! is empty.
+//! Only the base mode is supported.
+//!
+//! ```
+//! use hpke_rs::{cose_hpke, hpke_types::*, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let message = cose_hpke::seal_encrypt0(&mut hpke, &pk_r, Some(b"kid"), b"", b"message")?;
+//! let pt = cose_hpke::open_encrypt0(&hpke, &sk_r, b"", &message)?;
+//! assert_eq!(pt, b"message");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use ciborium::Value;
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+use zeroize::Zeroize;
+
+use crate::{Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, Mode};
+
+/// The header parameter of the encapsulated key `ek`.
+pub const HEADER_EK: i64 = -4;
+
+/// The header parameter `alg`.
+const HEADER_ALG: i64 = 1;
+
+/// The header parameter `kid`.
+const HEADER_KID: i64 = 4;
+
+/// The header parameter `IV`.
+const HEADER_IV: i64 = 5;
+
+/// The CBOR tag of `COSE_Encrypt0`.
+const TAG_ENCRYPT0: u64 = 16;
+
+/// The CBOR tag of `COSE_Encrypt`.
+const TAG_ENCRYPT: u64 = 96;
+
+/// The COSE-HPKE algorithms and their HPKE ciphersuites.
+const ALGORITHMS: [(i64, KemAlgorithm, KdfAlgorithm, AeadAlgorithm); 10] = [
+    (
+        35,
+        KemAlgorithm::DhKemP256,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::Aes128Gcm,
+    ),
+    (
+        36,
+        KemAlgorithm::DhKemP256,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        37,
+        KemAlgorithm::DhKemP384,
+        KdfAlgorithm::HkdfSha384,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        38,
+        KemAlgorithm::DhKemP384,
+        KdfAlgorithm::HkdfSha384,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        39,
+        KemAlgorithm::DhKemP521,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        40,
+        KemAlgorithm::DhKemP521,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        41,
+        KemAlgorithm::DhKem25519,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::Aes128Gcm,
+    ),
+    (
+        42,
+        KemAlgorithm::DhKem25519,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        43,
+        KemAlgorithm::DhKem448,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        44,
+        KemAlgorithm::DhKem448,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+];
+
+/// Get the COSE-HPKE algorithm of the ciphersuite, e.g. `41` for
+/// `HPKE-Base-X25519-SHA256-AES128GCM`.
+pub fn algorithm(kem: KemAlgorithm, kdf: KdfAlgorithm, aead: AeadAlgorithm) -> Option<i64> {
+    ALGORITHMS
+        .iter()
+        .find(|&&(_, k, d, a)| (k, d, a) == (kem, kdf, aead))
+        .map(|&(alg, ..)| alg)
+}
+
+/// Get the ciphersuite of the COSE-HPKE algorithm `alg`.
+pub fn ciphersuite(alg: i64) -> Option<(KemAlgorithm, KdfAlgorithm, AeadAlgorithm)> {
+    ALGORITHMS
+        .iter()
+        .find(|&&(id, ..)| id == alg)
+        .map(|&(_, kem, kdf, aead)| (kem, kdf, aead))
+}
+
+/// Get the COSE algorithm of the content encryption `aead`, e.g. `1` for
+/// `A128GCM`.
+pub fn content_algorithm(aead: AeadAlgorithm) -> Option<i64> {
+    match aead {
+        AeadAlgorithm::Aes128Gcm => Some(1),
+        AeadAlgorithm::Aes256Gcm => Some(3),
+        AeadAlgorithm::ChaCha20Poly1305 => Some(24),
+        _ => None,
+    }
+}
+
+/// Get the content encryption AEAD of the COSE algorithm `alg`.
+fn content_aead(alg: i64) -> Option<AeadAlgorithm> {
+    [
+        AeadAlgorithm::Aes128Gcm,
+        AeadAlgorithm::Aes256Gcm,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ]
+    .into_iter()
+    .find(|&aead| content_algorithm(aead) == Some(alg))
+}
+
+/// A layer of a `COSE_Encrypt0` or `COSE_Encrypt` structure.
+struct Layer {
+    /// The serialized protected header.
+    protected: Vec<u8>,
+    /// The protected header.
+    protected_map: Vec<(Value, Value)>,
+    /// The unprotected header.
+    unprotected: Vec<(Value, Value)>,
+    /// The ciphertext.
+    ciphertext: Vec<u8>,
+    /// The recipients of a `COSE_Encrypt`.
+    recipients: Vec<Layer>,
+}
+
+/// Encode the CBOR `value`.
+fn encode(value: &Value) -> Result<Vec<u8>, HpkeError> {
+    let mut cbor = Vec::new();
+    ciborium::into_writer(value, &mut cbor).map_err(|_| HpkeError::InvalidInput)?;
+    Ok(cbor)
+}
+
+/// Decode the single CBOR value in `cbor`.
+fn decode(cbor: &[u8]) -> Result<Value, HpkeError> {
+    let mut reader = cbor;
+    let value = ciborium::from_reader(&mut reader).map_err(|_| HpkeError::InvalidInput)?;
+    if !reader.is_empty() {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(value)
+}
+
+/// Get the value of the header parameter `label` in the `header`.
+fn header(header: &[(Value, Value)], label: i64) -> Option<&Value> {
+    header
+        .iter()
+        .find(|(key, _)| {
+            key.as_integer()
+                .and_then(|key| i64::try_from(key).ok())
+                .is_some_and(|key| key == label)
+        })
+        .map(|(_, value)| value)
+}
+
+/// Get the integer `alg` of the protected header of the `layer`.
+fn alg(layer: &Layer) -> Result<i64, HpkeError> {
+    header(&layer.protected_map, HEADER_ALG)
+        .and_then(Value::as_integer)
+        .and_then(|alg| i64::try_from(alg).ok())
+        .ok_or(HpkeError::InvalidInput)
+}
+
+/// Serialize the protected header with the `alg`.
+fn protected(alg: i64) -> Result<Vec<u8>, HpkeError> {
+    encode(&Value::Map(vec![(
+        Value::Integer(HEADER_ALG.into()),
+        Value::Integer(alg.into()),
+    )]))
+}
+
+/// Serialize the `Enc_structure` for the `context`.
+fn enc_structure(
+    context: &str,
+    protected: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    encode(&Value::Array(vec![
+        Value::Text(context.into()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(external_aad.to_vec()),
+    ]))
+}
+
+impl Layer {
+    /// Create a layer with the serialized `protected` header.
+    fn new(protected: Vec<u8>, unprotected: Vec<(Value, Value)>, ciphertext: Vec<u8>) -> Self {
+        Self {
+            protected,
+            protected_map: Vec::new(),
+            unprotected,
+            ciphertext,
+            recipients: Vec::new(),
+        }
+    }
+
+    /// Convert this layer into a CBOR array.
+    fn into_value(self, with_recipients: bool) -> Value {
+        let mut layer = vec![
+            Value::Bytes(self.protected),
+            Value::Map(self.unprotected),
+            Value::Bytes(self.ciphertext),
+        ];
+        if with_recipients {
+            layer.push(Value::Array(
+                self.recipients
+                    .into_iter()
+                    .map(|recipient| recipient.into_value(false))
+                    .collect(),
+            ));
+        }
+        Value::Array(layer)
+    }
+
+    /// Parse a layer from a CBOR array.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `value` is malformed or
+    /// has recipients unless `with_recipients` is set.
+    fn from_value(value: Value, with_recipients: bool) -> Result<Self, HpkeError> {
+        let Value::Array(layer) = value else {
+            return Err(HpkeError::InvalidInput);
+        };
+        let mut layer = layer.into_iter();
+        let (
+            Some(Value::Bytes(protected)),
+            Some(Value::Map(unprotected)),
+            Some(Value::Bytes(ciphertext)),
+        ) = (layer.next(), layer.next(), layer.next())
+        else {
+            return Err(HpkeError::InvalidInput);
+        };
+        let recipients = match layer.next() {
+            Some(Value::Array(recipients)) if with_recipients => recipients
+                .into_iter()
+                .map(|recipient| Self::from_value(recipient, false))
+                .collect::<Result<_, _>>()?,
+            None if !with_recipients => Vec::new(),
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        if layer.next().is_some() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let protected_map = if protected.is_empty() {
+            Vec::new()
+        } else {
+            decode(&protected)?
+                .into_map()
+                .map_err(|_| HpkeError::InvalidInput)?
+        };
+        Ok(Self {
+            protected,
+            protected_map,
+            unprotected,
+            ciphertext,
+            recipients,
+        })
+    }
+}
+
+/// Get the COSE-HPKE algorithm of the `hpke` configuration.
+///
+/// Returns an [`HpkeError::InvalidConfig`] if the configuration is not in
+/// base mode or has no COSE-HPKE algorithm.
+fn hpke_algorithm<Crypto: HpkeCrypto>(hpke: &Hpke<Crypto>) -> Result<i64, HpkeError> {
+    if hpke.mode != Mode::Base {
+        return Err(HpkeError::InvalidConfig);
+    }
+    algorithm(hpke.kem_id, hpke.kdf_id, hpke.aead_id).ok_or(HpkeError::InvalidConfig)
+}
+
+/// Seal `plain_txt` to `pk_r` with HPKE and return the `ek` header and the
+/// ciphertext.
+fn seal_layer<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    kid: Option<&[u8]>,
+    context: &str,
+    external_aad: &[u8],
+    plain_txt: &[u8],
+) -> Result<Layer, HpkeError> {
+    let alg = hpke_algorithm(hpke)?;
+    let protected = protected(alg)?;
+    let aad = enc_structure(context, &protected, external_aad)?;
+    let (enc, ct) = hpke.seal(pk_r, &[], &aad, plain_txt, None, None, None)?;
+    let mut unprotected = vec![(Value::Integer(HEADER_EK.into()), Value::Bytes(enc.into()))];
+    if let Some(kid) = kid {
+        unprotected.push((
+            Value::Integer(HEADER_KID.into()),
+            Value::Bytes(kid.to_vec()),
+        ));
+    }
+    Ok(Layer::new(protected, unprotected, ct.into()))
+}
+
+/// Open the HPKE ciphertext of the `layer` with `sk_r`.
+fn open_layer<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    sk_r: &HpkePrivateKey,
+    context: &str,
+    external_aad: &[u8],
+    layer: &Layer,
+) -> Result<Vec<u8>, HpkeError> {
+    if alg(layer)? != hpke_algorithm(hpke)? {
+        return Err(HpkeError::InvalidConfig);
+    }
+    let enc = header(&layer.unprotected, HEADER_EK)
+        .and_then(Value::as_bytes)
+        .ok_or(HpkeError::InvalidInput)?;
+    let enc = EncapsulatedSecret::new(hpke.kem_id, enc.clone())?;
+    let ct = Ciphertext::new(hpke.aead_id, layer.ciphertext.clone())?;
+    let aad = enc_structure(context, &layer.protected, external_aad)?;
+    hpke.open(&enc, sk_r, &[], &aad, &ct, None, None, None)
+}
+
+/// Encrypt `plain_txt` to `pk_r` into a tagged `COSE_Encrypt0` structure
+/// (integrated encryption).
+///
+/// The optional `kid` identifies the recipient key in the unprotected
+/// header.
+/// Returns an [`HpkeError::InvalidConfig`] if the `hpke` configuration is not
+/// in base mode or has no COSE-HPKE [`algorithm`].
+pub fn seal_encrypt0<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    kid: Option<&[u8]>,
+    external_aad: &[u8],
+    plain_txt: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let layer = seal_layer(hpke, pk_r, kid, "Encrypt0", external_aad, plain_txt)?;
+    encode(&Value::Tag(TAG_ENCRYPT0, Box::new(layer.into_value(false))))
+}
+
+/// Decrypt a tagged or untagged `COSE_Encrypt0` structure with `sk_r`
+/// (integrated encryption).
+///
+/// Returns an [`HpkeError::InvalidInput`] if the `message` is malformed and
+/// an [`HpkeError::InvalidConfig`] if its algorithm doesn't match the `hpke`
+/// configuration.
+pub fn open_encrypt0<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    sk_r: &HpkePrivateKey,
+    external_aad: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let layer = match decode(message)? {
+        Value::Tag(TAG_ENCRYPT0, value) => *value,
+        Value::Tag(..) => return Err(HpkeError::InvalidInput),
+        value => value,
+    };
+    let layer = Layer::from_value(layer, false)?;
+    open_layer(hpke, sk_r, "Encrypt0", external_aad, &layer)
+}
+
+/// Encrypt `plain_txt` with a random content encryption key for the
+/// `content_aead` into a tagged `COSE_Encrypt` structure, and the key to each
+/// of the `recipients` with HPKE (key encryption).
+///
+/// Each recipient is a public key with an optional `kid`.
+/// Returns an [`HpkeError::InvalidConfig`] if the `hpke` configuration is not
+/// in base mode or has no COSE-HPKE [`algorithm`], or the `content_aead` has
+/// no COSE [`content_algorithm`].
+pub fn seal_encrypt<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    content_aead: AeadAlgorithm,
+    recipients: &[(&HpkePublicKey, Option<&[u8]>)],
+    external_aad: &[u8],
+    plain_txt: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let content_alg = content_algorithm(content_aead).ok_or(HpkeError::InvalidConfig)?;
+    hpke_algorithm(hpke)?;
+    let protected = protected(content_alg)?;
+
+    let mut cek = hpke.random(Crypto::aead_key_length(content_aead))?;
+    let iv = hpke.random(Crypto::aead_nonce_length(content_aead))?;
+    let layers = recipients
+        .iter()
+        .map(|&(pk_r, kid)| seal_layer(hpke, pk_r, kid, "Enc_Recipient", external_aad, &cek))
+        .collect::<Result<Vec<_>, _>>();
+    let ct = enc_structure("Encrypt", &protected, external_aad).and_then(|aad| {
+        Crypto::aead_seal(content_aead, &cek, &iv, &aad, plain_txt).map_err(HpkeError::from)
+    });
+    cek.zeroize();
+
+    let mut layer = Layer::new(
+        protected,
+        vec![(Value::Integer(HEADER_IV.into()), Value::Bytes(iv))],
+        ct?,
+    );
+    layer.recipients = layers?;
+    encode(&Value::Tag(TAG_ENCRYPT, Box::new(layer.into_value(true))))
+}
+
+/// Decrypt a tagged or untagged `COSE_Encrypt` structure with `sk_r` (key
+/// encryption).
+///
+/// The content encryption key is decrypted from the first recipient with
+/// the algorithm of the `hpke` configuration and, if given, the `kid` that
+/// can be opened with `sk_r`.
+/// Returns an [`HpkeError::InvalidInput`] if the `message` is malformed and
+/// an [`HpkeError::InvalidConfig`] if there is no recipient for the `hpke`
+/// configuration and `kid`.
+pub fn open_encrypt<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    sk_r: &HpkePrivateKey,
+    kid: Option<&[u8]>,
+    external_aad: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let layer = match decode(message)? {
+        Value::Tag(TAG_ENCRYPT, value) => *value,
+        Value::Tag(..) => return Err(HpkeError::InvalidInput),
+        value => value,
+    };
+    let layer = Layer::from_value(layer, true)?;
+    let content_aead = content_aead(alg(&layer)?).ok_or(HpkeError::InvalidInput)?;
+    let iv = header(&layer.unprotected, HEADER_IV)
+        .and_then(Value::as_bytes)
+        .ok_or(HpkeError::InvalidInput)?;
+
+    let hpke_alg = hpke_algorithm(hpke)?;
+    let mut result = Err(HpkeError::InvalidConfig);
+    for recipient in layer.recipients.iter().filter(|recipient| {
+        alg(recipient).is_ok_and(|alg| alg == hpke_alg)
+            && kid.is_none_or(|kid| {
+                header(&recipient.unprotected, HEADER_KID)
+                    .and_then(Value::as_bytes)
+                    .map(Vec::as_slice)
+                    == Some(kid)
+            })
+    }) {
+        result = open_layer(hpke, sk_r, "Enc_Recipient", external_aad, recipient);
+        if result.is_ok() {
+            break;
+        }
+    }
+    let mut cek = result?;
+    if cek.len() != Crypto::aead_key_length(content_aead) {
+        cek.zeroize();
+        return Err(HpkeError::InvalidInput);
+    }
+
+    let pt = enc_structure("Encrypt", &layer.protected, external_aad).and_then(|aad| {
+        Crypto::aead_open(content_aead, &cek, iv, &aad, &layer.ciphertext).map_err(HpkeError::from)
+    });
+    cek.zeroize();
+    pt
+}
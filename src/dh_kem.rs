@@ -2,22 +2,28 @@
 
 use alloc::{string::ToString, vec::Vec};
 
-use hpke_rs_crypto::{error::Error, types::KemAlgorithm, HpkeCrypto};
+use hpke_rs_crypto::{
+    error::Error,
+    types::{KemAlgorithm, PrivateKeyRef},
+    HpkeCryptoInstance,
+};
 
 use crate::util::*;
 use crate::{
-    kdf::{labeled_expand, labeled_extract},
+    kdf::{labeled_expand_with, labeled_extract_with},
     kem::*,
 };
 
-fn extract_and_expand<Crypto: HpkeCrypto>(
+fn extract_and_expand(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
     pk: PublicKey,
     kem_context: &[u8],
     suite_id: &[u8],
 ) -> Result<Vec<u8>, Error> {
-    let prk = labeled_extract::<Crypto>(alg.into(), &[], suite_id, "eae_prk", &pk)?;
-    labeled_expand::<Crypto>(
+    let prk = labeled_extract_with(crypto, alg.into(), &[], suite_id, "eae_prk", &pk)?;
+    labeled_expand_with(
+        crypto,
         alg.into(),
         &prk,
         suite_id,
@@ -42,23 +48,26 @@ pub(super) fn deserialize(enc: &[u8]) -> Vec<u8> {
 }
 
 /// Return (private, public)
-pub(super) fn key_gen<Crypto: HpkeCrypto>(
+pub(super) fn key_gen<Crypto: HpkeCryptoInstance>(
+    crypto: &Crypto,
     alg: KemAlgorithm,
     prng: &mut Crypto::HpkePrng,
 ) -> Result<(Vec<u8>, Vec<u8>), Error> {
-    let (pk, sk) = Crypto::kem_key_gen(alg, prng)?;
+    let (pk, sk) = crypto.kem_key_gen(alg, prng)?;
     Ok((sk, pk))
 }
 
-pub(super) fn derive_key_pair<Crypto: HpkeCrypto>(
+pub(super) fn derive_key_pair(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
     suite_id: &[u8],
     ikm: &[u8],
 ) -> Result<(PublicKey, PrivateKey), Error> {
-    let dkp_prk = labeled_extract::<Crypto>(alg.into(), &[], suite_id, "dkp_prk", ikm)?;
+    let dkp_prk = labeled_extract_with(crypto, alg.into(), &[], suite_id, "dkp_prk", ikm)?;
 
     let sk = match alg {
-        KemAlgorithm::DhKem25519 => labeled_expand::<Crypto>(
+        KemAlgorithm::DhKem25519 | KemAlgorithm::DhKem448 => labeled_expand_with(
+            crypto,
             alg.into(),
             &dkp_prk,
             suite_id,
@@ -66,13 +75,22 @@ pub(super) fn derive_key_pair<Crypto: HpkeCrypto>(
             &[],
             alg.private_key_len(),
         )?,
-        KemAlgorithm::DhKemP256 | KemAlgorithm::DhKemK256 => {
+        KemAlgorithm::DhKemP256
+        | KemAlgorithm::DhKemK256
+        | KemAlgorithm::DhKemP384
+        | KemAlgorithm::DhKemP521 => {
+            // P-521 scalars have a single bit in the first byte.
+            let bitmask = match alg {
+                KemAlgorithm::DhKemP521 => 0x01,
+                _ => 0xff,
+            };
             let mut ctr = 0u8;
             // Do rejection sampling trying to find a valid key.
             // It is expected that there aren't too many iteration and that
             // the loop will always terminate.
             loop {
-                let candidate = labeled_expand::<Crypto>(
+                let candidate = labeled_expand_with(
+                    crypto,
                     alg.into(),
                     &dkp_prk,
                     suite_id,
@@ -80,8 +98,11 @@ pub(super) fn derive_key_pair<Crypto: HpkeCrypto>(
                     &ctr.to_be_bytes(),
                     alg.private_key_len(),
                 );
-                if let Ok(sk) = &candidate {
-                    if let Ok(sk) = Crypto::dh_validate_sk(alg, sk) {
+                if let Ok(mut sk) = candidate {
+                    if let Some(first) = sk.first_mut() {
+                        *first &= bitmask;
+                    }
+                    if let Ok(sk) = crypto.dh_validate_sk(alg, &sk) {
                         break sk;
                     }
                 }
@@ -91,89 +112,132 @@ pub(super) fn derive_key_pair<Crypto: HpkeCrypto>(
                         "Unable to generate a valid private key".to_string(),
                     ));
                 }
-                ctr += 1;
+                ctr = ctr.wrapping_add(1);
             }
         }
-        _ => {
-            panic!("This should be unreachable. Only x25519, P256, and K256 KEMs are implemented")
-        }
+        // Only X25519, X448, P256, K256, P384, and P521 KEMs are implemented.
+        _ => return Err(Error::UnsupportedKemOperation),
     };
-    Ok((Crypto::secret_to_public(alg, &sk)?, sk))
+    Ok((crypto.secret_to_public(alg, &sk)?, sk))
 }
 
-pub(super) fn encaps<Crypto: HpkeCrypto>(
+pub(super) fn encaps(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
     pk_r: &[u8],
     suite_id: &[u8],
     randomness: &[u8],
 ) -> Result<(Vec<u8>, Vec<u8>), Error> {
-    debug_assert_eq!(randomness.len(), alg.private_key_len());
-    let (pk_e, sk_e) = derive_key_pair::<Crypto>(alg, suite_id, randomness)?;
-    let dh_pk = Crypto::dh(alg, pk_r, &sk_e)?;
+    if randomness.len() != alg.private_key_len() {
+        return Err(Error::InsufficientRandomness);
+    }
+    let (pk_e, sk_e) = derive_key_pair(crypto, alg, suite_id, randomness)?;
+    let dh_pk = crypto.dh(alg, pk_r, &sk_e)?;
     let enc = serialize(&pk_e);
 
     let pk_rm = serialize(pk_r);
     let kem_context = concat(&[&enc, &pk_rm]);
 
-    let zz = extract_and_expand::<Crypto>(alg, dh_pk, &kem_context, suite_id)?;
+    let zz = extract_and_expand(crypto, alg, dh_pk, &kem_context, suite_id)?;
     Ok((zz, enc))
 }
 
-pub(super) fn decaps<Crypto: HpkeCrypto>(
+/// Run the key derivation on the DH outputs `dh` whether or not they are
+/// valid, such that invalid encapsulations take the same path as valid ones.
+///
+/// Failed DH operations are replaced with zeros and reported as
+/// [`Error::KemInvalidCiphertext`] after the key derivation.
+fn uniform_extract_and_expand(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
-    enc: &[u8],
-    sk_r: &[u8],
+    dh: &[Result<Vec<u8>, Error>],
+    kem_context: &[u8],
     suite_id: &[u8],
 ) -> Result<Vec<u8>, Error> {
-    let pk_e = deserialize(enc);
-    let dh_pk = Crypto::dh(alg, &pk_e, sk_r)?;
+    let mut valid = true;
+    let mut dh_pk = Vec::new();
+    for dh in dh {
+        match dh {
+            Ok(dh) => dh_pk.extend_from_slice(dh),
+            Err(_) => {
+                valid = false;
+                dh_pk.resize(dh_pk.len().saturating_add(alg.private_key_len()), 0);
+            }
+        }
+    }
+    let zz = extract_and_expand(crypto, alg, dh_pk, kem_context, suite_id)?;
+    if valid {
+        Ok(zz)
+    } else {
+        Err(Error::KemInvalidCiphertext)
+    }
+}
 
-    let pk_rm = serialize(&Crypto::secret_to_public(alg, sk_r)?);
+/// Decapsulation of `enc` with `sk_r`.
+///
+/// The private key is validated first because it is not attacker controlled.
+/// Failures depending on `enc` are reported as [`Error::KemInvalidCiphertext`]
+/// only after the key derivation, see [`uniform_extract_and_expand`].
+pub(super) fn decaps(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    enc: &[u8],
+    sk_r: PrivateKeyRef<'_>,
+    suite_id: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let pk_rm = serialize(&crypto.secret_to_public_with_key(alg, sk_r)?);
     let kem_context = concat(&[enc, &pk_rm]);
 
-    extract_and_expand::<Crypto>(alg, dh_pk, &kem_context, suite_id)
+    let pk_e = deserialize(enc);
+    let dh = crypto.dh_with_key(alg, &pk_e, sk_r);
+
+    uniform_extract_and_expand(crypto, alg, &[dh], &kem_context, suite_id)
 }
 
-pub(super) fn auth_encaps<Crypto: HpkeCrypto>(
+pub(super) fn auth_encaps(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
     pk_r: &[u8],
-    sk_s: &[u8],
+    sk_s: PrivateKeyRef<'_>,
     suite_id: &[u8],
     randomness: &[u8],
 ) -> Result<(Vec<u8>, Vec<u8>), Error> {
-    debug_assert_eq!(randomness.len(), alg.private_key_len());
-    let (pk_e, sk_e) = derive_key_pair::<Crypto>(alg, suite_id, randomness)?;
+    if randomness.len() != alg.private_key_len() {
+        return Err(Error::InsufficientRandomness);
+    }
+    let (pk_e, sk_e) = derive_key_pair(crypto, alg, suite_id, randomness)?;
     let dh_pk = concat(&[
-        &Crypto::dh(alg, pk_r, &sk_e)?,
-        &Crypto::dh(alg, pk_r, sk_s)?,
+        &crypto.dh(alg, pk_r, &sk_e)?,
+        &crypto.dh_with_key(alg, pk_r, sk_s)?,
     ]);
 
     let enc = serialize(&pk_e);
     let pk_rm = serialize(pk_r);
-    let pk_sm = serialize(&Crypto::secret_to_public(alg, sk_s)?);
+    let pk_sm = serialize(&crypto.secret_to_public_with_key(alg, sk_s)?);
 
     let kem_context = concat(&[&enc, &pk_rm, &pk_sm]);
 
-    let zz = extract_and_expand::<Crypto>(alg, dh_pk, &kem_context, suite_id)?;
+    let zz = extract_and_expand(crypto, alg, dh_pk, &kem_context, suite_id)?;
     Ok((zz, enc))
 }
 
-pub(super) fn auth_decaps<Crypto: HpkeCrypto>(
+pub(super) fn auth_decaps(
+    crypto: &impl HpkeCryptoInstance,
     alg: KemAlgorithm,
     enc: &[u8],
-    sk_r: &[u8],
+    sk_r: PrivateKeyRef<'_>,
     pk_s: &[u8],
     suite_id: &[u8],
 ) -> Result<Vec<u8>, Error> {
-    let pk_e = deserialize(enc);
-    let dh_pk = concat(&[
-        &Crypto::dh(alg, &pk_e, sk_r)?,
-        &Crypto::dh(alg, pk_s, sk_r)?,
-    ]);
-
-    let pk_rm = serialize(&Crypto::secret_to_public(alg, sk_r)?);
+    let pk_rm = serialize(&crypto.secret_to_public_with_key(alg, sk_r)?);
     let pk_sm = serialize(pk_s);
     let kem_context = concat(&[enc, &pk_rm, &pk_sm]);
 
-    extract_and_expand::<Crypto>(alg, dh_pk, &kem_context, suite_id)
+    let pk_e = deserialize(enc);
+    let dh = [
+        crypto.dh_with_key(alg, &pk_e, sk_r),
+        crypto.dh_with_key(alg, pk_s, sk_r),
+    ];
+
+    uniform_extract_and_expand(crypto, alg, &dh, &kem_context, suite_id)
 }
@@ -0,0 +1,277 @@
+//! # Runtime Provider Selection
+//!
+//! [`Hpke`] is generic over the crypto provider, which therefore has to be
+//! chosen at compile time.
+//! [`DynHpke`] is an object safe version of the HPKE operations that is
+//! implemented for every [`Hpke`], such that applications can hold a
+//! `Box<dyn DynHpke>` and pick the provider at runtime, e.g. from a config
+//! file, with [`Provider`].
+//!
+//! ```
+//! use hpke_rs::{dyn_hpke::boxed, hpke_types::*, HpkeSuite, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let suite = HpkeSuite::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! // With the `rustcrypto` feature this is `Provider::from_name("rustcrypto")?.hpke(suite)?`.
+//! let mut hpke = boxed::<HpkeRustCrypto>(suite)?;
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let (enc, ct) = hpke.seal(&pk_r, b"info", b"aad", b"hello", None, None, None)?;
+//! assert_eq!(hpke.open(&enc, &sk_r, b"info", b"aad", &ct, None, None, None)?, b"hello");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+//!
+//! The contexts are boxed as [`DynSenderContext`] and [`DynReceiverContext`].
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkeKeyPair, HpkePrivateKey, HpkePublicKey,
+    HpkeSuite, ReceiverContext, SenderContext,
+};
+
+/// An HPKE sender context with a provider chosen at runtime.
+///
+/// See [`SenderContext`].
+pub trait DynSenderContext {
+    /// See [`SenderContext::seal`].
+    fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Ciphertext, HpkeError>;
+
+    /// See [`Context::export`](crate::Context::export).
+    fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError>;
+}
+
+/// An HPKE receiver context with a provider chosen at runtime.
+///
+/// See [`ReceiverContext`].
+pub trait DynReceiverContext {
+    /// See [`ReceiverContext::open`].
+    fn open(&mut self, aad: &[u8], cipher_txt: &Ciphertext) -> Result<Vec<u8>, HpkeError>;
+
+    /// See [`Context::export`](crate::Context::export).
+    fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError>;
+}
+
+/// An HPKE configuration with a provider chosen at runtime.
+///
+/// See [`Hpke`] for the documentation of the operations.
+pub trait DynHpke {
+    /// The name of the crypto provider.
+    fn provider_name(&self) -> String;
+
+    /// See [`Hpke::suite`].
+    fn suite(&self) -> HpkeSuite;
+
+    /// See [`Hpke::generate_key_pair`].
+    fn generate_key_pair(&mut self) -> Result<HpkeKeyPair, HpkeError>;
+
+    /// See [`Hpke::derive_key_pair`].
+    fn derive_key_pair(&self, ikm: &[u8]) -> Result<HpkeKeyPair, HpkeError>;
+
+    /// See [`Hpke::setup_sender`].
+    fn setup_sender(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Box<dyn DynSenderContext>), HpkeError>;
+
+    /// See [`Hpke::setup_receiver`].
+    fn setup_receiver(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Box<dyn DynReceiverContext>, HpkeError>;
+
+    /// See [`Hpke::seal`].
+    #[allow(clippy::too_many_arguments)]
+    fn seal(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError>;
+
+    /// See [`Hpke::open`].
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError>;
+}
+
+impl<Crypto: HpkeCrypto> DynSenderContext for SenderContext<Crypto> {
+    fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Ciphertext, HpkeError> {
+        SenderContext::seal(self, aad, plain_txt)
+    }
+
+    fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError> {
+        (**self).export(exporter_context, length)
+    }
+}
+
+impl<Crypto: HpkeCrypto> DynReceiverContext for ReceiverContext<Crypto> {
+    fn open(&mut self, aad: &[u8], cipher_txt: &Ciphertext) -> Result<Vec<u8>, HpkeError> {
+        ReceiverContext::open(self, aad, cipher_txt)
+    }
+
+    fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError> {
+        (**self).export(exporter_context, length)
+    }
+}
+
+impl<Crypto: HpkeCrypto> DynHpke for Hpke<Crypto> {
+    fn provider_name(&self) -> String {
+        Crypto::name()
+    }
+
+    fn suite(&self) -> HpkeSuite {
+        Hpke::suite(self)
+    }
+
+    fn generate_key_pair(&mut self) -> Result<HpkeKeyPair, HpkeError> {
+        Hpke::generate_key_pair(self)
+    }
+
+    fn derive_key_pair(&self, ikm: &[u8]) -> Result<HpkeKeyPair, HpkeError> {
+        Hpke::derive_key_pair(self, ikm)
+    }
+
+    fn setup_sender(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Box<dyn DynSenderContext>), HpkeError> {
+        let (enc, context) = Hpke::setup_sender(self, pk_r, info, psk, psk_id, sk_s)?;
+        Ok((enc, Box::new(context)))
+    }
+
+    fn setup_receiver(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Box<dyn DynReceiverContext>, HpkeError> {
+        let context = Hpke::setup_receiver(self, enc, sk_r, info, psk, psk_id, pk_s)?;
+        Ok(Box::new(context))
+    }
+
+    fn seal(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        Hpke::seal(self, pk_r, info, aad, plain_txt, psk, psk_id, sk_s)
+    }
+
+    fn open(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError> {
+        Hpke::open(self, enc, sk_r, info, aad, ct, psk, psk_id, pk_s)
+    }
+}
+
+/// Create a boxed HPKE configuration for the `suite` with the `Crypto`
+/// provider.
+///
+/// Returns an [`HpkeError::UnsupportedAlgorithm`] if the provider doesn't
+/// support the `suite`.
+pub fn boxed<Crypto: 'static + HpkeCrypto>(
+    suite: HpkeSuite,
+) -> Result<Box<dyn DynHpke>, HpkeError> {
+    suite.check_support::<Crypto>()?;
+    Ok(Box::new(suite.into_hpke::<Crypto>()))
+}
+
+/// The built-in crypto providers that are enabled with features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    /// The RustCrypto provider, with the `rustcrypto` feature.
+    #[cfg(feature = "rustcrypto")]
+    RustCrypto,
+
+    /// The libcrux provider, with the `libcrux` feature.
+    #[cfg(feature = "libcrux")]
+    Libcrux,
+}
+
+impl Provider {
+    /// Get the provider for the `name` of its feature, i.e. `rustcrypto` or
+    /// `libcrux`, ignoring the case.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] for unknown providers and
+    /// providers whose feature is not enabled.
+    #[cfg_attr(
+        not(any(feature = "rustcrypto", feature = "libcrux")),
+        allow(unused_variables)
+    )]
+    pub fn from_name(name: &str) -> Result<Self, HpkeError> {
+        #[cfg(feature = "rustcrypto")]
+        if name.eq_ignore_ascii_case("rustcrypto") {
+            return Ok(Self::RustCrypto);
+        }
+        #[cfg(feature = "libcrux")]
+        if name.eq_ignore_ascii_case("libcrux") {
+            return Ok(Self::Libcrux);
+        }
+        Err(HpkeError::InvalidConfig)
+    }
+
+    /// Create a boxed HPKE configuration for the `suite` with this provider.
+    ///
+    /// See [`boxed`].
+    #[cfg_attr(
+        not(any(feature = "rustcrypto", feature = "libcrux")),
+        allow(unused_variables)
+    )]
+    pub fn hpke(self, suite: HpkeSuite) -> Result<Box<dyn DynHpke>, HpkeError> {
+        match self {
+            #[cfg(feature = "rustcrypto")]
+            Self::RustCrypto => boxed::<hpke_rs_rust_crypto::HpkeRustCrypto>(suite),
+            #[cfg(feature = "libcrux")]
+            Self::Libcrux => boxed::<hpke_rs_libcrux::HpkeLibcrux>(suite),
+        }
+    }
+}
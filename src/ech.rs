@@ -0,0 +1,337 @@
+//! # Encrypted Client Hello
+//!
+//! The HPKE parts of TLS Encrypted Client Hello (draft-ietf-tls-esni):
+//! parsing the `ECHConfigList` published by a server, selecting a config and
+//! cipher suite the `Crypto` provider supports, and setting up the HPKE
+//! context with the `info` of the selected config.
+//!
+//! ```text
+//! info = "tls ech" || 0x00 || ECHConfig
+//! ```
+//!
+//! Only version `0xfe0d` configs are parsed; configs with other versions are
+//! skipped.
+//! The `config_id` is the 8-bit identifier chosen by the server and sent in
+//! the `ClientHello` along with the cipher suite and the encapsulated key.
+//!
+//! ```
+//! use hpke_rs::{ech::{self, EchConfig}, hpke_types::*, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! // The client-facing server publishes its configs.
+//! let mut keys = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::Aes128Gcm,
+//! );
+//! let (sk_r, pk_r) = keys.generate_key_pair()?.into_keys();
+//! let server_config = EchConfig::new(
+//!     0x42,
+//!     KemAlgorithm::DhKem25519,
+//!     pk_r,
+//!     vec![(KdfAlgorithm::HkdfSha256, AeadAlgorithm::Aes128Gcm)],
+//!     0,
+//!     b"public.example".to_vec(),
+//!     vec![],
+//! )?;
+//! let ech_config_list = EchConfig::encode_list(&[server_config.clone()])?;
+//!
+//! // Client
+//! let configs = EchConfig::decode_list(&ech_config_list)?;
+//! let mut sender = ech::setup_sender::<HpkeRustCrypto>(&configs)?;
+//! let payload = sender.context.seal(b"outer", b"inner")?;
+//!
+//! // Client-facing server
+//! let mut receiver = server_config.setup_receiver::<HpkeRustCrypto>(
+//!     sender.suite.kdf,
+//!     sender.suite.aead,
+//!     &sender.enc,
+//!     &sk_r,
+//! )?;
+//! assert_eq!(receiver.open(b"outer", &payload)?, b"inner");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+
+use crate::{
+    util, EncapsulatedSecret, HpkeError, HpkePrivateKey, HpkePublicKey, HpkeSuite, Mode,
+    ReceiverContext, SenderContext,
+};
+
+/// The version of the `ECHConfig` structure.
+pub const ECH_VERSION: u16 = 0xfe0d;
+
+/// The prefix of the HPKE `info`.
+const INFO_LABEL: &[u8] = b"tls ech\0";
+
+/// An `ECHConfig` of version [`ECH_VERSION`].
+///
+/// The encoding of the config is kept, as it is part of the HPKE `info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EchConfig {
+    /// The identifier of the config.
+    pub config_id: u8,
+
+    /// The KEM of the public key.
+    pub kem: KemAlgorithm,
+
+    /// The public key.
+    pub public_key: HpkePublicKey,
+
+    /// The supported KDF and AEAD pairs.
+    pub cipher_suites: Vec<(KdfAlgorithm, AeadAlgorithm)>,
+
+    /// The maximum length of the server names that use this config.
+    pub maximum_name_length: u8,
+
+    /// The public name of the client-facing server.
+    pub public_name: Vec<u8>,
+
+    /// The extensions as type and data.
+    pub extensions: Vec<(u16, Vec<u8>)>,
+
+    /// The encoded `ECHConfig`.
+    encoding: Vec<u8>,
+}
+
+impl EchConfig {
+    /// Create an `ECHConfig` for the `public_key` of the `kem`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if a field is too long for its
+    /// encoding, and an [`HpkeError::InvalidConfig`] if the public name is
+    /// empty or longer than 255 bytes, or there are no cipher suites.
+    pub fn new(
+        config_id: u8,
+        kem: KemAlgorithm,
+        public_key: HpkePublicKey,
+        cipher_suites: Vec<(KdfAlgorithm, AeadAlgorithm)>,
+        maximum_name_length: u8,
+        public_name: Vec<u8>,
+        extensions: Vec<(u16, Vec<u8>)>,
+    ) -> Result<Self, HpkeError> {
+        let public_name_len = u8::try_from(public_name.len())
+            .ok()
+            .filter(|&len| len > 0)
+            .ok_or(HpkeError::InvalidConfig)?;
+        if cipher_suites.is_empty() || public_key.as_slice().is_empty() {
+            return Err(HpkeError::InvalidConfig);
+        }
+
+        let mut contents = Vec::new();
+        contents.push(config_id);
+        contents.extend_from_slice(&kem.id().to_be_bytes());
+        util::write_vec16(&mut contents, public_key.as_slice())?;
+        let mut suites = Vec::new();
+        for (kdf, aead) in &cipher_suites {
+            suites.extend_from_slice(&kdf.id().to_be_bytes());
+            suites.extend_from_slice(&aead.id().to_be_bytes());
+        }
+        util::write_vec16(&mut contents, &suites)?;
+        contents.push(maximum_name_length);
+        contents.push(public_name_len);
+        contents.extend_from_slice(&public_name);
+        let mut extension_bytes = Vec::new();
+        for (extension_type, data) in &extensions {
+            extension_bytes.extend_from_slice(&extension_type.to_be_bytes());
+            util::write_vec16(&mut extension_bytes, data)?;
+        }
+        util::write_vec16(&mut contents, &extension_bytes)?;
+
+        let mut encoding = ECH_VERSION.to_be_bytes().to_vec();
+        util::write_vec16(&mut encoding, &contents)?;
+        Ok(Self {
+            config_id,
+            kem,
+            public_key,
+            cipher_suites,
+            maximum_name_length,
+            public_name,
+            extensions,
+            encoding,
+        })
+    }
+
+    /// Get the encoded `ECHConfig`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.encoding
+    }
+
+    /// Decode the `ECHConfigContents` of the `encoding`.
+    fn decode_contents(encoding: &[u8], contents: &[u8]) -> Result<Self, HpkeError> {
+        let (&config_id, rest) = contents.split_first().ok_or(HpkeError::InvalidInput)?;
+        let (kem, rest) = util::read_u16(rest)?;
+        let (public_key, rest) = util::read_vec16(rest)?;
+        let (suites, rest) = util::read_vec16(rest)?;
+        let chunks = suites.chunks_exact(4);
+        if suites.is_empty() || !chunks.remainder().is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let cipher_suites = chunks
+            .map(|chunk| {
+                let (kdf, aead) = util::read_u16(chunk)?;
+                let (aead, _) = util::read_u16(aead)?;
+                Ok((KdfAlgorithm::from_id(kdf), AeadAlgorithm::from_id(aead)))
+            })
+            .collect::<Result<_, HpkeError>>()?;
+        let (&maximum_name_length, rest) = rest.split_first().ok_or(HpkeError::InvalidInput)?;
+        let (&public_name_len, rest) = rest.split_first().ok_or(HpkeError::InvalidInput)?;
+        let (public_name, rest) = util::split(rest, public_name_len.into())?;
+        let (mut extension_bytes, rest) = util::read_vec16(rest)?;
+        if public_key.is_empty() || public_name.is_empty() || !rest.is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let mut extensions = Vec::new();
+        while !extension_bytes.is_empty() {
+            let (extension_type, rest) = util::read_u16(extension_bytes)?;
+            let (data, rest) = util::read_vec16(rest)?;
+            extensions.push((extension_type, data.to_vec()));
+            extension_bytes = rest;
+        }
+
+        Ok(Self {
+            config_id,
+            kem: KemAlgorithm::from_id(kem),
+            public_key: HpkePublicKey::new(public_key.to_vec()),
+            cipher_suites,
+            maximum_name_length,
+            public_name: public_name.to_vec(),
+            extensions,
+            encoding: encoding.to_vec(),
+        })
+    }
+
+    /// Decode an `ECHConfigList`.
+    ///
+    /// Configs with versions other than [`ECH_VERSION`] are skipped.
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed.
+    pub fn decode_list(list: &[u8]) -> Result<Vec<Self>, HpkeError> {
+        let (mut configs_bytes, rest) = util::read_vec16(list)?;
+        if configs_bytes.is_empty() || !rest.is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let mut configs = Vec::new();
+        while !configs_bytes.is_empty() {
+            let (version, rest) = util::read_u16(configs_bytes)?;
+            let (contents, rest) = util::read_vec16(rest)?;
+            let encoding = configs_bytes
+                .get(..configs_bytes.len().saturating_sub(rest.len()))
+                .ok_or(HpkeError::InvalidInput)?;
+            if version == ECH_VERSION {
+                configs.push(Self::decode_contents(encoding, contents)?);
+            }
+            configs_bytes = rest;
+        }
+        Ok(configs)
+    }
+
+    /// Encode the `configs` as `ECHConfigList`.
+    pub fn encode_list(configs: &[Self]) -> Result<Vec<u8>, HpkeError> {
+        let configs: Vec<&[u8]> = configs.iter().map(Self::as_bytes).collect();
+        let mut list = Vec::new();
+        util::write_vec16(&mut list, &util::concat(&configs))?;
+        Ok(list)
+    }
+
+    /// Get the HPKE `info` of this config.
+    pub fn info(&self) -> Vec<u8> {
+        util::concat(&[INFO_LABEL, &self.encoding])
+    }
+
+    /// Select the first cipher suite of this config that the `Crypto` provider
+    /// supports.
+    ///
+    /// Returns `None` if the provider doesn't support the KEM or any of the
+    /// cipher suites, the public key doesn't have the length of the KEM, or
+    /// the config has mandatory extensions, which are not supported.
+    pub fn suite<Crypto: HpkeCrypto>(&self) -> Option<HpkeSuite> {
+        let mandatory = |&(extension_type, _): &(u16, _)| extension_type & 0x8000 != 0;
+        if self.extensions.iter().any(mandatory)
+            || Crypto::supports_kem(self.kem).is_err()
+            || self.public_key.as_slice().len() != self.kem.public_key_len()
+        {
+            return None;
+        }
+        self.cipher_suites
+            .iter()
+            .find(|&&(kdf, aead)| {
+                aead != AeadAlgorithm::HpkeExport
+                    && Crypto::supports_kdf(kdf).is_ok()
+                    && Crypto::supports_aead(aead).is_ok()
+            })
+            .map(|&(kdf, aead)| HpkeSuite::new(Mode::Base, self.kem, kdf, aead))
+    }
+
+    /// Set up the HPKE receiver context of the client-facing server for the
+    /// `kdf` and `aead` of the `ClientHello`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the cipher suite is not in
+    /// this config.
+    pub fn setup_receiver<Crypto: 'static + HpkeCrypto>(
+        &self,
+        kdf: KdfAlgorithm,
+        aead: AeadAlgorithm,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        if !self.cipher_suites.contains(&(kdf, aead)) {
+            return Err(HpkeError::InvalidConfig);
+        }
+        HpkeSuite::new(Mode::Base, self.kem, kdf, aead).setup_receiver(
+            enc,
+            sk_r,
+            &self.info(),
+            None,
+            None,
+            None,
+        )
+    }
+}
+
+/// The HPKE sender state of a client for the selected config.
+pub struct EchSender<Crypto: 'static + HpkeCrypto> {
+    /// The identifier of the selected config.
+    pub config_id: u8,
+
+    /// The selected cipher suite.
+    pub suite: HpkeSuite,
+
+    /// The encapsulated key.
+    pub enc: EncapsulatedSecret,
+
+    /// The HPKE context for sealing the `ClientHelloInner`.
+    pub context: SenderContext<Crypto>,
+}
+
+/// Select the first of the `configs` with a cipher suite that the `Crypto`
+/// provider supports, see [`EchConfig::suite`], and set up the HPKE sender
+/// context for it.
+///
+/// Returns an [`HpkeError::InvalidConfig`] if no config is supported.
+pub fn setup_sender<Crypto: HpkeCrypto>(
+    configs: &[EchConfig],
+) -> Result<EchSender<Crypto>, HpkeError> {
+    let (config, suite) = configs
+        .iter()
+        .find_map(|config| config.suite::<Crypto>().map(|suite| (config, suite)))
+        .ok_or(HpkeError::InvalidConfig)?;
+    let (enc, context) = suite.into_hpke::<Crypto>().setup_sender(
+        &config.public_key,
+        &config.info(),
+        None,
+        None,
+        None,
+    )?;
+    Ok(EchSender {
+        config_id: config.config_id,
+        suite,
+        enc,
+        context,
+    })
+}
@@ -0,0 +1,184 @@
+//! # Sealed Envelopes
+//!
+//! An [`Envelope`] is a self-describing single shot encryption, i.e. the
+//! encapsulated secret and the ciphertext together with the ciphersuite they
+//! were created with.
+//! Applications can exchange envelopes without agreeing on the ciphersuite or
+//! a framing out of band.
+//!
+//! The envelope is encoded as
+//!
+//! ```text
+//! envelope = version || suite || I2OSP(len(enc), 2) || enc || ct
+//! ```
+//!
+//! where `version` is [`ENVELOPE_VERSION`], `suite` is the
+//! [compact encoding](crate::HpkeSuite::to_bytes) of the ciphersuite, and the
+//! ciphertext `ct` extends to the end of the envelope.
+//!
+//! The `info` and `aad`, as well as PSKs and sender keys, are not part of the
+//! envelope and have to be known by the receiver.
+//!
+//! ```
+//! use hpke_rs::{envelope::Envelope, hpke_types::*, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let envelope = Envelope::seal(&mut hpke, &pk_r, b"info", b"aad", b"hello", None, None, None)?;
+//! let bytes = envelope.to_bytes()?;
+//!
+//! // The receiver doesn't need to know the ciphersuite.
+//! let envelope = Envelope::from_bytes(&bytes)?;
+//! let pt = envelope.open::<HpkeRustCrypto>(&sk_r, b"info", b"aad", None, None, None)?;
+//! assert_eq!(pt, b"hello");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    util, Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, HpkeSuite,
+};
+
+/// The version of the envelope encoding.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// A self-describing single shot encryption.
+///
+/// See the [module documentation](self) for the encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    suite: HpkeSuite,
+    enc: EncapsulatedSecret,
+    ct: Ciphertext,
+}
+
+impl Envelope {
+    /// Encrypt the bytes in `plain_txt` to the public key `pk_r` into an
+    /// envelope with the ciphersuite of `hpke`.
+    ///
+    /// See [`Hpke::seal`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal<Crypto: HpkeCrypto>(
+        hpke: &mut Hpke<Crypto>,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<Self, HpkeError> {
+        let (enc, ct) = hpke.seal(pk_r, info, aad, plain_txt, psk, psk_id, sk_s)?;
+        Ok(Self {
+            suite: hpke.suite(),
+            enc,
+            ct,
+        })
+    }
+
+    /// Decrypt this envelope with the private key `sk_r`.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if the `Crypto`
+    /// provider doesn't support the ciphersuite of the envelope.
+    /// See [`HpkeSuite::open`].
+    pub fn open<Crypto: 'static + HpkeCrypto>(
+        &self,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.suite.check_support::<Crypto>()?;
+        self.suite
+            .open::<Crypto>(&self.enc, sk_r, info, aad, &self.ct, psk, psk_id, pk_s)
+    }
+
+    /// Get the ciphersuite of this envelope.
+    pub fn suite(&self) -> HpkeSuite {
+        self.suite
+    }
+
+    /// Set the minimum PSK length for [`Envelope::open`].
+    ///
+    /// Sealed envelopes have the minimum PSK length of the sender's
+    /// configuration, decoded envelopes the default, because it is not part
+    /// of the encoding.
+    /// See [`HpkeSuite::set_min_psk_len`].
+    pub fn set_min_psk_len(&mut self, min_psk_len: usize) -> Result<(), HpkeError> {
+        self.suite.set_min_psk_len(min_psk_len)
+    }
+
+    /// Set the minimum PSK length for [`Envelope::open`], including lengths
+    /// below the default.
+    ///
+    /// See `HpkeSuite::set_min_psk_len_unchecked`.
+    #[cfg(feature = "hazmat")]
+    pub fn set_min_psk_len_unchecked(&mut self, min_psk_len: usize) {
+        self.suite.set_min_psk_len_unchecked(min_psk_len);
+    }
+
+    /// Get the encapsulated secret.
+    pub fn enc(&self) -> &EncapsulatedSecret {
+        &self.enc
+    }
+
+    /// Get the ciphertext.
+    pub fn ciphertext(&self) -> &Ciphertext {
+        &self.ct
+    }
+
+    /// Encode this envelope.
+    ///
+    /// See the [module documentation](self) for the encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, HpkeError> {
+        let enc_len =
+            u16::try_from(self.enc.as_slice().len()).map_err(|_| HpkeError::InvalidInput)?;
+        Ok(util::concat(&[
+            &[ENVELOPE_VERSION],
+            &self.suite.to_bytes(),
+            &enc_len.to_be_bytes(),
+            self.enc.as_slice(),
+            self.ct.as_slice(),
+        ]))
+    }
+
+    /// Decode an envelope encoded with [`Envelope::to_bytes`].
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the envelope is malformed,
+    /// of an unknown version, or if the lengths of the encapsulated secret or
+    /// ciphertext don't match the ciphersuite.
+    /// Returns an [`HpkeError::UnknownMode`] or an
+    /// [`HpkeError::UnsupportedAlgorithm`] for unknown ciphersuites.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HpkeError> {
+        let (version, rest) = bytes.split_first().ok_or(HpkeError::InvalidInput)?;
+        if *version != ENVELOPE_VERSION {
+            return Err(HpkeError::InvalidInput);
+        }
+        let (suite, rest) = rest
+            .split_first_chunk::<{ HpkeSuite::ENCODED_LEN }>()
+            .ok_or(HpkeError::InvalidInput)?;
+        let suite = HpkeSuite::try_from_bytes(suite)?;
+        let (enc_len, rest) = rest
+            .split_first_chunk::<2>()
+            .ok_or(HpkeError::InvalidInput)?;
+        let (enc, ct) = rest
+            .split_at_checked(u16::from_be_bytes(*enc_len).into())
+            .ok_or(HpkeError::InvalidInput)?;
+        Ok(Self {
+            suite,
+            enc: EncapsulatedSecret::new(suite.kem, enc.to_vec())?,
+            ct: Ciphertext::new(suite.aead, ct.to_vec())?,
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! # Fixed-Capacity Keys
+//!
+//! [`FixedPublicKey`] and [`FixedPrivateKey`] store keys inline in a
+//! [`heapless::Vec`] with the capacity of the largest supported KEM key,
+//! such that keys can be kept in static or stack memory.
+//! They convert to and from [`HpkePublicKey`] and [`HpkePrivateKey`].
+//!
+//! [`Context::seal_into`] and [`Context::open_into`] seal and open in place
+//! in caller provided buffers instead of returning a new `Vec`.
+//! Unlike [`Context::seal_in_place_detached`] they support padding.
+//!
+//! **Note** that this module doesn't make HPKE allocation-free.
+//! The key schedule, the conversions to [`HpkePublicKey`] and
+//! [`HpkePrivateKey`], and the crypto providers allocate small buffers, e.g.
+//! the nonce and the tag, because the [`HpkeCrypto`] trait returns `Vec`s.
+//! A global allocator is required.
+
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::{types::KemAlgorithm, HpkeCrypto};
+use zeroize::Zeroize;
+
+use crate::{Context, HpkeError, HpkePrivateKey, HpkePublicKey};
+
+/// The maximum length of an encoded public key of a supported KEM in bytes,
+/// a [`HybridKem`](crate::HybridKem) of two ML-KEM-1024.
+pub const MAX_PUBLIC_KEY_LEN: usize = 2 * KemAlgorithm::MlKem1024.public_key_len();
+
+/// The maximum length of a private key of a supported KEM in bytes,
+/// a [`HybridKem`](crate::HybridKem) of two X25519Kyber768Draft00.
+pub const MAX_PRIVATE_KEY_LEN: usize = 2 * KemAlgorithm::X25519Kyber768Draft00.private_key_len();
+
+/// A public key with a fixed capacity of [`MAX_PUBLIC_KEY_LEN`] bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FixedPublicKey {
+    value: heapless::Vec<u8, MAX_PUBLIC_KEY_LEN>,
+}
+
+impl FixedPublicKey {
+    /// Create a public key from its encoding.
+    ///
+    /// Returns an [`HpkeError::InvalidPublicKey`] if the key is longer than
+    /// [`MAX_PUBLIC_KEY_LEN`].
+    pub fn from_slice(pk: &[u8]) -> Result<Self, HpkeError> {
+        let value = heapless::Vec::from_slice(pk).map_err(|_| HpkeError::InvalidPublicKey)?;
+        Ok(Self { value })
+    }
+
+    /// Get the raw key as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl TryFrom<&HpkePublicKey> for FixedPublicKey {
+    type Error = HpkeError;
+
+    fn try_from(pk: &HpkePublicKey) -> Result<Self, HpkeError> {
+        Self::from_slice(pk.as_slice())
+    }
+}
+
+impl From<&FixedPublicKey> for HpkePublicKey {
+    fn from(pk: &FixedPublicKey) -> Self {
+        HpkePublicKey::new(pk.as_slice().to_vec())
+    }
+}
+
+/// A private key with a fixed capacity of [`MAX_PRIVATE_KEY_LEN`] bytes.
+///
+/// The key is zeroized on drop.
+#[derive(Default)]
+#[cfg_attr(feature = "hazmat", derive(Clone))]
+pub struct FixedPrivateKey {
+    value: heapless::Vec<u8, MAX_PRIVATE_KEY_LEN>,
+}
+
+impl FixedPrivateKey {
+    /// Create a private key from its bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidSecretKey`] if the key is longer than
+    /// [`MAX_PRIVATE_KEY_LEN`].
+    pub fn from_slice(sk: &[u8]) -> Result<Self, HpkeError> {
+        let value = heapless::Vec::from_slice(sk).map_err(|_| HpkeError::InvalidSecretKey)?;
+        Ok(Self { value })
+    }
+
+    /// Get the raw key as byte slice.
+    #[cfg(feature = "hazmat")]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl Drop for FixedPrivateKey {
+    fn drop(&mut self) {
+        self.value.as_mut_slice().zeroize();
+    }
+}
+
+impl core::fmt::Debug for FixedPrivateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        f.debug_struct("FixedPrivateKey")
+            .field("value", &"***")
+            .finish()
+    }
+}
+
+impl PartialEq for FixedPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        HpkePrivateKey::from(self) == HpkePrivateKey::from(other)
+    }
+}
+
+impl TryFrom<&HpkePrivateKey> for FixedPrivateKey {
+    type Error = HpkeError;
+
+    fn try_from(sk: &HpkePrivateKey) -> Result<Self, HpkeError> {
+        Self::from_slice(sk.bytes()?)
+    }
+}
+
+impl From<&FixedPrivateKey> for HpkePrivateKey {
+    fn from(sk: &FixedPrivateKey) -> Self {
+        HpkePrivateKey::new(sk.value.to_vec())
+    }
+}
+
+/// Copy `value` to the beginning of `out`.
+///
+/// Returns an [`HpkeError::InvalidInput`] if `out` is too short.
+fn write_into(value: &[u8], out: &mut [u8]) -> Result<usize, HpkeError> {
+    out.get_mut(..value.len())
+        .ok_or(HpkeError::InvalidInput)?
+        .copy_from_slice(value);
+    Ok(value.len())
+}
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Seal `plain_txt` with `aad` like [`Context::seal`] and write the
+    /// ciphertext into `out`.
+    ///
+    /// The plain text is padded and sealed in place in `out`.
+    /// Returns the length of the ciphertext.
+    /// If `out` is too short, an [`HpkeError::InvalidInput`] is returned and
+    /// the sequence number is not changed.
+    pub fn seal_into(
+        &mut self,
+        aad: &[u8],
+        plain_txt: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, HpkeError> {
+        self.check_aead()?;
+        let pt_len = self.padded_len(plain_txt.len())?;
+        let ct_len = pt_len
+            .checked_add(self.suite.aead.tag_length())
+            .ok_or(HpkeError::InputTooLong)?;
+        let (buffer, tag_out) = out
+            .get_mut(..ct_len)
+            .ok_or(HpkeError::InvalidInput)?
+            .split_at_mut(pt_len);
+        self.pad_into(plain_txt, buffer)?;
+        let tag = self.seal_padded_in_place_with(&PhantomData::<Crypto>, buffer, aad)?;
+        write_into(&tag, tag_out)?;
+        Ok(ct_len)
+    }
+
+    /// Open the ciphertext `cipher_txt` for the AEAD of this context with
+    /// `aad` like [`Context::open`] and write the plain text into `out`.
+    ///
+    /// The ciphertext is copied into `out` and opened in place.
+    /// Returns the length of the plain text.
+    /// If `out` is too short, an [`HpkeError::InvalidInput`] is returned and
+    /// the sequence number is not changed.
+    pub fn open_into(
+        &mut self,
+        aad: &[u8],
+        cipher_txt: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, HpkeError> {
+        self.check_aead()?;
+        let ct_len = cipher_txt
+            .len()
+            .checked_sub(self.suite.aead.tag_length())
+            .ok_or(HpkeError::InvalidInput)?;
+        let (ct, tag) = cipher_txt.split_at(ct_len);
+        let buffer = out.get_mut(..ct_len).ok_or(HpkeError::InvalidInput)?;
+        buffer.copy_from_slice(ct);
+        self.open_padded_in_place_with(&PhantomData::<Crypto>, buffer, aad, tag)?;
+        match self.unpadded_len(buffer) {
+            Ok(pt_len) => Ok(pt_len),
+            Err(e) => {
+                buffer.zeroize();
+                Err(e)
+            }
+        }
+    }
+}
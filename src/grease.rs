@@ -0,0 +1,48 @@
+//! # GREASE
+//!
+//! Protocols like ECH send dummy HPKE messages to keep middleboxes from
+//! ossifying on the presence of real ones ("GREASE").
+//! [`Hpke::grease`] creates an encapsulated secret and a ciphertext that are
+//! indistinguishable from a real single shot encryption of a plain text of a
+//! given length in this configuration.
+//!
+//! - The encapsulated secret is a real encapsulation to a fresh key pair that
+//!   is discarded, such that it is a valid encoding for the KEM, e.g. a point
+//!   on the curve.
+//! - The ciphertext consists of random bytes of the length of a real
+//!   ciphertext, including the [padding](crate::padding) of this
+//!   configuration.
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{kem, Ciphertext, EncapsulatedSecret, Hpke, HpkeError};
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Create a GREASE encapsulated secret and ciphertext for a plain text of
+    /// `payload_len` bytes.
+    ///
+    /// See the [module documentation](crate::grease) for details.
+    /// Returns an [`HpkeError::ExportOnlyMode`] for the export-only AEAD.
+    pub fn grease(
+        &mut self,
+        payload_len: usize,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        self.suite().check_aead()?;
+        self.suite().check_support::<Crypto>()?;
+        let (_, pk) = self.generate_key_pair()?.into_keys();
+        let (_, enc) = kem::encaps::<Crypto>(self, pk.as_slice())?;
+        let payload_len = match self.padding() {
+            Some(padding) => {
+                padding.padded_len(payload_len.checked_add(1).ok_or(HpkeError::InputTooLong)?)?
+            }
+            None => payload_len,
+        };
+        let ct_len = payload_len
+            .checked_add(self.aead_id.tag_length())
+            .ok_or(HpkeError::InputTooLong)?;
+        Ok((
+            EncapsulatedSecret::new(self.kem_id, enc)?,
+            Ciphertext::new(self.aead_id, self.random(ct_len)?)?,
+        ))
+    }
+}
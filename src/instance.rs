@@ -0,0 +1,375 @@
+//! # Provider Instances
+//!
+//! [`Hpke`](crate::Hpke) uses the associated functions of an [`HpkeCrypto`]
+//! provider, which therefore can't carry any state.
+//! [`HpkeInstance`] is an HPKE configuration that owns a provider value
+//! implementing [`HpkeCryptoInstance`] instead, such that the provider can
+//! hold configuration or handles, e.g. to an HSM, a remote KMS, or a
+//! configurable backend.
+//!
+//! Every [`HpkeCrypto`] provider is an [`HpkeCryptoInstance`], such that the
+//! built-in providers can be used as values as well.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, instance::HpkeInstance, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = HpkeInstance::new(
+//!     HpkeRustCrypto {},
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! )?;
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let (enc, ct) = hpke.seal(&pk_r, b"info", b"aad", b"hello", None, None, None)?;
+//! let pt = hpke.open(&enc, &sk_r, b"info", b"aad", &ct, None, None, None)?;
+//! assert_eq!(pt, b"hello");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+//!
+//! Contexts set up with an [`HpkeInstance`] borrow its provider and
+//! otherwise behave like a [`Context`](crate::Context).
+//! Compressed public keys are not supported.
+//!
+//! [`HpkeCrypto`]: hpke_rs_crypto::HpkeCrypto
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCryptoInstance,
+};
+
+use crate::{
+    audit, kem, padding::PaddingPolicy, Ciphertext, Context, EncapsulatedSecret, HpkeError,
+    HpkeKeyPair, HpkePrivateKey, HpkePublicKey, HpkeSuite, Mode,
+};
+
+/// An HPKE configuration with a provider value.
+///
+/// See [`Hpke`](crate::Hpke) for the documentation of the operations.
+#[derive(Debug)]
+pub struct HpkeInstance<P: HpkeCryptoInstance> {
+    provider: P,
+    suite: HpkeSuite,
+    prng: P::HpkePrng,
+    padding: Option<PaddingPolicy>,
+}
+
+impl<P: HpkeCryptoInstance> HpkeInstance<P> {
+    /// Set up the configuration for HPKE with the `provider`.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if the provider doesn't
+    /// support the ciphersuite.
+    pub fn new(
+        provider: P,
+        mode: Mode,
+        kem_id: KemAlgorithm,
+        kdf_id: KdfAlgorithm,
+        aead_id: AeadAlgorithm,
+    ) -> Result<Self, HpkeError> {
+        let suite = HpkeSuite::new(mode, kem_id, kdf_id, aead_id);
+        suite.check_support_with(&provider)?;
+        let prng = provider.prng();
+        Ok(Self {
+            provider,
+            suite,
+            prng,
+            padding: None,
+        })
+    }
+
+    /// Get the crypto provider.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Get the ciphersuite of this configuration, with its minimum PSK
+    /// length.
+    pub fn suite(&self) -> HpkeSuite {
+        self.suite
+    }
+
+    /// See [`Hpke::min_psk_len`](crate::Hpke::min_psk_len).
+    pub fn min_psk_len(&self) -> usize {
+        self.suite.min_psk_len()
+    }
+
+    /// See [`Hpke::set_min_psk_len`](crate::Hpke::set_min_psk_len).
+    pub fn set_min_psk_len(&mut self, min_psk_len: usize) -> Result<(), HpkeError> {
+        self.suite.set_min_psk_len(min_psk_len)
+    }
+
+    /// See `Hpke::set_min_psk_len_unchecked`.
+    #[cfg(feature = "hazmat")]
+    pub fn set_min_psk_len_unchecked(&mut self, min_psk_len: usize) {
+        self.suite.set_min_psk_len_unchecked(min_psk_len);
+    }
+
+    /// See [`Hpke::padding`](crate::Hpke::padding).
+    pub fn padding(&self) -> Option<&PaddingPolicy> {
+        self.padding.as_ref()
+    }
+
+    /// See [`Hpke::set_padding`](crate::Hpke::set_padding).
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        padding.as_ref().map(PaddingPolicy::check).transpose()?;
+        self.padding = padding;
+        Ok(())
+    }
+
+    /// See [`Hpke::generate_key_pair`](crate::Hpke::generate_key_pair).
+    pub fn generate_key_pair(&mut self) -> Result<HpkeKeyPair, HpkeError> {
+        let kem = self.suite.kem;
+        let (sk, pk) = kem::key_gen_with(&self.provider, kem, &mut self.prng)?;
+        Ok(HpkeKeyPair::for_kem(kem, sk, pk))
+    }
+
+    /// See [`Hpke::derive_key_pair`](crate::Hpke::derive_key_pair).
+    pub fn derive_key_pair(&self, ikm: &[u8]) -> Result<HpkeKeyPair, HpkeError> {
+        let kem = self.suite.kem;
+        let (pk, sk) = kem::derive_key_pair_with(&self.provider, kem, ikm)?;
+        Ok(HpkeKeyPair::for_kem(kem, sk, pk))
+    }
+
+    /// See [`Hpke::setup_sender`](crate::Hpke::setup_sender).
+    ///
+    /// Returns an [`HpkeError::InvalidKeyLength`] for compressed public keys.
+    pub fn setup_sender(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, InstanceContext<'_, P>), HpkeError> {
+        let kem = self.suite.kem;
+        let pk_r = public_key(kem, pk_r)?;
+        let (zz, enc) = match self.suite.mode {
+            Mode::Base | Mode::Psk => {
+                kem::encaps_with_rng_with(&self.provider, kem, pk_r, &mut self.prng)?
+            }
+            Mode::Auth | Mode::AuthPsk => {
+                let sk_s = sk_s.ok_or(HpkeError::InvalidInput)?;
+                sk_s.check_kem(kem)?;
+                sk_s.audit(kem, audit::KeyOperation::AuthEncapsulation);
+                kem::auth_encaps_with_rng_with(
+                    &self.provider,
+                    kem,
+                    pk_r,
+                    sk_s.key_ref(),
+                    &mut self.prng,
+                )?
+            }
+        };
+        let context = self.context(&zz, info, psk, psk_id)?;
+        Ok((EncapsulatedSecret::new(kem, enc)?, context))
+    }
+
+    /// See [`Hpke::setup_receiver`](crate::Hpke::setup_receiver).
+    ///
+    /// Returns an [`HpkeError::InvalidKeyLength`] for compressed public keys.
+    pub fn setup_receiver(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<InstanceContext<'_, P>, HpkeError> {
+        let kem = self.suite.kem;
+        if enc.kem() != kem {
+            return Err(HpkeError::InvalidInput);
+        }
+        sk_r.check_kem(kem)?;
+        sk_r.audit(kem, audit::KeyOperation::Decapsulation);
+        let zz = match self.suite.mode {
+            Mode::Base | Mode::Psk => {
+                kem::decaps_with(&self.provider, kem, enc.as_slice(), sk_r.key_ref())?
+            }
+            Mode::Auth | Mode::AuthPsk => {
+                let pk_s = public_key(kem, pk_s.ok_or(HpkeError::InvalidInput)?)?;
+                kem::auth_decaps_with(&self.provider, kem, enc.as_slice(), sk_r.key_ref(), pk_s)?
+            }
+        };
+        self.context(&zz, info, psk, psk_id)
+    }
+
+    /// See [`Hpke::seal`](crate::Hpke::seal).
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        self.suite.check_aead()?;
+        let (enc, mut context) = self.setup_sender(pk_r, info, psk, psk_id, sk_s)?;
+        let ctxt = context.seal(aad, plain_txt)?;
+        Ok((enc, ctxt))
+    }
+
+    /// See [`Hpke::open`](crate::Hpke::open).
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.suite.check_aead()?;
+        let mut context = self.setup_receiver(enc, sk_r, info, psk, psk_id, pk_s)?;
+        context.open(aad, ct)
+    }
+
+    /// Run the key schedule for the shared secret `zz`.
+    fn context(
+        &self,
+        zz: &[u8],
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<InstanceContext<'_, P>, HpkeError> {
+        let mut context = self.suite.key_schedule_with(
+            &self.provider,
+            zz,
+            info,
+            psk.unwrap_or_default(),
+            psk_id.unwrap_or_default(),
+        )?;
+        context.set_padding(self.padding.clone())?;
+        Ok(InstanceContext {
+            provider: &self.provider,
+            context,
+        })
+    }
+}
+
+/// Get the bytes of the public key `pk` for the `kem`.
+///
+/// Returns an [`HpkeError::InvalidKeyLength`] if `pk` doesn't have the
+/// public key length of the `kem`, e.g. because it is compressed.
+fn public_key(kem: KemAlgorithm, pk: &HpkePublicKey) -> Result<&[u8], HpkeError> {
+    pk.check_kem(kem)?;
+    let expected = kem.public_key_len();
+    let pk = pk.as_slice();
+    if pk.len() != expected {
+        return Err(HpkeError::InvalidKeyLength {
+            expected,
+            got: pk.len(),
+        });
+    }
+    Ok(pk)
+}
+
+/// An HPKE context set up with an [`HpkeInstance`].
+///
+/// See [`Context`] for the documentation of the operations.
+pub struct InstanceContext<'a, P: HpkeCryptoInstance> {
+    provider: &'a P,
+    context: Context<P>,
+}
+
+impl<P: HpkeCryptoInstance> core::fmt::Debug for InstanceContext<'_, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Instance{:?}", self.context)
+    }
+}
+
+impl<P: HpkeCryptoInstance> InstanceContext<'_, P> {
+    /// See [`Context::seal`].
+    pub fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Ciphertext, HpkeError> {
+        self.context.seal_with(self.provider, aad, plain_txt)
+    }
+
+    /// See [`Context::open`].
+    pub fn open(&mut self, aad: &[u8], cipher_txt: &Ciphertext) -> Result<Vec<u8>, HpkeError> {
+        self.context.open_with(self.provider, aad, cipher_txt)
+    }
+
+    /// See [`Context::seal_in_place_detached`].
+    pub fn seal_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.context
+            .seal_in_place_detached_with(self.provider, buffer, aad)
+    }
+
+    /// See [`Context::open_in_place_detached`].
+    pub fn open_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+        tag: &[u8],
+    ) -> Result<(), HpkeError> {
+        self.context
+            .open_in_place_detached_with(self.provider, buffer, aad, tag)
+    }
+
+    /// See [`Context::open_batch`].
+    pub fn open_batch(
+        &mut self,
+        messages: &[(&[u8], &Ciphertext)],
+    ) -> Result<Vec<Vec<u8>>, HpkeError> {
+        self.context.open_batch_with(self.provider, messages)
+    }
+
+    /// See [`Context::export`].
+    pub fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError> {
+        self.context
+            .export_with(self.provider, exporter_context, length)
+    }
+
+    /// See [`Context::suite`].
+    pub fn suite(&self) -> HpkeSuite {
+        self.context.suite()
+    }
+
+    /// See [`Context::message_limit`].
+    pub fn message_limit(&self) -> u64 {
+        self.context.message_limit()
+    }
+
+    /// See [`Context::set_message_limit`].
+    pub fn set_message_limit(&mut self, limit: u64) -> Result<(), HpkeError> {
+        self.context.set_message_limit(limit)
+    }
+
+    /// See [`Context::padding`].
+    pub fn padding(&self) -> Option<&PaddingPolicy> {
+        self.context.padding()
+    }
+
+    /// See [`Context::set_padding`].
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        self.context.set_padding(padding)
+    }
+
+    /// See [`Context::enable_transcript`].
+    pub fn enable_transcript(&mut self) -> Result<(), HpkeError> {
+        self.context.enable_transcript_with(self.provider)
+    }
+
+    /// See [`Context::transcript`].
+    pub fn transcript(&self) -> Option<&[u8]> {
+        self.context.transcript()
+    }
+
+    /// See [`Context::transcript_commitment`].
+    pub fn transcript_commitment(&self) -> Result<Vec<u8>, HpkeError> {
+        self.context.transcript_commitment_with(self.provider)
+    }
+}
@@ -0,0 +1,411 @@
+//! # JOSE-HPKE
+//!
+//! HPKE for JWE (draft-ietf-jose-hpke-encrypt) in its two modes:
+//!
+//! - **Integrated encryption** encrypts the plaintext with HPKE directly,
+//!   using the JWE Compact Serialization, see [`seal_compact`] and
+//!   [`open_compact`].
+//!   The JWE Encrypted Key is the encapsulated key, the JWE Ciphertext the
+//!   HPKE ciphertext, and the IV and Authentication Tag are empty.
+//!   The HPKE `aad` is the encoded protected header.
+//! - **Key encryption** encrypts the plaintext with a random content
+//!   encryption key (CEK) and the `enc` algorithm, and the CEK with HPKE to
+//!   each recipient, using the general JWE JSON Serialization, see
+//!   [`seal_json`] and [`open_json`].
+//!   The encapsulated key is the `ek` parameter of the recipient header, and
+//!   the HPKE `aad` is empty.
+//!
+//! The HPKE algorithm of the [`Hpke`] configuration is identified by the
+//! `alg` [`algorithm`], with a `-KE` suffix for key encryption.
+//! The HPKE `info` is empty.
+//! Only the base mode is supported.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, jose, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let jwe = jose::seal_compact(&mut hpke, &pk_r, Some("kid"), b"message")?;
+//! let pt = jose::open_compact(&hpke, &sk_r, &jwe)?;
+//! assert_eq!(pt, b"message");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    util, Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, Mode,
+};
+
+/// The JOSE-HPKE algorithms and their HPKE ciphersuites.
+const ALGORITHMS: [(&str, KemAlgorithm, KdfAlgorithm, AeadAlgorithm); 8] = [
+    (
+        "HPKE-0",
+        KemAlgorithm::DhKemP256,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::Aes128Gcm,
+    ),
+    (
+        "HPKE-1",
+        KemAlgorithm::DhKemP384,
+        KdfAlgorithm::HkdfSha384,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        "HPKE-2",
+        KemAlgorithm::DhKemP521,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        "HPKE-3",
+        KemAlgorithm::DhKem25519,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::Aes128Gcm,
+    ),
+    (
+        "HPKE-4",
+        KemAlgorithm::DhKem25519,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        "HPKE-5",
+        KemAlgorithm::DhKem448,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+    (
+        "HPKE-6",
+        KemAlgorithm::DhKem448,
+        KdfAlgorithm::HkdfSha512,
+        AeadAlgorithm::ChaCha20Poly1305,
+    ),
+    (
+        "HPKE-7",
+        KemAlgorithm::DhKemP256,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::Aes256Gcm,
+    ),
+];
+
+/// The suffix of key encryption algorithms.
+const KEY_ENCRYPTION: &str = "-KE";
+
+/// Get the JOSE-HPKE algorithm of the ciphersuite, e.g. `HPKE-3` for
+/// DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and AES-128-GCM.
+pub fn algorithm(
+    kem: KemAlgorithm,
+    kdf: KdfAlgorithm,
+    aead: AeadAlgorithm,
+) -> Option<&'static str> {
+    ALGORITHMS
+        .iter()
+        .find(|&&(_, k, d, a)| (k, d, a) == (kem, kdf, aead))
+        .map(|&(alg, ..)| alg)
+}
+
+/// Get the ciphersuite of the JOSE-HPKE algorithm `alg`, with or without
+/// the `-KE` suffix.
+pub fn ciphersuite(alg: &str) -> Option<(KemAlgorithm, KdfAlgorithm, AeadAlgorithm)> {
+    let alg = alg.strip_suffix(KEY_ENCRYPTION).unwrap_or(alg);
+    ALGORITHMS
+        .iter()
+        .find(|&&(name, ..)| name == alg)
+        .map(|&(_, kem, kdf, aead)| (kem, kdf, aead))
+}
+
+/// Get the JWE `enc` algorithm of the content encryption `aead`, e.g.
+/// `A128GCM`.
+pub fn content_algorithm(aead: AeadAlgorithm) -> Option<&'static str> {
+    match aead {
+        AeadAlgorithm::Aes128Gcm => Some("A128GCM"),
+        AeadAlgorithm::Aes256Gcm => Some("A256GCM"),
+        _ => None,
+    }
+}
+
+/// Get the content encryption AEAD of the JWE `enc` algorithm.
+fn content_aead(enc: &str) -> Option<AeadAlgorithm> {
+    [AeadAlgorithm::Aes128Gcm, AeadAlgorithm::Aes256Gcm]
+        .into_iter()
+        .find(|&aead| content_algorithm(aead) == Some(enc))
+}
+
+/// A JOSE header with the parameters used by JOSE-HPKE.
+///
+/// Other parameters are ignored.
+#[derive(Default, Serialize, Deserialize)]
+struct Header {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ek: Option<String>,
+}
+
+/// A recipient of the general JWE JSON Serialization.
+#[derive(Serialize, Deserialize)]
+struct Recipient {
+    header: Header,
+    encrypted_key: String,
+}
+
+/// The general JWE JSON Serialization.
+#[derive(Serialize, Deserialize)]
+struct JsonJwe {
+    protected: String,
+    recipients: Vec<Recipient>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aad: Option<String>,
+    iv: String,
+    ciphertext: String,
+    tag: String,
+}
+
+/// Base64url encode `value`.
+fn encode(value: &[u8]) -> String {
+    Base64UrlUnpadded::encode_string(value)
+}
+
+/// Decode the base64url encoded `value`.
+fn decode(value: &str) -> Result<Vec<u8>, HpkeError> {
+    Base64UrlUnpadded::decode_vec(value).map_err(|_| HpkeError::InvalidInput)
+}
+
+/// Serialize the `header` as base64url encoded JSON.
+fn encode_header(header: &Header) -> Result<String, HpkeError> {
+    let json = serde_json::to_vec(header).map_err(|_| HpkeError::InvalidInput)?;
+    Ok(encode(&json))
+}
+
+/// Deserialize the JSON `value`.
+fn from_json<T: DeserializeOwned>(value: &[u8]) -> Result<T, HpkeError> {
+    serde_json::from_slice(value).map_err(|_| HpkeError::InvalidInput)
+}
+
+/// Get the JOSE-HPKE algorithm of the `hpke` configuration.
+///
+/// Returns an [`HpkeError::InvalidConfig`] if the configuration is not in
+/// base mode or has no JOSE-HPKE algorithm.
+fn hpke_algorithm<Crypto: HpkeCrypto>(hpke: &Hpke<Crypto>) -> Result<&'static str, HpkeError> {
+    if hpke.mode != Mode::Base {
+        return Err(HpkeError::InvalidConfig);
+    }
+    algorithm(hpke.kem_id, hpke.kdf_id, hpke.aead_id).ok_or(HpkeError::InvalidConfig)
+}
+
+/// Encrypt `plain_txt` to `pk_r` into a JWE in the Compact Serialization
+/// (integrated encryption).
+///
+/// The optional `kid` identifies the recipient key in the protected header.
+/// Returns an [`HpkeError::InvalidConfig`] if the `hpke` configuration is not
+/// in base mode or has no JOSE-HPKE [`algorithm`].
+pub fn seal_compact<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    kid: Option<&str>,
+    plain_txt: &[u8],
+) -> Result<String, HpkeError> {
+    let header = Header {
+        alg: Some(hpke_algorithm(hpke)?.to_string()),
+        kid: kid.map(ToString::to_string),
+        ..Default::default()
+    };
+    let protected = encode_header(&header)?;
+    let (enc, ct) = hpke.seal(pk_r, &[], protected.as_bytes(), plain_txt, None, None, None)?;
+    Ok(format!(
+        "{}.{}..{}.",
+        protected,
+        encode(enc.as_slice()),
+        encode(ct.as_slice())
+    ))
+}
+
+/// Decrypt a JWE in the Compact Serialization with `sk_r` (integrated
+/// encryption).
+///
+/// Returns an [`HpkeError::InvalidInput`] if the `jwe` is malformed and an
+/// [`HpkeError::InvalidConfig`] if its algorithm doesn't match the `hpke`
+/// configuration.
+pub fn open_compact<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    sk_r: &HpkePrivateKey,
+    jwe: &str,
+) -> Result<Vec<u8>, HpkeError> {
+    let mut parts = jwe.split('.');
+    let (Some(protected), Some(enc), Some(""), Some(ct), Some(""), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Err(HpkeError::InvalidInput);
+    };
+    let header: Header = from_json(&decode(protected)?)?;
+    if header.enc.is_some() {
+        return Err(HpkeError::InvalidInput);
+    }
+    if header.alg.as_deref() != Some(hpke_algorithm(hpke)?) {
+        return Err(HpkeError::InvalidConfig);
+    }
+    let enc = EncapsulatedSecret::new(hpke.kem_id, decode(enc)?)?;
+    let ct = Ciphertext::new(hpke.aead_id, decode(ct)?)?;
+    hpke.open(&enc, sk_r, &[], protected.as_bytes(), &ct, None, None, None)
+}
+
+/// Get the additional data of the content encryption.
+fn content_aad(protected: &str, aad: Option<&str>) -> String {
+    match aad {
+        Some(aad) => format!("{}.{}", protected, aad),
+        None => protected.to_string(),
+    }
+}
+
+/// Encrypt `plain_txt` with a random content encryption key for the
+/// `content_aead` and the additional data `aad`, and the key to each of the
+/// `recipients` with HPKE, into a JWE in the general JSON Serialization (key
+/// encryption).
+///
+/// Each recipient is a public key with an optional `kid`.
+/// Returns an [`HpkeError::InvalidConfig`] if the `hpke` configuration is not
+/// in base mode or has no JOSE-HPKE [`algorithm`], or the `content_aead` has
+/// no JWE [`content_algorithm`].
+pub fn seal_json<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    content_aead: AeadAlgorithm,
+    recipients: &[(&HpkePublicKey, Option<&str>)],
+    aad: &[u8],
+    plain_txt: &[u8],
+) -> Result<String, HpkeError> {
+    let enc = content_algorithm(content_aead).ok_or(HpkeError::InvalidConfig)?;
+    let alg = format!("{}{}", hpke_algorithm(hpke)?, KEY_ENCRYPTION);
+    let protected = encode_header(&Header {
+        enc: Some(enc.to_string()),
+        ..Default::default()
+    })?;
+    let aad = (!aad.is_empty()).then(|| encode(aad));
+
+    let mut cek = hpke.random(Crypto::aead_key_length(content_aead))?;
+    let iv = hpke.random(Crypto::aead_nonce_length(content_aead))?;
+    let recipients = recipients
+        .iter()
+        .map(|&(pk_r, kid)| {
+            let (enc, ct) = hpke.seal(pk_r, &[], &[], &cek, None, None, None)?;
+            Ok(Recipient {
+                header: Header {
+                    alg: Some(alg.clone()),
+                    kid: kid.map(ToString::to_string),
+                    ek: Some(encode(enc.as_slice())),
+                    ..Default::default()
+                },
+                encrypted_key: encode(ct.as_slice()),
+            })
+        })
+        .collect::<Result<Vec<_>, HpkeError>>();
+    let ct = Crypto::aead_seal(
+        content_aead,
+        &cek,
+        &iv,
+        content_aad(&protected, aad.as_deref()).as_bytes(),
+        plain_txt,
+    );
+    cek.zeroize();
+
+    let mut ciphertext = ct?;
+    let tag = ciphertext
+        .len()
+        .checked_sub(content_aead.tag_length())
+        .map(|len| ciphertext.split_off(len))
+        .ok_or(HpkeError::CryptoError("Invalid AEAD output".into()))?;
+    let jwe = JsonJwe {
+        protected,
+        recipients: recipients?,
+        aad,
+        iv: encode(&iv),
+        ciphertext: encode(&ciphertext),
+        tag: encode(&tag),
+    };
+    serde_json::to_string(&jwe).map_err(|_| HpkeError::InvalidInput)
+}
+
+/// Decrypt a JWE in the general JSON Serialization with `sk_r` (key
+/// encryption).
+///
+/// The content encryption key is decrypted from the first recipient with
+/// the algorithm of the `hpke` configuration and, if given, the `kid` that
+/// can be opened with `sk_r`.
+/// Returns an [`HpkeError::InvalidInput`] if the `jwe` is malformed and an
+/// [`HpkeError::InvalidConfig`] if there is no recipient for the `hpke`
+/// configuration and `kid`.
+pub fn open_json<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    sk_r: &HpkePrivateKey,
+    kid: Option<&str>,
+    jwe: &str,
+) -> Result<Vec<u8>, HpkeError> {
+    let jwe: JsonJwe = from_json(jwe.as_bytes())?;
+    let header: Header = from_json(&decode(&jwe.protected)?)?;
+    let content_aead = header
+        .enc
+        .as_deref()
+        .and_then(content_aead)
+        .ok_or(HpkeError::InvalidInput)?;
+
+    let alg = format!("{}{}", hpke_algorithm(hpke)?, KEY_ENCRYPTION);
+    let mut result = Err(HpkeError::InvalidConfig);
+    for recipient in jwe.recipients.iter().filter(|recipient| {
+        recipient.header.alg.as_deref() == Some(alg.as_str())
+            && kid.is_none_or(|kid| recipient.header.kid.as_deref() == Some(kid))
+    }) {
+        result = (|| {
+            let enc = recipient
+                .header
+                .ek
+                .as_deref()
+                .ok_or(HpkeError::InvalidInput)?;
+            let enc = EncapsulatedSecret::new(hpke.kem_id, decode(enc)?)?;
+            let ct = Ciphertext::new(hpke.aead_id, decode(&recipient.encrypted_key)?)?;
+            hpke.open(&enc, sk_r, &[], &[], &ct, None, None, None)
+        })();
+        if result.is_ok() {
+            break;
+        }
+    }
+    let mut cek = result?;
+    if cek.len() != Crypto::aead_key_length(content_aead) {
+        cek.zeroize();
+        return Err(HpkeError::InvalidInput);
+    }
+
+    let pt = decode(&jwe.iv).and_then(|iv| {
+        let ct = util::concat(&[&decode(&jwe.ciphertext)?, &decode(&jwe.tag)?]);
+        let aad = content_aad(&jwe.protected, jwe.aad.as_deref());
+        Crypto::aead_open(content_aead, &cek, &iv, aad.as_bytes(), &ct).map_err(HpkeError::from)
+    });
+    cek.zeroize();
+    pt
+}
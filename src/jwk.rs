@@ -0,0 +1,193 @@
+//! # JSON Web Keys
+//!
+//! Conversions between HPKE keys and JSON Web Keys (RFC 7517), e.g. to
+//! exchange HPKE recipient keys in JOSE-based systems.
+//!
+//! - X25519 and X448 keys are `OKP` keys (RFC 8037).
+//! - Keys of the NIST curves and secp256k1 are `EC` keys (RFC 7518,
+//!   RFC 8812) with the coordinates of the uncompressed point.
+//!
+//! [`Jwk`] implements serde's `Serialize` and `Deserialize`, e.g. for
+//! `serde_json`:
+//!
+//! ```
+//! use hpke_rs::{hpke_types::KemAlgorithm, jwk::Jwk, HpkePublicKey};
+//!
+//! // The X25519 public key of RFC 8037, Appendix A.6.
+//! let jwk: Jwk = serde_json::from_str(
+//!     r#"{"kty":"OKP","crv":"X25519","x":"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo"}"#,
+//! )?;
+//! let pk_r = HpkePublicKey::from_jwk(&jwk)?;
+//! assert_eq!(pk_r.kem(), Some(KemAlgorithm::DhKem25519));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use hpke_rs_crypto::types::KemAlgorithm;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{util, HpkeError, HpkeKeyPair, HpkePrivateKey, HpkePublicKey};
+
+/// The `kty` of X25519 and X448 keys.
+const OKP: &str = "OKP";
+
+/// The `kty` of keys on the NIST curves and secp256k1.
+const EC: &str = "EC";
+
+/// A JSON Web Key of an HPKE KEM.
+///
+/// The private key `d` is zeroized on drop.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct Jwk {
+    /// The key type, `OKP` or `EC`.
+    pub kty: String,
+
+    /// The curve, e.g. `X25519` or `P-256`.
+    pub crv: String,
+
+    /// The base64url encoded public key (`OKP`) or its x coordinate (`EC`).
+    pub x: String,
+
+    /// The base64url encoded y coordinate of `EC` keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+
+    /// The base64url encoded private key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+}
+
+impl core::fmt::Debug for Jwk {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.kty)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &self.d.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// Get the `kty` and `crv` of the `kem`.
+fn key_type(kem: KemAlgorithm) -> Option<(&'static str, &'static str)> {
+    match kem {
+        KemAlgorithm::DhKem25519 => Some((OKP, "X25519")),
+        KemAlgorithm::DhKem448 => Some((OKP, "X448")),
+        KemAlgorithm::DhKemP256 => Some((EC, "P-256")),
+        KemAlgorithm::DhKemP384 => Some((EC, "P-384")),
+        KemAlgorithm::DhKemP521 => Some((EC, "P-521")),
+        KemAlgorithm::DhKemK256 => Some((EC, "secp256k1")),
+        _ => None,
+    }
+}
+
+/// Get the KEM of the `kty` and `crv`.
+///
+/// Returns an [`HpkeError::InvalidInput`] for unknown key types.
+fn kem_from_key_type(kty: &str, crv: &str) -> Result<KemAlgorithm, HpkeError> {
+    [
+        KemAlgorithm::DhKem25519,
+        KemAlgorithm::DhKem448,
+        KemAlgorithm::DhKemP256,
+        KemAlgorithm::DhKemP384,
+        KemAlgorithm::DhKemP521,
+        KemAlgorithm::DhKemK256,
+    ]
+    .into_iter()
+    .find(|&kem| key_type(kem) == Some((kty, crv)))
+    .ok_or(HpkeError::InvalidInput)
+}
+
+/// Decode the base64url encoded `value`.
+fn decode(value: &str) -> Result<Vec<u8>, HpkeError> {
+    Base64UrlUnpadded::decode_vec(value).map_err(|_| HpkeError::InvalidInput)
+}
+
+impl HpkePublicKey {
+    /// Convert a JSON Web Key into a public key.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `jwk` is malformed or
+    /// not a key of a KEM of this crate, and an
+    /// [`HpkeError::InvalidKeyLength`] if the key doesn't have the length
+    /// required by the KEM.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, HpkeError> {
+        let kem = kem_from_key_type(&jwk.kty, &jwk.crv)?;
+        let x = decode(&jwk.x)?;
+        let value = match &jwk.y {
+            None if jwk.kty == OKP => x,
+            Some(y) if jwk.kty == EC => {
+                let y = decode(y)?;
+                if x.len() != kem.private_key_len() || y.len() != kem.private_key_len() {
+                    return Err(HpkeError::InvalidInput);
+                }
+                util::concat(&[&[0x04], &x, &y])
+            }
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        Self::new_checked(kem, value)
+    }
+
+    /// Convert this public key for the `kem` into a JSON Web Key.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM
+    /// or the `kem` has no JSON Web Key type, and an
+    /// [`HpkeError::InvalidInput`] if the key is compressed or doesn't have
+    /// the length required by the `kem`.
+    pub fn to_jwk(&self, kem: KemAlgorithm) -> Result<Jwk, HpkeError> {
+        self.check_kem(kem)?;
+        let (kty, crv) = key_type(kem).ok_or(HpkeError::InvalidConfig)?;
+        if self.value.len() != kem.public_key_len() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let (x, y) = match self.value.split_first() {
+            Some((0x04, point)) if kty == EC => {
+                let (x, y) = point
+                    .split_at_checked(kem.private_key_len())
+                    .ok_or(HpkeError::InvalidInput)?;
+                (x, Some(Base64UrlUnpadded::encode_string(y)))
+            }
+            _ if kty == OKP => (self.value.as_slice(), None),
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        Ok(Jwk {
+            kty: kty.into(),
+            crv: crv.into(),
+            x: Base64UrlUnpadded::encode_string(x),
+            y,
+            d: None,
+        })
+    }
+}
+
+impl HpkeKeyPair {
+    /// Convert a JSON Web Key with its private key `d` into a key pair.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `jwk` has no private key.
+    /// See [`HpkePublicKey::from_jwk`] for the other errors.
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, HpkeError> {
+        let public_key = HpkePublicKey::from_jwk(jwk)?;
+        let d = jwk.d.as_ref().ok_or(HpkeError::InvalidInput)?;
+        let kem = kem_from_key_type(&jwk.kty, &jwk.crv)?;
+        let private_key = HpkePrivateKey::new_checked(kem, decode(d)?)?;
+        Ok(Self::from_keys(private_key, public_key))
+    }
+
+    /// Convert this key pair for the `kem` into a JSON Web Key with the
+    /// private key `d`.
+    ///
+    /// See [`HpkePublicKey::to_jwk`].
+    #[cfg(feature = "hazmat")]
+    pub fn to_jwk(&self, kem: KemAlgorithm) -> Result<Jwk, HpkeError> {
+        self.private_key().check_kem(kem)?;
+        let mut jwk = self.public_key().to_jwk(kem)?;
+        jwk.d = Some(Base64UrlUnpadded::encode_string(
+            self.private_key().bytes()?,
+        ));
+        Ok(jwk)
+    }
+}
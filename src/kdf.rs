@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 
-use hpke_rs_crypto::{error::Error, types::KdfAlgorithm, HpkeCrypto};
+use hpke_rs_crypto::{error::Error, types::KdfAlgorithm, HpkeCrypto, HpkeCryptoInstance};
 
 use crate::util::concat;
 
@@ -13,8 +14,7 @@ pub(crate) fn labeled_extract<Crypto: HpkeCrypto>(
     label: &str,
     ikm: &[u8],
 ) -> Result<Vec<u8>, Error> {
-    let labeled_ikm = concat(&[HPKE_VERSION, suite_id, label.as_bytes(), ikm]);
-    Crypto::kdf_extract(alg, salt, &labeled_ikm)
+    labeled_extract_with(&PhantomData::<Crypto>, alg, salt, suite_id, label, ikm)
 }
 
 pub(crate) fn labeled_expand<Crypto: HpkeCrypto>(
@@ -25,8 +25,35 @@ pub(crate) fn labeled_expand<Crypto: HpkeCrypto>(
     info: &[u8],
     len: usize,
 ) -> Result<Vec<u8>, Error> {
-    debug_assert!(len < 256);
-    let len_bytes = (len as u16).to_be_bytes();
+    labeled_expand_with(&PhantomData::<Crypto>, alg, prk, suite_id, label, info, len)
+}
+
+/// [`labeled_extract`] with the provider instance `crypto`.
+pub(crate) fn labeled_extract_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KdfAlgorithm,
+    salt: &[u8],
+    suite_id: &[u8],
+    label: &str,
+    ikm: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let labeled_ikm = concat(&[HPKE_VERSION, suite_id, label.as_bytes(), ikm]);
+    crypto.kdf_extract(alg, salt, &labeled_ikm)
+}
+
+/// [`labeled_expand`] with the provider instance `crypto`.
+pub(crate) fn labeled_expand_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KdfAlgorithm,
+    prk: &[u8],
+    suite_id: &[u8],
+    label: &'static str,
+    info: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, Error> {
+    let len_bytes = u16::try_from(len)
+        .map_err(|_| Error::HpkeInvalidOutputLength)?
+        .to_be_bytes();
     let labeled_info = concat(&[&len_bytes, HPKE_VERSION, suite_id, label.as_bytes(), info]);
-    Crypto::kdf_expand(alg, prk, &labeled_info, len)
+    crypto.kdf_expand(alg, prk, &labeled_info, len)
 }
@@ -1,15 +1,36 @@
+//! # KEMs
+//!
+//! The KEMs of HPKE are selected with a [`KemAlgorithm`] in the [`Hpke`]
+//! configuration.
+//!
+//! [`HybridKem`] composes two of them into a hybrid KEM with its own code
+//! point, e.g. DHKEM(X25519) and ML-KEM-768.
+//! The encapsulations are concatenated and the two shared secrets are
+//! combined with the KDF of the hybrid KEM.
+
 use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
 
-use hpke_rs_crypto::{error::Error, types::KemAlgorithm, HpkeCrypto, RngCore};
+use hpke_rs_crypto::{
+    error::Error,
+    types::{KdfAlgorithm, KemAlgorithm, PrivateKeyRef},
+    CryptoRng, HpkeCrypto, HpkeCryptoInstance, RngCore,
+};
+use zeroize::{Zeroize, Zeroizing};
 
-use crate::{dh_kem, util, Hpke};
+use crate::{
+    dh_kem,
+    kdf::{labeled_expand, labeled_extract},
+    util, xyber, AlgorithmKind, EncapsulatedSecret, Hpke, HpkeError, HpkeKeyPair, HpkePrivateKey,
+    HpkePublicKey, Mode, ReceiverContext, SenderContext, SharedSecret,
+};
 
 pub(crate) type PrivateKey = Vec<u8>;
 pub(crate) type PublicKey = Vec<u8>;
 
 #[inline(always)]
-fn ciphersuite(alg: KemAlgorithm) -> Vec<u8> {
-    util::concat(&[b"KEM", &(alg as u16).to_be_bytes()])
+pub(crate) fn ciphersuite(alg: KemAlgorithm) -> Vec<u8> {
+    util::concat(&[b"KEM", &alg.id().to_be_bytes()])
 }
 
 pub(crate) fn encaps<Crypto: HpkeCrypto>(
@@ -27,16 +48,194 @@ pub(crate) fn encaps<Crypto: HpkeCrypto>(
             let randomness = hpke
                 .random(alg.private_key_len())
                 .map_err(|_| Error::InsufficientRandomness)?;
-            dh_kem::encaps::<Crypto>(alg, pk_r, &ciphersuite(alg), &randomness)
+            dh_kem::encaps(
+                &PhantomData::<Crypto>,
+                alg,
+                pk_r,
+                &ciphersuite(alg),
+                &randomness,
+            )
+        }
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024 => Crypto::kem_encaps(alg, pk_r, hpke.rng()),
+        KemAlgorithm::X25519Kyber768Draft00 => {
+            let randomness = hpke
+                .random(KemAlgorithm::DhKem25519.private_key_len())
+                .map_err(|_| Error::InsufficientRandomness)?;
+            xyber::encaps(&PhantomData::<Crypto>, pk_r, &randomness, hpke.rng())
+        }
+        _ => Err(Error::UnknownKemAlgorithm),
+    }
+}
+
+/// The length of the randomness of an encapsulation with the KEM `alg`,
+/// i.e. the randomness for the ephemeral key of DH KEMs.
+pub(crate) fn encaps_randomness_len(alg: KemAlgorithm) -> usize {
+    match alg {
+        KemAlgorithm::X25519Kyber768Draft00 => KemAlgorithm::DhKem25519
+            .private_key_len()
+            .saturating_add(alg.encaps_randomness_len().unwrap_or_default()),
+        _ => alg.encaps_randomness_len().unwrap_or(alg.private_key_len()),
+    }
+}
+
+/// Draw the randomness for an encapsulation with the KEM `alg` from `rng`
+/// and run `encaps` with it.
+fn with_encaps_randomness<T>(
+    alg: KemAlgorithm,
+    rng: &mut impl CryptoRng,
+    encaps: impl FnOnce(&[u8]) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut randomness = vec![0u8; encaps_randomness_len(alg)];
+    rng.fill_bytes(&mut randomness);
+    let result = encaps(&randomness);
+    randomness.zeroize();
+    result
+}
+
+/// Encapsulation to `pk_r` with randomness from `rng`.
+///
+/// Returns (shared secret, encapsulated secret).
+pub(crate) fn encaps_with_rng<Crypto: HpkeCrypto>(
+    alg: KemAlgorithm,
+    pk_r: &[u8],
+    rng: &mut impl CryptoRng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    encaps_with_rng_with(&PhantomData::<Crypto>, alg, pk_r, rng)
+}
+
+/// [`encaps_with_rng`] with the provider instance `crypto`.
+pub(crate) fn encaps_with_rng_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    pk_r: &[u8],
+    rng: &mut impl CryptoRng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    match alg {
+        KemAlgorithm::DhKemP256
+        | KemAlgorithm::DhKemK256
+        | KemAlgorithm::DhKemP384
+        | KemAlgorithm::DhKemP521
+        | KemAlgorithm::DhKem25519
+        | KemAlgorithm::DhKem448 => with_encaps_randomness(alg, rng, |randomness| {
+            dh_kem::encaps(crypto, alg, pk_r, &ciphersuite(alg), randomness)
+        }),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024 => with_encaps_randomness(alg, rng, |randomness| {
+            crypto.kem_encaps_derand(alg, pk_r, randomness)
+        }),
+        KemAlgorithm::X25519Kyber768Draft00 => with_encaps_randomness(alg, rng, |randomness| {
+            xyber::encaps_derand(crypto, pk_r, randomness)
+        }),
+        _ => Err(Error::UnknownKemAlgorithm),
+    }
+}
+
+/// Authenticated encapsulation to `pk_r` with `sk_s` and randomness from
+/// `rng` for the ephemeral key.
+///
+/// Returns (shared secret, encapsulated secret).
+pub(crate) fn auth_encaps_with_rng<Crypto: HpkeCrypto>(
+    alg: KemAlgorithm,
+    pk_r: &[u8],
+    sk_s: PrivateKeyRef<'_>,
+    rng: &mut impl CryptoRng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    auth_encaps_with_rng_with(&PhantomData::<Crypto>, alg, pk_r, sk_s, rng)
+}
+
+/// [`auth_encaps_with_rng`] with the provider instance `crypto`.
+pub(crate) fn auth_encaps_with_rng_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    pk_r: &[u8],
+    sk_s: PrivateKeyRef<'_>,
+    rng: &mut impl CryptoRng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    match alg {
+        KemAlgorithm::DhKemP256
+        | KemAlgorithm::DhKemK256
+        | KemAlgorithm::DhKemP384
+        | KemAlgorithm::DhKemP521
+        | KemAlgorithm::DhKem25519
+        | KemAlgorithm::DhKem448 => with_encaps_randomness(alg, rng, |randomness| {
+            dh_kem::auth_encaps(crypto, alg, pk_r, sk_s, &ciphersuite(alg), randomness)
+        }),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024
+        | KemAlgorithm::X25519Kyber768Draft00 => Err(Error::UnsupportedKemOperation),
+        _ => Err(Error::UnknownKemAlgorithm),
+    }
+}
+
+/// Encapsulation to `pk_r` with the given `randomness` of length
+/// [`encaps_randomness_len`].
+///
+/// Returns (shared secret, encapsulated secret).
+#[cfg(feature = "kem")]
+pub(crate) fn encaps_with_randomness<Crypto: HpkeCrypto>(
+    alg: KemAlgorithm,
+    pk_r: &[u8],
+    randomness: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    match alg {
+        KemAlgorithm::DhKemP256
+        | KemAlgorithm::DhKemK256
+        | KemAlgorithm::DhKemP384
+        | KemAlgorithm::DhKemP521
+        | KemAlgorithm::DhKem25519
+        | KemAlgorithm::DhKem448 => dh_kem::encaps(
+            &PhantomData::<Crypto>,
+            alg,
+            pk_r,
+            &ciphersuite(alg),
+            randomness,
+        ),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024 => Crypto::kem_encaps_derand(alg, pk_r, randomness),
+        KemAlgorithm::X25519Kyber768Draft00 => {
+            xyber::encaps_derand(&PhantomData::<Crypto>, pk_r, randomness)
         }
-        KemAlgorithm::XWingDraft06 => Crypto::kem_encaps(alg, pk_r, hpke.rng()),
+        _ => Err(Error::UnknownKemAlgorithm),
     }
 }
 
+/// Decapsulate `enc` with `sk_r`.
+///
+/// All failures that depend on `enc` surface as a single
+/// [`Error::KemInvalidCiphertext`], independent of the KEM:
+/// - DH KEMs run the full key derivation before reporting an invalid `enc`.
+/// - KEMs with implicit rejection (X-Wing, ML-KEM, Kyber) return a pseudorandom
+///   shared secret for invalid ciphertexts, which fails later when opening.
+///   Errors of the provider, e.g. for malformed lengths, are mapped to the
+///   same error.
+///
+/// Errors for an invalid `sk_r` are reported as is, because the private key
+/// is not attacker controlled.
+/// Private keys that are stored as their key generation seed are expanded
+/// on every decapsulation, and expansion failures are errors for `sk_r`.
 pub(crate) fn decaps<Crypto: HpkeCrypto>(
     alg: KemAlgorithm,
     enc: &[u8],
-    sk_r: &[u8],
+    sk_r: PrivateKeyRef<'_>,
+) -> Result<Vec<u8>, Error> {
+    decaps_with(&PhantomData::<Crypto>, alg, enc, sk_r)
+}
+
+/// [`decaps`] with the provider instance `crypto`.
+pub(crate) fn decaps_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    enc: &[u8],
+    sk_r: PrivateKeyRef<'_>,
 ) -> Result<Vec<u8>, Error> {
     match alg {
         KemAlgorithm::DhKemP256
@@ -44,15 +243,40 @@ pub(crate) fn decaps<Crypto: HpkeCrypto>(
         | KemAlgorithm::DhKemP384
         | KemAlgorithm::DhKemP521
         | KemAlgorithm::DhKem25519
-        | KemAlgorithm::DhKem448 => dh_kem::decaps::<Crypto>(alg, enc, sk_r, &ciphersuite(alg)),
-        KemAlgorithm::XWingDraft06 => Crypto::kem_decaps(alg, enc, sk_r),
+        | KemAlgorithm::DhKem448 => dh_kem::decaps(crypto, alg, enc, sk_r, &ciphersuite(alg)),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024 => {
+            // Expand seeds first, such that expansion failures are reported
+            // as errors for the private key.
+            let expanded;
+            let sk_r = match sk_r {
+                PrivateKeyRef::Bytes(seed) if alg.seed_len() == Some(seed.len()) => {
+                    expanded = Zeroizing::new(key_gen_from_seed_with(crypto, alg, seed)?.1);
+                    PrivateKeyRef::Bytes(&expanded)
+                }
+                sk_r => sk_r,
+            };
+            crypto
+                .kem_decaps_with_key(alg, enc, sk_r)
+                .map_err(|e| match e {
+                    Error::KemInvalidSecretKey => e,
+                    _ => Error::KemInvalidCiphertext,
+                })
+        }
+        KemAlgorithm::X25519Kyber768Draft00 => match sk_r {
+            PrivateKeyRef::Bytes(sk_r) => xyber::decaps(crypto, enc, sk_r),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        },
+        _ => Err(Error::UnknownKemAlgorithm),
     }
 }
 
 pub(crate) fn auth_encaps<Crypto: HpkeCrypto>(
     hpke: &mut Hpke<Crypto>,
     pk_r: &[u8],
-    sk_s: &[u8],
+    sk_s: PrivateKeyRef<'_>,
 ) -> Result<(Vec<u8>, Vec<u8>), Error> {
     let alg = hpke.kem_id;
     match alg {
@@ -65,16 +289,39 @@ pub(crate) fn auth_encaps<Crypto: HpkeCrypto>(
             let randomness = hpke
                 .random(alg.private_key_len())
                 .map_err(|_| Error::InsufficientRandomness)?;
-            dh_kem::auth_encaps::<Crypto>(alg, pk_r, sk_s, &ciphersuite(alg), &randomness)
+            dh_kem::auth_encaps(
+                &PhantomData::<Crypto>,
+                alg,
+                pk_r,
+                sk_s,
+                &ciphersuite(alg),
+                &randomness,
+            )
         }
-        KemAlgorithm::XWingDraft06 => Err(Error::UnsupportedKemOperation),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024
+        | KemAlgorithm::X25519Kyber768Draft00 => Err(Error::UnsupportedKemOperation),
+        _ => Err(Error::UnknownKemAlgorithm),
     }
 }
 
 pub(crate) fn auth_decaps<Crypto: HpkeCrypto>(
     alg: KemAlgorithm,
     enc: &[u8],
-    sk_r: &[u8],
+    sk_r: PrivateKeyRef<'_>,
+    pk_s: &[u8],
+) -> Result<Vec<u8>, Error> {
+    auth_decaps_with(&PhantomData::<Crypto>, alg, enc, sk_r, pk_s)
+}
+
+/// [`auth_decaps`] with the provider instance `crypto`.
+pub(crate) fn auth_decaps_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    enc: &[u8],
+    sk_r: PrivateKeyRef<'_>,
     pk_s: &[u8],
 ) -> Result<Vec<u8>, Error> {
     match alg {
@@ -84,9 +331,14 @@ pub(crate) fn auth_decaps<Crypto: HpkeCrypto>(
         | KemAlgorithm::DhKemP521
         | KemAlgorithm::DhKem25519
         | KemAlgorithm::DhKem448 => {
-            dh_kem::auth_decaps::<Crypto>(alg, enc, sk_r, pk_s, &ciphersuite(alg))
+            dh_kem::auth_decaps(crypto, alg, enc, sk_r, pk_s, &ciphersuite(alg))
         }
-        KemAlgorithm::XWingDraft06 => Err(Error::UnsupportedKemOperation),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024
+        | KemAlgorithm::X25519Kyber768Draft00 => Err(Error::UnsupportedKemOperation),
+        _ => Err(Error::UnknownKemAlgorithm),
     }
 }
 
@@ -94,6 +346,15 @@ pub(crate) fn auth_decaps<Crypto: HpkeCrypto>(
 pub(crate) fn key_gen<Crypto: HpkeCrypto>(
     alg: KemAlgorithm,
     prng: &mut Crypto::HpkePrng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    key_gen_with(&PhantomData::<Crypto>, alg, prng)
+}
+
+/// [`key_gen`] with the provider instance `crypto`.
+pub(crate) fn key_gen_with<Crypto: HpkeCryptoInstance>(
+    crypto: &Crypto,
+    alg: KemAlgorithm,
+    prng: &mut Crypto::HpkePrng,
 ) -> Result<(Vec<u8>, Vec<u8>), Error> {
     match alg {
         // For ECDH based keys, we generate a completely fresh key.
@@ -102,14 +363,49 @@ pub(crate) fn key_gen<Crypto: HpkeCrypto>(
         | KemAlgorithm::DhKemP384
         | KemAlgorithm::DhKemP521
         | KemAlgorithm::DhKem25519
-        | KemAlgorithm::DhKem448 => dh_kem::key_gen::<Crypto>(alg, prng),
-        KemAlgorithm::XWingDraft06 => {
-            // For XWing we use the derive key pair function.
-            let mut seed = vec![0u8; alg.private_key_len()];
+        | KemAlgorithm::DhKem448 => dh_kem::key_gen(crypto, alg, prng),
+        KemAlgorithm::XWingDraft06
+        | KemAlgorithm::MlKem512
+        | KemAlgorithm::MlKem768
+        | KemAlgorithm::MlKem1024 => {
+            // For XWing and ML-KEM we use the derive key pair function.
+            let mut seed = vec![0u8; alg.seed_len().unwrap_or(alg.private_key_len())];
             prng.fill_bytes(&mut seed);
-            let (pk, sk) = derive_key_pair::<Crypto>(alg, &seed)?;
+            let (pk, sk) = derive_key_pair_with(crypto, alg, &seed)?;
+            Ok((sk, pk))
+        }
+        KemAlgorithm::X25519Kyber768Draft00 => {
+            let mut ikm = vec![0u8; alg.shared_secret_len()];
+            prng.fill_bytes(&mut ikm);
+            let key_pair = xyber::derive_key_pair(crypto, &ikm);
+            ikm.zeroize();
+            let (pk, sk) = key_pair?;
             Ok((sk, pk))
         }
+        _ => Err(Error::UnknownKemAlgorithm),
+    }
+}
+
+/// Expand the key generation `seed` into a key pair.
+///
+/// Returns (PublicKey, PrivateKey).
+pub(crate) fn key_gen_from_seed<Crypto: HpkeCrypto>(
+    alg: KemAlgorithm,
+    seed: &[u8],
+) -> Result<(PublicKey, PrivateKey), Error> {
+    key_gen_from_seed_with(&PhantomData::<Crypto>, alg, seed)
+}
+
+/// [`key_gen_from_seed`] with the provider instance `crypto`.
+fn key_gen_from_seed_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    seed: &[u8],
+) -> Result<(PublicKey, PrivateKey), Error> {
+    match alg.seed_len() {
+        Some(len) if len == seed.len() => crypto.kem_key_gen_derand(alg, seed),
+        Some(_) => Err(Error::KemInvalidSecretKey),
+        None => Err(Error::UnsupportedKemOperation),
     }
 }
 
@@ -119,6 +415,15 @@ pub(crate) fn key_gen<Crypto: HpkeCrypto>(
 pub(crate) fn derive_key_pair<Crypto: HpkeCrypto>(
     alg: KemAlgorithm,
     ikm: &[u8],
+) -> Result<(PublicKey, PrivateKey), Error> {
+    derive_key_pair_with(&PhantomData::<Crypto>, alg, ikm)
+}
+
+/// [`derive_key_pair`] with the provider instance `crypto`.
+pub(crate) fn derive_key_pair_with(
+    crypto: &impl HpkeCryptoInstance,
+    alg: KemAlgorithm,
+    ikm: &[u8],
 ) -> Result<(PublicKey, PrivateKey), Error> {
     match alg {
         KemAlgorithm::DhKemP256
@@ -126,11 +431,352 @@ pub(crate) fn derive_key_pair<Crypto: HpkeCrypto>(
         | KemAlgorithm::DhKemP384
         | KemAlgorithm::DhKemP521
         | KemAlgorithm::DhKem25519
-        | KemAlgorithm::DhKem448 => dh_kem::derive_key_pair::<Crypto>(alg, &ciphersuite(alg), ikm),
+        | KemAlgorithm::DhKem448 => dh_kem::derive_key_pair(crypto, alg, &ciphersuite(alg), ikm),
         KemAlgorithm::XWingDraft06 => {
             let seed = libcrux_sha3::shake256::<32>(ikm);
-            let kp = Crypto::kem_key_gen_derand(alg, &seed)?;
+            let kp = crypto.kem_key_gen_derand(alg, &seed)?;
             Ok(kp)
         }
+        KemAlgorithm::MlKem512 | KemAlgorithm::MlKem768 | KemAlgorithm::MlKem1024 => {
+            let seed = libcrux_sha3::shake256::<64>(ikm);
+            crypto.kem_key_gen_derand(alg, &seed)
+        }
+        KemAlgorithm::X25519Kyber768Draft00 => xyber::derive_key_pair(crypto, ikm),
+        _ => Err(Error::UnknownKemAlgorithm),
     }
 }
+
+/// A hybrid KEM composed of two KEMs.
+///
+/// The hybrid KEM has its own code point `id`, which must not be the code
+/// point of a KEM known to this crate.
+/// Its KEM in an [`Hpke`] configuration is `KemAlgorithm::from_id(id)`.
+///
+/// * Keys and encapsulations are the concatenations of the keys and
+///   encapsulations of the `first` and the `second` KEM.
+///   Private keys of KEMs with a key generation seed are stored as the seed.
+/// * The shared secret is
+///   `LabeledExpand(LabeledExtract("", "eae_prk", ss1 || ss2), "shared_secret", enc || pkR, Nh)`
+///   with the `kdf` of the hybrid KEM and the suite identifier
+///   `"KEM" || I2OSP(id, 2)`.
+///
+/// Only the base and PSK modes are supported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HybridKem {
+    id: u16,
+    first: KemAlgorithm,
+    second: KemAlgorithm,
+    kdf: KdfAlgorithm,
+}
+
+impl HybridKem {
+    /// Create a hybrid KEM with the code point `id` from the `first` and the
+    /// `second` KEM and the `kdf` for the shared secret combiner.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if `id` is the code point of a
+    /// known KEM, or if one of the algorithms is unknown.
+    pub fn new(
+        id: u16,
+        first: KemAlgorithm,
+        second: KemAlgorithm,
+        kdf: KdfAlgorithm,
+    ) -> Result<Self, HpkeError> {
+        if !matches!(KemAlgorithm::from_id(id), KemAlgorithm::Unknown(_))
+            || matches!(first, KemAlgorithm::Unknown(_))
+            || matches!(second, KemAlgorithm::Unknown(_))
+            || matches!(kdf, KdfAlgorithm::Unknown(_))
+        {
+            return Err(HpkeError::InvalidConfig);
+        }
+        Ok(Self {
+            id,
+            first,
+            second,
+            kdf,
+        })
+    }
+
+    /// Get the code point of this hybrid KEM.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Get the KEM of this hybrid KEM in an [`Hpke`] configuration.
+    pub fn kem(&self) -> KemAlgorithm {
+        KemAlgorithm::from_id(self.id)
+    }
+
+    /// Get the two component KEMs.
+    pub fn components(&self) -> (KemAlgorithm, KemAlgorithm) {
+        (self.first, self.second)
+    }
+
+    /// Get the KDF of the shared secret combiner.
+    pub fn kdf(&self) -> KdfAlgorithm {
+        self.kdf
+    }
+
+    /// The length of a public key in bytes.
+    pub fn public_key_len(&self) -> usize {
+        self.first
+            .public_key_len()
+            .saturating_add(self.second.public_key_len())
+    }
+
+    /// The length of a private key in bytes.
+    pub fn private_key_len(&self) -> usize {
+        component_private_key_len(self.first).saturating_add(component_private_key_len(self.second))
+    }
+
+    /// Create an [`EncapsulatedSecret`] of this hybrid KEM from `value`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the length of `value` isn't
+    /// [`HybridKem::encapsulated_key_len`].
+    pub fn encapsulated_secret(&self, value: Vec<u8>) -> Result<EncapsulatedSecret, HpkeError> {
+        EncapsulatedSecret::with_len(self.kem(), value, self.encapsulated_key_len())
+    }
+
+    /// The length of an encapsulation in bytes.
+    pub fn encapsulated_key_len(&self) -> usize {
+        self.first
+            .encapsulated_key_len()
+            .saturating_add(self.second.encapsulated_key_len())
+    }
+
+    /// The length of the shared secret in bytes.
+    pub fn shared_secret_len(&self) -> usize {
+        self.kdf.digest_len()
+    }
+
+    /// Check that the `Crypto` provider supports both KEMs and the KDF.
+    pub fn check_support<Crypto: HpkeCrypto>(&self) -> Result<(), HpkeError> {
+        for kem in [self.first, self.second] {
+            Crypto::supports_kem(kem).map_err(|_| HpkeError::UnsupportedAlgorithm {
+                kind: AlgorithmKind::Kem,
+                id: kem.id(),
+            })?;
+        }
+        Crypto::supports_kdf(self.kdf).map_err(|_| HpkeError::UnsupportedAlgorithm {
+            kind: AlgorithmKind::Kdf,
+            id: self.kdf.id(),
+        })
+    }
+
+    /// Derive a key pair from the input key material `ikm`.
+    ///
+    /// The input key material of the two KEMs is expanded from `ikm` with
+    /// the labels `"sk1"` and `"sk2"`.
+    pub fn derive_key_pair<Crypto: HpkeCrypto>(
+        &self,
+        ikm: &[u8],
+    ) -> Result<HpkeKeyPair, HpkeError> {
+        self.check_support::<Crypto>()?;
+        let suite_id = self.suite_id();
+        let dkp_prk = labeled_extract::<Crypto>(self.kdf, &[], &suite_id, "dkp_prk", ikm)?;
+        let (mut pk, mut sk) = self.derive_component::<Crypto>(self.first, &dkp_prk, "sk1")?;
+        let (pk2, mut sk2) = self.derive_component::<Crypto>(self.second, &dkp_prk, "sk2")?;
+        pk.extend_from_slice(&pk2);
+        sk.extend_from_slice(&sk2);
+        sk2.zeroize();
+        Ok(HpkeKeyPair::for_kem(self.kem(), sk, pk))
+    }
+
+    /// Generate a fresh key pair with randomness from the `hpke`
+    /// configuration.
+    pub fn generate_key_pair<Crypto: HpkeCrypto>(
+        &self,
+        hpke: &mut Hpke<Crypto>,
+    ) -> Result<HpkeKeyPair, HpkeError> {
+        let mut ikm = hpke.random(self.kdf.digest_len())?;
+        let key_pair = self.derive_key_pair::<Crypto>(&ikm);
+        ikm.zeroize();
+        key_pair
+    }
+
+    /// Encapsulate a fresh shared secret to `pk_r` with randomness from `rng`.
+    ///
+    /// Returns (shared secret, encapsulation), or an
+    /// [`HpkeError::InvalidPublicKey`] if `pk_r` isn't
+    /// [`HybridKem::public_key_len`] bytes long.
+    pub fn encaps<Crypto: HpkeCrypto>(
+        &self,
+        pk_r: &HpkePublicKey,
+        rng: &mut impl CryptoRng,
+    ) -> Result<(SharedSecret, EncapsulatedSecret), HpkeError> {
+        self.check_support::<Crypto>()?;
+        pk_r.check_kem(self.kem())?;
+        let pk_r = self.check_public_key(pk_r)?;
+        let (pk1, pk2) = self.split(pk_r, self.first.public_key_len())?;
+        let (ss1, mut enc) = encaps_with_rng::<Crypto>(self.first, pk1, rng)?;
+        let ss1 = Zeroizing::new(ss1);
+        let (ss2, enc2) = encaps_with_rng::<Crypto>(self.second, pk2, rng)?;
+        let ss2 = Zeroizing::new(ss2);
+        enc.extend_from_slice(&enc2);
+        let ss = self.combine::<Crypto>(&ss1, &ss2, &enc, pk_r)?;
+        Ok((ss, self.encapsulated_secret(enc)?))
+    }
+
+    /// Decapsulate the shared secret from the encapsulation `enc` with the
+    /// private key `sk_r` and the public key `pk_r` of the receiver.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `enc` isn't an encapsulation
+    /// of this hybrid KEM, and an [`HpkeError::InvalidPublicKey`] if `pk_r`
+    /// isn't [`HybridKem::public_key_len`] bytes long.
+    pub fn decaps<Crypto: HpkeCrypto>(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        pk_r: &HpkePublicKey,
+    ) -> Result<SharedSecret, HpkeError> {
+        self.check_support::<Crypto>()?;
+        sk_r.check_kem(self.kem())?;
+        pk_r.check_kem(self.kem())?;
+        let pk_r = self.check_public_key(pk_r)?;
+        let PrivateKeyRef::Bytes(sk) = sk_r.key_ref() else {
+            return Err(HpkeError::InvalidConfig);
+        };
+        let (sk1, sk2) = self.split(sk, component_private_key_len(self.first))?;
+        if enc.kem() != self.kem() || enc.as_slice().len() != self.encapsulated_key_len() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let enc = enc.as_slice();
+        let (enc1, enc2) = self.split(enc, self.first.encapsulated_key_len())?;
+        let ss1 = Zeroizing::new(decaps::<Crypto>(
+            self.first,
+            enc1,
+            PrivateKeyRef::Bytes(sk1),
+        )?);
+        let ss2 = Zeroizing::new(decaps::<Crypto>(
+            self.second,
+            enc2,
+            PrivateKeyRef::Bytes(sk2),
+        )?);
+        self.combine::<Crypto>(&ss1, &ss2, enc, pk_r)
+    }
+
+    /// Set up a sender context for `pk_r` with the `hpke` configuration.
+    ///
+    /// The KEM of `hpke` must be [`HybridKem::kem`] and its mode must be
+    /// [`Mode::Base`] or [`Mode::Psk`], otherwise an
+    /// [`HpkeError::InvalidConfig`] is returned.
+    ///
+    /// Returns (encapsulation, sender context).
+    pub fn setup_sender<Crypto: HpkeCrypto>(
+        &self,
+        hpke: &mut Hpke<Crypto>,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
+        self.check_config(hpke)?;
+        let (ss, enc) = self.encaps::<Crypto>(pk_r, hpke.rng())?;
+        let context = hpke.context_from_shared_secret(&ss, info, psk, psk_id)?;
+        Ok((enc, SenderContext::new(context)))
+    }
+
+    /// Set up a receiver context for the encapsulation `enc` with the key
+    /// pair `sk_r`, `pk_r` and the `hpke` configuration.
+    ///
+    /// The configuration is checked as in [`HybridKem::setup_sender`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn setup_receiver<Crypto: HpkeCrypto>(
+        &self,
+        hpke: &Hpke<Crypto>,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        self.check_config(hpke)?;
+        let ss = self.decaps::<Crypto>(enc, sk_r, pk_r)?;
+        let context = hpke.context_from_shared_secret(&ss, info, psk, psk_id)?;
+        Ok(ReceiverContext::new(context))
+    }
+
+    #[inline(always)]
+    fn suite_id(&self) -> Vec<u8> {
+        util::concat(&[b"KEM", &self.id.to_be_bytes()])
+    }
+
+    fn check_config<Crypto: HpkeCrypto>(&self, hpke: &Hpke<Crypto>) -> Result<(), HpkeError> {
+        if hpke.kem() != self.kem() || !matches!(hpke.mode(), Mode::Base | Mode::Psk) {
+            return Err(HpkeError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes of `pk_r`, or an [`HpkeError::InvalidPublicKey`] if
+    /// it isn't [`HybridKem::public_key_len`] bytes long.
+    fn check_public_key<'a>(&self, pk_r: &'a HpkePublicKey) -> Result<&'a [u8], HpkeError> {
+        let pk_r = pk_r.as_slice();
+        if pk_r.len() != self.public_key_len() {
+            return Err(HpkeError::InvalidPublicKey);
+        }
+        Ok(pk_r)
+    }
+
+    /// Split `value` after the `mid` bytes of the first KEM.
+    fn split<'a>(&self, value: &'a [u8], mid: usize) -> Result<(&'a [u8], &'a [u8]), HpkeError> {
+        value.split_at_checked(mid).ok_or(HpkeError::InvalidInput)
+    }
+
+    /// Derive the key pair (public key, private key) of the component `kem`
+    /// from `dkp_prk`.
+    fn derive_component<Crypto: HpkeCrypto>(
+        &self,
+        kem: KemAlgorithm,
+        dkp_prk: &[u8],
+        label: &'static str,
+    ) -> Result<(PublicKey, PrivateKey), Error> {
+        let suite_id = self.suite_id();
+        let mut ikm = labeled_expand::<Crypto>(
+            self.kdf,
+            dkp_prk,
+            &suite_id,
+            label,
+            &[],
+            component_private_key_len(kem),
+        )?;
+        let key_pair = match kem.seed_len() {
+            // Keep the seed as private key.
+            Some(_) => key_gen_from_seed::<Crypto>(kem, &ikm).map(|(pk, _)| (pk, ikm.clone())),
+            None => derive_key_pair::<Crypto>(kem, &ikm),
+        };
+        ikm.zeroize();
+        key_pair
+    }
+
+    /// The shared secret combiner.
+    fn combine<Crypto: HpkeCrypto>(
+        &self,
+        ss1: &[u8],
+        ss2: &[u8],
+        enc: &[u8],
+        pk_r: &[u8],
+    ) -> Result<SharedSecret, HpkeError> {
+        let suite_id = self.suite_id();
+        let mut ikm = util::concat(&[ss1, ss2]);
+        let eae_prk = labeled_extract::<Crypto>(self.kdf, &[], &suite_id, "eae_prk", &ikm);
+        ikm.zeroize();
+        let mut eae_prk = eae_prk?;
+        let ss = labeled_expand::<Crypto>(
+            self.kdf,
+            &eae_prk,
+            &suite_id,
+            "shared_secret",
+            &util::concat(&[enc, pk_r]),
+            self.kdf.digest_len(),
+        );
+        eae_prk.zeroize();
+        Ok(SharedSecret::new(ss?))
+    }
+}
+
+/// The length of a private key of the component `kem`, the seed for KEMs
+/// with a key generation seed.
+fn component_private_key_len(kem: KemAlgorithm) -> usize {
+    kem.seed_len().unwrap_or(kem.private_key_len())
+}
@@ -0,0 +1,132 @@
+//! # RustCrypto KEM Traits
+//!
+//! [`Encapsulator`] and [`Decapsulator`] expose the KEMs of a crypto provider
+//! through the [`kem`](::kem) crate traits [`Encapsulate`] and
+//! [`Decapsulate`], such that they can be composed with other KEM-generic
+//! code, e.g. in hybrid protocols.
+//!
+//! The encapsulated key is an [`EncapsulatedSecret`] and the shared secret a
+//! [`SharedSecret`], which can be used with
+//! [`Hpke::context_from_shared_secret`](crate::Hpke::context_from_shared_secret).
+//!
+//! Only the base KEM operations are exposed; the authenticated DH KEM
+//! operations have no counterpart in the `kem` traits.
+//!
+//! **Note** that the DH KEMs draw their ephemeral keys from the RNG passed to
+//! [`Encapsulate::encapsulate`], while provider KEMs like X-Wing use the PRNG
+//! of the crypto provider.
+
+use core::marker::PhantomData;
+
+use ::kem::{Decapsulate, Encapsulate};
+use alloc::vec;
+use hpke_rs_crypto::{types::KemAlgorithm, HpkeCrypto};
+use rand_core_06::CryptoRngCore;
+use zeroize::Zeroize;
+
+use crate::{
+    kem, AlgorithmKind, EncapsulatedSecret, HpkeError, HpkePrivateKey, HpkePublicKey, SharedSecret,
+};
+
+/// Returns an [`HpkeError::UnsupportedAlgorithm`] if the `Crypto` provider
+/// doesn't support the `kem`.
+fn check_kem_support<Crypto: HpkeCrypto>(kem: KemAlgorithm) -> Result<(), HpkeError> {
+    Crypto::supports_kem(kem).map_err(|_| HpkeError::UnsupportedAlgorithm {
+        kind: AlgorithmKind::Kem,
+        id: kem.id(),
+    })
+}
+
+/// A recipient public key implementing [`Encapsulate`].
+#[derive(Debug, Clone)]
+pub struct Encapsulator<Crypto: 'static + HpkeCrypto> {
+    kem: KemAlgorithm,
+    pk_r: HpkePublicKey,
+    phantom: PhantomData<Crypto>,
+}
+
+impl<Crypto: HpkeCrypto> Encapsulator<Crypto> {
+    /// Create an encapsulator to the public key `pk_r` of the `kem`.
+    ///
+    /// Returns an error if the `kem` is not supported by the `Crypto`
+    /// provider, the key is for another KEM, or the key doesn't have the
+    /// length required by the `kem`.
+    pub fn new(kem: KemAlgorithm, pk_r: HpkePublicKey) -> Result<Self, HpkeError> {
+        check_kem_support::<Crypto>(kem)?;
+        let pk_r = pk_r.decompress::<Crypto>(kem)?;
+        let expected = kem.public_key_len();
+        if pk_r.as_slice().len() != expected {
+            return Err(HpkeError::InvalidKeyLength {
+                expected,
+                got: pk_r.as_slice().len(),
+            });
+        }
+        Ok(Self {
+            kem,
+            pk_r,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> Encapsulate<EncapsulatedSecret, SharedSecret> for Encapsulator<Crypto> {
+    type Error = HpkeError;
+
+    fn encapsulate(
+        &self,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(EncapsulatedSecret, SharedSecret), HpkeError> {
+        let mut randomness = vec![0u8; kem::encaps_randomness_len(self.kem)];
+        rng.try_fill_bytes(&mut randomness)
+            .map_err(|_| HpkeError::InsufficientRandomness)?;
+        let encapsulation =
+            kem::encaps_with_randomness::<Crypto>(self.kem, self.pk_r.as_slice(), &randomness);
+        randomness.zeroize();
+        let (zz, enc) = encapsulation?;
+        Ok((
+            EncapsulatedSecret::new(self.kem, enc)?,
+            SharedSecret::new(zz),
+        ))
+    }
+}
+
+/// A recipient private key implementing [`Decapsulate`].
+#[derive(Debug)]
+pub struct Decapsulator<Crypto: 'static + HpkeCrypto> {
+    kem: KemAlgorithm,
+    sk_r: HpkePrivateKey,
+    phantom: PhantomData<Crypto>,
+}
+
+impl<Crypto: HpkeCrypto> Decapsulator<Crypto> {
+    /// Create a decapsulator with the private key `sk_r` of the `kem`.
+    ///
+    /// Returns an error if the `kem` is not supported by the `Crypto`
+    /// provider or the key is for another KEM.
+    pub fn new(kem: KemAlgorithm, sk_r: HpkePrivateKey) -> Result<Self, HpkeError> {
+        check_kem_support::<Crypto>(kem)?;
+        sk_r.check_kem(kem)?;
+        Ok(Self {
+            kem,
+            sk_r,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> Decapsulate<EncapsulatedSecret, SharedSecret> for Decapsulator<Crypto> {
+    type Error = HpkeError;
+
+    fn decapsulate(
+        &self,
+        encapsulated_key: &EncapsulatedSecret,
+    ) -> Result<SharedSecret, HpkeError> {
+        if encapsulated_key.kem != self.kem {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.sk_r
+            .audit(self.kem, crate::audit::KeyOperation::Decapsulation);
+        let zz = kem::decaps::<Crypto>(self.kem, encapsulated_key.as_slice(), self.sk_r.key_ref())?;
+        Ok(SharedSecret::new(zz))
+    }
+}
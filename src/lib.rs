@@ -9,14 +9,28 @@
     unused_qualifications
 )]
 #![cfg_attr(not(test), no_std)]
+#![cfg_attr(
+    not(test),
+    warn(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects
+    )
+)]
 
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+use core::marker::PhantomData;
+
 use alloc::{
+    borrow::Cow,
     format,
     string::{String, ToString},
+    sync::Arc,
     vec,
     vec::Vec,
 };
@@ -24,13 +38,19 @@ use alloc::{
 #[cfg(feature = "hpke-test-prng")]
 use hpke_rs_crypto::HpkeTestRng;
 use hpke_rs_crypto::{
-    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
-    HpkeCrypto,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm, PrivateKeyRef},
+    CryptoRng, HpkeCrypto, HpkeCryptoInstance,
 };
-use prelude::kdf::{labeled_expand, labeled_extract};
+use prelude::kdf::{labeled_expand_with, labeled_extract_with};
 
 /// Re-export of the HPKE types from the [`hpke_rs_crypto`] crate.
 pub use hpke_rs_crypto::types as hpke_types;
+pub use kem::HybridKem;
+pub use role::{ReceiverContext, SenderContext};
+
+/// Re-export of the RNG adapters from the [`hpke_rs_crypto`] crate.
+#[cfg(feature = "rand_core_06")]
+pub use hpke_rs_crypto::rng;
 
 /// Re-export of the RustCrypto crate.
 #[cfg(feature = "rustcrypto")]
@@ -47,16 +67,71 @@ use rand_core::TryRngCore;
 pub(crate) use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+#[cfg(feature = "aead")]
+pub mod aead_context;
+#[cfg(feature = "allocator-api2")]
+pub mod allocator;
+pub mod audit;
+pub mod auth;
+pub mod builder;
+pub mod channel;
+pub mod config;
+#[cfg(feature = "cose")]
+pub mod cose;
+#[cfg(feature = "cose")]
+pub mod cose_hpke;
 mod dh_kem;
+pub mod dyn_hpke;
+pub mod ech;
+pub mod envelope;
+#[cfg(feature = "fixed")]
+pub mod fixed;
+pub mod grease;
+pub mod instance;
+#[cfg(feature = "jose")]
+pub mod jose;
+#[cfg(feature = "jwk")]
+pub mod jwk;
 pub(crate) mod kdf;
 mod kem;
+#[cfg(feature = "kem")]
+pub mod kem_traits;
+pub mod message;
+pub mod negotiation;
+pub mod odoh;
+pub mod ohttp;
+pub mod padding;
+#[cfg(feature = "pem")]
+pub mod pem;
+#[cfg(feature = "pkcs8")]
+pub mod pkcs8;
+#[cfg(feature = "std")]
+pub mod pool;
 pub mod prelude;
+pub mod ratchet;
+pub mod replay;
+pub mod response;
+#[cfg(feature = "hazmat")]
+pub mod resumption;
+mod role;
+#[cfg(feature = "rust-hpke")]
+pub mod rust_hpke;
+#[cfg(feature = "signature")]
+pub mod signed;
+#[cfg(feature = "rustcrypto")]
+pub mod simple;
+pub mod single_shot;
+pub mod stream;
+pub mod transcript;
 
 mod util;
+mod xyber;
 
 #[cfg(test)]
 mod test_aead;
 #[cfg(test)]
+mod test_context;
+#[cfg(test)]
 mod test_kdf;
 
 #[deprecated(
@@ -68,7 +143,15 @@ mod test_kdf;
 type HPKEError = HpkeError;
 
 /// HPKE Error types.
+///
+/// With the `serialization` feature errors can be serialized, e.g. to
+/// transport them across an RPC boundary.
+/// [`HpkeError::code`] provides a stable numeric code for each error.
+///
+/// New errors may be added in minor releases.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum HpkeError {
     /// Error opening an HPKE ciphertext.
     OpenError,
@@ -102,6 +185,117 @@ pub enum HpkeError {
 
     /// Unable to collect enough randomness.
     InsufficientRandomness,
+
+    /// Invalid nonce for the AEAD.
+    InvalidNonce,
+
+    /// Invalid AEAD or KEM ciphertext.
+    InvalidCiphertext,
+
+    /// Invalid KEM public key.
+    InvalidPublicKey,
+
+    /// Invalid KEM secret key.
+    InvalidSecretKey,
+
+    /// The key doesn't have the length required by the KEM.
+    InvalidKeyLength {
+        /// The expected key length in bytes.
+        expected: usize,
+
+        /// The actual key length in bytes.
+        got: usize,
+    },
+
+    /// The algorithm is not supported by the crypto provider.
+    UnsupportedAlgorithm {
+        /// The kind of the unsupported algorithm.
+        kind: AlgorithmKind,
+
+        /// The IANA identifier of the unsupported algorithm.
+        id: u16,
+    },
+
+    /// The encapsulated secret has been seen before.
+    ReusedEncapsulation,
+
+    /// The signature of the sender is invalid.
+    InvalidSignature,
+
+    /// The plain text or associated data exceed the limits of a single AEAD
+    /// invocation.
+    /// Split the plain text into multiple messages, e.g. with a
+    /// [`stream::ChunkSealer`].
+    InputTooLong,
+
+    /// The message has been opened before or is too old for the replay
+    /// window.
+    ReplayedMessage,
+
+    /// The ciphersuite uses the export-only AEAD and can't seal or open.
+    ExportOnlyMode,
+
+    /// No ciphersuite offered by the peer is both preferred locally and
+    /// supported by the crypto provider.
+    NoCommonSuite,
+
+    /// The crypto provider refused an algorithm during an operation.
+    ///
+    /// Unlike [`HpkeError::UnsupportedAlgorithm`] the identifier of the
+    /// algorithm isn't known, because the provider doesn't report it.
+    UnsupportedByProvider {
+        /// The kind of the unsupported algorithm.
+        kind: AlgorithmKind,
+    },
+}
+
+/// The kind of an HPKE algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum AlgorithmKind {
+    /// A KEM algorithm.
+    Kem,
+
+    /// A KDF algorithm.
+    Kdf,
+
+    /// An AEAD algorithm.
+    Aead,
+}
+
+impl HpkeError {
+    /// Get the stable numeric code of this error.
+    ///
+    /// The codes don't change between releases and new errors get new codes.
+    /// Note that the code doesn't include the data carried by an error.
+    pub const fn code(&self) -> u16 {
+        match self {
+            HpkeError::OpenError => 1,
+            HpkeError::InvalidConfig => 2,
+            HpkeError::InvalidInput => 3,
+            HpkeError::UnknownMode => 4,
+            HpkeError::InconsistentPsk => 5,
+            HpkeError::MissingPsk => 6,
+            HpkeError::UnnecessaryPsk => 7,
+            HpkeError::InsecurePsk => 8,
+            HpkeError::CryptoError(_) => 9,
+            HpkeError::MessageLimitReached => 10,
+            HpkeError::InsufficientRandomness => 11,
+            HpkeError::InvalidNonce => 12,
+            HpkeError::InvalidCiphertext => 13,
+            HpkeError::InvalidPublicKey => 14,
+            HpkeError::InvalidSecretKey => 15,
+            HpkeError::InvalidKeyLength { .. } => 16,
+            HpkeError::UnsupportedAlgorithm { .. } => 17,
+            HpkeError::ReusedEncapsulation => 18,
+            HpkeError::InvalidSignature => 19,
+            HpkeError::InputTooLong => 20,
+            HpkeError::ReplayedMessage => 21,
+            HpkeError::ExportOnlyMode => 22,
+            HpkeError::NoCommonSuite => 23,
+            HpkeError::UnsupportedByProvider { .. } => 24,
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -122,10 +316,16 @@ impl core::fmt::Display for HpkeError {
 pub type HPKEPublicKey = HpkePublicKey;
 
 /// An HPKE public key is a byte vector.
-#[derive(Debug, PartialEq, Clone, Default)]
+///
+/// Keys that are generated by [`Hpke`] or created with
+/// [`HpkePublicKey::new_checked`] carry their KEM, and using them with
+/// another KEM is rejected.
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct HpkePublicKey {
     value: Vec<u8>,
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    kem: Option<KemAlgorithm>,
 }
 
 #[deprecated(
@@ -137,12 +337,24 @@ pub struct HpkePublicKey {
 pub type HPKEPrivateKey = HpkePrivateKey;
 
 /// An HPKE private key is a byte vector.
+///
+/// Like [`HpkePublicKey`]s, keys that are generated by [`Hpke`] or created
+/// with [`HpkePrivateKey::new_checked`] carry their KEM.
 #[derive(Default, Zeroize)]
 #[zeroize(drop)] // XXX: Change to `ZeroizeOnDrop` when moving to 1.5
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "hazmat", derive(Clone))]
 pub struct HpkePrivateKey {
     value: Vec<u8>,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    kem: Option<KemAlgorithm>,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    auditor: Option<Arc<dyn audit::KeyUsageAuditor>>,
+    #[zeroize(skip)]
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    handle: bool,
 }
 
 #[deprecated(
@@ -162,6 +374,17 @@ pub struct HpkeKeyPair {
     public_key: HpkePublicKey,
 }
 
+/// A KEM shared secret that was established outside of HPKE.
+///
+/// This is the input to [`Hpke::context_from_shared_secret`] for protocols
+/// that perform the KEM step themselves.
+/// The shared secret is zeroized on drop.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct SharedSecret {
+    value: Vec<u8>,
+}
+
 /// HPKE supports four modes.
 #[derive(PartialEq, Copy, Clone, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
@@ -199,13 +422,116 @@ impl TryFrom<u8> for Mode {
     }
 }
 
-/// Type alias for encapsulated secrets.
-/// A byte vector.
-type EncapsulatedSecret = Vec<u8>;
+/// An HPKE encapsulated secret (`enc`) for a KEM.
+///
+/// The length of the encapsulated secret is checked against the KEM when
+/// creating it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EncapsulatedSecret {
+    kem: KemAlgorithm,
+    value: Vec<u8>,
+}
 
-/// Type alias for ciphertexts.
-/// A byte vector.
-type Ciphertext = Vec<u8>;
+impl EncapsulatedSecret {
+    /// Create a new encapsulated secret for the `kem`.
+    /// Consumes the encapsulated secret bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the length of `value` doesn't
+    /// match the `kem`.
+    ///
+    /// Encapsulated secrets of a [`HybridKem`] are created with
+    /// [`HybridKem::encapsulated_secret`].
+    pub fn new(kem: KemAlgorithm, value: Vec<u8>) -> Result<Self, HpkeError> {
+        Self::with_len(kem, value, kem.encapsulated_key_len())
+    }
+
+    /// Create a new encapsulated secret for the `kem` with the encapsulation
+    /// length `len`, for KEMs that don't know their length, e.g. a
+    /// [`HybridKem`].
+    pub(crate) fn with_len(
+        kem: KemAlgorithm,
+        value: Vec<u8>,
+        len: usize,
+    ) -> Result<Self, HpkeError> {
+        if value.len() != len {
+            return Err(HpkeError::InvalidInput);
+        }
+        Ok(Self { kem, value })
+    }
+
+    /// Get the KEM this encapsulated secret is for.
+    pub fn kem(&self) -> KemAlgorithm {
+        self.kem
+    }
+
+    /// Get the raw encapsulated secret as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.value.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for EncapsulatedSecret {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<EncapsulatedSecret> for Vec<u8> {
+    fn from(enc: EncapsulatedSecret) -> Self {
+        enc.value
+    }
+}
+
+/// An HPKE ciphertext for an AEAD.
+///
+/// The ciphertext is checked to be at least as long as the AEAD tag when
+/// creating it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ciphertext {
+    aead: AeadAlgorithm,
+    value: Vec<u8>,
+}
+
+impl Ciphertext {
+    /// Create a new ciphertext for the `aead`.
+    /// Consumes the ciphertext bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `value` is shorter than the
+    /// tag of the `aead`.
+    pub fn new(aead: AeadAlgorithm, value: Vec<u8>) -> Result<Self, HpkeError> {
+        if value.len() < aead.tag_length() {
+            return Err(HpkeError::InvalidInput);
+        }
+        Ok(Self { aead, value })
+    }
+
+    /// Get the AEAD this ciphertext is for.
+    pub fn aead(&self) -> AeadAlgorithm {
+        self.aead
+    }
+
+    /// Get the raw ciphertext as byte slice.
+    pub fn as_slice(&self) -> &[u8] {
+        self.value.as_slice()
+    }
+
+    /// The length of the plain text in this ciphertext in bytes.
+    fn plaintext_len(&self) -> usize {
+        self.value.len().saturating_sub(self.aead.tag_length())
+    }
+}
+
+impl AsRef<[u8]> for Ciphertext {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Ciphertext> for Vec<u8> {
+    fn from(ct: Ciphertext) -> Self {
+        ct.value
+    }
+}
 
 /// Type alias for plain text.
 /// A byte vector.
@@ -214,16 +540,20 @@ type Plaintext = Vec<u8>;
 /// The HPKE context.
 /// Note that the RFC currently doesn't define this.
 /// Also see <https://github.com/cfrg/draft-irtf-cfrg-hpke/issues/161>.
-pub struct Context<Crypto: 'static + HpkeCrypto> {
+pub struct Context<Crypto> {
     key: Vec<u8>,
     nonce: Vec<u8>,
     exporter_secret: Vec<u8>,
     sequence_number: u32,
-    hpke: Hpke<Crypto>,
+    message_limit: u64,
+    suite: HpkeSuite,
+    transcript: Option<Vec<u8>>,
+    padding: Option<padding::PaddingPolicy>,
+    phantom: PhantomData<Crypto>,
 }
 
 #[cfg(feature = "hazmat")]
-impl<Crypto: HpkeCrypto> core::fmt::Debug for Context<Crypto> {
+impl<Crypto> core::fmt::Debug for Context<Crypto> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
@@ -234,7 +564,7 @@ impl<Crypto: HpkeCrypto> core::fmt::Debug for Context<Crypto> {
 }
 
 #[cfg(not(feature = "hazmat"))]
-impl<Crypto: HpkeCrypto> core::fmt::Debug for Context<Crypto> {
+impl<Crypto> core::fmt::Debug for Context<Crypto> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
@@ -256,22 +586,20 @@ impl<Crypto: HpkeCrypto> Context<Crypto> {
     ///   self.IncrementSeq()
     ///   return ct
     /// ```
+    ///
+    /// Returns an [`HpkeError::InputTooLong`] if `aad` or `plain_txt` exceed
+    /// the limits of the AEAD, see
+    /// [`AeadAlgorithm::max_plaintext_length`] and
+    /// [`AeadAlgorithm::max_aad_length`].
     pub fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Ciphertext, HpkeError> {
-        let ctxt = Crypto::aead_seal(
-            self.hpke.aead_id,
-            &self.key,
-            &self.compute_nonce(),
-            aad,
-            plain_txt,
-        )?;
-        self.increment_seq()?;
-        Ok(ctxt)
+        self.seal_with(&PhantomData::<Crypto>, aad, plain_txt)
     }
 
     /// 5.2. Encryption and Decryption
     ///
-    /// Takes the associated data and the ciphertext as byte slices and returns
-    /// the plain text or an error.
+    /// Takes the associated data and the ciphertext and returns the plain text
+    /// or an error.
+    /// The ciphertext must be for the AEAD of this context.
     ///
     /// ```text
     /// def Context.Open(aad, ct):
@@ -281,16 +609,142 @@ impl<Crypto: HpkeCrypto> Context<Crypto> {
     ///   self.IncrementSeq()
     ///   return pt
     /// ```
-    pub fn open(&mut self, aad: &[u8], cipher_txt: &[u8]) -> Result<Plaintext, HpkeError> {
-        let ptxt = Crypto::aead_open(
-            self.hpke.aead_id,
+    pub fn open(&mut self, aad: &[u8], cipher_txt: &Ciphertext) -> Result<Plaintext, HpkeError> {
+        self.open_with(&PhantomData::<Crypto>, aad, cipher_txt)
+    }
+
+    /// Seal the plain text in `buffer` with `aad` in place.
+    ///
+    /// This is [`Context::seal`] without allocating the ciphertext.
+    /// On success `buffer` holds the ciphertext and the detached tag is
+    /// returned.
+    /// Returns an [`HpkeError::InvalidConfig`] if
+    /// [padding](Context::set_padding) is enabled.
+    pub fn seal_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.seal_in_place_detached_with(&PhantomData::<Crypto>, buffer, aad)
+    }
+
+    /// Open the ciphertext in `buffer` with `aad` and the detached `tag` in
+    /// place.
+    ///
+    /// This is [`Context::open`] without allocating the plain text.
+    /// On success `buffer` holds the plain text.
+    /// On failure `buffer` is not changed.
+    /// Returns an [`HpkeError::InvalidConfig`] if
+    /// [padding](Context::set_padding) is enabled.
+    pub fn open_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+        tag: &[u8],
+    ) -> Result<(), HpkeError> {
+        self.open_in_place_detached_with(&PhantomData::<Crypto>, buffer, aad, tag)
+    }
+
+    /// Seal `plain_txt` with `aad` using the given AEAD `nonce` instead of
+    /// the nonce derived from the sequence number.
+    ///
+    /// The sequence number of the context is not changed.
+    ///
+    /// **Note** that this is only meant for conformance testing against
+    /// external known-answer tests and for debugging interoperability issues.
+    /// Reusing a nonce breaks the security of the AEAD.
+    #[cfg(feature = "hazmat")]
+    pub fn seal_with_nonce(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<Ciphertext, HpkeError> {
+        self.check_aead()?;
+        self.check_lengths(aad.len(), plain_txt.len())?;
+        if nonce.len() != self.nonce.len() {
+            return Err(HpkeError::InvalidNonce);
+        }
+        let ctxt = Crypto::aead_seal(self.suite.aead, &self.key, nonce, aad, plain_txt)?;
+        Ok(Ciphertext {
+            aead: self.suite.aead,
+            value: ctxt,
+        })
+    }
+
+    /// Open `cipher_txt` with `aad` using the given AEAD `nonce` instead of
+    /// the nonce derived from the sequence number.
+    ///
+    /// The sequence number of the context is not changed.
+    ///
+    /// **Note** that this is only meant for conformance testing against
+    /// external known-answer tests and for debugging interoperability issues.
+    #[cfg(feature = "hazmat")]
+    pub fn open_with_nonce(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        self.check_aead()?;
+        if cipher_txt.aead != self.suite.aead {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.check_lengths(aad.len(), cipher_txt.plaintext_len())?;
+        if nonce.len() != self.nonce.len() {
+            return Err(HpkeError::InvalidNonce);
+        }
+        Ok(Crypto::aead_open(
+            self.suite.aead,
             &self.key,
-            &self.compute_nonce(),
+            nonce,
             aad,
-            cipher_txt,
-        )?;
-        self.increment_seq()?;
-        Ok(ptxt)
+            cipher_txt.as_slice(),
+        )?)
+    }
+
+    /// Open `cipher_txt` with `aad`, consuming the context if it fails.
+    ///
+    /// This is [`Context::open`] for receivers that must not process further
+    /// ciphertexts after an authentication failure, e.g. to avoid serving as
+    /// an oracle for attacker controlled ciphertexts.
+    ///
+    /// Returns the plain text together with the context for the next message.
+    /// If opening fails, the secrets of the context are zeroized and the
+    /// context is dropped.
+    pub fn open_strict(
+        mut self,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<(Plaintext, Self), HpkeError> {
+        match self.open(aad, cipher_txt) {
+            Ok(ptxt) => Ok((ptxt, self)),
+            Err(e) => {
+                self.key.zeroize();
+                self.nonce.zeroize();
+                self.exporter_secret.zeroize();
+                Err(e)
+            }
+        }
+    }
+
+    /// Open a batch of ciphertexts that were sealed in order on the same
+    /// sender context.
+    ///
+    /// Each message is a pair of associated data and ciphertext.
+    /// The messages are opened with consecutive sequence numbers, starting at
+    /// the current sequence number of this context.
+    /// Missing, reordered, or modified messages therefore fail to open.
+    ///
+    /// Returns the plain texts in order, or the error of the first message
+    /// that failed to open.
+    /// In case of an error the sequence number and the transcript of the
+    /// context are not changed.
+    pub fn open_batch(
+        &mut self,
+        messages: &[(&[u8], &Ciphertext)],
+    ) -> Result<Vec<Plaintext>, HpkeError> {
+        self.open_batch_with(&PhantomData::<Crypto>, messages)
     }
 
     /// 5.3. Secret Export
@@ -303,10 +757,226 @@ impl<Crypto: HpkeCrypto> Context<Crypto> {
     ///  return LabeledExpand(self.exporter_secret, "sec", exporter_context, L)
     ///```
     pub fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError> {
-        labeled_expand::<Crypto>(
-            self.hpke.kdf_id,
+        self.export_with(&PhantomData::<Crypto>, exporter_context, length)
+    }
+}
+
+impl<Crypto> Context<Crypto> {
+    /// Get the [`HpkeSuite`] this context was set up with.
+    pub fn suite(&self) -> HpkeSuite {
+        self.suite
+    }
+
+    /// Get the mode of this context.
+    pub fn mode(&self) -> Mode {
+        self.suite.mode
+    }
+
+    /// Get the KEM algorithm of this context.
+    pub fn kem(&self) -> KemAlgorithm {
+        self.suite.kem
+    }
+
+    /// Get the KDF algorithm of this context.
+    pub fn kdf(&self) -> KdfAlgorithm {
+        self.suite.kdf
+    }
+
+    /// Get the AEAD algorithm of this context.
+    pub fn aead(&self) -> AeadAlgorithm {
+        self.suite.aead
+    }
+
+    /// Get the `suite_id` of this context.
+    ///
+    /// See [`HpkeSuite::suite_id`].
+    pub fn suite_id(&self) -> Vec<u8> {
+        self.suite.suite_id()
+    }
+
+    /// Get the number of messages this context can seal or open.
+    ///
+    /// This defaults to the usage limit of the AEAD, see
+    /// [`AeadAlgorithm::max_messages`].
+    /// Once the sequence number reaches the limit, sealing and opening fail
+    /// with an [`HpkeError::MessageLimitReached`].
+    pub fn message_limit(&self) -> u64 {
+        self.message_limit
+    }
+
+    /// Lower the [message limit](Context::message_limit) of this context to
+    /// `limit`, e.g. to keep a safety margin to the usage limit of the AEAD.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `limit` exceeds the usage
+    /// limit of the AEAD.
+    pub fn set_message_limit(&mut self, limit: u64) -> Result<(), HpkeError> {
+        if limit > self.suite.aead.max_messages() {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.message_limit = limit;
+        Ok(())
+    }
+
+    /// [`Context::seal`] with the provider instance `crypto`.
+    pub(crate) fn seal_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<Ciphertext, HpkeError> {
+        self.check_aead()?;
+        let plain_txt = self.pad(plain_txt)?;
+        self.check_lengths(aad.len(), plain_txt.len())?;
+        let ctxt = crypto.aead_seal(
+            self.suite.aead,
+            &self.key,
+            &self.compute_nonce()?,
+            aad,
+            &plain_txt,
+        )?;
+        self.record_transcript_with(crypto, aad.len(), ctxt.len())?;
+        self.increment_seq_with(crypto)?;
+        Ok(Ciphertext {
+            aead: self.suite.aead,
+            value: ctxt,
+        })
+    }
+
+    /// [`Context::open`] with the provider instance `crypto`.
+    pub(crate) fn open_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        self.check_aead()?;
+        if cipher_txt.aead != self.suite.aead {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.check_lengths(aad.len(), cipher_txt.plaintext_len())?;
+        let ptxt = crypto.aead_open(
+            self.suite.aead,
+            &self.key,
+            &self.compute_nonce()?,
+            aad,
+            cipher_txt.as_slice(),
+        )?;
+        self.record_transcript_with(crypto, aad.len(), cipher_txt.as_slice().len())?;
+        self.increment_seq_with(crypto)?;
+        self.unpad(ptxt)
+    }
+
+    /// [`Context::seal_in_place_detached`] with the provider instance
+    /// `crypto`.
+    pub(crate) fn seal_in_place_detached_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        buffer: &mut [u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.check_aead()?;
+        self.check_no_padding()?;
+        self.seal_padded_in_place_with(crypto, buffer, aad)
+    }
+
+    /// Seal the padded plain text in `buffer` with `aad` in place.
+    ///
+    /// The caller checks the AEAD of this context.
+    pub(crate) fn seal_padded_in_place_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        buffer: &mut [u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.check_lengths(aad.len(), buffer.len())?;
+        let tag = crypto.aead_seal_in_place_detached(
+            self.suite.aead,
+            &self.key,
+            &self.compute_nonce()?,
+            aad,
+            buffer,
+        )?;
+        self.record_transcript_with(crypto, aad.len(), buffer.len().saturating_add(tag.len()))?;
+        self.increment_seq_with(crypto)?;
+        Ok(tag)
+    }
+
+    /// [`Context::open_in_place_detached`] with the provider instance
+    /// `crypto`.
+    pub(crate) fn open_in_place_detached_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        buffer: &mut [u8],
+        aad: &[u8],
+        tag: &[u8],
+    ) -> Result<(), HpkeError> {
+        self.check_aead()?;
+        self.check_no_padding()?;
+        self.open_padded_in_place_with(crypto, buffer, aad, tag)
+    }
+
+    /// Open the ciphertext in `buffer` with `aad` and the detached `tag` in
+    /// place, leaving the padded plain text in `buffer`.
+    ///
+    /// The caller checks the AEAD of this context.
+    pub(crate) fn open_padded_in_place_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        buffer: &mut [u8],
+        aad: &[u8],
+        tag: &[u8],
+    ) -> Result<(), HpkeError> {
+        if tag.len() != self.suite.aead.tag_length() {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.check_lengths(aad.len(), buffer.len())?;
+        crypto.aead_open_in_place_detached(
+            self.suite.aead,
+            &self.key,
+            &self.compute_nonce()?,
+            aad,
+            buffer,
+            tag,
+        )?;
+        self.record_transcript_with(crypto, aad.len(), buffer.len().saturating_add(tag.len()))?;
+        self.increment_seq_with(crypto)?;
+        Ok(())
+    }
+
+    /// [`Context::open_batch`] with the provider instance `crypto`.
+    pub(crate) fn open_batch_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        messages: &[(&[u8], &Ciphertext)],
+    ) -> Result<Vec<Plaintext>, HpkeError> {
+        let sequence_number = self.sequence_number;
+        let transcript = self.transcript.clone();
+        let mut ptxts = Vec::with_capacity(messages.len());
+        for (aad, cipher_txt) in messages {
+            match self.open_with(crypto, aad, cipher_txt) {
+                Ok(ptxt) => ptxts.push(ptxt),
+                Err(e) => {
+                    self.sequence_number = sequence_number;
+                    self.transcript = transcript;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(ptxts)
+    }
+
+    /// [`Context::export`] with the provider instance `crypto`.
+    pub(crate) fn export_with(
+        &self,
+        crypto: &impl HpkeCryptoInstance,
+        exporter_context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, HpkeError> {
+        labeled_expand_with(
+            crypto,
+            self.suite.kdf,
             &self.exporter_secret,
-            &self.hpke.ciphersuite(),
+            &self.suite.suite_id(),
             "sec",
             exporter_context,
             length,
@@ -314,72 +984,692 @@ impl<Crypto: HpkeCrypto> Context<Crypto> {
         .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))
     }
 
+    /// Returns an [`HpkeError::InputTooLong`] if the associated data of
+    /// `aad_len` bytes or the plain text of `ptxt_len` bytes exceed the limits
+    /// of the AEAD.
+    #[inline]
+    fn check_lengths(&self, aad_len: usize, ptxt_len: usize) -> Result<(), HpkeError> {
+        let aead = self.suite.aead;
+        let too_long = |len: usize, max: u64| u64::try_from(len).map_or(true, |len| len > max);
+        if too_long(aad_len, aead.max_aad_length())
+            || too_long(ptxt_len, aead.max_plaintext_length())
+        {
+            return Err(HpkeError::InputTooLong);
+        }
+        Ok(())
+    }
+
+    /// Export-only contexts have no AEAD key and nonce and can't be used to
+    /// seal or open.
+    /// This is checked before any nonce or sequence number is touched.
+    #[inline]
+    fn check_aead(&self) -> Result<(), HpkeError> {
+        self.suite.check_aead()
+    }
+
     /// def Context<ROLE>.ComputeNonce(seq):
     ///     seq_bytes = I2OSP(seq, Nn)
     ///     return xor(self.base_nonce, seq_bytes)
-    fn compute_nonce(&self) -> Vec<u8> {
-        let seq = self.sequence_number.to_be_bytes();
-        let mut enc_seq = vec![0u8; self.nonce.len() - seq.len()];
-        enc_seq.extend_from_slice(&seq);
-        util::xor_bytes(&enc_seq, &self.nonce)
+    ///
+    /// Returns an [`HpkeError::MessageLimitReached`] if the sequence number
+    /// doesn't fit into `Nn` bytes.
+    fn compute_nonce(&self) -> Result<Vec<u8>, HpkeError> {
+        self.compute_nonce_for(self.sequence_number)
+    }
+
+    /// Compute the nonce for the sequence number `seq`.
+    ///
+    /// Returns an [`HpkeError::MessageLimitReached`] if `seq` exceeds the
+    /// [message limit](Context::message_limit).
+    fn compute_nonce_for(&self, seq: u32) -> Result<Vec<u8>, HpkeError> {
+        if u64::from(seq) >= self.message_limit {
+            return Err(HpkeError::MessageLimitReached);
+        }
+        util::xor_nonce(&self.nonce, seq).ok_or(HpkeError::MessageLimitReached)
     }
 
     /// def Context<ROLE>.IncrementSeq():
     ///     if self.seq >= (1 << (8*Nn)) - 1:
     ///       raise MessageLimitReached
     ///     self.seq += 1
-    fn increment_seq(&mut self) -> Result<(), HpkeError> {
-        if u128::from(self.sequence_number)
-            >= ((1u128 << (8 * Crypto::aead_nonce_length(self.hpke.aead_id))) - 1)
-        {
+    ///
+    /// The sequence number is additionally limited to `u32::MAX`.
+    fn increment_seq_with(&mut self, crypto: &impl HpkeCryptoInstance) -> Result<(), HpkeError> {
+        let limit = crypto
+            .aead_nonce_length(self.suite.aead)
+            .checked_mul(8)
+            .and_then(|bits| u32::try_from(bits).ok())
+            .and_then(|bits| 1u128.checked_shl(bits))
+            .map_or(u128::MAX, |max| max.saturating_sub(1));
+        if u128::from(self.sequence_number) >= limit {
             return Err(HpkeError::MessageLimitReached);
         }
-        self.sequence_number += 1;
+        self.sequence_number = self
+            .sequence_number
+            .checked_add(1)
+            .ok_or(HpkeError::MessageLimitReached)?;
         Ok(())
     }
 }
 
-/// The HPKE configuration struct.
-/// This holds the configuration for HPKE but no state.
-/// To use HPKE first instantiate the configuration with
-/// `let hpke = Hpke::new(mode, kem_mode, kdf_mode, aead_mode)`.
-/// Now one can use the `hpke` configuration.
+/// The (key, base nonce, exporter secret) of a key schedule.
+type KeyScheduleSecrets = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+/// The default minimum PSK length in bytes.
 ///
-/// Note that cloning does NOT clone the PRNG state.
-#[derive(Debug)]
-pub struct Hpke<Crypto: 'static + HpkeCrypto> {
-    mode: Mode,
-    kem_id: KemAlgorithm,
-    kdf_id: KdfAlgorithm,
-    aead_id: AeadAlgorithm,
-    prng: Crypto::HpkePrng,
-}
+/// RFC 9180 requires the PSK to have at least 32 bytes of entropy.
+pub const DEFAULT_MIN_PSK_LEN: usize = 32;
 
-impl<Crypto: 'static + HpkeCrypto> Clone for Hpke<Crypto> {
-    fn clone(&self) -> Self {
-        Self {
-            mode: self.mode,
-            kem_id: self.kem_id,
-            kdf_id: self.kdf_id,
-            aead_id: self.aead_id,
-            prng: Crypto::prng(),
+/// An HPKE ciphersuite, i.e. the mode and the KEM, KDF, and AEAD algorithms.
+///
+/// Unlike [`Hpke`] this doesn't carry a PRNG and can be constructed in a
+/// `const` context, e.g. to build static configuration tables.
+///
+/// ```
+/// use hpke_rs::{hpke_types::*, HpkeSuite, Mode};
+///
+/// const SUITE: HpkeSuite = HpkeSuite::new(
+///     Mode::Base,
+///     KemAlgorithm::DhKem25519,
+///     KdfAlgorithm::HkdfSha256,
+///     AeadAlgorithm::ChaCha20Poly1305,
+/// );
+/// ```
+///
+/// Use [`HpkeSuite::into_hpke`] to get an operational [`Hpke`] instance.
+/// Receiver operations don't need randomness and can be used directly on the
+/// suite, e.g. [`HpkeSuite::open`], without creating a PRNG.
+///
+/// The suite also carries the [minimum PSK length](HpkeSuite::min_psk_len)
+/// of the [`Hpke`] configuration it was created from.
+/// It is a local policy and not part of the ciphersuite, i.e. it is neither
+/// encoded nor compared.
+#[derive(Copy, Clone, Debug)]
+pub struct HpkeSuite {
+    /// The HPKE mode.
+    pub mode: Mode,
+
+    /// The KEM algorithm.
+    pub kem: KemAlgorithm,
+
+    /// The KDF algorithm.
+    pub kdf: KdfAlgorithm,
+
+    /// The AEAD algorithm.
+    pub aead: AeadAlgorithm,
+
+    min_psk_len: usize,
+}
+
+impl PartialEq for HpkeSuite {
+    fn eq(&self, other: &Self) -> bool {
+        self.mode == other.mode
+            && self.kem == other.kem
+            && self.kdf == other.kdf
+            && self.aead == other.aead
+    }
+}
+
+impl HpkeSuite {
+    /// Create a new ciphersuite.
+    pub const fn new(
+        mode: Mode,
+        kem: KemAlgorithm,
+        kdf: KdfAlgorithm,
+        aead: AeadAlgorithm,
+    ) -> Self {
+        Self {
+            mode,
+            kem,
+            kdf,
+            aead,
+            min_psk_len: DEFAULT_MIN_PSK_LEN,
+        }
+    }
+
+    /// Get the minimum PSK length in bytes.
+    ///
+    /// See [`Hpke::min_psk_len`].
+    pub fn min_psk_len(&self) -> usize {
+        self.min_psk_len
+    }
+
+    /// Set the minimum PSK length in bytes, e.g. on a suite that was decoded
+    /// or created with [`HpkeSuite::new`].
+    ///
+    /// See [`Hpke::set_min_psk_len`].
+    pub fn set_min_psk_len(&mut self, min_psk_len: usize) -> Result<(), HpkeError> {
+        if min_psk_len < DEFAULT_MIN_PSK_LEN {
+            return Err(HpkeError::InsecurePsk);
         }
+        self.min_psk_len = min_psk_len;
+        Ok(())
+    }
+
+    /// Set the minimum PSK length in bytes, including lengths below
+    /// [`DEFAULT_MIN_PSK_LEN`].
+    ///
+    /// See `Hpke::set_min_psk_len_unchecked`.
+    #[cfg(feature = "hazmat")]
+    pub fn set_min_psk_len_unchecked(&mut self, min_psk_len: usize) {
+        self.min_psk_len = min_psk_len;
+    }
+
+    /// The length of the compact encoding of a ciphersuite in bytes.
+    pub const ENCODED_LEN: usize = 7;
+
+    /// Encode this ciphersuite compactly as the mode followed by the KEM, KDF,
+    /// and AEAD identifiers as big-endian `u16`s.
+    ///
+    /// ```text
+    /// mode (1) || kem_id (2) || kdf_id (2) || aead_id (2)
+    /// ```
+    pub const fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let kem = self.kem.id().to_be_bytes();
+        let kdf = self.kdf.id().to_be_bytes();
+        let aead = self.aead.id().to_be_bytes();
+        [
+            self.mode as u8,
+            kem[0],
+            kem[1],
+            kdf[0],
+            kdf[1],
+            aead[0],
+            aead[1],
+        ]
+    }
+
+    /// Decode a ciphersuite encoded with [`HpkeSuite::to_bytes`].
+    ///
+    /// Returns an [`HpkeError::UnknownMode`] for an unknown mode and an
+    /// [`HpkeError::UnsupportedAlgorithm`] for unknown algorithm identifiers.
+    pub fn try_from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Self, HpkeError> {
+        let [mode, kem_0, kem_1, kdf_0, kdf_1, aead_0, aead_1] = *bytes;
+        let (kem, kdf, aead) = (
+            u16::from_be_bytes([kem_0, kem_1]),
+            u16::from_be_bytes([kdf_0, kdf_1]),
+            u16::from_be_bytes([aead_0, aead_1]),
+        );
+        let unknown = |kind, id| HpkeError::UnsupportedAlgorithm { kind, id };
+        Ok(Self::new(
+            Mode::try_from(mode)?,
+            KemAlgorithm::try_from(kem).map_err(|_| unknown(AlgorithmKind::Kem, kem))?,
+            KdfAlgorithm::try_from(kdf).map_err(|_| unknown(AlgorithmKind::Kdf, kdf))?,
+            AeadAlgorithm::try_from(aead).map_err(|_| unknown(AlgorithmKind::Aead, aead))?,
+        ))
+    }
+
+    /// Create an [`Hpke`] instance for this ciphersuite and its minimum PSK
+    /// length.
+    /// This creates a new PRNG for the `Crypto` provider.
+    pub fn into_hpke<Crypto: HpkeCrypto>(self) -> Hpke<Crypto> {
+        let mut hpke = Hpke::new(self.mode, self.kem, self.kdf, self.aead);
+        hpke.min_psk_len = self.min_psk_len;
+        hpke
+    }
+
+    /// Set up an HPKE receiver.
+    ///
+    /// For the base and PSK modes this decapsulates `enc` with the secret key
+    /// `sk_r` of the receiver.
+    /// For the Auth and AuthPSK modes this decapsulates and authenticates `enc`
+    /// with the secret key `sk_r` of the receiver and the senders public key `pk_s`.
+    ///
+    /// **Note** that this API expects the public key to be encoded.
+    /// This differs from the RFC.
+    /// But the public keys will be present in encoded form rather than raw form
+    /// such that it doesn't make sense to deserialize before passing it in.
+    ///
+    /// The receiver context based on the decapsulated values and, if present,
+    /// the PSK is returned.
+    /// If the secret key is missing in an authenticated mode, an error is returned.
+    /// If `enc` is not an encapsulation for this KEM, an error is returned.
+    /// PSKs must be at least [`HpkeSuite::min_psk_len`] bytes long.
+    pub fn setup_receiver<Crypto: 'static + HpkeCrypto>(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        let zz = self.decapsulate::<Crypto>(enc, sk_r, pk_s)?;
+        self.key_schedule::<Crypto>(
+            &zz,
+            info,
+            psk.unwrap_or_default(),
+            psk_id.unwrap_or_default(),
+        )
+        .map(ReceiverContext::new)
+    }
+
+    /// Decapsulate the shared secret from `enc` for the receiver setup.
+    fn decapsulate<Crypto: 'static + HpkeCrypto>(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.check_support::<Crypto>()?;
+        if enc.kem != self.kem {
+            return Err(HpkeError::InvalidInput);
+        }
+        sk_r.check_kem(self.kem)?;
+        sk_r.audit(self.kem, audit::KeyOperation::Decapsulation);
+        let enc = enc.as_slice();
+        let zz = match self.mode {
+            Mode::Base | Mode::Psk => kem::decaps::<Crypto>(self.kem, enc, sk_r.key_ref())?,
+            Mode::Auth | Mode::AuthPsk => {
+                let pk_s = match pk_s {
+                    Some(s) => {
+                        s.check_kem(self.kem)?;
+                        s.uncompressed::<Crypto>(self.kem)?
+                    }
+                    None => return Err(HpkeError::InvalidInput),
+                };
+                kem::auth_decaps::<Crypto>(self.kem, enc, sk_r.key_ref(), &pk_s)?
+            }
+        };
+        Ok(zz)
+    }
+
+    /// 6. Single-Shot APIs
+    /// 6.1. Encryption and Decryption
+    ///
+    /// Single shot API to decrypt the bytes in `ct` with the private key `sk_r`.
+    ///
+    /// **Note** that this API expects the public key to be encoded.
+    /// This differs from the RFC.
+    /// But the public keys will be present in encoded form rather than raw form
+    /// such that it doesn't make sense to deserialize before passing it in.
+    ///
+    /// Returns the decrypted plain text, or an error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open<Crypto: 'static + HpkeCrypto>(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Plaintext, HpkeError> {
+        self.check_aead()?;
+        let mut context = self.setup_receiver::<Crypto>(enc, sk_r, info, psk, psk_id, pk_s)?;
+        context.open(aad, ct)
+    }
+
+    /// 6. Single-Shot APIs
+    /// 6.2. Secret Export
+    ///
+    /// Single shot API to derive an exporter secret for receiver with private key
+    /// `sk_r`.
+    ///
+    /// **Note** that this API expects the public key to be encoded.
+    /// This differs from the RFC.
+    /// But the public keys will be present in encoded form rather than raw form
+    /// such that it doesn't make sense to deserialize before passing it in.
+    ///
+    /// Returns the exporter secret for the given exporter context and length.
+    #[allow(clippy::too_many_arguments)]
+    pub fn receiver_export<Crypto: 'static + HpkeCrypto>(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+        exporter_context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>, HpkeError> {
+        let context = self.setup_receiver::<Crypto>(enc, sk_r, info, psk, psk_id, pk_s)?;
+        context.export(exporter_context, length)
+    }
+
+    /// Check that the `Crypto` provider supports the KEM of this suite.
+    #[inline]
+    fn check_kem_support<Crypto: HpkeCrypto>(&self) -> Result<(), HpkeError> {
+        self.check_kem_support_with(&PhantomData::<Crypto>)
+    }
+
+    /// Check that the provider instance `crypto` supports the KEM of this
+    /// suite.
+    #[inline]
+    fn check_kem_support_with(&self, crypto: &impl HpkeCryptoInstance) -> Result<(), HpkeError> {
+        crypto
+            .supports_kem(self.kem)
+            .map_err(|_| HpkeError::UnsupportedAlgorithm {
+                kind: AlgorithmKind::Kem,
+                id: self.kem.id(),
+            })
+    }
+
+    /// Returns an [`HpkeError::ExportOnlyMode`] if this suite uses the
+    /// export-only AEAD.
+    #[inline]
+    fn check_aead(&self) -> Result<(), HpkeError> {
+        if self.aead == AeadAlgorithm::HpkeExport {
+            return Err(HpkeError::ExportOnlyMode);
+        }
+        Ok(())
+    }
+
+    /// Check that the `Crypto` provider supports all algorithms of this suite.
+    #[inline]
+    fn check_support<Crypto: HpkeCrypto>(&self) -> Result<(), HpkeError> {
+        self.check_support_with(&PhantomData::<Crypto>)
+    }
+
+    /// Check that the provider instance `crypto` supports all algorithms of
+    /// this suite.
+    #[inline]
+    fn check_support_with(&self, crypto: &impl HpkeCryptoInstance) -> Result<(), HpkeError> {
+        self.check_kem_support_with(crypto)?;
+        crypto
+            .supports_kdf(self.kdf)
+            .map_err(|_| HpkeError::UnsupportedAlgorithm {
+                kind: AlgorithmKind::Kdf,
+                id: self.kdf.id(),
+            })?;
+        crypto
+            .supports_aead(self.aead)
+            .map_err(|_| HpkeError::UnsupportedAlgorithm {
+                kind: AlgorithmKind::Aead,
+                id: self.aead.id(),
+            })
+    }
+
+    /// Verify PSKs.
+    ///
+    /// The checks don't return early on conditions derived from the PSK.
+    /// The PSK and PSK id lengths are evaluated in constant time and the
+    /// error is only selected after all checks were performed.
+    #[inline(always)]
+    fn verify_psk_inputs(&self, psk: &[u8], psk_id: &[u8]) -> Result<(), HpkeError> {
+        let got_psk = util::ct_is_nonzero(psk.len());
+        let got_psk_id = util::ct_is_nonzero(psk_id.len());
+        let psk_mode = (self.mode == Mode::Psk || self.mode == Mode::AuthPsk) as u8;
+
+        let inconsistent = got_psk ^ got_psk_id;
+        let unnecessary = got_psk & (psk_mode ^ 1);
+        let missing = (got_psk ^ 1) & psk_mode;
+        // The PSK MUST have at least 32 bytes of entropy and SHOULD be of length Nh bytes or longer.
+        let insecure = psk_mode & util::ct_lt(psk.len(), self.min_psk_len);
+
+        let error = (inconsistent << 3) | (unnecessary << 2) | (missing << 1) | insecure;
+        match error {
+            0 => Ok(()),
+            e if e & 0b1000 != 0 => Err(HpkeError::InconsistentPsk),
+            e if e & 0b0100 != 0 => Err(HpkeError::UnnecessaryPsk),
+            e if e & 0b0010 != 0 => Err(HpkeError::MissingPsk),
+            _ => Err(HpkeError::InsecurePsk),
+        }
+    }
+
+    /// Verify the PSK inputs for this suite.
+    ///
+    /// This performs the same checks as the setup functions.
+    ///
+    /// Security critical checks, required by RFC 9180:
+    /// - The PSK and PSK id must either both be set or both be empty
+    ///   ([`HpkeError::InconsistentPsk`]).
+    /// - A PSK must be given in the PSK modes ([`HpkeError::MissingPsk`]).
+    /// - A PSK must not be given in the non-PSK modes
+    ///   ([`HpkeError::UnnecessaryPsk`]).
+    ///
+    /// Policy checks:
+    /// - The PSK must be at least [`HpkeSuite::min_psk_len`] bytes long
+    ///   ([`HpkeError::InsecurePsk`]), i.e. the length of the [`Hpke`]
+    ///   configuration the suite was created from.
+    ///   The RFC requires 32 bytes of entropy, which can't be checked.
+    ///   The length is only a proxy for it.
+    #[cfg(feature = "hazmat")]
+    pub fn check_psk_inputs(&self, psk: &[u8], psk_id: &[u8]) -> Result<(), HpkeError> {
+        self.verify_psk_inputs(psk, psk_id)
+    }
+
+    /// Get the `suite_id` of this ciphersuite.
+    ///
+    /// ```text
+    /// suite_id = concat("HPKE", I2OSP(kem_id, 2), I2OSP(kdf_id, 2), I2OSP(aead_id, 2))
+    /// ```
+    pub fn suite_id(&self) -> Vec<u8> {
+        util::concat(&[
+            b"HPKE",
+            &self.kem.id().to_be_bytes(),
+            &self.kdf.id().to_be_bytes(),
+            &self.aead.id().to_be_bytes(),
+        ])
+    }
+
+    #[inline]
+    fn key_schedule_context(
+        &self,
+        crypto: &impl HpkeCryptoInstance,
+        info: &[u8],
+        psk_id: &[u8],
+        suite_id: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        let psk_id_hash =
+            labeled_extract_with(crypto, self.kdf, &[0], suite_id, "psk_id_hash", psk_id)?;
+        let info_hash = labeled_extract_with(crypto, self.kdf, &[0], suite_id, "info_hash", info)?;
+        Ok(util::concat(&[
+            &[self.mode as u8],
+            &psk_id_hash,
+            &info_hash,
+        ]))
+    }
+
+    /// Creating the Encryption Context
+    /// Generate the HPKE context from the given input.
+    ///
+    /// PSKs must be at least [`HpkeSuite::min_psk_len`] bytes long.
+    pub fn key_schedule<Crypto: 'static + HpkeCrypto>(
+        &self,
+        shared_secret: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+    ) -> Result<Context<Crypto>, HpkeError> {
+        self.key_schedule_with(&PhantomData::<Crypto>, shared_secret, info, psk, psk_id)
+    }
+
+    /// Like [`HpkeSuite::key_schedule`] with the provider instance `crypto`.
+    pub(crate) fn key_schedule_with<C>(
+        &self,
+        crypto: &impl HpkeCryptoInstance,
+        shared_secret: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+    ) -> Result<Context<C>, HpkeError> {
+        let (key, base_nonce, exporter_secret) =
+            self.key_schedule_secrets(crypto, shared_secret, info, psk, psk_id)?;
+        Ok(Context {
+            key,
+            nonce: base_nonce,
+            exporter_secret,
+            sequence_number: 0,
+            message_limit: self.aead.max_messages(),
+            suite: *self,
+            transcript: None,
+            padding: None,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Run the key schedule with the provider instance `crypto`.
+    fn key_schedule_secrets(
+        &self,
+        crypto: &impl HpkeCryptoInstance,
+        shared_secret: &[u8],
+        info: &[u8],
+        psk: &[u8],
+        psk_id: &[u8],
+    ) -> Result<KeyScheduleSecrets, HpkeError> {
+        self.verify_psk_inputs(psk, psk_id)?;
+        let suite_id = self.suite_id();
+        let key_schedule_context = self.key_schedule_context(crypto, info, psk_id, &suite_id)?;
+        let secret =
+            labeled_extract_with(crypto, self.kdf, shared_secret, &suite_id, "secret", psk)
+                .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
+
+        // The export-only AEAD has neither key nor nonce.
+        let (key, base_nonce) = if self.aead == AeadAlgorithm::HpkeExport {
+            (Vec::new(), Vec::new())
+        } else {
+            let key = labeled_expand_with(
+                crypto,
+                self.kdf,
+                &secret,
+                &suite_id,
+                "key",
+                &key_schedule_context,
+                crypto.aead_key_length(self.aead),
+            )
+            .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
+            let base_nonce = labeled_expand_with(
+                crypto,
+                self.kdf,
+                &secret,
+                &suite_id,
+                "base_nonce",
+                &key_schedule_context,
+                crypto.aead_nonce_length(self.aead),
+            )
+            .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
+            (key, base_nonce)
+        };
+        let exporter_secret = labeled_expand_with(
+            crypto,
+            self.kdf,
+            &secret,
+            &suite_id,
+            "exp",
+            &key_schedule_context,
+            crypto.kdf_digest_length(self.kdf),
+        )
+        .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
+        Ok((key, base_nonce, exporter_secret))
     }
 }
 
-impl<Crypto: HpkeCrypto> core::fmt::Display for Hpke<Crypto> {
+impl core::fmt::Display for HpkeSuite {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(
             f,
             "{}_{}_{}_{}",
             self.mode.to_string().to_lowercase(),
-            self.kem_id.to_string().to_lowercase(),
-            self.kdf_id.to_string().to_lowercase(),
-            self.aead_id.to_string().to_lowercase()
+            self.kem.to_string().to_lowercase(),
+            self.kdf.to_string().to_lowercase(),
+            self.aead.to_string().to_lowercase()
         )
     }
 }
 
+impl core::str::FromStr for HpkeSuite {
+    type Err = HpkeError;
+
+    /// Parse a ciphersuite from its [`Display`](core::fmt::Display) string,
+    /// e.g. `base_dhkem25519_hkdfsha256_chacha20poly1305`.
+    ///
+    /// The names are matched case-insensitively.
+    /// Returns an [`HpkeError::UnknownMode`] for an unknown mode and an
+    /// [`HpkeError::InvalidInput`] for any other malformed string.
+    fn from_str(s: &str) -> Result<Self, HpkeError> {
+        let mut parts = s.split('_');
+        let mut next = || parts.next().ok_or(HpkeError::InvalidInput);
+        let (mode, kem, kdf, aead) = (next()?, next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(HpkeError::InvalidInput);
+        }
+        Ok(Self::new(
+            [Mode::Base, Mode::Psk, Mode::Auth, Mode::AuthPsk]
+                .into_iter()
+                .find(|m| m.to_string().eq_ignore_ascii_case(mode))
+                .ok_or(HpkeError::UnknownMode)?,
+            kem.parse().map_err(|_| HpkeError::InvalidInput)?,
+            kdf.parse().map_err(|_| HpkeError::InvalidInput)?,
+            aead.parse().map_err(|_| HpkeError::InvalidInput)?,
+        ))
+    }
+}
+
+impl TryFrom<&str> for HpkeSuite {
+    type Error = HpkeError;
+
+    fn try_from(s: &str) -> Result<Self, HpkeError> {
+        s.parse()
+    }
+}
+
+impl<Crypto: HpkeCrypto> From<HpkeSuite> for Hpke<Crypto> {
+    fn from(suite: HpkeSuite) -> Self {
+        suite.into_hpke()
+    }
+}
+
+impl<Crypto: HpkeCrypto> core::str::FromStr for Hpke<Crypto> {
+    type Err = HpkeError;
+
+    /// Parse the configuration from its ciphersuite string.
+    ///
+    /// See [`HpkeSuite::from_str`](HpkeSuite#method.from_str).
+    fn from_str(s: &str) -> Result<Self, HpkeError> {
+        s.parse::<HpkeSuite>().map(HpkeSuite::into_hpke)
+    }
+}
+
+impl<Crypto: HpkeCrypto> TryFrom<&str> for Hpke<Crypto> {
+    type Error = HpkeError;
+
+    fn try_from(s: &str) -> Result<Self, HpkeError> {
+        s.parse()
+    }
+}
+
+/// The HPKE configuration struct.
+/// This holds the configuration for HPKE but no state.
+/// To use HPKE first instantiate the configuration with
+/// `let hpke = Hpke::new(mode, kem_mode, kdf_mode, aead_mode)`.
+/// Now one can use the `hpke` configuration.
+///
+/// Note that cloning does NOT clone the PRNG state.
+#[derive(Debug)]
+pub struct Hpke<Crypto: 'static + HpkeCrypto> {
+    mode: Mode,
+    kem_id: KemAlgorithm,
+    kdf_id: KdfAlgorithm,
+    aead_id: AeadAlgorithm,
+    min_psk_len: usize,
+    padding: Option<padding::PaddingPolicy>,
+    prng: Crypto::HpkePrng,
+}
+
+impl<Crypto: 'static + HpkeCrypto> Clone for Hpke<Crypto> {
+    fn clone(&self) -> Self {
+        Self {
+            mode: self.mode,
+            kem_id: self.kem_id,
+            kdf_id: self.kdf_id,
+            aead_id: self.aead_id,
+            min_psk_len: self.min_psk_len,
+            padding: self.padding.clone(),
+            prng: Crypto::prng(),
+        }
+    }
+}
+
+impl<Crypto: HpkeCrypto> core::fmt::Display for Hpke<Crypto> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.suite())
+    }
+}
+
 impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     /// Set up the configuration for HPKE.
     pub fn new(
@@ -393,10 +1683,128 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
             kem_id,
             kdf_id,
             aead_id,
+            min_psk_len: DEFAULT_MIN_PSK_LEN,
+            padding: None,
             prng: Crypto::prng(),
         }
     }
 
+    /// Set up the configuration for HPKE if the `Crypto` provider supports
+    /// the ciphersuite.
+    ///
+    /// Unlike [`Hpke::new`], an unsupported algorithm is reported here with an
+    /// [`HpkeError::UnsupportedAlgorithm`] rather than on first use.
+    ///
+    /// **Note** that this was an [`HpkeError::InvalidConfig`] before
+    /// [`HpkeError::UnsupportedAlgorithm`] was added.
+    /// It is the error of all other operations for unsupported algorithms,
+    /// and carries the kind and identifier of the algorithm, while an
+    /// [`HpkeError::InvalidConfig`] is reserved for inconsistent
+    /// configurations.
+    pub fn try_new(
+        mode: Mode,
+        kem_id: KemAlgorithm,
+        kdf_id: KdfAlgorithm,
+        aead_id: AeadAlgorithm,
+    ) -> Result<Self, HpkeError> {
+        let suite = HpkeSuite::new(mode, kem_id, kdf_id, aead_id);
+        suite.check_support::<Crypto>()?;
+        Ok(suite.into_hpke())
+    }
+
+    /// Get the minimum PSK length in bytes.
+    pub fn min_psk_len(&self) -> usize {
+        self.min_psk_len
+    }
+
+    /// Set the minimum PSK length in bytes.
+    ///
+    /// The default is [`DEFAULT_MIN_PSK_LEN`].
+    /// Lengths below the default are rejected with an
+    /// [`HpkeError::InsecurePsk`].
+    /// Use `set_min_psk_len_unchecked` with the `hazmat` feature to lower it.
+    pub fn set_min_psk_len(&mut self, min_psk_len: usize) -> Result<(), HpkeError> {
+        if min_psk_len < DEFAULT_MIN_PSK_LEN {
+            return Err(HpkeError::InsecurePsk);
+        }
+        self.min_psk_len = min_psk_len;
+        Ok(())
+    }
+
+    /// Set the minimum PSK length in bytes, including lengths below
+    /// [`DEFAULT_MIN_PSK_LEN`].
+    ///
+    /// **Note** that RFC 9180 requires PSKs with at least 32 bytes of entropy.
+    /// Only use this if the PSK is known to have sufficient entropy, e.g.
+    /// because it is derived with a KDF with a shorter output.
+    #[cfg(feature = "hazmat")]
+    pub fn set_min_psk_len_unchecked(&mut self, min_psk_len: usize) {
+        self.min_psk_len = min_psk_len;
+    }
+
+    /// Replace the PRNG of this HPKE configuration, e.g. with a provider PRNG
+    /// seeded from an external RNG.
+    ///
+    /// With the `rand_core_06` feature, the adapters in the `rng` module
+    /// allow using RNGs implementing the `rand_core` 0.6 traits.
+    pub fn set_prng(&mut self, prng: Crypto::HpkePrng) {
+        self.prng = prng;
+    }
+
+    /// Get the mode of this HPKE configuration.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Get the KEM algorithm of this HPKE configuration.
+    pub fn kem(&self) -> KemAlgorithm {
+        self.kem_id
+    }
+
+    /// Get the KDF algorithm of this HPKE configuration.
+    pub fn kdf(&self) -> KdfAlgorithm {
+        self.kdf_id
+    }
+
+    /// Get the AEAD algorithm of this HPKE configuration.
+    pub fn aead(&self) -> AeadAlgorithm {
+        self.aead_id
+    }
+
+    /// Get the [`HpkeSuite`] of this HPKE configuration, with its minimum
+    /// PSK length.
+    pub fn suite(&self) -> HpkeSuite {
+        HpkeSuite {
+            min_psk_len: self.min_psk_len,
+            ..HpkeSuite::new(self.mode, self.kem_id, self.kdf_id, self.aead_id)
+        }
+    }
+
+    /// Get the `suite_id` of this HPKE configuration.
+    ///
+    /// See [`HpkeSuite::suite_id`].
+    pub fn suite_id(&self) -> Vec<u8> {
+        self.suite().suite_id()
+    }
+
+    /// Encode the ciphersuite of this HPKE configuration compactly.
+    ///
+    /// See [`HpkeSuite::to_bytes`] for the encoding.
+    pub fn suite_bytes(&self) -> [u8; HpkeSuite::ENCODED_LEN] {
+        self.suite().to_bytes()
+    }
+
+    /// Set up the configuration for HPKE from a ciphersuite encoded with
+    /// [`Hpke::suite_bytes`].
+    ///
+    /// Returns an error if the encoding is invalid or the `Crypto` provider
+    /// doesn't support the ciphersuite.
+    pub fn try_from_suite_bytes(bytes: &[u8; HpkeSuite::ENCODED_LEN]) -> Result<Self, HpkeError> {
+        let suite = HpkeSuite::try_from_bytes(bytes)?;
+        suite.check_support::<Crypto>()?;
+        Ok(suite.into_hpke())
+    }
+
     /// Set up an HPKE sender.
     ///
     /// For the base and PSK modes this encapsulates the public key `pk_r`
@@ -409,8 +1817,13 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     /// But the public keys will be present in encoded form rather than raw form
     /// such that it doesn't make sense to deserialize before passing it in.
     ///
-    /// The encapsulated secret is returned together with the context.
+    /// The encapsulated secret is returned together with the sender context.
     /// If the secret key is missing in an authenticated mode, an error is returned.
+    /// If the crypto provider doesn't support the ciphersuite, an
+    /// [`HpkeError::UnsupportedAlgorithm`] is returned.
+    /// If `pk_r` doesn't have the public key length of the KEM, an
+    /// [`HpkeError::InvalidKeyLength`] is returned.
+    /// Compressed SEC1 keys are decompressed with the crypto provider.
     pub fn setup_sender(
         &mut self,
         pk_r: &HpkePublicKey,
@@ -418,25 +1831,98 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
         psk: Option<&[u8]>,
         psk_id: Option<&[u8]>,
         sk_s: Option<&HpkePrivateKey>,
-    ) -> Result<(EncapsulatedSecret, Context<Crypto>), HpkeError> {
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
+        let pk_r = self.receiver_key(pk_r)?;
         let (zz, enc) = match self.mode {
-            Mode::Base | Mode::Psk => kem::encaps::<Crypto>(self, pk_r.value.as_slice())?,
+            Mode::Base | Mode::Psk => kem::encaps::<Crypto>(self, &pk_r)?,
             Mode::Auth | Mode::AuthPsk => {
-                let sk_s = match sk_s {
-                    Some(s) => &s.value,
-                    None => return Err(HpkeError::InvalidInput),
-                };
-                kem::auth_encaps::<Crypto>(self, pk_r.value.as_slice(), sk_s)?
+                let sk_s = self.sender_key(sk_s)?;
+                kem::auth_encaps::<Crypto>(self, &pk_r, sk_s)?
             }
         };
+        self.sender_context(zz, enc, info, psk, psk_id)
+    }
+
+    /// Set up an HPKE sender with randomness from the caller provided `rng`.
+    ///
+    /// This is [`Hpke::setup_sender`] without using the PRNG of this
+    /// configuration, such that a single configuration can be shared, e.g.
+    /// across threads.
+    /// The KEM encapsulation draws all its randomness from `rng`.
+    /// X-Wing, ML-KEM, and X25519Kyber768Draft00 require a provider that
+    /// implements [`HpkeCrypto::kem_encaps_derand`].
+    pub fn setup_sender_with_rng(
+        &self,
+        rng: &mut impl CryptoRng,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
+        let pk_r = self.receiver_key(pk_r)?;
+        let (zz, enc) = match self.mode {
+            Mode::Base | Mode::Psk => kem::encaps_with_rng::<Crypto>(self.kem_id, &pk_r, rng)?,
+            Mode::Auth | Mode::AuthPsk => {
+                let sk_s = self.sender_key(sk_s)?;
+                kem::auth_encaps_with_rng::<Crypto>(self.kem_id, &pk_r, sk_s, rng)?
+            }
+        };
+        self.sender_context(zz, enc, info, psk, psk_id)
+    }
+
+    /// Check that the provider supports the ciphersuite and that `pk_r` is a
+    /// key for the KEM with its public key length.
+    ///
+    /// Returns the bytes of `pk_r`, decompressed if it is compressed.
+    fn receiver_key<'a>(&self, pk_r: &'a HpkePublicKey) -> Result<Cow<'a, [u8]>, HpkeError> {
+        self.suite().check_support::<Crypto>()?;
+        pk_r.check_kem(self.kem_id)?;
+        let pk_r = pk_r.uncompressed::<Crypto>(self.kem_id)?;
+        let expected = self.kem_id.public_key_len();
+        if pk_r.len() != expected {
+            return Err(HpkeError::InvalidKeyLength {
+                expected,
+                got: pk_r.len(),
+            });
+        }
+        Ok(pk_r)
+    }
+
+    /// Get the sender key for an authenticated encapsulation.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if it is missing.
+    fn sender_key<'a>(
+        &self,
+        sk_s: Option<&'a HpkePrivateKey>,
+    ) -> Result<PrivateKeyRef<'a>, HpkeError> {
+        let sk_s = sk_s.ok_or(HpkeError::InvalidInput)?;
+        sk_s.check_kem(self.kem_id)?;
+        sk_s.audit(self.kem_id, audit::KeyOperation::AuthEncapsulation);
+        Ok(sk_s.key_ref())
+    }
+
+    /// Run the key schedule of the sender for the shared secret `zz` and the
+    /// encapsulated secret `enc`.
+    fn sender_context(
+        &self,
+        zz: Vec<u8>,
+        enc: Vec<u8>,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<(EncapsulatedSecret, SenderContext<Crypto>), HpkeError> {
         Ok((
-            enc,
-            self.clone().key_schedule(
+            EncapsulatedSecret {
+                kem: self.kem_id,
+                value: enc,
+            },
+            SenderContext::new(self.key_schedule(
                 &zz,
                 info,
                 psk.unwrap_or_default(),
                 psk_id.unwrap_or_default(),
-            )?,
+            )?),
         ))
     }
 
@@ -452,34 +1938,27 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     /// But the public keys will be present in encoded form rather than raw form
     /// such that it doesn't make sense to deserialize before passing it in.
     ///
-    /// The context based on the decapsulated values and, if present, the PSK is
-    /// returned.
+    /// The receiver context based on the decapsulated values and, if present,
+    /// the PSK is returned.
     /// If the secret key is missing in an authenticated mode, an error is returned.
+    /// If `enc` is not an encapsulation for this KEM, an error is returned.
     pub fn setup_receiver(
         &self,
-        enc: &[u8],
+        enc: &EncapsulatedSecret,
         sk_r: &HpkePrivateKey,
         info: &[u8],
         psk: Option<&[u8]>,
         psk_id: Option<&[u8]>,
         pk_s: Option<&HpkePublicKey>,
-    ) -> Result<Context<Crypto>, HpkeError> {
-        let zz = match self.mode {
-            Mode::Base | Mode::Psk => kem::decaps::<Crypto>(self.kem_id, enc, &sk_r.value)?,
-            Mode::Auth | Mode::AuthPsk => {
-                let pk_s = match pk_s {
-                    Some(s) => s.value.as_slice(),
-                    None => return Err(HpkeError::InvalidInput),
-                };
-                kem::auth_decaps::<Crypto>(self.kem_id, enc, &sk_r.value, pk_s)?
-            }
-        };
-        self.clone().key_schedule(
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        let zz = self.suite().decapsulate::<Crypto>(enc, sk_r, pk_s)?;
+        self.key_schedule(
             &zz,
             info,
             psk.unwrap_or_default(),
             psk_id.unwrap_or_default(),
         )
+        .map(ReceiverContext::new)
     }
 
     /// 6. Single-Shot APIs
@@ -505,11 +1984,34 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
         psk_id: Option<&[u8]>,
         sk_s: Option<&HpkePrivateKey>,
     ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        self.suite().check_aead()?;
         let (enc, mut context) = self.setup_sender(pk_r, info, psk, psk_id, sk_s)?;
         let ctxt = context.seal(aad, plain_txt)?;
         Ok((enc, ctxt))
     }
 
+    /// Single shot API to encrypt the bytes in `plain_txt` to the public key
+    /// `pk_r` with randomness from the caller provided `rng`.
+    ///
+    /// See [`Hpke::setup_sender_with_rng`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal_with_rng(
+        &self,
+        rng: &mut impl CryptoRng,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        self.suite().check_aead()?;
+        let (enc, mut context) = self.setup_sender_with_rng(rng, pk_r, info, psk, psk_id, sk_s)?;
+        let ctxt = context.seal(aad, plain_txt)?;
+        Ok((enc, ctxt))
+    }
+
     /// 6. Single-Shot APIs
     /// 6.1. Encryption and Decryption
     ///
@@ -524,19 +2026,76 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     #[allow(clippy::too_many_arguments)]
     pub fn open(
         &self,
-        enc: &[u8],
+        enc: &EncapsulatedSecret,
         sk_r: &HpkePrivateKey,
         info: &[u8],
         aad: &[u8],
-        ct: &[u8],
+        ct: &Ciphertext,
         psk: Option<&[u8]>,
         psk_id: Option<&[u8]>,
         pk_s: Option<&HpkePublicKey>,
     ) -> Result<Plaintext, HpkeError> {
+        self.suite().check_aead()?;
         let mut context = self.setup_receiver(enc, sk_r, info, psk, psk_id, pk_s)?;
         context.open(aad, ct)
     }
 
+    /// Decrypt a batch of ciphertexts that were sealed in order on the sender
+    /// context for `enc`.
+    ///
+    /// The receiver context is set up once and the messages are opened with
+    /// the sequence numbers `0..messages.len()`.
+    /// See [`Context::open_batch`] for details.
+    ///
+    /// Returns the decrypted plain texts, or an error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_batch(
+        &self,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        messages: &[(&[u8], &Ciphertext)],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<Plaintext>, HpkeError> {
+        self.suite().check_aead()?;
+        let mut context = self.setup_receiver(enc, sk_r, info, psk, psk_id, pk_s)?;
+        context.open_batch(messages)
+    }
+
+    /// Single shot API to decrypt the bytes in `ct` with any of the private
+    /// keys in `sk_rs`, e.g. the current and previous keys of a key rotation.
+    ///
+    /// The keys are tried in order.
+    /// Returns the index of the key that decrypted `ct` together with the
+    /// decrypted plain text.
+    /// If no key decrypts `ct`, the error of the last key is returned, or an
+    /// [`HpkeError::InvalidInput`] if `sk_rs` is empty.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_with_any(
+        &self,
+        sk_rs: &[&HpkePrivateKey],
+        enc: &EncapsulatedSecret,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<(usize, Plaintext), HpkeError> {
+        let mut result = Err(HpkeError::InvalidInput);
+        for (i, sk_r) in sk_rs.iter().enumerate() {
+            result = self
+                .open(enc, sk_r, info, aad, ct, psk, psk_id, pk_s)
+                .map(|ptxt| (i, ptxt));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
     /// 6. Single-Shot APIs
     /// 6.2. Secret Export
     ///
@@ -580,7 +2139,7 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     #[allow(clippy::too_many_arguments)]
     pub fn receiver_export(
         &self,
-        enc: &[u8],
+        enc: &EncapsulatedSecret,
         sk_r: &HpkePrivateKey,
         info: &[u8],
         psk: Option<&[u8]>,
@@ -593,59 +2152,10 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
         context.export(exporter_context, length)
     }
 
-    /// Verify PSKs.
-    #[inline(always)]
-    fn verify_psk_inputs(&self, psk: &[u8], psk_id: &[u8]) -> Result<(), HpkeError> {
-        let got_psk = !psk.is_empty();
-        let got_psk_id = !psk_id.is_empty();
-        if (got_psk && !got_psk_id) || (!got_psk && got_psk_id) {
-            return Err(HpkeError::InconsistentPsk);
-        }
-
-        if got_psk && (self.mode == Mode::Base || self.mode == Mode::Auth) {
-            return Err(HpkeError::UnnecessaryPsk);
-        }
-        if !got_psk && (self.mode == Mode::Psk || self.mode == Mode::AuthPsk) {
-            return Err(HpkeError::MissingPsk);
-        }
-
-        // The PSK MUST have at least 32 bytes of entropy and SHOULD be of length Nh bytes or longer.
-        if (self.mode == Mode::Psk || self.mode == Mode::AuthPsk) && psk.len() < 32 {
-            return Err(HpkeError::InsecurePsk);
-        }
-
-        Ok(())
-    }
-
-    #[inline]
-    fn ciphersuite(&self) -> Vec<u8> {
-        util::concat(&[
-            b"HPKE",
-            &(self.kem_id as u16).to_be_bytes(),
-            &(self.kdf_id as u16).to_be_bytes(),
-            &(self.aead_id as u16).to_be_bytes(),
-        ])
-    }
-
-    #[inline]
-    fn key_schedule_context(
-        &self,
-        info: &[u8],
-        psk_id: &[u8],
-        suite_id: &[u8],
-    ) -> Result<Vec<u8>, HpkeError> {
-        let psk_id_hash =
-            labeled_extract::<Crypto>(self.kdf_id, &[0], suite_id, "psk_id_hash", psk_id)?;
-        let info_hash = labeled_extract::<Crypto>(self.kdf_id, &[0], suite_id, "info_hash", info)?;
-        Ok(util::concat(&[
-            &[self.mode as u8],
-            &psk_id_hash,
-            &info_hash,
-        ]))
-    }
-
     /// Creating the Encryption Context
     /// Generate the HPKE context from the given input.
+    ///
+    /// PSKs must be at least [`Hpke::min_psk_len`] bytes long.
     pub fn key_schedule(
         &self,
         shared_secret: &[u8],
@@ -653,48 +2163,33 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
         psk: &[u8],
         psk_id: &[u8],
     ) -> Result<Context<Crypto>, HpkeError> {
-        self.verify_psk_inputs(psk, psk_id)?;
-        let suite_id = self.ciphersuite();
-        let key_schedule_context = self.key_schedule_context(info, psk_id, &suite_id)?;
-        let secret =
-            labeled_extract::<Crypto>(self.kdf_id, shared_secret, &suite_id, "secret", psk)
-                .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
+        let mut context = self
+            .suite()
+            .key_schedule::<Crypto>(shared_secret, info, psk, psk_id)?;
+        context.padding = self.padding.clone();
+        Ok(context)
+    }
 
-        let key = labeled_expand::<Crypto>(
-            self.kdf_id,
-            &secret,
-            &suite_id,
-            "key",
-            &key_schedule_context,
-            Crypto::aead_key_length(self.aead_id),
-        )
-        .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
-        let base_nonce = labeled_expand::<Crypto>(
-            self.kdf_id,
-            &secret,
-            &suite_id,
-            "base_nonce",
-            &key_schedule_context,
-            Crypto::aead_nonce_length(self.aead_id),
-        )
-        .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
-        let exporter_secret = labeled_expand::<Crypto>(
-            self.kdf_id,
-            &secret,
-            &suite_id,
-            "exp",
-            &key_schedule_context,
-            Crypto::kdf_digest_length(self.kdf_id),
+    /// Generate the HPKE context from a `shared_secret` that was established
+    /// outside of HPKE, e.g. by KEMTLS or a custom hybrid KEM.
+    ///
+    /// This runs the key schedule of this configuration as the sender and the
+    /// receiver would after the KEM step.
+    /// The KEM of this configuration is only used for the suite identifier.
+    /// The PSK inputs are checked as in [`Hpke::setup_sender`].
+    pub fn context_from_shared_secret(
+        &self,
+        shared_secret: &SharedSecret,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<Context<Crypto>, HpkeError> {
+        self.key_schedule(
+            &shared_secret.value,
+            info,
+            psk.unwrap_or_default(),
+            psk_id.unwrap_or_default(),
         )
-        .map_err(|e| HpkeError::CryptoError(format!("Crypto error: {}", e)))?;
-
-        Ok(Context {
-            key,
-            nonce: base_nonce,
-            exporter_secret,
-            sequence_number: 0,
-            hpke: self.clone(),
-        })
     }
 
     /// 4. Cryptographic Dependencies
@@ -703,8 +2198,9 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     ///
     /// Returns an `HpkeKeyPair`.
     pub fn generate_key_pair(&mut self) -> Result<HpkeKeyPair, HpkeError> {
+        self.suite().check_kem_support::<Crypto>()?;
         let (sk, pk) = kem::key_gen::<Crypto>(self.kem_id, &mut self.prng)?;
-        Ok(HpkeKeyPair::new(sk, pk))
+        Ok(HpkeKeyPair::for_kem(self.kem_id, sk, pk))
     }
 
     /// 7.1.2. DeriveKeyPair
@@ -712,8 +2208,30 @@ impl<Crypto: HpkeCrypto> Hpke<Crypto> {
     ///
     /// Returns an `HpkeKeyPair` result or an `HpkeError` if key derivation fails.
     pub fn derive_key_pair(&self, ikm: &[u8]) -> Result<HpkeKeyPair, HpkeError> {
+        self.suite().check_kem_support::<Crypto>()?;
         let (pk, sk) = kem::derive_key_pair::<Crypto>(self.kem_id, ikm)?;
-        Ok(HpkeKeyPair::new(sk, pk))
+        Ok(HpkeKeyPair::for_kem(self.kem_id, sk, pk))
+    }
+
+    /// Generate a key pair for the used KEM whose private key is stored as
+    /// its key generation seed.
+    ///
+    /// Seed private keys are much smaller than expanded private keys and are
+    /// expanded on use, as recommended by FIPS 203.
+    /// They can be used wherever a private key is expected.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the KEM has no key
+    /// generation seed.
+    pub fn generate_seed_key_pair(&mut self) -> Result<HpkeKeyPair, HpkeError> {
+        self.suite().check_kem_support::<Crypto>()?;
+        let seed_len = self.kem_id.seed_len().ok_or(HpkeError::InvalidConfig)?;
+        let seed = self.random(seed_len)?;
+        let sk = HpkePrivateKey::from_seed(self.kem_id, seed)?;
+        let pk = sk.public_key_from_seed::<Crypto>(self.kem_id)?;
+        Ok(HpkeKeyPair {
+            private_key: sk,
+            public_key: pk,
+        })
     }
 
     #[inline]
@@ -747,6 +2265,14 @@ impl HpkeKeyPair {
         }
     }
 
+    /// Create a new HPKE key pair for the `kem` from freshly generated keys.
+    fn for_kem(kem: KemAlgorithm, sk: Vec<u8>, pk: Vec<u8>) -> Self {
+        Self {
+            private_key: HpkePrivateKey::new(sk).with_kem(kem),
+            public_key: HpkePublicKey::new(pk).with_kem(kem),
+        }
+    }
+
     /// Get a reference to the HPKE private key of this key pair.
     pub fn private_key(&self) -> &HpkePrivateKey {
         &self.private_key
@@ -787,7 +2313,163 @@ impl HpkePrivateKey {
     /// Create a new HPKE private key.
     /// Consumes the private key bytes.
     pub fn new(b: Vec<u8>) -> Self {
-        Self { value: b }
+        Self {
+            value: b,
+            kem: None,
+            auditor: None,
+            handle: false,
+        }
+    }
+
+    /// Create a new HPKE private key for the `kem` from an opaque `handle`
+    /// of a key that is held by the crypto provider, e.g. in an HSM.
+    ///
+    /// The encoding of the handle is defined by the provider, see
+    /// [`PrivateKeyRef::Handle`](hpke_rs_crypto::types::PrivateKeyRef::Handle).
+    /// Providers that don't support handles fail to use the key.
+    /// Handles can't be exported, e.g. as PKCS#8, and are not serialized.
+    pub fn from_handle(kem: KemAlgorithm, handle: Vec<u8>) -> Self {
+        let mut sk = Self::new(handle).with_kem(kem);
+        sk.handle = true;
+        sk
+    }
+
+    /// Returns `true` if this key is a handle of a key that is held by the
+    /// crypto provider.
+    pub fn is_handle(&self) -> bool {
+        self.handle
+    }
+
+    /// Get the reference to this key for the crypto provider.
+    pub(crate) fn key_ref(&self) -> PrivateKeyRef<'_> {
+        if self.handle {
+            PrivateKeyRef::Handle(&self.value)
+        } else {
+            PrivateKeyRef::Bytes(&self.value)
+        }
+    }
+
+    /// Get the bytes of this key.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if this key is a handle.
+    #[cfg(any(
+        all(
+            feature = "hazmat",
+            any(feature = "pkcs8", feature = "jwk", feature = "cose")
+        ),
+        feature = "rust-hpke",
+        feature = "fixed"
+    ))]
+    pub(crate) fn bytes(&self) -> Result<&[u8], HpkeError> {
+        if self.handle {
+            return Err(HpkeError::InvalidConfig);
+        }
+        Ok(&self.value)
+    }
+
+    /// Mark this key as a key for the `kem`.
+    fn with_kem(mut self, kem: KemAlgorithm) -> Self {
+        self.kem = Some(kem);
+        self
+    }
+
+    /// Get the KEM of this key, if it is known.
+    pub fn kem(&self) -> Option<KemAlgorithm> {
+        self.kem
+    }
+
+    /// Check that this key can be used with the `kem`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM.
+    pub(crate) fn check_kem(&self, kem: KemAlgorithm) -> Result<(), HpkeError> {
+        match self.kem {
+            Some(key_kem) if key_kem != kem => Err(HpkeError::InvalidConfig),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create a new HPKE private key for the `kem`.
+    /// Consumes the private key bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidKeyLength`] if the key doesn't have the
+    /// length required by the `kem`.
+    pub fn new_checked(kem: KemAlgorithm, b: Vec<u8>) -> Result<Self, HpkeError> {
+        let expected = kem.private_key_len();
+        if b.len() != expected {
+            let got = b.len();
+            let mut b = b;
+            b.zeroize();
+            return Err(HpkeError::InvalidKeyLength { expected, got });
+        }
+        Ok(Self::new(b).with_kem(kem))
+    }
+
+    /// Create a new HPKE private key for the `kem` from its key generation
+    /// `seed`.
+    /// Consumes the seed bytes.
+    ///
+    /// The key is expanded on every use, see [`HpkePrivateKey::expand`] to
+    /// expand it once.
+    /// Returns an [`HpkeError::InvalidConfig`] if the `kem` has no key
+    /// generation seed, and an [`HpkeError::InvalidKeyLength`] if the seed
+    /// doesn't have the length required by the `kem`.
+    pub fn from_seed(kem: KemAlgorithm, seed: Vec<u8>) -> Result<Self, HpkeError> {
+        let mut seed = seed;
+        let expected = match kem.seed_len() {
+            Some(expected) => expected,
+            None => {
+                seed.zeroize();
+                return Err(HpkeError::InvalidConfig);
+            }
+        };
+        if seed.len() != expected {
+            let got = seed.len();
+            seed.zeroize();
+            return Err(HpkeError::InvalidKeyLength { expected, got });
+        }
+        Ok(Self::new(seed).with_kem(kem))
+    }
+
+    /// Returns `true` if this is a private key for the `kem` that is stored as
+    /// its key generation seed.
+    pub fn is_seed(&self, kem: KemAlgorithm) -> bool {
+        !self.handle && kem.seed_len() == Some(self.value.len())
+    }
+
+    /// Expand this private key for the `kem` if it is stored as its key
+    /// generation seed.
+    ///
+    /// Private keys that are already expanded are returned unchanged.
+    /// The expanded key keeps the auditor of this key.
+    pub fn expand<Crypto: HpkeCrypto>(&self, kem: KemAlgorithm) -> Result<Self, HpkeError> {
+        if !self.is_seed(kem) {
+            return Ok(Self {
+                value: self.value.clone(),
+                kem: self.kem,
+                auditor: self.auditor.clone(),
+                handle: self.handle,
+            });
+        }
+        let (_, sk) = kem::key_gen_from_seed::<Crypto>(kem, &self.value)?;
+        let mut expanded = Self::new(sk).with_kem(kem);
+        expanded.auditor = self.auditor.clone();
+        Ok(expanded)
+    }
+
+    /// Compute the public key of this private key for the `kem`, which must be
+    /// stored as its key generation seed.
+    ///
+    /// Returns an [`HpkeError::InvalidSecretKey`] if this key is not a seed.
+    pub fn public_key_from_seed<Crypto: HpkeCrypto>(
+        &self,
+        kem: KemAlgorithm,
+    ) -> Result<HpkePublicKey, HpkeError> {
+        if !self.is_seed(kem) {
+            return Err(HpkeError::InvalidSecretKey);
+        }
+        let (pk, mut sk) = kem::key_gen_from_seed::<Crypto>(kem, &self.value)?;
+        sk.zeroize();
+        Ok(HpkePublicKey::new(pk).with_kem(kem))
     }
 
     /// Get the raw key as byte slice.
@@ -813,7 +2495,7 @@ impl From<&[u8]> for HpkePrivateKey {
 /// same length.
 impl PartialEq for HpkePrivateKey {
     fn eq(&self, other: &Self) -> bool {
-        if self.value.len() != other.value.len() {
+        if self.value.len() != other.value.len() || self.handle != other.handle {
             return false;
         }
 
@@ -843,11 +2525,190 @@ impl core::fmt::Debug for HpkePrivateKey {
     }
 }
 
+impl SharedSecret {
+    /// Create a new shared secret.
+    /// Consumes the shared secret bytes.
+    pub fn new(value: Vec<u8>) -> Self {
+        Self { value }
+    }
+
+    /// Create a new shared secret for the `kem`.
+    /// Consumes the shared secret bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidKeyLength`] if the shared secret doesn't
+    /// have the length of the shared secrets of the `kem`.
+    pub fn new_checked(kem: KemAlgorithm, value: Vec<u8>) -> Result<Self, HpkeError> {
+        let expected = kem.shared_secret_len();
+        if value.len() != expected {
+            let got = value.len();
+            let mut value = value;
+            value.zeroize();
+            return Err(HpkeError::InvalidKeyLength { expected, got });
+        }
+        Ok(Self { value })
+    }
+
+    /// Get the raw shared secret as byte slice.
+    #[cfg(feature = "hazmat")]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+impl From<Vec<u8>> for SharedSecret {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl core::fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        f.debug_struct("SharedSecret")
+            .field("value", &"***")
+            .finish()
+    }
+}
+
 impl HpkePublicKey {
     /// Create a new HPKE public key.
     /// Consumes the public key bytes.
     pub fn new(value: Vec<u8>) -> Self {
-        Self { value }
+        Self { value, kem: None }
+    }
+
+    /// Mark this key as a key for the `kem`.
+    fn with_kem(mut self, kem: KemAlgorithm) -> Self {
+        self.kem = Some(kem);
+        self
+    }
+
+    /// Get the KEM of this key, if it is known.
+    pub fn kem(&self) -> Option<KemAlgorithm> {
+        self.kem
+    }
+
+    /// Check that this key can be used with the `kem`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM.
+    pub(crate) fn check_kem(&self, kem: KemAlgorithm) -> Result<(), HpkeError> {
+        match self.kem {
+            Some(key_kem) if key_kem != kem => Err(HpkeError::InvalidConfig),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create a new HPKE public key for the `kem`.
+    /// Consumes the public key bytes.
+    ///
+    /// Compressed SEC1 points are accepted for the NIST curves and secp256k1,
+    /// and decompressed on use.
+    ///
+    /// Returns an [`HpkeError::InvalidKeyLength`] if the key doesn't have the
+    /// length required by the `kem`.
+    pub fn new_checked(kem: KemAlgorithm, value: Vec<u8>) -> Result<Self, HpkeError> {
+        let expected = kem.public_key_len();
+        if value.len() != expected && kem.compressed_public_key_len() != Some(value.len()) {
+            return Err(HpkeError::InvalidKeyLength {
+                expected,
+                got: value.len(),
+            });
+        }
+        Ok(Self::new(value).with_kem(kem))
+    }
+
+    /// Validate this public key for the `kem`.
+    ///
+    /// In addition to the checks of [`HpkePublicKey::new_checked`], this
+    /// checks with the `Crypto` provider that the key of a DH KEM is a valid
+    /// public key, i.e. a point on the curve that is not the identity for the
+    /// NIST curves, and not all zero for X25519.
+    /// Use this to reject malformed keys from untrusted peers before doing
+    /// any work with them.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if the provider doesn't
+    /// support the `kem`, an [`HpkeError::InvalidConfig`] if the key is for
+    /// another KEM, an [`HpkeError::InvalidKeyLength`] if the key doesn't
+    /// have the length required by the `kem`, and an
+    /// [`HpkeError::InvalidPublicKey`] if it is not a valid key.
+    pub fn validate<Crypto: HpkeCrypto>(&self, kem: KemAlgorithm) -> Result<(), HpkeError> {
+        Crypto::supports_kem(kem).map_err(|_| HpkeError::UnsupportedAlgorithm {
+            kind: AlgorithmKind::Kem,
+            id: kem.id(),
+        })?;
+        self.check_kem(kem)?;
+        let value = self.uncompressed::<Crypto>(kem)?;
+        let expected = kem.public_key_len();
+        if value.len() != expected {
+            return Err(HpkeError::InvalidKeyLength {
+                expected,
+                got: value.len(),
+            });
+        }
+        match kem {
+            // The KEM validates its encapsulation keys on use.
+            KemAlgorithm::XWingDraft06
+            | KemAlgorithm::MlKem512
+            | KemAlgorithm::MlKem768
+            | KemAlgorithm::MlKem1024 => Ok(()),
+            // Only the X25519 key of the hybrid is validated.
+            KemAlgorithm::X25519Kyber768Draft00 => {
+                let pk_x = value.get(..32).ok_or(HpkeError::InvalidInput)?;
+                Crypto::dh_validate_pk(KemAlgorithm::DhKem25519, pk_x).map_err(HpkeError::from)
+            }
+            _ => Crypto::dh_validate_pk(kem, &value).map_err(HpkeError::from),
+        }
+    }
+
+    /// Returns `true` if this is a compressed SEC1 public key for the `kem`.
+    pub fn is_compressed(&self, kem: KemAlgorithm) -> bool {
+        kem.compressed_public_key_len() == Some(self.value.len())
+            && matches!(self.value.first(), Some(0x02 | 0x03))
+    }
+
+    /// Compress this SEC1 public key for the `kem`, e.g. for serializing it
+    /// in the compressed form.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the `kem` or the `Crypto`
+    /// provider doesn't support compressed keys, and an
+    /// [`HpkeError::InvalidPublicKey`] if the key is invalid.
+    pub fn compress<Crypto: HpkeCrypto>(&self, kem: KemAlgorithm) -> Result<Self, HpkeError> {
+        self.check_kem(kem)?;
+        if self.is_compressed(kem) {
+            return Ok(self.clone());
+        }
+        if kem.compressed_public_key_len().is_none() {
+            return Err(HpkeError::InvalidConfig);
+        }
+        let value = Crypto::dh_encode_pk(kem, &self.value, true)?;
+        Ok(Self::new(value).with_kem(kem))
+    }
+
+    /// Decompress this public key for the `kem` if it is a compressed SEC1
+    /// public key.
+    ///
+    /// Uncompressed keys are returned unchanged.
+    /// Returns an [`HpkeError::InvalidConfig`] if the `Crypto` provider
+    /// doesn't support compressed keys, and an
+    /// [`HpkeError::InvalidPublicKey`] if the key is invalid.
+    pub fn decompress<Crypto: HpkeCrypto>(&self, kem: KemAlgorithm) -> Result<Self, HpkeError> {
+        self.check_kem(kem)?;
+        let value = self.uncompressed::<Crypto>(kem)?.into_owned();
+        Ok(Self {
+            value,
+            kem: self.kem,
+        })
+    }
+
+    /// Get the bytes of this key for the `kem`, decompressed if it is a
+    /// compressed SEC1 public key.
+    pub(crate) fn uncompressed<Crypto: HpkeCrypto>(
+        &self,
+        kem: KemAlgorithm,
+    ) -> Result<Cow<'_, [u8]>, HpkeError> {
+        if !self.is_compressed(kem) {
+            return Ok(Cow::Borrowed(&self.value));
+        }
+        Ok(Cow::Owned(Crypto::dh_encode_pk(kem, &self.value, false)?))
     }
 
     /// Get the raw key as byte slice.
@@ -856,6 +2717,14 @@ impl HpkePublicKey {
     }
 }
 
+/// Public keys are compared by their bytes, independent of whether their KEM
+/// is known.
+impl PartialEq for HpkePublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
 impl From<Vec<u8>> for HpkePublicKey {
     fn from(b: Vec<u8>) -> Self {
         Self::new(b)
@@ -904,9 +2773,9 @@ impl tls_codec::Serialize for &HpkePublicKey {
 impl tls_codec::Deserialize for HpkePublicKey {
     #[inline(always)]
     fn tls_deserialize<R: std::io::Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
-        Ok(Self {
-            value: tls_codec::VLBytes::tls_deserialize(bytes)?.into(),
-        })
+        Ok(Self::new(
+            tls_codec::VLBytes::tls_deserialize(bytes)?.into(),
+        ))
     }
 }
 
@@ -920,14 +2789,139 @@ impl tls_codec::Deserialize for &HpkePublicKey {
     }
 }
 
+#[cfg(feature = "serialization")]
+impl HpkePublicKey {
+    /// Strictly deserialize a TLS encoded public key for the `kem`.
+    ///
+    /// In addition to the regular decoding, this rejects keys that don't
+    /// have the length required by the `kem` and trailing bytes after the
+    /// encoded key.
+    /// Use this when accepting keys from untrusted peers.
+    pub fn tls_deserialize_strict(
+        kem: KemAlgorithm,
+        bytes: &[u8],
+    ) -> Result<Self, tls_codec::Error> {
+        let value = <tls_codec::VLBytes as tls_codec::Deserialize>::tls_deserialize_exact(bytes)?;
+        Self::new_checked(kem, value.into())
+            .map_err(|_| tls_codec::Error::DecodingError("Invalid public key length.".to_string()))
+    }
+
+    /// Strictly deserialize a serde encoded public key for the `kem`.
+    ///
+    /// In addition to the regular decoding, this rejects keys that don't
+    /// have the length required by the `kem`.
+    /// Trailing bytes are rejected by the serde data format, e.g.
+    /// `serde_json::from_slice`.
+    pub fn deserialize_strict<'de, D: serde::Deserializer<'de>>(
+        kem: KemAlgorithm,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let key = Self::deserialize(deserializer)?;
+        Self::new_checked(kem, key.value)
+            .map_err(|_| serde::de::Error::custom("Invalid public key length."))
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl HpkePrivateKey {
+    /// Strictly deserialize a serde encoded private key for the `kem`.
+    ///
+    /// In addition to the regular decoding, this rejects keys that don't
+    /// have the length required by the `kem`.
+    /// Trailing bytes are rejected by the serde data format, e.g.
+    /// `serde_json::from_slice`.
+    pub fn deserialize_strict<'de, D: serde::Deserializer<'de>>(
+        kem: KemAlgorithm,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let mut key = Self::deserialize(deserializer)?;
+        let value = core::mem::take(&mut key.value);
+        Self::new_checked(kem, value)
+            .map_err(|_| serde::de::Error::custom("Invalid private key length."))
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Size for EncapsulatedSecret {
+    #[inline(always)]
+    fn tls_serialized_len(&self) -> usize {
+        tls_codec::VLByteSlice(self.as_slice()).tls_serialized_len()
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Serialize for EncapsulatedSecret {
+    #[inline(always)]
+    fn tls_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        tls_codec::VLByteSlice(self.as_slice()).tls_serialize(writer)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl EncapsulatedSecret {
+    /// Deserialize a TLS encoded encapsulated secret for the `kem`.
+    ///
+    /// Note that the algorithm is not part of the encoding and has to be known
+    /// by the caller.
+    pub fn tls_deserialize_with<R: std::io::Read>(
+        kem: KemAlgorithm,
+        bytes: &mut R,
+    ) -> Result<Self, tls_codec::Error> {
+        let value = <tls_codec::VLBytes as tls_codec::Deserialize>::tls_deserialize(bytes)?;
+        Self::new(kem, value.into()).map_err(|_| {
+            tls_codec::Error::DecodingError("Invalid encapsulated secret length.".to_string())
+        })
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Size for Ciphertext {
+    #[inline(always)]
+    fn tls_serialized_len(&self) -> usize {
+        tls_codec::VLByteSlice(self.as_slice()).tls_serialized_len()
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Serialize for Ciphertext {
+    #[inline(always)]
+    fn tls_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        tls_codec::VLByteSlice(self.as_slice()).tls_serialize(writer)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl Ciphertext {
+    /// Deserialize a TLS encoded ciphertext for the `aead`.
+    ///
+    /// Note that the algorithm is not part of the encoding and has to be known
+    /// by the caller.
+    pub fn tls_deserialize_with<R: std::io::Read>(
+        aead: AeadAlgorithm,
+        bytes: &mut R,
+    ) -> Result<Self, tls_codec::Error> {
+        let value = <tls_codec::VLBytes as tls_codec::Deserialize>::tls_deserialize(bytes)?;
+        Self::new(aead, value.into())
+            .map_err(|_| tls_codec::Error::DecodingError("Invalid ciphertext length.".to_string()))
+    }
+}
+
 /// Test util module. Should be moved really.
 #[cfg(feature = "hpke-test")]
+#[allow(
+    clippy::panic,
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
 pub mod test_util {
     use alloc::{format, string::String, vec, vec::Vec};
 
     use crate::HpkeError;
     use hpke_rs_crypto::{HpkeCrypto, HpkeTestRng};
 
+    pub mod kat;
+
     impl<Crypto: HpkeCrypto> super::Hpke<Crypto> {
         /// Set PRNG state for testing.
         pub fn seed(&mut self, seed: &[u8]) -> Result<(), HpkeError> {
@@ -1002,30 +2996,30 @@ impl From<hpke_rs_crypto::error::Error> for HpkeError {
     fn from(e: hpke_rs_crypto::error::Error) -> Self {
         match e {
             hpke_rs_crypto::error::Error::AeadOpenError => HpkeError::OpenError,
-            hpke_rs_crypto::error::Error::AeadInvalidNonce
-            | hpke_rs_crypto::error::Error::AeadInvalidCiphertext => HpkeError::InvalidInput,
-            hpke_rs_crypto::error::Error::UnknownAeadAlgorithm => HpkeError::UnknownMode,
+            hpke_rs_crypto::error::Error::AeadInvalidNonce => HpkeError::InvalidNonce,
+            hpke_rs_crypto::error::Error::AeadInvalidCiphertext
+            | hpke_rs_crypto::error::Error::KemInvalidCiphertext => HpkeError::InvalidCiphertext,
+            hpke_rs_crypto::error::Error::UnknownAeadAlgorithm => {
+                HpkeError::UnsupportedByProvider {
+                    kind: AlgorithmKind::Aead,
+                }
+            }
             hpke_rs_crypto::error::Error::CryptoLibraryError(s) => HpkeError::CryptoError(s),
             hpke_rs_crypto::error::Error::HpkeInvalidOutputLength => {
                 HpkeError::CryptoError("Invalid HPKE output length".to_string())
             }
-            hpke_rs_crypto::error::Error::UnknownKdfAlgorithm => {
-                HpkeError::CryptoError("Unknown KDF algorithm.".to_string())
-            }
-            hpke_rs_crypto::error::Error::KemInvalidSecretKey => {
-                HpkeError::CryptoError("Invalid KEM secret key".to_string())
-            }
-            hpke_rs_crypto::error::Error::KemInvalidPublicKey => {
-                HpkeError::CryptoError("Invalid KEM public key".to_string())
-            }
-            hpke_rs_crypto::error::Error::UnknownKemAlgorithm => {
-                HpkeError::CryptoError("Unknown KEM algorithm".to_string())
-            }
+            hpke_rs_crypto::error::Error::UnknownKdfAlgorithm => HpkeError::UnsupportedByProvider {
+                kind: AlgorithmKind::Kdf,
+            },
+            hpke_rs_crypto::error::Error::KemInvalidSecretKey => HpkeError::InvalidSecretKey,
+            hpke_rs_crypto::error::Error::KemInvalidPublicKey => HpkeError::InvalidPublicKey,
+            hpke_rs_crypto::error::Error::UnknownKemAlgorithm => HpkeError::UnsupportedByProvider {
+                kind: AlgorithmKind::Kem,
+            },
             hpke_rs_crypto::error::Error::InsufficientRandomness => {
                 HpkeError::InsufficientRandomness
             }
             hpke_rs_crypto::error::Error::UnsupportedKemOperation => HpkeError::InvalidConfig,
-            hpke_rs_crypto::error::Error::KemInvalidCiphertext => HpkeError::InvalidInput,
         }
     }
 }
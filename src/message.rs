@@ -0,0 +1,313 @@
+//! # HPKE Messages
+//!
+//! An [`HpkeMessage`] bundles the encapsulated secret and the ciphertext of a
+//! single shot encryption, optionally together with a [`KeyHint`] for the
+//! recipient key.
+//!
+//! ## Recipient key hints
+//!
+//! A receiver that holds many private keys can't tell which key an HPKE
+//! message was encrypted to and has to try all of them.
+//! The optional key hint is a truncated, suite specific fingerprint of the
+//! recipient public key that lets the receiver select the right private key
+//! with a single lookup.
+//!
+//! The hint is **off by default** because it has privacy implications:
+//! - Messages to the same recipient key carry the same hint. An observer can
+//!   therefore link all messages to one recipient, which HPKE ciphertexts
+//!   otherwise don't allow.
+//! - An observer who knows a candidate public key can compute its hint and
+//!   check whether a message was sent to it.
+//! - The hint is truncated to [`KEY_HINT_LEN`] bytes. Collisions between the
+//!   keys of a receiver are possible but unlikely; receivers should fall back
+//!   to trial decryption if the selected key fails.
+//!
+//! Only use the hint when recipient anonymity towards observers is not
+//! required, e.g. when the recipient is identified by the transport anyway.
+//!
+//! ## MLS ciphertexts
+//!
+//! An [`HpkeCiphertext`] is the `HPKECiphertext` structure of MLS (RFC 9420),
+//! i.e. the `kem_output` and `ciphertext` with variable length encoding.
+//! Unlike [`HpkeMessage`], it doesn't check the lengths against a suite and
+//! can therefore be deserialized without knowing the suite.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    kdf::labeled_extract, Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey,
+    HpkePublicKey, HpkeSuite,
+};
+
+/// The length of a [`KeyHint`] in bytes.
+pub const KEY_HINT_LEN: usize = 8;
+
+/// A truncated fingerprint of a recipient public key.
+///
+/// See the [module documentation](self) for the privacy implications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyHint([u8; KEY_HINT_LEN]);
+
+impl KeyHint {
+    /// Compute the key hint for the public key `pk` in the `suite`.
+    ///
+    /// ```text
+    /// hint = LabeledExtract("", "key_hint", pk)[..KEY_HINT_LEN]
+    /// ```
+    pub fn new<Crypto: HpkeCrypto>(
+        suite: HpkeSuite,
+        pk: &HpkePublicKey,
+    ) -> Result<Self, HpkeError> {
+        let fingerprint = labeled_extract::<Crypto>(
+            suite.kdf,
+            &[],
+            &suite.suite_id(),
+            "key_hint",
+            pk.as_slice(),
+        )?;
+        let hint = fingerprint
+            .get(..KEY_HINT_LEN)
+            .and_then(|hint| hint.try_into().ok())
+            .ok_or(HpkeError::InvalidConfig)?;
+        Ok(Self(hint))
+    }
+
+    /// Create a key hint from its raw bytes.
+    pub fn from_bytes(bytes: [u8; KEY_HINT_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Get the raw bytes of the key hint.
+    pub fn as_bytes(&self) -> &[u8; KEY_HINT_LEN] {
+        &self.0
+    }
+}
+
+/// An HPKE message, i.e. the encapsulated secret and the ciphertext of a
+/// single shot encryption, with an optional [`KeyHint`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpkeMessage {
+    key_hint: Option<KeyHint>,
+    enc: EncapsulatedSecret,
+    ct: Ciphertext,
+}
+
+impl HpkeMessage {
+    /// Create a new message without a key hint.
+    pub fn new(enc: EncapsulatedSecret, ct: Ciphertext) -> Self {
+        Self {
+            key_hint: None,
+            enc,
+            ct,
+        }
+    }
+
+    /// Add the `key_hint` to this message.
+    pub fn with_key_hint(mut self, key_hint: KeyHint) -> Self {
+        self.key_hint = Some(key_hint);
+        self
+    }
+
+    /// Get the key hint, if any.
+    pub fn key_hint(&self) -> Option<&KeyHint> {
+        self.key_hint.as_ref()
+    }
+
+    /// Get the encapsulated secret.
+    pub fn enc(&self) -> &EncapsulatedSecret {
+        &self.enc
+    }
+
+    /// Get the ciphertext.
+    pub fn ciphertext(&self) -> &Ciphertext {
+        &self.ct
+    }
+
+    /// Split the message into its key hint, encapsulated secret, and
+    /// ciphertext.
+    pub fn into_parts(self) -> (Option<KeyHint>, EncapsulatedSecret, Ciphertext) {
+        (self.key_hint, self.enc, self.ct)
+    }
+}
+
+/// The encapsulated secret and the ciphertext of a single shot encryption
+/// as carried by MLS.
+///
+/// ```text
+/// struct {
+///     opaque kem_output<V>;
+///     opaque ciphertext<V>;
+/// } HPKECiphertext;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HpkeCiphertext {
+    enc: Vec<u8>,
+    ct: Vec<u8>,
+}
+
+impl HpkeCiphertext {
+    /// Create a new ciphertext from the encapsulated secret and the
+    /// ciphertext.
+    pub fn new(enc: EncapsulatedSecret, ct: Ciphertext) -> Self {
+        Self {
+            enc: enc.into(),
+            ct: ct.into(),
+        }
+    }
+
+    /// Get the raw encapsulated secret (`kem_output`).
+    pub fn enc(&self) -> &[u8] {
+        &self.enc
+    }
+
+    /// Get the raw ciphertext.
+    pub fn ct(&self) -> &[u8] {
+        &self.ct
+    }
+
+    /// Split the ciphertext into the encapsulated secret and ciphertext for
+    /// the `suite`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the lengths don't match the
+    /// `suite`.
+    pub fn into_parts(
+        self,
+        suite: HpkeSuite,
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        Ok((
+            EncapsulatedSecret::new(suite.kem, self.enc)?,
+            Ciphertext::new(suite.aead, self.ct)?,
+        ))
+    }
+}
+
+impl From<(EncapsulatedSecret, Ciphertext)> for HpkeCiphertext {
+    fn from((enc, ct): (EncapsulatedSecret, Ciphertext)) -> Self {
+        Self::new(enc, ct)
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Compute the [`KeyHint`] for the public key `pk` in this configuration.
+    pub fn key_hint(&self, pk: &HpkePublicKey) -> Result<KeyHint, HpkeError> {
+        KeyHint::new::<Crypto>(self.suite(), pk)
+    }
+
+    /// Single shot API to encrypt the bytes in `plain_txt` to the public key
+    /// `pk_r` into an [`HpkeCiphertext`].
+    ///
+    /// See [`Hpke::seal`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal_to_ciphertext(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        sk_s: Option<&HpkePrivateKey>,
+    ) -> Result<HpkeCiphertext, HpkeError> {
+        self.seal(pk_r, info, aad, plain_txt, psk, psk_id, sk_s)
+            .map(HpkeCiphertext::from)
+    }
+
+    /// Single shot API to decrypt the [`HpkeCiphertext`] `ct` with the
+    /// private key `sk_r`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the lengths in `ct` don't
+    /// match this configuration.
+    /// See [`Hpke::open`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_ciphertext(
+        &self,
+        ct: &HpkeCiphertext,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<Vec<u8>, HpkeError> {
+        let (enc, ct) = ct.clone().into_parts(self.suite())?;
+        self.open(&enc, sk_r, info, aad, &ct, psk, psk_id, pk_s)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Size for HpkeMessage {
+    #[inline(always)]
+    fn tls_serialized_len(&self) -> usize {
+        self.key_hint
+            .map(|hint| hint.0)
+            .tls_serialized_len()
+            .saturating_add(self.enc.tls_serialized_len())
+            .saturating_add(self.ct.tls_serialized_len())
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Serialize for HpkeMessage {
+    #[inline(always)]
+    fn tls_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        let written = self.key_hint.map(|hint| hint.0).tls_serialize(writer)?;
+        let written = written.saturating_add(self.enc.tls_serialize(writer)?);
+        Ok(written.saturating_add(self.ct.tls_serialize(writer)?))
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl HpkeMessage {
+    /// Deserialize a TLS encoded message for the `suite`.
+    ///
+    /// Note that the suite is not part of the encoding and has to be known
+    /// by the caller.
+    pub fn tls_deserialize_with<R: std::io::Read>(
+        suite: HpkeSuite,
+        bytes: &mut R,
+    ) -> Result<Self, tls_codec::Error> {
+        let key_hint =
+            <Option<[u8; KEY_HINT_LEN]> as tls_codec::Deserialize>::tls_deserialize(bytes)?;
+        let enc = EncapsulatedSecret::tls_deserialize_with(suite.kem, bytes)?;
+        let ct = Ciphertext::tls_deserialize_with(suite.aead, bytes)?;
+        Ok(Self {
+            key_hint: key_hint.map(KeyHint),
+            enc,
+            ct,
+        })
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Size for HpkeCiphertext {
+    #[inline(always)]
+    fn tls_serialized_len(&self) -> usize {
+        tls_codec::VLByteSlice(&self.enc)
+            .tls_serialized_len()
+            .saturating_add(tls_codec::VLByteSlice(&self.ct).tls_serialized_len())
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Serialize for HpkeCiphertext {
+    #[inline(always)]
+    fn tls_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        let written = tls_codec::VLByteSlice(&self.enc).tls_serialize(writer)?;
+        Ok(written.saturating_add(tls_codec::VLByteSlice(&self.ct).tls_serialize(writer)?))
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl tls_codec::Deserialize for HpkeCiphertext {
+    #[inline(always)]
+    fn tls_deserialize<R: std::io::Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
+        let enc = tls_codec::VLBytes::tls_deserialize(bytes)?;
+        let ct = tls_codec::VLBytes::tls_deserialize(bytes)?;
+        Ok(Self {
+            enc: enc.into(),
+            ct: ct.into(),
+        })
+    }
+}
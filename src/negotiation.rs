@@ -0,0 +1,54 @@
+//! # Ciphersuite Negotiation
+//!
+//! Servers, e.g. for ECH or OHTTP, pick the ciphersuite from a list offered
+//! by the peer.
+//! [`negotiate`] selects the suite that the local side prefers most among
+//! the offered suites that the crypto provider supports.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, negotiation::negotiate, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let offered = [
+//!     (KemAlgorithm::DhKemP256, KdfAlgorithm::HkdfSha256, AeadAlgorithm::Aes128Gcm),
+//!     (KemAlgorithm::DhKem25519, KdfAlgorithm::HkdfSha256, AeadAlgorithm::ChaCha20Poly1305),
+//! ];
+//! let suite = negotiate::<HpkeRustCrypto>(Mode::Base, &offered, &[
+//!     (KemAlgorithm::DhKem25519, KdfAlgorithm::HkdfSha256, AeadAlgorithm::ChaCha20Poly1305),
+//!     (KemAlgorithm::DhKemP256, KdfAlgorithm::HkdfSha256, AeadAlgorithm::Aes128Gcm),
+//! ])?;
+//! assert_eq!(suite.kem, KemAlgorithm::DhKem25519);
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    HpkeCrypto,
+};
+
+use crate::{HpkeError, HpkeSuite, Mode};
+
+/// The algorithms of a ciphersuite as offered by a peer.
+pub type SuiteOffer = (KemAlgorithm, KdfAlgorithm, AeadAlgorithm);
+
+/// Select the ciphersuite for the `mode` from the suites `offered` by the
+/// peer.
+///
+/// The `preferences` list the locally acceptable suites, most preferred
+/// first.
+/// The first of them that is `offered` and supported by the `Crypto`
+/// provider is returned; the order of `offered` doesn't matter.
+///
+/// Returns an [`HpkeError::NoCommonSuite`] if there is no such suite.
+pub fn negotiate<Crypto: HpkeCrypto>(
+    mode: Mode,
+    offered: &[SuiteOffer],
+    preferences: &[SuiteOffer],
+) -> Result<HpkeSuite, HpkeError> {
+    preferences
+        .iter()
+        .filter(|preference| offered.contains(preference))
+        .map(|&(kem, kdf, aead)| HpkeSuite::new(mode, kem, kdf, aead))
+        .find(|suite| suite.check_support::<Crypto>().is_ok())
+        .ok_or(HpkeError::NoCommonSuite)
+}
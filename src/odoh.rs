@@ -0,0 +1,415 @@
+//! # Oblivious DNS over HTTPS
+//!
+//! The HPKE encryption of Oblivious DoH (RFC 9230).
+//!
+//! - A target publishes its [`OdohConfig`]s with [`OdohConfig::encode_list`].
+//! - A client encrypts a query to a config with [`encrypt_query`] and keeps
+//!   the returned [`QueryContext`] to decrypt the response.
+//! - The target decrypts the query with [`decrypt_query`] and encrypts the
+//!   response with the returned [`ResponseContext`].
+//!
+//! Queries and responses are `ObliviousDoHMessage`s with the padded DNS
+//! message as plaintext.
+//! The response is protected with a key derived from the query context
+//! (Section 6.4 of RFC 9230):
+//!
+//! ```text
+//! secret = Export("odoh response", Nk)
+//! salt   = Q_plain || len(resp_nonce) || resp_nonce
+//! prk    = Extract(salt, secret)
+//! key    = Expand(prk, "odoh key", Nk)
+//! nonce  = Expand(prk, "odoh nonce", Nn)
+//! ```
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, odoh::{self, OdohConfig}, Hpke, Mode};
+//! use hpke_rs_crypto::HpkeCrypto;
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! // The target publishes its configs.
+//! let mut keys = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::Aes128Gcm,
+//! );
+//! let (sk_r, pk_r) = keys.generate_key_pair()?.into_keys();
+//! let target_config = OdohConfig::new(
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::Aes128Gcm,
+//!     pk_r,
+//! );
+//! let odoh_configs = OdohConfig::encode_list(&[target_config.clone()])?;
+//!
+//! // Client
+//! let config = &OdohConfig::decode_list(&odoh_configs)?[0];
+//! let (query, query_context) = odoh::encrypt_query::<HpkeRustCrypto>(config, b"dns query", 0)?;
+//!
+//! // Target
+//! let (dns_query, response_context) =
+//!     odoh::decrypt_query::<HpkeRustCrypto>(&target_config, &sk_r, &query)?;
+//! assert_eq!(dns_query, b"dns query");
+//! let response =
+//!     response_context.encrypt_response(&mut HpkeRustCrypto::prng(), b"dns response", 0)?;
+//!
+//! // Client
+//! let dns_response = query_context.decrypt_response(&response)?;
+//! assert_eq!(dns_response, b"dns response");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::{format, vec, vec::Vec};
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    CryptoRng, HpkeCrypto,
+};
+
+use crate::{
+    util, Ciphertext, Context, EncapsulatedSecret, HpkeError, HpkePrivateKey, HpkePublicKey,
+    HpkeSuite, Mode, ReceiverContext, SenderContext,
+};
+
+/// The version of the `ObliviousDoHConfig` structure.
+pub const ODOH_VERSION: u16 = 0x0001;
+
+/// The message type of queries.
+const QUERY: u8 = 0x01;
+
+/// The message type of responses.
+const RESPONSE: u8 = 0x02;
+
+/// An `ObliviousDoHConfig` of version [`ODOH_VERSION`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OdohConfig {
+    /// The KEM of the public key.
+    pub kem: KemAlgorithm,
+
+    /// The KDF.
+    pub kdf: KdfAlgorithm,
+
+    /// The AEAD.
+    pub aead: AeadAlgorithm,
+
+    /// The public key of the target.
+    pub public_key: HpkePublicKey,
+}
+
+/// Encode an `ObliviousDoHMessage`.
+fn encode_message(
+    message_type: u8,
+    key_id: &[u8],
+    encrypted_message: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let mut message = vec![message_type];
+    util::write_vec16(&mut message, key_id)?;
+    util::write_vec16(&mut message, encrypted_message)?;
+    Ok(message)
+}
+
+/// Decode an `ObliviousDoHMessage` of the `message_type` into the key id and
+/// the encrypted message.
+fn decode_message(message_type: u8, message: &[u8]) -> Result<(&[u8], &[u8]), HpkeError> {
+    let (&got_type, rest) = message.split_first().ok_or(HpkeError::InvalidInput)?;
+    let (key_id, rest) = util::read_vec16(rest)?;
+    let (encrypted_message, rest) = util::read_vec16(rest)?;
+    if got_type != message_type || encrypted_message.is_empty() || !rest.is_empty() {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok((key_id, encrypted_message))
+}
+
+/// Encode an `ObliviousDoHMessagePlaintext` with `padding_len` zero bytes.
+fn encode_plaintext(dns_message: &[u8], padding_len: usize) -> Result<Vec<u8>, HpkeError> {
+    if dns_message.is_empty() {
+        return Err(HpkeError::InvalidInput);
+    }
+    let mut plaintext = Vec::new();
+    util::write_vec16(&mut plaintext, dns_message)?;
+    util::write_vec16(&mut plaintext, &vec![0; padding_len])?;
+    Ok(plaintext)
+}
+
+/// Decode an `ObliviousDoHMessagePlaintext` into the DNS message.
+///
+/// Returns an [`HpkeError::InvalidInput`] if the padding is not all zeros.
+fn decode_plaintext(plaintext: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let (dns_message, rest) = util::read_vec16(plaintext)?;
+    let (padding, rest) = util::read_vec16(rest)?;
+    if dns_message.is_empty() || padding.iter().any(|&b| b != 0) || !rest.is_empty() {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(dns_message.to_vec())
+}
+
+impl OdohConfig {
+    /// Create a config for the `public_key` of the `kem`.
+    pub fn new(
+        kem: KemAlgorithm,
+        kdf: KdfAlgorithm,
+        aead: AeadAlgorithm,
+        public_key: HpkePublicKey,
+    ) -> Self {
+        Self {
+            kem,
+            kdf,
+            aead,
+            public_key,
+        }
+    }
+
+    /// The HPKE suite of this config.
+    fn suite(&self) -> HpkeSuite {
+        HpkeSuite::new(Mode::Base, self.kem, self.kdf, self.aead)
+    }
+
+    /// Encode the `ObliviousDoHConfigContents` of this config.
+    ///
+    /// ```text
+    /// kem_id (2) || kdf_id (2) || aead_id (2) || public_key<1..2^16-1>
+    /// ```
+    pub fn contents(&self) -> Result<Vec<u8>, HpkeError> {
+        if self.public_key.as_slice().is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let mut contents = util::concat(&[
+            &self.kem.id().to_be_bytes(),
+            &self.kdf.id().to_be_bytes(),
+            &self.aead.id().to_be_bytes(),
+        ]);
+        util::write_vec16(&mut contents, self.public_key.as_slice())?;
+        Ok(contents)
+    }
+
+    /// Compute the key identifier of this config.
+    ///
+    /// ```text
+    /// key_id = Expand(Extract("", contents), "odoh key id", Nh)
+    /// ```
+    pub fn key_id<Crypto: HpkeCrypto>(&self) -> Result<Vec<u8>, HpkeError> {
+        let crypto_error = |e| HpkeError::CryptoError(format!("Crypto error: {}", e));
+        let prk = Crypto::kdf_extract(self.kdf, &[], &self.contents()?).map_err(crypto_error)?;
+        Crypto::kdf_expand(
+            self.kdf,
+            &prk,
+            b"odoh key id",
+            Crypto::kdf_digest_length(self.kdf),
+        )
+        .map_err(crypto_error)
+    }
+
+    /// Decode `ObliviousDoHConfigs`.
+    ///
+    /// Configs with versions other than [`ODOH_VERSION`] or with unknown
+    /// algorithms are skipped.
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed.
+    pub fn decode_list(list: &[u8]) -> Result<Vec<Self>, HpkeError> {
+        let (mut configs_bytes, rest) = util::read_vec16(list)?;
+        if configs_bytes.is_empty() || !rest.is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let mut configs = Vec::new();
+        while !configs_bytes.is_empty() {
+            let (version, rest) = util::read_u16(configs_bytes)?;
+            let (contents, rest) = util::read_vec16(rest)?;
+            if version == ODOH_VERSION {
+                let (kem, contents) = util::read_u16(contents)?;
+                let (kdf, contents) = util::read_u16(contents)?;
+                let (aead, contents) = util::read_u16(contents)?;
+                let (public_key, contents) = util::read_vec16(contents)?;
+                if public_key.is_empty() || !contents.is_empty() {
+                    return Err(HpkeError::InvalidInput);
+                }
+                let config = Self::new(
+                    KemAlgorithm::from_id(kem),
+                    KdfAlgorithm::from_id(kdf),
+                    AeadAlgorithm::from_id(aead),
+                    HpkePublicKey::new(public_key.to_vec()),
+                );
+                if !matches!(config.kem, KemAlgorithm::Unknown(_))
+                    && !matches!(config.kdf, KdfAlgorithm::Unknown(_))
+                    && !matches!(config.aead, AeadAlgorithm::Unknown(_))
+                {
+                    configs.push(config);
+                }
+            }
+            configs_bytes = rest;
+        }
+        Ok(configs)
+    }
+
+    /// Encode the `configs` as `ObliviousDoHConfigs`.
+    pub fn encode_list(configs: &[Self]) -> Result<Vec<u8>, HpkeError> {
+        let mut configs_bytes = Vec::new();
+        for config in configs {
+            configs_bytes.extend_from_slice(&ODOH_VERSION.to_be_bytes());
+            util::write_vec16(&mut configs_bytes, &config.contents()?)?;
+        }
+        let mut list = Vec::new();
+        util::write_vec16(&mut list, &configs_bytes)?;
+        Ok(list)
+    }
+}
+
+/// Derive the response key and nonce from the query `context`.
+fn derive_secrets<Crypto: HpkeCrypto>(
+    context: &Context<Crypto>,
+    query_plain: &[u8],
+    response_nonce: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), HpkeError> {
+    let crypto_error = |e| HpkeError::CryptoError(format!("Crypto error: {}", e));
+    let suite = context.suite();
+    let key_len = Crypto::aead_key_length(suite.aead);
+    let secret = context.export(b"odoh response", key_len)?;
+    let nonce_len = u16::try_from(response_nonce.len()).map_err(|_| HpkeError::InvalidInput)?;
+    let salt = util::concat(&[query_plain, &nonce_len.to_be_bytes(), response_nonce]);
+    let prk = Crypto::kdf_extract(suite.kdf, &salt, &secret).map_err(crypto_error)?;
+    let key = Crypto::kdf_expand(suite.kdf, &prk, b"odoh key", key_len).map_err(crypto_error)?;
+    let nonce = Crypto::kdf_expand(
+        suite.kdf,
+        &prk,
+        b"odoh nonce",
+        Crypto::aead_nonce_length(suite.aead),
+    )
+    .map_err(crypto_error)?;
+    Ok((key, nonce))
+}
+
+/// The additional data of a response with the `response_nonce`.
+fn response_aad(response_nonce: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let mut aad = vec![RESPONSE];
+    util::write_vec16(&mut aad, response_nonce)?;
+    Ok(aad)
+}
+
+/// The state of a client for decrypting the response to its query.
+pub struct QueryContext<Crypto: 'static + HpkeCrypto> {
+    context: SenderContext<Crypto>,
+    query_plain: Vec<u8>,
+}
+
+/// Encrypt the `dns_message` with `padding_len` bytes of padding to the
+/// `config` (client).
+///
+/// Returns the encrypted `ObliviousDoHMessage` and the state for decrypting
+/// the response.
+pub fn encrypt_query<Crypto: HpkeCrypto>(
+    config: &OdohConfig,
+    dns_message: &[u8],
+    padding_len: usize,
+) -> Result<(Vec<u8>, QueryContext<Crypto>), HpkeError> {
+    let key_id = config.key_id::<Crypto>()?;
+    let query_plain = encode_plaintext(dns_message, padding_len)?;
+    let (enc, mut context) = config.suite().into_hpke::<Crypto>().setup_sender(
+        &config.public_key,
+        b"odoh query",
+        None,
+        None,
+        None,
+    )?;
+    let mut aad = vec![QUERY];
+    util::write_vec16(&mut aad, &key_id)?;
+    let ct = context.seal(&aad, &query_plain)?;
+    let query = encode_message(
+        QUERY,
+        &key_id,
+        &util::concat(&[enc.as_slice(), ct.as_slice()]),
+    )?;
+    Ok((
+        query,
+        QueryContext {
+            context,
+            query_plain,
+        },
+    ))
+}
+
+impl<Crypto: HpkeCrypto> QueryContext<Crypto> {
+    /// Decrypt the `response` to the query into the DNS message.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `response` is malformed,
+    /// an [`HpkeError::OpenError`] if it can't be decrypted, and an
+    /// [`HpkeError::ExportOnlyMode`] for the export-only AEAD.
+    pub fn decrypt_response(self, response: &[u8]) -> Result<Vec<u8>, HpkeError> {
+        self.context.suite().check_aead()?;
+        let (response_nonce, ct) = decode_message(RESPONSE, response)?;
+        let (key, nonce) = derive_secrets(&self.context, &self.query_plain, response_nonce)?;
+        let plaintext = Crypto::aead_open(
+            self.context.aead(),
+            &key,
+            &nonce,
+            &response_aad(response_nonce)?,
+            ct,
+        )?;
+        decode_plaintext(&plaintext)
+    }
+}
+
+/// The state of a target for encrypting the response to a query.
+pub struct ResponseContext<Crypto: 'static + HpkeCrypto> {
+    context: ReceiverContext<Crypto>,
+    query_plain: Vec<u8>,
+}
+
+/// Decrypt the `query` to the `config` with its private key `sk_r`
+/// (target).
+///
+/// Returns the DNS message and the state for encrypting the response.
+/// Returns an [`HpkeError::InvalidConfig`] if the query is for another key
+/// identifier and an [`HpkeError::InvalidInput`] if it is malformed.
+pub fn decrypt_query<Crypto: 'static + HpkeCrypto>(
+    config: &OdohConfig,
+    sk_r: &HpkePrivateKey,
+    query: &[u8],
+) -> Result<(Vec<u8>, ResponseContext<Crypto>), HpkeError> {
+    let (key_id, encrypted_message) = decode_message(QUERY, query)?;
+    if key_id != config.key_id::<Crypto>()? {
+        return Err(HpkeError::InvalidConfig);
+    }
+    let (enc, ct) = util::split(encrypted_message, config.kem.encapsulated_key_len())?;
+    let enc = EncapsulatedSecret::new(config.kem, enc.to_vec())?;
+    let mut context =
+        config
+            .suite()
+            .setup_receiver::<Crypto>(&enc, sk_r, b"odoh query", None, None, None)?;
+    let mut aad = vec![QUERY];
+    util::write_vec16(&mut aad, key_id)?;
+    let query_plain = context.open(&aad, &Ciphertext::new(config.aead, ct.to_vec())?)?;
+    let dns_message = decode_plaintext(&query_plain)?;
+    Ok((
+        dns_message,
+        ResponseContext {
+            context,
+            query_plain,
+        },
+    ))
+}
+
+impl<Crypto: HpkeCrypto> ResponseContext<Crypto> {
+    /// Encrypt the `dns_message` with `padding_len` bytes of padding as
+    /// response to the query, with a response nonce drawn from `rng`.
+    ///
+    /// Returns an [`HpkeError::ExportOnlyMode`] for the export-only AEAD.
+    pub fn encrypt_response(
+        self,
+        rng: &mut impl CryptoRng,
+        dns_message: &[u8],
+        padding_len: usize,
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.context.suite().check_aead()?;
+        let aead = self.context.aead();
+        let mut response_nonce =
+            vec![0u8; Crypto::aead_key_length(aead).max(Crypto::aead_nonce_length(aead))];
+        rng.fill_bytes(&mut response_nonce);
+        let (key, nonce) = derive_secrets(&self.context, &self.query_plain, &response_nonce)?;
+        let ct = Crypto::aead_seal(
+            aead,
+            &key,
+            &nonce,
+            &response_aad(&response_nonce)?,
+            &encode_plaintext(dns_message, padding_len)?,
+        )?;
+        encode_message(RESPONSE, &response_nonce, &ct)
+    }
+}
@@ -0,0 +1,363 @@
+//! # Oblivious HTTP
+//!
+//! The HPKE encapsulation of Oblivious HTTP (RFC 9458) for `message/bhttp`
+//! requests and responses.
+//!
+//! - A gateway publishes its [`KeyConfig`]s, e.g. as
+//!   `application/ohttp-keys` with [`KeyConfig::encode_list`].
+//! - A client encapsulates a request to a key configuration with
+//!   [`encapsulate_request`] and keeps the returned [`ClientResponse`] to
+//!   decapsulate the response.
+//! - The gateway decapsulates the request with [`decapsulate_request`] and
+//!   encapsulates the response with the returned [`ServerResponse`].
+//!
+//! The response is protected with the [response context](crate::response)
+//! derived from the request context.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, ohttp::{self, KeyConfig}, Hpke, Mode};
+//! use hpke_rs_crypto::HpkeCrypto;
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! // The gateway publishes its key configurations.
+//! let mut keys = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::Aes128Gcm,
+//! );
+//! let (sk_r, pk_r) = keys.generate_key_pair()?.into_keys();
+//! let gateway_config = KeyConfig::new(
+//!     1,
+//!     KemAlgorithm::DhKem25519,
+//!     pk_r,
+//!     vec![(KdfAlgorithm::HkdfSha256, AeadAlgorithm::Aes128Gcm)],
+//! );
+//! let ohttp_keys = KeyConfig::encode_list(&[gateway_config.clone()])?;
+//!
+//! // Client
+//! let config = &KeyConfig::decode_list(&ohttp_keys)?[0];
+//! let mut hpke = config.hpke::<HpkeRustCrypto>()?;
+//! let (enc_request, client_response) = ohttp::encapsulate_request(&mut hpke, config, b"request")?;
+//!
+//! // Gateway
+//! let (request, server_response) =
+//!     ohttp::decapsulate_request::<HpkeRustCrypto>(&gateway_config, &sk_r, &enc_request)?;
+//! assert_eq!(request, b"request");
+//! let enc_response = server_response.encapsulate(&mut HpkeRustCrypto::prng(), b"response")?;
+//!
+//! // Client
+//! let response = client_response.decapsulate(&enc_response)?;
+//! assert_eq!(response, b"response");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+    CryptoRng, HpkeCrypto,
+};
+
+use crate::{
+    response::OHTTP_RESPONSE_LABEL, util, AlgorithmKind, Ciphertext, EncapsulatedSecret, Hpke,
+    HpkeError, HpkePrivateKey, HpkePublicKey, HpkeSuite, Mode, ReceiverContext, SenderContext,
+};
+
+/// The media type of requests, used in the HPKE `info`.
+pub const OHTTP_REQUEST_LABEL: &[u8] = b"message/bhttp request";
+
+/// The length of the request header in bytes.
+const HEADER_LEN: usize = 7;
+
+/// An Oblivious HTTP key configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyConfig {
+    /// The key identifier.
+    pub key_id: u8,
+
+    /// The KEM of the public key.
+    pub kem: KemAlgorithm,
+
+    /// The public key.
+    pub public_key: HpkePublicKey,
+
+    /// The supported KDF and AEAD pairs, in order of preference.
+    pub symmetric: Vec<(KdfAlgorithm, AeadAlgorithm)>,
+}
+
+impl KeyConfig {
+    /// Create a key configuration for the `public_key` of the `kem`.
+    pub fn new(
+        key_id: u8,
+        kem: KemAlgorithm,
+        public_key: HpkePublicKey,
+        symmetric: Vec<(KdfAlgorithm, AeadAlgorithm)>,
+    ) -> Self {
+        Self {
+            key_id,
+            kem,
+            public_key,
+            symmetric,
+        }
+    }
+
+    /// Encode this key configuration.
+    ///
+    /// ```text
+    /// key_id (1) || kem_id (2) || public_key (Npk) ||
+    ///     symmetric_len (2) || (kdf_id (2) || aead_id (2))*
+    /// ```
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the public key is not an
+    /// uncompressed key of the KEM, or there are no or too many symmetric
+    /// algorithms.
+    pub fn encode(&self) -> Result<Vec<u8>, HpkeError> {
+        self.public_key.check_kem(self.kem)?;
+        if self.public_key.as_slice().len() != self.kem.public_key_len() {
+            return Err(HpkeError::InvalidConfig);
+        }
+        let symmetric_len = self
+            .symmetric
+            .len()
+            .checked_mul(4)
+            .and_then(|len| u16::try_from(len).ok())
+            .filter(|&len| len > 0)
+            .ok_or(HpkeError::InvalidConfig)?;
+
+        let mut config = Vec::new();
+        config.push(self.key_id);
+        config.extend_from_slice(&self.kem.id().to_be_bytes());
+        config.extend_from_slice(self.public_key.as_slice());
+        config.extend_from_slice(&symmetric_len.to_be_bytes());
+        for (kdf, aead) in &self.symmetric {
+            config.extend_from_slice(&kdf.id().to_be_bytes());
+            config.extend_from_slice(&aead.id().to_be_bytes());
+        }
+        Ok(config)
+    }
+
+    /// Decode a key configuration encoded with [`KeyConfig::encode`].
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed
+    /// and an [`HpkeError::UnsupportedAlgorithm`] for an unknown KEM.
+    pub fn decode(bytes: &[u8]) -> Result<Self, HpkeError> {
+        let (&key_id, rest) = bytes.split_first().ok_or(HpkeError::InvalidInput)?;
+        let (kem, rest) = util::read_u16(rest)?;
+        let kem = KemAlgorithm::from_id(kem);
+        if let KemAlgorithm::Unknown(id) = kem {
+            return Err(HpkeError::UnsupportedAlgorithm {
+                kind: AlgorithmKind::Kem,
+                id: id.get(),
+            });
+        }
+        let (public_key, rest) = util::split(rest, kem.public_key_len())?;
+        let public_key = HpkePublicKey::new_checked(kem, public_key.to_vec())?;
+        let (symmetric_len, rest) = util::read_u16(rest)?;
+        let (symmetric_bytes, rest) = util::split(rest, symmetric_len.into())?;
+        if !rest.is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let chunks = symmetric_bytes.chunks_exact(4);
+        if symmetric_bytes.is_empty() || !chunks.remainder().is_empty() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let symmetric = chunks
+            .map(|chunk| {
+                let (kdf, aead) = util::read_u16(chunk)?;
+                let (aead, _) = util::read_u16(aead)?;
+                Ok((KdfAlgorithm::from_id(kdf), AeadAlgorithm::from_id(aead)))
+            })
+            .collect::<Result<_, HpkeError>>()?;
+        Ok(Self::new(key_id, kem, public_key, symmetric))
+    }
+
+    /// Encode the key configurations as `application/ohttp-keys`, i.e. each
+    /// prefixed with its big-endian `u16` length.
+    pub fn encode_list(configs: &[Self]) -> Result<Vec<u8>, HpkeError> {
+        let mut list = Vec::new();
+        for config in configs {
+            let config = config.encode()?;
+            util::write_vec16(&mut list, &config)?;
+        }
+        Ok(list)
+    }
+
+    /// Decode `application/ohttp-keys` encoded with
+    /// [`KeyConfig::encode_list`].
+    ///
+    /// Key configurations with unknown KEMs are skipped.
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed.
+    pub fn decode_list(mut bytes: &[u8]) -> Result<Vec<Self>, HpkeError> {
+        let mut configs = Vec::new();
+        while !bytes.is_empty() {
+            let (config, rest) = util::read_vec16(bytes)?;
+            match Self::decode(config) {
+                Ok(config) => configs.push(config),
+                Err(HpkeError::UnsupportedAlgorithm { .. }) => (),
+                Err(e) => return Err(e),
+            }
+            bytes = rest;
+        }
+        Ok(configs)
+    }
+
+    /// Create an [`Hpke`] configuration in base mode for the KEM and the
+    /// first KDF and AEAD pair that the `Crypto` provider supports.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if the provider doesn't
+    /// support the KEM and an [`HpkeError::InvalidConfig`] if it supports none
+    /// of the pairs.
+    pub fn hpke<Crypto: HpkeCrypto>(&self) -> Result<Hpke<Crypto>, HpkeError> {
+        Crypto::supports_kem(self.kem).map_err(|_| HpkeError::UnsupportedAlgorithm {
+            kind: AlgorithmKind::Kem,
+            id: self.kem.id(),
+        })?;
+        let &(kdf, aead) = self
+            .symmetric
+            .iter()
+            .find(|&&(kdf, aead)| {
+                aead != AeadAlgorithm::HpkeExport
+                    && Crypto::supports_kdf(kdf).is_ok()
+                    && Crypto::supports_aead(aead).is_ok()
+            })
+            .ok_or(HpkeError::InvalidConfig)?;
+        Ok(Hpke::new(Mode::Base, self.kem, kdf, aead))
+    }
+}
+
+/// Build the request header for the `key_id` and `suite`.
+///
+/// ```text
+/// key_id (1) || kem_id (2) || kdf_id (2) || aead_id (2)
+/// ```
+fn header(key_id: u8, suite: &HpkeSuite) -> Vec<u8> {
+    util::concat(&[
+        &[key_id],
+        &suite.kem.id().to_be_bytes(),
+        &suite.kdf.id().to_be_bytes(),
+        &suite.aead.id().to_be_bytes(),
+    ])
+}
+
+/// Build the HPKE `info` for the request `header`.
+fn info(header: &[u8]) -> Vec<u8> {
+    util::concat(&[OHTTP_REQUEST_LABEL, &[0], header])
+}
+
+/// Check that the `suite` is a base mode suite of the `config`.
+fn check_suite(config: &KeyConfig, suite: &HpkeSuite) -> Result<(), HpkeError> {
+    if suite.mode != Mode::Base
+        || suite.kem != config.kem
+        || !config.symmetric.contains(&(suite.kdf, suite.aead))
+    {
+        return Err(HpkeError::InvalidConfig);
+    }
+    Ok(())
+}
+
+/// The state of a client for decapsulating the response to its request.
+pub struct ClientResponse<Crypto: 'static + HpkeCrypto> {
+    enc: EncapsulatedSecret,
+    context: SenderContext<Crypto>,
+}
+
+/// Encapsulate the `request` to the `config` (client).
+///
+/// Returns the encapsulated request and the state for decapsulating the
+/// response.
+/// Returns an [`HpkeError::InvalidConfig`] if the `hpke` configuration is not
+/// in base mode or its suite is not in the `config`, see
+/// [`KeyConfig::hpke`].
+pub fn encapsulate_request<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    config: &KeyConfig,
+    request: &[u8],
+) -> Result<(Vec<u8>, ClientResponse<Crypto>), HpkeError> {
+    let suite = hpke.suite();
+    check_suite(config, &suite)?;
+    let header = header(config.key_id, &suite);
+    let (enc, mut context) =
+        hpke.setup_sender(&config.public_key, &info(&header), None, None, None)?;
+    let ct = context.seal(&[], request)?;
+    let enc_request = util::concat(&[&header, enc.as_slice(), ct.as_slice()]);
+    Ok((enc_request, ClientResponse { enc, context }))
+}
+
+impl<Crypto: HpkeCrypto> ClientResponse<Crypto> {
+    /// Decapsulate the encapsulated response to the request.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `enc_response` is too
+    /// short and an [`HpkeError::OpenError`] if it can't be decrypted.
+    pub fn decapsulate(self, enc_response: &[u8]) -> Result<Vec<u8>, HpkeError> {
+        let (response_nonce, ct) = util::split(enc_response, self.context.response_nonce_len())?;
+        let mut context =
+            self.context
+                .response_context(OHTTP_RESPONSE_LABEL, &self.enc, response_nonce)?;
+        context.open(&[], &Ciphertext::new(context.aead(), ct.to_vec())?)
+    }
+}
+
+/// The state of a gateway for encapsulating the response to a request.
+pub struct ServerResponse<Crypto: 'static + HpkeCrypto> {
+    enc: EncapsulatedSecret,
+    context: ReceiverContext<Crypto>,
+}
+
+/// Get the key identifier of the encapsulated request, e.g. to select the
+/// key configuration and private key of the gateway.
+pub fn request_key_id(enc_request: &[u8]) -> Result<u8, HpkeError> {
+    enc_request.first().copied().ok_or(HpkeError::InvalidInput)
+}
+
+/// Decapsulate the `enc_request` to the `config` with its private key `sk_r`
+/// (gateway).
+///
+/// Returns the request and the state for encapsulating the response.
+/// Returns an [`HpkeError::InvalidConfig`] if the request is for another key
+/// identifier, KEM or a suite that is not in the `config`, and an
+/// [`HpkeError::InvalidInput`] if it is malformed.
+pub fn decapsulate_request<Crypto: 'static + HpkeCrypto>(
+    config: &KeyConfig,
+    sk_r: &HpkePrivateKey,
+    enc_request: &[u8],
+) -> Result<(Vec<u8>, ServerResponse<Crypto>), HpkeError> {
+    let (header_bytes, rest) = util::split(enc_request, HEADER_LEN)?;
+    let (&key_id, ids) = header_bytes.split_first().ok_or(HpkeError::InvalidInput)?;
+    let (kem, ids) = util::read_u16(ids)?;
+    let (kdf, ids) = util::read_u16(ids)?;
+    let (aead, _) = util::read_u16(ids)?;
+    let suite = HpkeSuite::new(
+        Mode::Base,
+        KemAlgorithm::from_id(kem),
+        KdfAlgorithm::from_id(kdf),
+        AeadAlgorithm::from_id(aead),
+    );
+    if key_id != config.key_id {
+        return Err(HpkeError::InvalidConfig);
+    }
+    check_suite(config, &suite)?;
+
+    let (enc, ct) = util::split(rest, suite.kem.encapsulated_key_len())?;
+    let enc = EncapsulatedSecret::new(suite.kem, enc.to_vec())?;
+    let mut context =
+        suite.setup_receiver::<Crypto>(&enc, sk_r, &info(header_bytes), None, None, None)?;
+    let request = context.open(&[], &Ciphertext::new(suite.aead, ct.to_vec())?)?;
+    Ok((request, ServerResponse { enc, context }))
+}
+
+impl<Crypto: HpkeCrypto> ServerResponse<Crypto> {
+    /// Encapsulate the `response` to the request with a response nonce drawn
+    /// from `rng`.
+    pub fn encapsulate(
+        self,
+        rng: &mut impl CryptoRng,
+        response: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        let (response_nonce, mut context) =
+            self.context
+                .response_context(OHTTP_RESPONSE_LABEL, &self.enc, rng)?;
+        let ct = context.seal(&[], response)?;
+        Ok(util::concat(&[&response_nonce, ct.as_slice()]))
+    }
+}
@@ -0,0 +1,236 @@
+//! # Plaintext Padding
+//!
+//! An opt-in [`PaddingPolicy`] hides the exact length of the plain texts
+//! from observers of the ciphertexts, as done by ECH and OHTTP deployments.
+//!
+//! When a policy is set on a [`Context`], [`Context::seal`] pads every plain
+//! text before sealing and [`Context::open`] removes the padding after
+//! opening.
+//! Policies set on an [`Hpke`] configuration with [`Hpke::set_padding`] are
+//! applied to all contexts set up with it, including the single shot APIs.
+//! Sender and receiver have to agree on using padding, but not on the policy.
+//!
+//! The plain text is padded with a `0x80` delimiter followed by zeros up to
+//! the padded length, i.e. ISO/IEC 7816-4 padding:
+//!
+//! ```text
+//! padded = pt || 0x80 || 0x00 * (padded_len(len(pt) + 1) - len(pt) - 1)
+//! ```
+//!
+//! The in place APIs, e.g. [`Context::seal_in_place_detached`], can't change
+//! the length of the buffer and fail with an [`HpkeError::InvalidConfig`]
+//! when padding is enabled.
+
+use alloc::{borrow::Cow, vec, vec::Vec};
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{util, Context, Hpke, HpkeError};
+
+/// The delimiter between the plain text and the padding.
+const DELIMITER: u8 = 0x80;
+
+/// A policy for the length of padded plain texts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaddingPolicy {
+    /// Pad to a multiple of the given number of bytes.
+    Multiple(usize),
+
+    /// Pad to the smallest of the given bucket sizes that fits.
+    /// Plain texts that are longer than the largest bucket are padded to a
+    /// multiple of the largest bucket.
+    Buckets(Vec<usize>),
+
+    /// Padmé padding, which leaks at most `O(log log n)` bits of the length
+    /// `n` with an overhead of at most 12%.
+    Padme,
+}
+
+impl PaddingPolicy {
+    /// Check that the policy is well formed, i.e. that the multiple and the
+    /// buckets are not zero and that there is at least one bucket.
+    pub(crate) fn check(&self) -> Result<(), HpkeError> {
+        let valid = match self {
+            Self::Multiple(multiple) => *multiple > 0,
+            Self::Buckets(buckets) => !buckets.is_empty() && !buckets.contains(&0),
+            Self::Padme => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(HpkeError::InvalidConfig)
+        }
+    }
+
+    /// The padded length for a length of `len` bytes.
+    ///
+    /// Note that the padding adds a delimiter byte, such that a plain text
+    /// of length `n` is padded to `padded_len(n + 1)` bytes.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the policy is malformed and
+    /// an [`HpkeError::InputTooLong`] if the padded length overflows.
+    pub fn padded_len(&self, len: usize) -> Result<usize, HpkeError> {
+        self.check()?;
+        match self {
+            Self::Multiple(multiple) => round_up(len, *multiple),
+            Self::Buckets(buckets) => match buckets.iter().filter(|&&b| b >= len).min() {
+                Some(bucket) => Ok(*bucket),
+                None => round_up(len, buckets.iter().copied().max().unwrap_or(1)),
+            },
+            Self::Padme => padme(len),
+        }
+    }
+
+    /// Pad the `plain_txt` according to this policy.
+    pub(crate) fn pad(&self, plain_txt: &[u8]) -> Result<Vec<u8>, HpkeError> {
+        let len = plain_txt
+            .len()
+            .checked_add(1)
+            .ok_or(HpkeError::InputTooLong)?;
+        let zeros = self.padded_len(len)?.saturating_sub(len);
+        Ok(util::concat(&[plain_txt, &[DELIMITER], &vec![0; zeros]]))
+    }
+}
+
+/// Round `len` up to a multiple of `multiple`.
+fn round_up(len: usize, multiple: usize) -> Result<usize, HpkeError> {
+    let remainder = len.checked_rem(multiple).ok_or(HpkeError::InvalidConfig)?;
+    if remainder == 0 {
+        return Ok(len);
+    }
+    len.checked_add(multiple.saturating_sub(remainder))
+        .ok_or(HpkeError::InputTooLong)
+}
+
+/// The Padmé length for `len`.
+///
+/// ```text
+/// E = floor(log2(len)), S = floor(log2(E)) + 1
+/// padme(len) = round_up(len, 2^(E - S))
+/// ```
+fn padme(len: usize) -> Result<usize, HpkeError> {
+    if len < 2 {
+        return Ok(len);
+    }
+    let e = len.ilog2();
+    let s = e.ilog2().saturating_add(1);
+    round_up(len, 1usize << e.saturating_sub(s))
+}
+
+/// Remove the padding from the padded plain text `padded`.
+///
+/// Returns an [`HpkeError::InvalidInput`] if there is no delimiter.
+pub(crate) fn unpad(mut padded: Vec<u8>) -> Result<Vec<u8>, HpkeError> {
+    let len = unpadded_len(&padded)?;
+    padded.truncate(len);
+    Ok(padded)
+}
+
+/// The length of the `padded` plain text without the padding.
+fn unpadded_len(padded: &[u8]) -> Result<usize, HpkeError> {
+    let delimiter = padded
+        .iter()
+        .rposition(|&b| b != 0)
+        .ok_or(HpkeError::InvalidInput)?;
+    if padded.get(delimiter) != Some(&DELIMITER) {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(delimiter)
+}
+
+impl<Crypto> Context<Crypto> {
+    /// Get the padding policy of this context, if any.
+    pub fn padding(&self) -> Option<&PaddingPolicy> {
+        self.padding.as_ref()
+    }
+
+    /// Set the padding policy of this context, or disable padding with
+    /// `None`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the policy is malformed.
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        padding.as_ref().map(PaddingPolicy::check).transpose()?;
+        self.padding = padding;
+        Ok(())
+    }
+
+    /// Pad the `plain_txt` if a padding policy is set.
+    pub(crate) fn pad<'a>(&self, plain_txt: &'a [u8]) -> Result<Cow<'a, [u8]>, HpkeError> {
+        Ok(match &self.padding {
+            Some(padding) => padding.pad(plain_txt)?.into(),
+            None => plain_txt.into(),
+        })
+    }
+
+    /// The length of the plain text of length `len` after padding.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn padded_len(&self, len: usize) -> Result<usize, HpkeError> {
+        match &self.padding {
+            Some(padding) => padding.padded_len(len.checked_add(1).ok_or(HpkeError::InputTooLong)?),
+            None => Ok(len),
+        }
+    }
+
+    /// Write `plain_txt` into the `buffer` of its
+    /// [padded length](Context::padded_len) and pad it if a padding policy is
+    /// set.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn pad_into(&self, plain_txt: &[u8], buffer: &mut [u8]) -> Result<(), HpkeError> {
+        buffer
+            .get_mut(..plain_txt.len())
+            .ok_or(HpkeError::InvalidInput)?
+            .copy_from_slice(plain_txt);
+        if self.padding.is_some() {
+            let (delimiter, zeros) = buffer
+                .get_mut(plain_txt.len()..)
+                .and_then(<[u8]>::split_first_mut)
+                .ok_or(HpkeError::InvalidInput)?;
+            *delimiter = DELIMITER;
+            zeros.fill(0);
+        }
+        Ok(())
+    }
+
+    /// The length of `plain_txt` without the padding if a padding policy is
+    /// set.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn unpadded_len(&self, plain_txt: &[u8]) -> Result<usize, HpkeError> {
+        match self.padding {
+            Some(_) => unpadded_len(plain_txt),
+            None => Ok(plain_txt.len()),
+        }
+    }
+
+    /// Remove the padding from `plain_txt` if a padding policy is set.
+    pub(crate) fn unpad(&self, plain_txt: Vec<u8>) -> Result<Vec<u8>, HpkeError> {
+        match self.padding {
+            Some(_) => unpad(plain_txt),
+            None => Ok(plain_txt),
+        }
+    }
+
+    /// Fail with an [`HpkeError::InvalidConfig`] if padding is enabled.
+    pub(crate) fn check_no_padding(&self) -> Result<(), HpkeError> {
+        match self.padding {
+            Some(_) => Err(HpkeError::InvalidConfig),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Get the padding policy of this configuration, if any.
+    pub fn padding(&self) -> Option<&PaddingPolicy> {
+        self.padding.as_ref()
+    }
+
+    /// Set the padding policy for all contexts set up with this
+    /// configuration, or disable padding with `None`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the policy is malformed.
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        padding.as_ref().map(PaddingPolicy::check).transpose()?;
+        self.padding = padding;
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+//! # PEM
+//!
+//! PEM armor (RFC 7468) for the [DER encodings](crate::pkcs8) of keys, such
+//! that HPKE keys can be pasted into configuration files like TLS keys.
+//!
+//! - Public keys use the `PUBLIC KEY` label (SubjectPublicKeyInfo).
+//! - Private keys use the `PRIVATE KEY` label (PKCS#8).
+//! - Key pairs are the `PRIVATE KEY` followed by the `PUBLIC KEY`, as
+//!   written by concatenating the files of `openssl genpkey` and
+//!   `openssl pkey -pubout`.
+//!
+//! ```no_run
+//! use hpke_rs::HpkeKeyPair;
+//!
+//! let key_pair = HpkeKeyPair::from_pem(&std::fs::read_to_string("hpke.pem")?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::{format, string::String, vec::Vec};
+
+use hpke_rs_crypto::types::KemAlgorithm;
+use pkcs8::der::pem::{decode_vec, encode_string, LineEnding};
+use zeroize::Zeroize;
+
+use crate::{HpkeError, HpkeKeyPair, HpkePrivateKey, HpkePublicKey};
+
+/// The label of public keys.
+const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+/// The label of private keys.
+const PRIVATE_KEY_LABEL: &str = "PRIVATE KEY";
+
+/// Decode the single PEM block in `pem` with the `label`.
+fn decode(label: &str, pem: &str) -> Result<Vec<u8>, HpkeError> {
+    let (pem_label, der) =
+        decode_vec(pem.trim().as_bytes()).map_err(|_| HpkeError::InvalidInput)?;
+    if pem_label != label {
+        let mut der = der;
+        der.zeroize();
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(der)
+}
+
+/// Encode `der` as PEM block with the `label`.
+fn encode(label: &str, der: &[u8]) -> Result<String, HpkeError> {
+    encode_string(label, LineEnding::LF, der).map_err(|_| HpkeError::InvalidInput)
+}
+
+/// Split `pem` into its blocks.
+fn split_blocks(pem: &str) -> Result<Vec<String>, HpkeError> {
+    let mut blocks = Vec::new();
+    let mut rest = pem.trim_start();
+    while !rest.is_empty() {
+        let (block, end) = rest
+            .split_once("-----END ")
+            .ok_or(HpkeError::InvalidInput)?;
+        let (label, after) = end.split_once("-----").ok_or(HpkeError::InvalidInput)?;
+        blocks.push(format!("{}-----END {}-----", block, label));
+        rest = after.trim_start();
+    }
+    Ok(blocks)
+}
+
+impl HpkePublicKey {
+    /// Decode a PEM encoded `PUBLIC KEY`.
+    ///
+    /// See [`HpkePublicKey::from_public_key_der`].
+    pub fn from_pem(pem: &str) -> Result<Self, HpkeError> {
+        Self::from_public_key_der(&decode(PUBLIC_KEY_LABEL, pem)?)
+    }
+
+    /// Encode this public key for the `kem` as PEM encoded `PUBLIC KEY`.
+    ///
+    /// See [`HpkePublicKey::to_public_key_der`].
+    pub fn to_pem(&self, kem: KemAlgorithm) -> Result<String, HpkeError> {
+        encode(PUBLIC_KEY_LABEL, &self.to_public_key_der(kem)?)
+    }
+}
+
+impl HpkePrivateKey {
+    /// Decode a PEM encoded `PRIVATE KEY`.
+    ///
+    /// See [`HpkePrivateKey::from_pkcs8_der`].
+    pub fn from_pem(pem: &str) -> Result<Self, HpkeError> {
+        let mut der = decode(PRIVATE_KEY_LABEL, pem)?;
+        let sk = Self::from_pkcs8_der(&der);
+        der.zeroize();
+        sk
+    }
+
+    /// Encode this private key for the `kem` as PEM encoded `PRIVATE KEY`.
+    ///
+    /// See [`HpkePrivateKey::to_pkcs8_der`].
+    #[cfg(feature = "hazmat")]
+    pub fn to_pem(&self, kem: KemAlgorithm) -> Result<String, HpkeError> {
+        let mut der = self.to_pkcs8_der(kem)?;
+        let pem = encode(PRIVATE_KEY_LABEL, &der);
+        der.zeroize();
+        pem
+    }
+}
+
+impl HpkeKeyPair {
+    /// Decode a PEM encoded key pair, i.e. a `PRIVATE KEY` and a
+    /// `PUBLIC KEY` in any order.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if there is not exactly one key
+    /// of each kind or the keys are for different KEMs.
+    pub fn from_pem(pem: &str) -> Result<Self, HpkeError> {
+        let mut private_key = None;
+        let mut public_key = None;
+        for mut block in split_blocks(pem)? {
+            let key = if block.contains(PUBLIC_KEY_LABEL) {
+                public_key
+                    .replace(HpkePublicKey::from_pem(&block))
+                    .map(|_| ())
+            } else {
+                private_key
+                    .replace(HpkePrivateKey::from_pem(&block))
+                    .map(|_| ())
+            };
+            block.zeroize();
+            if key.is_some() {
+                return Err(HpkeError::InvalidInput);
+            }
+        }
+        let private_key = private_key.ok_or(HpkeError::InvalidInput)??;
+        let public_key = public_key.ok_or(HpkeError::InvalidInput)??;
+        if private_key.kem() != public_key.kem() {
+            return Err(HpkeError::InvalidInput);
+        }
+        Ok(Self::from_keys(private_key, public_key))
+    }
+
+    /// Encode this key pair for the `kem` as PEM encoded `PRIVATE KEY`
+    /// followed by the `PUBLIC KEY`.
+    #[cfg(feature = "hazmat")]
+    pub fn to_pem(&self, kem: KemAlgorithm) -> Result<String, HpkeError> {
+        let mut private_key = self.private_key().to_pem(kem)?;
+        let pem = self
+            .public_key()
+            .to_pem(kem)
+            .map(|public_key| format!("{}{}", private_key, public_key));
+        private_key.zeroize();
+        pem
+    }
+}
@@ -0,0 +1,205 @@
+//! # PKCS#8 and SubjectPublicKeyInfo
+//!
+//! Conversions between HPKE keys and the DER encodings of PKCS#8 private keys
+//! (RFC 5958) and SubjectPublicKeyInfo public keys (RFC 5280), e.g. to load
+//! keys generated by OpenSSL or stored in standard key files.
+//!
+//! - X25519 and X448 keys use the algorithm identifiers of RFC 8410, and the
+//!   private key is a `CurvePrivateKey`.
+//! - Keys of the NIST curves and secp256k1 use `id-ecPublicKey` with the
+//!   named curve (RFC 5480), and the private key is an `ECPrivateKey`
+//!   (RFC 5915).
+//!
+//! X-Wing keys have no algorithm identifier yet and can't be converted.
+//! Decoded keys carry their KEM, see [`HpkePublicKey::kem`].
+//!
+//! ```no_run
+//! use hpke_rs::{HpkePrivateKey, HpkePublicKey};
+//!
+//! let sk_r = HpkePrivateKey::from_pkcs8_der(&std::fs::read("key.der")?)?;
+//! let pk_r = HpkePublicKey::from_public_key_der(&std::fs::read("pub.der")?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::types::KemAlgorithm;
+use pkcs8::{
+    der::{
+        asn1::{AnyRef, BitStringRef, OctetStringRef},
+        Decode, Encode,
+    },
+    spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef},
+    ObjectIdentifier, PrivateKeyInfo,
+};
+use sec1::{EcParameters, EcPrivateKey};
+#[cfg(feature = "hazmat")]
+use zeroize::Zeroize;
+
+use crate::{HpkeError, HpkePrivateKey, HpkePublicKey};
+
+/// `id-X25519` (RFC 8410)
+const ID_X25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.110");
+
+/// `id-X448` (RFC 8410)
+const ID_X448: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.111");
+
+/// `id-ecPublicKey` (RFC 5480)
+const ID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+
+/// `secp256r1` (RFC 5480)
+static SECP256R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+
+/// `secp384r1` (RFC 5480)
+static SECP384R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+
+/// `secp521r1` (RFC 5480)
+static SECP521R1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.35");
+
+/// `secp256k1` (SEC 2)
+static SECP256K1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.10");
+
+/// Get the named curve of the EC KEM `kem`.
+fn named_curve(kem: KemAlgorithm) -> Option<&'static ObjectIdentifier> {
+    match kem {
+        KemAlgorithm::DhKemP256 => Some(&SECP256R1),
+        KemAlgorithm::DhKemP384 => Some(&SECP384R1),
+        KemAlgorithm::DhKemP521 => Some(&SECP521R1),
+        KemAlgorithm::DhKemK256 => Some(&SECP256K1),
+        _ => None,
+    }
+}
+
+/// Get the algorithm identifier of the `kem`.
+///
+/// Returns an [`HpkeError::InvalidConfig`] for KEMs without one.
+fn algorithm_identifier(kem: KemAlgorithm) -> Result<AlgorithmIdentifierRef<'static>, HpkeError> {
+    let (oid, parameters) = match kem {
+        KemAlgorithm::DhKem25519 => (ID_X25519, None),
+        KemAlgorithm::DhKem448 => (ID_X448, None),
+        _ => {
+            let curve = named_curve(kem).ok_or(HpkeError::InvalidConfig)?;
+            (ID_EC_PUBLIC_KEY, Some(AnyRef::from(curve)))
+        }
+    };
+    Ok(AlgorithmIdentifierRef { oid, parameters })
+}
+
+/// Get the KEM of the `algorithm` identifier.
+///
+/// Returns an [`HpkeError::InvalidInput`] for unknown algorithms.
+fn kem_from_algorithm(algorithm: &AlgorithmIdentifierRef<'_>) -> Result<KemAlgorithm, HpkeError> {
+    if algorithm.oid == ID_X25519 {
+        return Ok(KemAlgorithm::DhKem25519);
+    }
+    if algorithm.oid == ID_X448 {
+        return Ok(KemAlgorithm::DhKem448);
+    }
+    if algorithm.oid != ID_EC_PUBLIC_KEY {
+        return Err(HpkeError::InvalidInput);
+    }
+    let curve = algorithm
+        .parameters_oid()
+        .map_err(|_| HpkeError::InvalidInput)?;
+    [
+        KemAlgorithm::DhKemP256,
+        KemAlgorithm::DhKemP384,
+        KemAlgorithm::DhKemP521,
+        KemAlgorithm::DhKemK256,
+    ]
+    .into_iter()
+    .find(|&kem| named_curve(kem) == Some(&curve))
+    .ok_or(HpkeError::InvalidInput)
+}
+
+impl HpkePublicKey {
+    /// Decode a DER encoded SubjectPublicKeyInfo.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed or
+    /// the algorithm is not a KEM of this crate, and an
+    /// [`HpkeError::InvalidKeyLength`] if the key doesn't have the length
+    /// required by the KEM.
+    pub fn from_public_key_der(der: &[u8]) -> Result<Self, HpkeError> {
+        let spki = SubjectPublicKeyInfoRef::from_der(der).map_err(|_| HpkeError::InvalidInput)?;
+        let kem = kem_from_algorithm(&spki.algorithm)?;
+        let pk = spki
+            .subject_public_key
+            .as_bytes()
+            .ok_or(HpkeError::InvalidInput)?;
+        Self::new_checked(kem, pk.to_vec())
+    }
+
+    /// Encode this public key for the `kem` as DER encoded
+    /// SubjectPublicKeyInfo.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM
+    /// or the `kem` has no algorithm identifier.
+    pub fn to_public_key_der(&self, kem: KemAlgorithm) -> Result<Vec<u8>, HpkeError> {
+        self.check_kem(kem)?;
+        let spki = SubjectPublicKeyInfoRef {
+            algorithm: algorithm_identifier(kem)?,
+            subject_public_key: BitStringRef::from_bytes(&self.value)
+                .map_err(|_| HpkeError::InvalidInput)?,
+        };
+        spki.to_der().map_err(|_| HpkeError::InvalidInput)
+    }
+}
+
+impl HpkePrivateKey {
+    /// Decode a DER encoded PKCS#8 private key.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the encoding is malformed or
+    /// the algorithm is not a KEM of this crate, and an
+    /// [`HpkeError::InvalidKeyLength`] if the key doesn't have the length
+    /// required by the KEM.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, HpkeError> {
+        let info = PrivateKeyInfo::from_der(der).map_err(|_| HpkeError::InvalidInput)?;
+        let kem = kem_from_algorithm(&info.algorithm)?;
+        let sk = match kem {
+            KemAlgorithm::DhKem25519 | KemAlgorithm::DhKem448 => {
+                OctetStringRef::from_der(info.private_key)
+                    .map_err(|_| HpkeError::InvalidInput)?
+                    .as_bytes()
+            }
+            _ => {
+                let sk = EcPrivateKey::from_der(info.private_key)
+                    .map_err(|_| HpkeError::InvalidInput)?;
+                if let Some(EcParameters::NamedCurve(curve)) = sk.parameters {
+                    if named_curve(kem) != Some(&curve) {
+                        return Err(HpkeError::InvalidInput);
+                    }
+                }
+                sk.private_key
+            }
+        };
+        Self::new_checked(kem, sk.to_vec())
+    }
+
+    /// Encode this private key for the `kem` as DER encoded PKCS#8 private
+    /// key.
+    ///
+    /// The encoding contains the private key in plain text and should be
+    /// zeroized after use.
+    /// Returns an [`HpkeError::InvalidConfig`] if the key is for another KEM
+    /// or the `kem` has no algorithm identifier.
+    #[cfg(feature = "hazmat")]
+    pub fn to_pkcs8_der(&self, kem: KemAlgorithm) -> Result<Vec<u8>, HpkeError> {
+        self.check_kem(kem)?;
+        let algorithm = algorithm_identifier(kem)?;
+        let private_key = match kem {
+            KemAlgorithm::DhKem25519 | KemAlgorithm::DhKem448 => {
+                OctetStringRef::new(self.bytes()?).and_then(|sk| sk.to_der())
+            }
+            _ => EcPrivateKey {
+                private_key: self.bytes()?,
+                parameters: None,
+                public_key: None,
+            }
+            .to_der(),
+        };
+        let mut private_key = private_key.map_err(|_| HpkeError::InvalidInput)?;
+        let der = PrivateKeyInfo::new(algorithm, &private_key).to_der();
+        private_key.zeroize();
+        der.map_err(|_| HpkeError::InvalidInput)
+    }
+}
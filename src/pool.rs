@@ -0,0 +1,185 @@
+//! # Context Pool
+//!
+//! A [`ContextPool`] caches sender [`Context`]s keyed by the recipient public
+//! key.
+//! Sealing many messages to the same recipient then only requires a single
+//! encapsulation instead of one per message.
+//!
+//! Each pooled context is a regular HPKE context.
+//! The recipient must therefore set up one receiver context per
+//! [`EncapsulatedSecret`] and open the ciphertexts in the order they were
+//! sealed.
+//!
+//! The pool is not shared between threads.
+//! High throughput servers should keep one pool per thread, e.g. in a
+//! `thread_local!`.
+//!
+//! Entries are rotated, i.e. a fresh encapsulation is performed, after they
+//! were used `max_uses` times.
+//! When the pool holds `max_entries` contexts, the oldest entry is evicted to
+//! make room for a new one.
+
+use alloc::vec::Vec;
+use std::collections::HashMap;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePublicKey, SenderContext};
+
+/// The default maximum number of contexts in a [`ContextPool`].
+pub const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+/// The default number of messages sealed with a pooled context before it is
+/// rotated.
+pub const DEFAULT_MAX_USES: u32 = 1 << 16;
+
+/// A pooled sender context.
+struct PoolEntry<Crypto: 'static + HpkeCrypto> {
+    enc: EncapsulatedSecret,
+    context: SenderContext<Crypto>,
+    uses: u32,
+    created: u64,
+}
+
+/// A pool of reusable sender contexts keyed by the recipient public key.
+///
+/// The pool uses the [`Hpke`] configuration it was created with.
+/// Only the base mode is supported because the pool doesn't hold any PSK or
+/// sender key material.
+pub struct ContextPool<Crypto: 'static + HpkeCrypto> {
+    hpke: Hpke<Crypto>,
+    info: Vec<u8>,
+    entries: HashMap<Vec<u8>, PoolEntry<Crypto>>,
+    max_entries: usize,
+    max_uses: u32,
+    counter: u64,
+}
+
+impl<Crypto: HpkeCrypto> core::fmt::Debug for ContextPool<Crypto> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ContextPool")
+            .field("hpke", &self.hpke.suite())
+            .field("entries", &self.entries.len())
+            .field("max_entries", &self.max_entries)
+            .field("max_uses", &self.max_uses)
+            .finish()
+    }
+}
+
+impl<Crypto: HpkeCrypto> ContextPool<Crypto> {
+    /// Create a new, empty pool for the given `hpke` configuration and `info`.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if `hpke` isn't in base mode.
+    pub fn new(hpke: Hpke<Crypto>, info: &[u8]) -> Result<Self, HpkeError> {
+        if hpke.mode != crate::Mode::Base {
+            return Err(HpkeError::InvalidConfig);
+        }
+        Ok(Self {
+            hpke,
+            info: info.to_vec(),
+            entries: HashMap::new(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_uses: DEFAULT_MAX_USES,
+            counter: 0,
+        })
+    }
+
+    /// Set the maximum number of pooled contexts.
+    ///
+    /// A `max_entries` of `0` disables pooling.
+    /// Excess entries are evicted, oldest first.
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        while self.entries.len() > max_entries {
+            self.evict_oldest();
+        }
+    }
+
+    /// Set the number of messages sealed with a pooled context before it is
+    /// rotated.
+    pub fn set_max_uses(&mut self, max_uses: u32) {
+        self.max_uses = max_uses;
+    }
+
+    /// The number of pooled contexts.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no pooled contexts.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop the pooled context for `pk_r`, if any.
+    /// The next [`ContextPool::seal`] to `pk_r` performs a fresh encapsulation.
+    pub fn rotate(&mut self, pk_r: &HpkePublicKey) {
+        self.entries.remove(pk_r.as_slice());
+    }
+
+    /// Drop all pooled contexts.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Seal `plain_txt` with `aad` to the recipient `pk_r`.
+    ///
+    /// The pooled context for `pk_r` is used if there is one.
+    /// Otherwise a new context is set up and added to the pool.
+    /// Returns the encapsulated secret of the context that was used together
+    /// with the ciphertext.
+    pub fn seal(
+        &mut self,
+        pk_r: &HpkePublicKey,
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+        let key = pk_r.as_slice();
+        let expired = self
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.uses >= self.max_uses);
+        if expired {
+            self.entries.remove(key);
+        }
+
+        if !self.entries.contains_key(key) {
+            let (enc, mut context) = self.hpke.setup_sender(pk_r, &self.info, None, None, None)?;
+            if self.max_entries == 0 {
+                let ctxt = context.seal(aad, plain_txt)?;
+                return Ok((enc, ctxt));
+            }
+            if self.entries.len() >= self.max_entries {
+                self.evict_oldest();
+            }
+            self.counter = self.counter.saturating_add(1);
+            self.entries.insert(
+                key.to_vec(),
+                PoolEntry {
+                    enc,
+                    context,
+                    uses: 0,
+                    created: self.counter,
+                },
+            );
+        }
+
+        // The entry was either found or inserted above.
+        let entry = self.entries.get_mut(key).ok_or(HpkeError::InvalidInput)?;
+        let ctxt = entry.context.seal(aad, plain_txt)?;
+        entry.uses = entry.uses.saturating_add(1);
+        Ok((entry.enc.clone(), ctxt))
+    }
+
+    /// Remove the entry that was created first.
+    fn evict_oldest(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.created)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! # Ratcheting Exporter
+//!
+//! A [`RatchetingExporter`] derives a one-way chain of exporter secrets from
+//! an HPKE [`Context`].
+//! Each epoch has its own secret, which is derived from the secret of the
+//! previous epoch and then replaces it.
+//! Compromising the exporter in some epoch therefore doesn't reveal secrets
+//! exported in earlier epochs, without requiring a new KEM operation.
+//!
+//! ```text
+//! secret_0         = Export("hpke-rs ratchet" || label, Nh)
+//! secret_{n+1}     = LabeledExpand(secret_n, "ratchet", I2OSP(n, 8), Nh)
+//! Export_n(ctx, L) = LabeledExpand(secret_n, "sec", ctx, L)
+//! ```
+//!
+//! **Note** that the forward secrecy only holds for secrets derived through
+//! the ratcheting exporter.
+//! The parent context still holds its exporter secret and must be dropped
+//! for the initial secret to be forward secure.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::HpkeCrypto;
+use zeroize::Zeroize;
+
+use crate::{kdf::labeled_expand, util, Context, HpkeError, HpkeSuite};
+
+const RATCHET_LABEL: &[u8] = b"hpke-rs ratchet";
+
+/// A one-way chain of exporter secrets.
+pub struct RatchetingExporter<Crypto: 'static + HpkeCrypto> {
+    secret: Vec<u8>,
+    epoch: u64,
+    suite: HpkeSuite,
+    phantom: PhantomData<Crypto>,
+}
+
+impl<Crypto: HpkeCrypto> core::fmt::Debug for RatchetingExporter<Crypto> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RatchetingExporter")
+            .field("secret", &"***")
+            .field("epoch", &self.epoch)
+            .field("suite", &self.suite)
+            .finish()
+    }
+}
+
+impl<Crypto: HpkeCrypto> Drop for RatchetingExporter<Crypto> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Create a [`RatchetingExporter`] for the given `label` from this context.
+    ///
+    /// The exporter starts in epoch `0`.
+    pub fn ratcheting_exporter(
+        &self,
+        label: &[u8],
+    ) -> Result<RatchetingExporter<Crypto>, HpkeError> {
+        let secret = self.export(
+            &util::concat(&[RATCHET_LABEL, label]),
+            Crypto::kdf_digest_length(self.suite.kdf),
+        )?;
+        Ok(RatchetingExporter {
+            secret,
+            epoch: 0,
+            suite: self.suite,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> RatchetingExporter<Crypto> {
+    /// Get the current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Export a secret of `length` bytes for the `exporter_context` in the
+    /// current epoch.
+    pub fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, HpkeError> {
+        Ok(labeled_expand::<Crypto>(
+            self.suite.kdf,
+            &self.secret,
+            &self.suite.suite_id(),
+            "sec",
+            exporter_context,
+            length,
+        )?)
+    }
+
+    /// Advance to the next epoch.
+    ///
+    /// The secret of the current epoch is erased.
+    /// Returns a [`HpkeError::MessageLimitReached`] if the epoch counter is
+    /// exhausted.
+    pub fn ratchet(&mut self) -> Result<(), HpkeError> {
+        let epoch = self
+            .epoch
+            .checked_add(1)
+            .ok_or(HpkeError::MessageLimitReached)?;
+        let mut next = labeled_expand::<Crypto>(
+            self.suite.kdf,
+            &self.secret,
+            &self.suite.suite_id(),
+            "ratchet",
+            &self.epoch.to_be_bytes(),
+            Crypto::kdf_digest_length(self.suite.kdf),
+        )?;
+        core::mem::swap(&mut self.secret, &mut next);
+        next.zeroize();
+        self.epoch = epoch;
+        Ok(())
+    }
+
+    /// Advance to the given `epoch`, erasing the secrets of all epochs before
+    /// it.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `epoch` is before the current
+    /// epoch, because earlier secrets can't be recovered.
+    pub fn ratchet_to(&mut self, epoch: u64) -> Result<(), HpkeError> {
+        if epoch < self.epoch {
+            return Err(HpkeError::InvalidInput);
+        }
+        while self.epoch < epoch {
+            self.ratchet()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! # Encapsulation Reuse Detection
+//!
+//! Senders use a fresh ephemeral key for every encapsulation.
+//! A receiver seeing the same encapsulated secret twice therefore indicates a
+//! replayed message or a misbehaving sender.
+//!
+//! An [`EncCache`] records the encapsulated secrets a receiver has seen.
+//! [`Hpke::setup_receiver_with_cache`] rejects encapsulated secrets that are
+//! in the cache with an [`HpkeError::ReusedEncapsulation`].
+//!
+//! [`BoundedEncCache`] is a simple in-memory cache that remembers the most
+//! recent encapsulated secrets.
+//! Applications can implement [`EncCache`] for shared or persistent caches.
+//!
+//! ## Replay Window
+//!
+//! Within a session, [`ReceiverContext::open_at`] opens messages with an
+//! explicit sequence number, e.g. for datagram transports that drop or
+//! reorder messages.
+//! Like DTLS it keeps a sliding window over the last
+//! [`REPLAY_WINDOW_SIZE`] sequence numbers and rejects messages that were
+//! opened before or are older than the window with an
+//! [`HpkeError::ReplayedMessage`].
+
+use alloc::{
+    collections::{BTreeSet, VecDeque},
+    vec::Vec,
+};
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    Ciphertext, Context, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey,
+    Plaintext, ReceiverContext,
+};
+
+/// A cache of encapsulated secrets seen by a receiver.
+pub trait EncCache {
+    /// Returns `true` if `enc` has been recorded.
+    fn contains(&self, enc: &[u8]) -> bool;
+
+    /// Record `enc`.
+    fn insert(&mut self, enc: &[u8]);
+}
+
+/// An in-memory [`EncCache`] holding up to `capacity` encapsulated secrets.
+///
+/// When the cache is full, the oldest encapsulated secret is forgotten.
+#[derive(Debug, Clone)]
+pub struct BoundedEncCache {
+    capacity: usize,
+    entries: BTreeSet<Vec<u8>>,
+    order: VecDeque<Vec<u8>>,
+}
+
+impl BoundedEncCache {
+    /// Create a new, empty cache for up to `capacity` encapsulated secrets.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeSet::new(),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The number of recorded encapsulated secrets.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl EncCache for BoundedEncCache {
+    fn contains(&self, enc: &[u8]) -> bool {
+        self.entries.contains(enc)
+    }
+
+    fn insert(&mut self, enc: &[u8]) {
+        if self.capacity == 0 || !self.entries.insert(enc.to_vec()) {
+            return;
+        }
+        self.order.push_back(enc.to_vec());
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Set up an HPKE receiver, rejecting encapsulated secrets that are in the
+    /// `cache`.
+    ///
+    /// This is [`Hpke::setup_receiver`] with encapsulation reuse detection.
+    /// Returns an [`HpkeError::ReusedEncapsulation`] if `enc` is in the
+    /// `cache`.
+    /// Otherwise `enc` is recorded in the `cache` after the receiver context
+    /// was set up successfully.
+    #[allow(clippy::too_many_arguments)]
+    pub fn setup_receiver_with_cache(
+        &self,
+        cache: &mut impl EncCache,
+        enc: &EncapsulatedSecret,
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+        pk_s: Option<&HpkePublicKey>,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        if cache.contains(enc.as_slice()) {
+            return Err(HpkeError::ReusedEncapsulation);
+        }
+        let context = self.setup_receiver(enc, sk_r, info, psk, psk_id, pk_s)?;
+        cache.insert(enc.as_slice());
+        Ok(context)
+    }
+}
+
+/// The number of sequence numbers below the highest opened one that
+/// [`ReceiverContext::open_at`] still accepts.
+pub const REPLAY_WINDOW_SIZE: u32 = u64::BITS;
+
+/// A sliding window over the sequence numbers opened by a receiver.
+///
+/// Bit `i` of the `bitmap` is set if the sequence number `top - 1 - i` was
+/// opened.
+/// All sequence numbers from `top` on are unused.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplayWindow {
+    top: u32,
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    /// Slide the window to `top`, marking all sequence numbers in between as
+    /// opened.
+    fn slide(&mut self, top: u32) {
+        let Some(shift) = top.checked_sub(self.top).filter(|&shift| shift > 0) else {
+            return;
+        };
+        let opened = u64::MAX.checked_shr(REPLAY_WINDOW_SIZE.saturating_sub(shift));
+        self.bitmap = self.bitmap.checked_shl(shift).unwrap_or(0) | opened.unwrap_or(0);
+        self.top = top;
+    }
+
+    /// Get the bit of `seq` in the window.
+    ///
+    /// Returns an [`HpkeError::ReplayedMessage`] if `seq` was opened or is
+    /// too old for the window.
+    fn check(&self, seq: u32) -> Result<Option<u32>, HpkeError> {
+        let Some(age) = self.top.checked_sub(seq).and_then(|d| d.checked_sub(1)) else {
+            return Ok(None);
+        };
+        if age >= REPLAY_WINDOW_SIZE || self.bitmap.checked_shr(age).unwrap_or(0) & 1 == 1 {
+            return Err(HpkeError::ReplayedMessage);
+        }
+        Ok(Some(age))
+    }
+
+    /// Open `cipher_txt` with `aad` and the sequence number `seq` on the
+    /// `context`.
+    ///
+    /// Messages opened in order on the `context` since the last call are
+    /// recorded first.
+    /// After a successful open the sequence number of the `context` continues
+    /// after the highest opened sequence number.
+    pub(crate) fn open_at<Crypto: HpkeCrypto>(
+        &mut self,
+        context: &mut Context<Crypto>,
+        seq: u32,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        if context.transcript.is_some() {
+            return Err(HpkeError::InvalidConfig);
+        }
+        context.check_aead()?;
+        if cipher_txt.aead != context.suite.aead {
+            return Err(HpkeError::InvalidInput);
+        }
+        context.check_lengths(aad.len(), cipher_txt.plaintext_len())?;
+        self.slide(context.sequence_number);
+        let age = self.check(seq)?;
+        let next = seq.checked_add(1).ok_or(HpkeError::MessageLimitReached)?;
+        let ptxt = Crypto::aead_open(
+            context.suite.aead,
+            &context.key,
+            &context.compute_nonce_for(seq)?,
+            aad,
+            cipher_txt.as_slice(),
+        )?;
+        let ptxt = context.unpad(ptxt)?;
+        match age {
+            Some(age) => self.bitmap |= 1u64.checked_shl(age).unwrap_or(0),
+            None => {
+                // Only `seq` itself is opened, not the sequence numbers up to it.
+                let skipped = next.saturating_sub(self.top);
+                self.bitmap = self.bitmap.checked_shl(skipped).unwrap_or(0) | 1;
+                self.top = next;
+                context.sequence_number = next;
+            }
+        }
+        Ok(ptxt)
+    }
+}
@@ -0,0 +1,119 @@
+//! # Response Contexts
+//!
+//! Request/response protocols like Oblivious HTTP (RFC 9458) encrypt the
+//! response to an HPKE request under a key derived from the request context.
+//! The receiver of the request derives a [`SenderContext`] for the response
+//! with [`ReceiverContext::response_context`], and the sender of the request
+//! the matching [`ReceiverContext`] with [`SenderContext::response_context`].
+//!
+//! The derivation follows Section 4.4 of RFC 9458 with the exporter `label`
+//! as parameter:
+//!
+//! ```text
+//! secret          = Export(label, max(Nn, Nk))
+//! response_nonce  = random(max(Nn, Nk))
+//! prk             = Extract(enc || response_nonce, secret)
+//! key             = Expand(prk, "key", Nk)
+//! base_nonce      = Expand(prk, "nonce", Nn)
+//! exporter_secret = Expand(prk, "exp", Nh)
+//! ```
+//!
+//! The `response_nonce` is sent along with the response.
+//! The first response is sealed with sequence number `0`, i.e. with the
+//! `base_nonce`, and is therefore compatible with RFC 9458.
+//! Subsequent responses use the following sequence numbers.
+//! The exporter secret of the response context is an extension of this
+//! crate.
+
+use alloc::{format, vec, vec::Vec};
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::{CryptoRng, HpkeCrypto};
+
+use crate::{util, Context, EncapsulatedSecret, HpkeError, ReceiverContext, SenderContext};
+
+/// The exporter label for responses in Oblivious HTTP (RFC 9458).
+pub const OHTTP_RESPONSE_LABEL: &[u8] = b"message/bhttp response";
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Get the length of the response nonce, `max(Nn, Nk)`.
+    pub fn response_nonce_len(&self) -> usize {
+        let aead = self.suite.aead;
+        Crypto::aead_key_length(aead).max(Crypto::aead_nonce_length(aead))
+    }
+
+    /// Derive the response context for the request encapsulated in `enc`.
+    fn derive_response(
+        &self,
+        label: &[u8],
+        enc: &EncapsulatedSecret,
+        response_nonce: &[u8],
+    ) -> Result<Context<Crypto>, HpkeError> {
+        self.check_aead()?;
+        if response_nonce.len() != self.response_nonce_len() {
+            return Err(HpkeError::InvalidInput);
+        }
+        let crypto_error = |e| HpkeError::CryptoError(format!("Crypto error: {}", e));
+        let (aead, kdf) = (self.suite.aead, self.suite.kdf);
+        let secret = self.export(label, self.response_nonce_len())?;
+        let salt = util::concat(&[enc.as_slice(), response_nonce]);
+        let prk = Crypto::kdf_extract(kdf, &salt, &secret).map_err(crypto_error)?;
+        let expand = |info: &[u8], len: usize| {
+            Crypto::kdf_expand(kdf, &prk, info, len).map_err(crypto_error)
+        };
+        Ok(Context {
+            key: expand(b"key", Crypto::aead_key_length(aead))?,
+            nonce: expand(b"nonce", Crypto::aead_nonce_length(aead))?,
+            exporter_secret: expand(b"exp", Crypto::kdf_digest_length(kdf))?,
+            sequence_number: 0,
+            message_limit: aead.max_messages(),
+            suite: self.suite,
+            transcript: None,
+            padding: None,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> ReceiverContext<Crypto> {
+    /// Derive the context for sealing the responses to the request that was
+    /// encapsulated in `enc`, with the exporter `label`, e.g.
+    /// [`OHTTP_RESPONSE_LABEL`].
+    ///
+    /// Returns the random response nonce drawn from `rng`, which must be sent
+    /// to the requester, and the response context.
+    /// See the [module documentation](self) for the derivation.
+    ///
+    /// Returns an [`HpkeError::ExportOnlyMode`] for export-only contexts.
+    pub fn response_context(
+        &self,
+        label: &[u8],
+        enc: &EncapsulatedSecret,
+        rng: &mut impl CryptoRng,
+    ) -> Result<(Vec<u8>, SenderContext<Crypto>), HpkeError> {
+        let mut response_nonce = vec![0u8; self.response_nonce_len()];
+        rng.fill_bytes(&mut response_nonce);
+        let context = self.derive_response(label, enc, &response_nonce)?;
+        Ok((response_nonce, SenderContext::new(context)))
+    }
+}
+
+impl<Crypto: HpkeCrypto> SenderContext<Crypto> {
+    /// Derive the context for opening the responses to the request that was
+    /// encapsulated in `enc`, with the exporter `label` and the
+    /// `response_nonce` received from the responder.
+    ///
+    /// See [`ReceiverContext::response_context`].
+    /// Returns an [`HpkeError::InvalidInput`] if the `response_nonce` doesn't
+    /// have [`Context::response_nonce_len`] bytes, and an
+    /// [`HpkeError::ExportOnlyMode`] for export-only contexts.
+    pub fn response_context(
+        &self,
+        label: &[u8],
+        enc: &EncapsulatedSecret,
+        response_nonce: &[u8],
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        self.derive_response(label, enc, response_nonce)
+            .map(ReceiverContext::new)
+    }
+}
@@ -0,0 +1,168 @@
+//! # Context Resumption
+//!
+//! [`Context::to_bytes`] serializes the complete state of a context, such
+//! that a long-lived session can be resumed with [`Context::from_bytes`]
+//! after a restart.
+//!
+//! The state is encoded as
+//!
+//! ```text
+//! state = version || suite || I2OSP(seq, 4) || I2OSP(message_limit, 8)
+//!         || I2OSP(len(key), 2) || key
+//!         || I2OSP(len(base_nonce), 2) || base_nonce
+//!         || I2OSP(len(exporter_secret), 2) || exporter_secret
+//!         || transcript
+//! transcript = 0x00 | 0x01 || I2OSP(len(transcript), 2) || transcript
+//! ```
+//!
+//! where `version` is [`STATE_VERSION`] and `suite` is the
+//! [compact encoding](crate::HpkeSuite::to_bytes) of the ciphersuite.
+//! The [padding policy](Context::set_padding) is not part of the state and
+//! has to be set again on the resumed context.
+//!
+//! **Hazmat:** the state contains the AEAD key and the exporter secret in
+//! plain text.
+//! It must be stored as confidentially as a private key.
+//! [`Context::to_bytes`] returns it in a [`Zeroizing`] buffer, which is
+//! zeroized when it is dropped, but copies of it must be zeroized after use
+//! by the caller.
+//! Resuming the same state twice, e.g. from a stale backup, reuses sequence
+//! numbers and therefore nonces, which breaks the security of the AEAD.
+//! Persist the state again after every sealed message, or discard it once it
+//! was resumed.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::HpkeCrypto;
+use zeroize::Zeroizing;
+
+use crate::{util, Context, HpkeError, HpkeSuite, ReceiverContext, SenderContext};
+
+/// The version of the state encoding.
+pub const STATE_VERSION: u8 = 1;
+
+/// Take the next `len` bytes from `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], HpkeError> {
+    let value = bytes.get(..len).ok_or(HpkeError::InvalidInput)?;
+    *bytes = bytes.get(len..).ok_or(HpkeError::InvalidInput)?;
+    Ok(value)
+}
+
+/// Take the next `u16` length prefixed value from `bytes`.
+fn take_prefixed<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], HpkeError> {
+    let len = take(bytes, 2)?;
+    let len = u16::from_be_bytes([
+        *len.first().ok_or(HpkeError::InvalidInput)?,
+        *len.get(1).ok_or(HpkeError::InvalidInput)?,
+    ]);
+    take(bytes, len.into())
+}
+
+/// Encode the length of `value` as `u16`.
+fn prefix(value: &[u8]) -> Result<[u8; 2], HpkeError> {
+    Ok(u16::try_from(value.len())
+        .map_err(|_| HpkeError::InvalidInput)?
+        .to_be_bytes())
+}
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Serialize the state of this context.
+    ///
+    /// The state is zeroized when it is dropped.
+    /// See the [module documentation](self) for the format and the security
+    /// considerations.
+    pub fn to_bytes(&self) -> Result<Zeroizing<Vec<u8>>, HpkeError> {
+        let transcript = match &self.transcript {
+            Some(transcript) => util::concat(&[&[1], &prefix(transcript)?, transcript]),
+            None => alloc::vec![0],
+        };
+        Ok(Zeroizing::new(util::concat(&[
+            &[STATE_VERSION],
+            &self.suite.to_bytes(),
+            &self.sequence_number.to_be_bytes(),
+            &self.message_limit.to_be_bytes(),
+            &prefix(&self.key)?,
+            &self.key,
+            &prefix(&self.nonce)?,
+            &self.nonce,
+            &prefix(&self.exporter_secret)?,
+            &self.exporter_secret,
+            &transcript,
+        ])))
+    }
+
+    /// Resume a context from its serialized `state`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if the `state` is malformed, of
+    /// an unknown version, or inconsistent with its ciphersuite, and an
+    /// [`HpkeError::UnsupportedAlgorithm`] if the crypto provider doesn't
+    /// support the ciphersuite.
+    pub fn from_bytes(state: &[u8]) -> Result<Self, HpkeError> {
+        let mut state = state;
+        if take(&mut state, 1)? != [STATE_VERSION] {
+            return Err(HpkeError::InvalidInput);
+        }
+        let suite = HpkeSuite::try_from_bytes(
+            take(&mut state, HpkeSuite::ENCODED_LEN)?
+                .try_into()
+                .map_err(|_| HpkeError::InvalidInput)?,
+        )?;
+        suite.check_support::<Crypto>()?;
+        let sequence_number = u32::from_be_bytes(
+            take(&mut state, 4)?
+                .try_into()
+                .map_err(|_| HpkeError::InvalidInput)?,
+        );
+        let message_limit = u64::from_be_bytes(
+            take(&mut state, 8)?
+                .try_into()
+                .map_err(|_| HpkeError::InvalidInput)?,
+        );
+        let key = take_prefixed(&mut state)?;
+        let nonce = take_prefixed(&mut state)?;
+        let exporter_secret = take_prefixed(&mut state)?;
+        let transcript = match take(&mut state, 1)? {
+            [0] => None,
+            [1] => Some(take_prefixed(&mut state)?.to_vec()),
+            _ => return Err(HpkeError::InvalidInput),
+        };
+        if !state.is_empty()
+            || message_limit > suite.aead.max_messages()
+            || key.len() != Crypto::aead_key_length(suite.aead)
+            || nonce.len() != Crypto::aead_nonce_length(suite.aead)
+            || exporter_secret.len() != Crypto::kdf_digest_length(suite.kdf)
+        {
+            return Err(HpkeError::InvalidInput);
+        }
+        Ok(Self {
+            key: key.to_vec(),
+            nonce: nonce.to_vec(),
+            exporter_secret: exporter_secret.to_vec(),
+            sequence_number,
+            message_limit,
+            suite,
+            transcript,
+            padding: None,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> SenderContext<Crypto> {
+    /// Resume a sender context from its serialized `state`.
+    ///
+    /// See [`Context::from_bytes`].
+    pub fn from_bytes(state: &[u8]) -> Result<Self, HpkeError> {
+        Context::from_bytes(state).map(Self::new)
+    }
+}
+
+impl<Crypto: HpkeCrypto> ReceiverContext<Crypto> {
+    /// Resume a receiver context from its serialized `state`.
+    ///
+    /// See [`Context::from_bytes`].
+    pub fn from_bytes(state: &[u8]) -> Result<Self, HpkeError> {
+        Context::from_bytes(state).map(Self::new)
+    }
+}
@@ -0,0 +1,213 @@
+//! # Sender and Receiver Contexts
+//!
+//! [`Hpke::setup_sender`](crate::Hpke::setup_sender) returns a
+//! [`SenderContext`] that can only seal, and
+//! [`Hpke::setup_receiver`](crate::Hpke::setup_receiver) a
+//! [`ReceiverContext`] that can only open.
+//! Both dereference to the underlying [`Context`] for the operations of
+//! either role, e.g. [`Context::export`].
+//! There is no conversion back into a [`Context`], which could both seal
+//! and open.
+
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    padding::PaddingPolicy, replay::ReplayWindow, Ciphertext, Context, HpkeError, Plaintext,
+};
+
+/// The context of an HPKE sender.
+pub struct SenderContext<Crypto: 'static + HpkeCrypto> {
+    context: Context<Crypto>,
+}
+
+/// The context of an HPKE receiver.
+pub struct ReceiverContext<Crypto: 'static + HpkeCrypto> {
+    context: Context<Crypto>,
+    window: ReplayWindow,
+}
+
+impl<Crypto: HpkeCrypto> SenderContext<Crypto> {
+    pub(crate) fn new(context: Context<Crypto>) -> Self {
+        Self { context }
+    }
+
+    /// See [`Context::seal`].
+    pub fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Ciphertext, HpkeError> {
+        self.context.seal(aad, plain_txt)
+    }
+
+    /// See [`Context::seal_in_place_detached`].
+    pub fn seal_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, HpkeError> {
+        self.context.seal_in_place_detached(buffer, aad)
+    }
+
+    /// See [`Context::seal_into`].
+    #[cfg(feature = "fixed")]
+    pub fn seal_into(
+        &mut self,
+        aad: &[u8],
+        plain_txt: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, HpkeError> {
+        self.context.seal_into(aad, plain_txt, out)
+    }
+
+    /// See [`Context::seal_in`].
+    #[cfg(feature = "allocator-api2")]
+    pub fn seal_in<A: allocator_api2::alloc::Allocator>(
+        &mut self,
+        aad: &[u8],
+        plain_txt: &[u8],
+        alloc: A,
+    ) -> Result<allocator_api2::vec::Vec<u8, A>, HpkeError> {
+        self.context.seal_in(aad, plain_txt, alloc)
+    }
+
+    /// See [`Context::enable_transcript`].
+    pub fn enable_transcript(&mut self) -> Result<(), HpkeError> {
+        self.context.enable_transcript()
+    }
+
+    /// See [`Context::set_message_limit`].
+    pub fn set_message_limit(&mut self, limit: u64) -> Result<(), HpkeError> {
+        self.context.set_message_limit(limit)
+    }
+
+    /// See [`Context::set_padding`].
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        self.context.set_padding(padding)
+    }
+}
+
+impl<Crypto: HpkeCrypto> ReceiverContext<Crypto> {
+    pub(crate) fn new(context: Context<Crypto>) -> Self {
+        Self {
+            context,
+            window: ReplayWindow::default(),
+        }
+    }
+
+    /// See [`Context::open`].
+    pub fn open(&mut self, aad: &[u8], cipher_txt: &Ciphertext) -> Result<Plaintext, HpkeError> {
+        self.context.open(aad, cipher_txt)
+    }
+
+    /// Open `cipher_txt` with `aad` and the explicit sequence number `seq`.
+    ///
+    /// Unlike [`ReceiverContext::open`] the messages can be opened in any
+    /// order, as long as they are within the replay window.
+    /// See the [replay window](crate::replay#replay-window) for details.
+    ///
+    /// Returns an [`HpkeError::ReplayedMessage`] if `seq` was opened before
+    /// or is too old, and an [`HpkeError::InvalidConfig`] if the transcript
+    /// is enabled, because it requires messages in order.
+    pub fn open_at(
+        &mut self,
+        seq: u32,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<Plaintext, HpkeError> {
+        self.window.open_at(&mut self.context, seq, aad, cipher_txt)
+    }
+
+    /// See [`Context::open_strict`].
+    pub fn open_strict(
+        self,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+    ) -> Result<(Plaintext, Self), HpkeError> {
+        let window = self.window;
+        let (ptxt, context) = self.context.open_strict(aad, cipher_txt)?;
+        Ok((ptxt, Self { context, window }))
+    }
+
+    /// See [`Context::open_batch`].
+    pub fn open_batch(
+        &mut self,
+        messages: &[(&[u8], &Ciphertext)],
+    ) -> Result<Vec<Plaintext>, HpkeError> {
+        self.context.open_batch(messages)
+    }
+
+    /// See [`Context::open_in_place_detached`].
+    pub fn open_in_place_detached(
+        &mut self,
+        buffer: &mut [u8],
+        aad: &[u8],
+        tag: &[u8],
+    ) -> Result<(), HpkeError> {
+        self.context.open_in_place_detached(buffer, aad, tag)
+    }
+
+    /// See [`Context::open_into`].
+    #[cfg(feature = "fixed")]
+    pub fn open_into(
+        &mut self,
+        aad: &[u8],
+        cipher_txt: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, HpkeError> {
+        self.context.open_into(aad, cipher_txt, out)
+    }
+
+    /// See [`Context::open_in`].
+    #[cfg(feature = "allocator-api2")]
+    pub fn open_in<A: allocator_api2::alloc::Allocator>(
+        &mut self,
+        aad: &[u8],
+        cipher_txt: &Ciphertext,
+        alloc: A,
+    ) -> Result<allocator_api2::vec::Vec<u8, A>, HpkeError> {
+        self.context.open_in(aad, cipher_txt, alloc)
+    }
+
+    /// See [`Context::enable_transcript`].
+    pub fn enable_transcript(&mut self) -> Result<(), HpkeError> {
+        self.context.enable_transcript()
+    }
+
+    /// See [`Context::set_message_limit`].
+    pub fn set_message_limit(&mut self, limit: u64) -> Result<(), HpkeError> {
+        self.context.set_message_limit(limit)
+    }
+
+    /// See [`Context::set_padding`].
+    pub fn set_padding(&mut self, padding: Option<PaddingPolicy>) -> Result<(), HpkeError> {
+        self.context.set_padding(padding)
+    }
+}
+
+impl<Crypto: HpkeCrypto> Deref for SenderContext<Crypto> {
+    type Target = Context<Crypto>;
+
+    fn deref(&self) -> &Context<Crypto> {
+        &self.context
+    }
+}
+
+impl<Crypto: HpkeCrypto> Deref for ReceiverContext<Crypto> {
+    type Target = Context<Crypto>;
+
+    fn deref(&self) -> &Context<Crypto> {
+        &self.context
+    }
+}
+
+impl<Crypto: HpkeCrypto> core::fmt::Debug for SenderContext<Crypto> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sender{:?}", self.context)
+    }
+}
+
+impl<Crypto: HpkeCrypto> core::fmt::Debug for ReceiverContext<Crypto> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Receiver{:?}", self.context)
+    }
+}
@@ -0,0 +1,140 @@
+//! # rust-hpke Interoperability
+//!
+//! Conversions between the types of this crate and the [`hpke`](rust_hpke)
+//! crate (rust-hpke), for projects migrating between the two
+//! implementations.
+//!
+//! rust-hpke encodes the ciphersuite in the type system, with one key type
+//! per KEM.
+//! The conversions are therefore generic over the rust-hpke KEM, KDF, and
+//! AEAD types:
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, Hpke, HpkePublicKey, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//! use rust_hpke::kem::X25519HkdfSha256;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let pk = hpke.generate_key_pair()?.public_key().clone();
+//! let their_pk = pk.to_rust_hpke::<X25519HkdfSha256>()?;
+//! let pk_r = HpkePublicKey::from_rust_hpke::<X25519HkdfSha256>(&their_pk);
+//! assert_eq!(pk_r.as_slice(), pk.as_slice());
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+//!
+//! Keys and encapsulated secrets use the same serialization in both crates.
+
+use rust_hpke::{aead::Aead, kdf::Kdf, Deserializable, Kem, Serializable};
+use zeroize::Zeroize;
+
+use hpke_rs_crypto::types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm};
+
+use crate::{
+    AlgorithmKind, EncapsulatedSecret, HpkeError, HpkePrivateKey, HpkePublicKey, HpkeSuite, Mode,
+};
+
+/// A rust-hpke KEM with its counterpart in this crate.
+pub trait RustHpkeKem: Kem {
+    /// The KEM algorithm of this crate.
+    const ALGORITHM: KemAlgorithm;
+}
+
+impl RustHpkeKem for rust_hpke::kem::X25519HkdfSha256 {
+    const ALGORITHM: KemAlgorithm = KemAlgorithm::DhKem25519;
+}
+
+impl RustHpkeKem for rust_hpke::kem::DhP256HkdfSha256 {
+    const ALGORITHM: KemAlgorithm = KemAlgorithm::DhKemP256;
+}
+
+impl RustHpkeKem for rust_hpke::kem::DhP384HkdfSha384 {
+    const ALGORITHM: KemAlgorithm = KemAlgorithm::DhKemP384;
+}
+
+impl RustHpkeKem for rust_hpke::kem::DhP521HkdfSha512 {
+    const ALGORITHM: KemAlgorithm = KemAlgorithm::DhKemP521;
+}
+
+impl HpkePublicKey {
+    /// Convert a rust-hpke public key of the KEM `K`.
+    pub fn from_rust_hpke<K: RustHpkeKem>(pk: &K::PublicKey) -> Self {
+        Self::new(pk.to_bytes().to_vec())
+    }
+
+    /// Convert this public key into a rust-hpke public key of the KEM `K`.
+    ///
+    /// Returns an [`HpkeError::InvalidPublicKey`] if this is not a valid key
+    /// for the KEM.
+    pub fn to_rust_hpke<K: RustHpkeKem>(&self) -> Result<K::PublicKey, HpkeError> {
+        K::PublicKey::from_bytes(self.as_slice()).map_err(|_| HpkeError::InvalidPublicKey)
+    }
+}
+
+impl HpkePrivateKey {
+    /// Convert a rust-hpke private key of the KEM `K`.
+    pub fn from_rust_hpke<K: RustHpkeKem>(sk: &K::PrivateKey) -> Self {
+        let mut bytes = sk.to_bytes();
+        let sk = Self::new(bytes.to_vec());
+        bytes.as_mut_slice().zeroize();
+        sk
+    }
+
+    /// Convert this private key into a rust-hpke private key of the KEM `K`.
+    ///
+    /// Returns an [`HpkeError::InvalidSecretKey`] if this is not a valid key
+    /// for the KEM.
+    pub fn to_rust_hpke<K: RustHpkeKem>(&self) -> Result<K::PrivateKey, HpkeError> {
+        K::PrivateKey::from_bytes(self.bytes()?).map_err(|_| HpkeError::InvalidSecretKey)
+    }
+}
+
+impl EncapsulatedSecret {
+    /// Convert a rust-hpke encapsulated key of the KEM `K`.
+    pub fn from_rust_hpke<K: RustHpkeKem>(enc: &K::EncappedKey) -> Result<Self, HpkeError> {
+        Self::new(K::ALGORITHM, enc.to_bytes().to_vec())
+    }
+
+    /// Convert this encapsulated secret into a rust-hpke encapsulated key of
+    /// the KEM `K`.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if this is an encapsulated
+    /// secret for a different KEM, and an [`HpkeError::InvalidCiphertext`] if
+    /// rust-hpke rejects it.
+    pub fn to_rust_hpke<K: RustHpkeKem>(&self) -> Result<K::EncappedKey, HpkeError> {
+        if self.kem != K::ALGORITHM {
+            return Err(HpkeError::InvalidInput);
+        }
+        K::EncappedKey::from_bytes(self.as_slice()).map_err(|_| HpkeError::InvalidCiphertext)
+    }
+}
+
+impl HpkeSuite {
+    /// The ciphersuite of the rust-hpke AEAD `A`, KDF `F`, and KEM `K` in the
+    /// `mode`.
+    ///
+    /// Returns an [`HpkeError::UnsupportedAlgorithm`] if this crate doesn't
+    /// know one of the algorithms.
+    pub fn from_rust_hpke<A: Aead, F: Kdf, K: Kem>(mode: Mode) -> Result<Self, HpkeError> {
+        let unknown = |kind, id| HpkeError::UnsupportedAlgorithm { kind, id };
+        Ok(Self::new(
+            mode,
+            KemAlgorithm::try_from(K::KEM_ID)
+                .map_err(|_| unknown(AlgorithmKind::Kem, K::KEM_ID))?,
+            KdfAlgorithm::try_from(F::KDF_ID)
+                .map_err(|_| unknown(AlgorithmKind::Kdf, F::KDF_ID))?,
+            AeadAlgorithm::try_from(A::AEAD_ID)
+                .map_err(|_| unknown(AlgorithmKind::Aead, A::AEAD_ID))?,
+        ))
+    }
+
+    /// Returns `true` if this ciphersuite uses the rust-hpke AEAD `A`, KDF `F`,
+    /// and KEM `K`.
+    pub fn is_rust_hpke<A: Aead, F: Kdf, K: Kem>(&self) -> bool {
+        self.kem.id() == K::KEM_ID && self.kdf.id() == F::KDF_ID && self.aead.id() == A::AEAD_ID
+    }
+}
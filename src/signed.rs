@@ -0,0 +1,172 @@
+//! # Signature-Authenticated HPKE
+//!
+//! Sign-then-HPKE sender authentication for KEMs without an Auth mode, like
+//! X-Wing or ML-KEM.
+//!
+//! The sender signs the encapsulated secret and the `info` with a signature
+//! key, e.g. Ed25519 or ECDSA, using the [`signature`] crate traits.
+//! The signed message is domain separated and bound to the ciphersuite:
+//!
+//! ```text
+//! message = "hpke-rs signed" || suite_id || I2OSP(len(enc), 2) || enc || info
+//! ```
+//!
+//! The signature travels next to the encapsulated secret.
+//! The receiver verifies it before decapsulating, such that only contexts
+//! set up by the holder of the signature key are accepted.
+//!
+//! The single-shot APIs [`Hpke::seal_signed`] and [`Hpke::open_signed`]
+//! additionally bind the signature to the ciphertext by prepending it to the
+//! associated data:
+//!
+//! ```text
+//! aad' = I2OSP(len(signature), 2) || signature || aad
+//! ```
+//!
+//! **Note** that the Auth and AuthPSK modes are not supported.
+//! Use them directly for DH KEMs.
+
+use alloc::{format, vec::Vec};
+
+use hpke_rs_crypto::HpkeCrypto;
+use signature::{SignatureEncoding, Signer, Verifier};
+
+use crate::{
+    util, Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey,
+    HpkeSuite, Mode, Plaintext, ReceiverContext, SenderContext,
+};
+
+const SIGNED_LABEL: &[u8] = b"hpke-rs signed";
+
+/// The message signed by the sender for `enc` and `info` in the `suite`.
+pub fn signed_message(
+    suite: HpkeSuite,
+    enc: &EncapsulatedSecret,
+    info: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    let enc_len = u16::try_from(enc.as_slice().len()).map_err(|_| HpkeError::InvalidInput)?;
+    Ok(util::concat(&[
+        SIGNED_LABEL,
+        &suite.suite_id(),
+        &enc_len.to_be_bytes(),
+        enc.as_slice(),
+        info,
+    ]))
+}
+
+/// The associated data binding the `signature` to a ciphertext.
+pub fn signed_aad(signature: &[u8], aad: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let signature_len = u16::try_from(signature.len()).map_err(|_| HpkeError::InvalidInput)?;
+    Ok(util::concat(&[
+        &signature_len.to_be_bytes(),
+        signature,
+        aad,
+    ]))
+}
+
+impl<Crypto: HpkeCrypto> Hpke<Crypto> {
+    /// Returns an [`HpkeError::InvalidConfig`] in the Auth and AuthPSK modes.
+    fn check_signed_mode(&self) -> Result<(), HpkeError> {
+        match self.mode {
+            Mode::Base | Mode::Psk => Ok(()),
+            Mode::Auth | Mode::AuthPsk => Err(HpkeError::InvalidConfig),
+        }
+    }
+
+    /// Set up an HPKE sender and sign the encapsulated secret with the
+    /// `signing_key`.
+    ///
+    /// This is [`Hpke::setup_sender`] without a sender key.
+    /// Returns the encapsulated secret, the encoded signature, and the
+    /// context.
+    pub fn setup_sender_signed<S: SignatureEncoding>(
+        &mut self,
+        signing_key: &impl Signer<S>,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<(EncapsulatedSecret, Vec<u8>, SenderContext<Crypto>), HpkeError> {
+        self.check_signed_mode()?;
+        let (enc, context) = self.setup_sender(pk_r, info, psk, psk_id, None)?;
+        let signature = signing_key
+            .try_sign(&signed_message(self.suite(), &enc, info)?)
+            .map_err(|e| HpkeError::CryptoError(format!("Signature error: {}", e)))?;
+        Ok((enc, signature.to_vec(), context))
+    }
+
+    /// Verify the `signature` on `enc` with the `verifying_key` and set up an
+    /// HPKE receiver.
+    ///
+    /// This is [`Hpke::setup_receiver`] without a sender key.
+    /// Returns an [`HpkeError::InvalidSignature`] if the signature is
+    /// invalid.
+    /// `enc` is decapsulated only after the signature has been verified.
+    #[allow(clippy::too_many_arguments)]
+    pub fn setup_receiver_signed<S: SignatureEncoding>(
+        &self,
+        verifying_key: &impl Verifier<S>,
+        enc: &EncapsulatedSecret,
+        signature: &[u8],
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<ReceiverContext<Crypto>, HpkeError> {
+        self.check_signed_mode()?;
+        let signature = S::try_from(signature).map_err(|_| HpkeError::InvalidSignature)?;
+        verifying_key
+            .verify(&signed_message(self.suite(), enc, info)?, &signature)
+            .map_err(|_| HpkeError::InvalidSignature)?;
+        self.setup_receiver(enc, sk_r, info, psk, psk_id, None)
+    }
+
+    /// Single shot API to encrypt the bytes in `plain_txt` to the public key
+    /// `pk_r` and sign the encapsulated secret with the `signing_key`.
+    ///
+    /// The signature is bound to the ciphertext through the associated data.
+    /// See the [module documentation](crate::signed) for details.
+    ///
+    /// Returns the encapsulated secret, the encoded signature, and the
+    /// ciphertext, or an error.
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal_signed<S: SignatureEncoding>(
+        &mut self,
+        signing_key: &impl Signer<S>,
+        pk_r: &HpkePublicKey,
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<(EncapsulatedSecret, Vec<u8>, Ciphertext), HpkeError> {
+        let (enc, signature, mut context) =
+            self.setup_sender_signed(signing_key, pk_r, info, psk, psk_id)?;
+        let ctxt = context.seal(&signed_aad(&signature, aad)?, plain_txt)?;
+        Ok((enc, signature, ctxt))
+    }
+
+    /// Single shot API to verify the `signature` with the `verifying_key` and
+    /// decrypt the bytes in `ct` with the private key `sk_r`.
+    ///
+    /// Returns the decrypted plain text, or an error.
+    /// Returns an [`HpkeError::InvalidSignature`] if the signature is
+    /// invalid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_signed<S: SignatureEncoding>(
+        &self,
+        verifying_key: &impl Verifier<S>,
+        enc: &EncapsulatedSecret,
+        signature: &[u8],
+        sk_r: &HpkePrivateKey,
+        info: &[u8],
+        aad: &[u8],
+        ct: &Ciphertext,
+        psk: Option<&[u8]>,
+        psk_id: Option<&[u8]>,
+    ) -> Result<Plaintext, HpkeError> {
+        let mut context =
+            self.setup_receiver_signed(verifying_key, enc, signature, sk_r, info, psk, psk_id)?;
+        context.open(&signed_aad(signature, aad)?, ct)
+    }
+}
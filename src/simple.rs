@@ -0,0 +1,81 @@
+//! # Simple Public-Key Encryption
+//!
+//! Public-key authenticated encryption (base mode, anonymous sender) with a
+//! fixed, modern ciphersuite, for applications that don't need to choose HPKE
+//! parameters.
+//!
+//! ```
+//! use hpke_rs::simple;
+//!
+//! let (sk, pk) = simple::generate_keypair().unwrap();
+//! let ciphertext = simple::encrypt(&pk, b"secret message").unwrap();
+//! let plaintext = simple::decrypt(&sk, &ciphertext).unwrap();
+//! assert_eq!(plaintext, b"secret message");
+//! ```
+//!
+//! The ciphersuite is [`SUITE`], i.e. the base mode with DHKEM(X25519,
+//! HKDF-SHA256), HKDF-SHA256, and ChaCha20Poly1305, using the RustCrypto
+//! provider.
+//! Messages are the encapsulated secret followed by the ciphertext:
+//!
+//! ```text
+//! message = enc || ct
+//! ```
+//!
+//! **Note** that anyone with the public key can encrypt.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+
+use crate::{
+    util, Ciphertext, EncapsulatedSecret, HpkeError, HpkePrivateKey, HpkePublicKey, HpkeSuite, Mode,
+};
+
+/// The ciphersuite of the simple API.
+pub const SUITE: HpkeSuite = HpkeSuite::new(
+    Mode::Base,
+    KemAlgorithm::DhKem25519,
+    KdfAlgorithm::HkdfSha256,
+    AeadAlgorithm::ChaCha20Poly1305,
+);
+
+/// The `info` binding messages to the simple API.
+const INFO: &[u8] = b"hpke-rs simple";
+
+/// Generate a new key pair.
+///
+/// Returns the private and the public key.
+pub fn generate_keypair() -> Result<(HpkePrivateKey, HpkePublicKey), HpkeError> {
+    Ok(SUITE
+        .into_hpke::<HpkeRustCrypto>()
+        .generate_key_pair()?
+        .into_keys())
+}
+
+/// Encrypt the `plaintext` to the public key `pk`.
+///
+/// Returns the message for [`decrypt`].
+pub fn encrypt(pk: &HpkePublicKey, plaintext: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let (enc, ct) =
+        SUITE
+            .into_hpke::<HpkeRustCrypto>()
+            .seal(pk, INFO, &[], plaintext, None, None, None)?;
+    Ok(util::concat(&[enc.as_slice(), ct.as_slice()]))
+}
+
+/// Decrypt the `message` with the private key `sk`.
+///
+/// Returns an [`HpkeError::InvalidCiphertext`] if the message is too short,
+/// and an error if it can't be decrypted.
+pub fn decrypt(sk: &HpkePrivateKey, message: &[u8]) -> Result<Vec<u8>, HpkeError> {
+    let enc_len = SUITE.kem.encapsulated_key_len();
+    let (enc, ct) = (
+        message.get(..enc_len).ok_or(HpkeError::InvalidCiphertext)?,
+        message.get(enc_len..).ok_or(HpkeError::InvalidCiphertext)?,
+    );
+    let enc = EncapsulatedSecret::new(SUITE.kem, enc.to_vec())?;
+    let ct = Ciphertext::new(SUITE.aead, ct.to_vec()).map_err(|_| HpkeError::InvalidCiphertext)?;
+    SUITE.open::<HpkeRustCrypto>(&enc, sk, INFO, &[], &ct, None, None, None)
+}
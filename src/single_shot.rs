@@ -0,0 +1,336 @@
+//! # RFC-Named Single-Shot Functions
+//!
+//! Section 6 of RFC 9180 defines a single-shot function per mode, e.g.
+//! `SealBase(pkR, info, aad, pt)` or `OpenAuthPSK(enc, skR, info, aad, ct,
+//! psk, psk_id, pkS)`.
+//! The functions in this module mirror these names and take exactly the
+//! parameters of their mode, instead of the `Option`s of [`Hpke::seal`] and
+//! [`Hpke::open`].
+//!
+//! The `hpke` configuration provides the ciphersuite and, for senders, the
+//! PRNG.
+//! Its mode must match the function, otherwise an
+//! [`HpkeError::InvalidConfig`] is returned.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, single_shot, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let (enc, ct) = single_shot::seal_base(&mut hpke, &pk_r, b"info", b"aad", b"message")?;
+//! let pt = single_shot::open_base(&hpke, &enc, &sk_r, b"info", b"aad", &ct)?;
+//! assert_eq!(pt, b"message");
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{
+    Ciphertext, EncapsulatedSecret, Hpke, HpkeError, HpkePrivateKey, HpkePublicKey, Mode, Plaintext,
+};
+
+/// Returns an [`HpkeError::InvalidConfig`] if `hpke` is not in `mode`.
+fn check_mode<Crypto: HpkeCrypto>(hpke: &Hpke<Crypto>, mode: Mode) -> Result<(), HpkeError> {
+    if hpke.mode != mode {
+        return Err(HpkeError::InvalidConfig);
+    }
+    Ok(())
+}
+
+/// `SealBase(pkR, info, aad, pt)`
+pub fn seal_base<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plain_txt: &[u8],
+) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+    check_mode(hpke, Mode::Base)?;
+    hpke.seal(pk_r, info, aad, plain_txt, None, None, None)
+}
+
+/// `OpenBase(enc, skR, info, aad, ct)`
+pub fn open_base<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    aad: &[u8],
+    ct: &Ciphertext,
+) -> Result<Plaintext, HpkeError> {
+    check_mode(hpke, Mode::Base)?;
+    hpke.open(enc, sk_r, info, aad, ct, None, None, None)
+}
+
+/// `SealPSK(pkR, info, aad, pt, psk, psk_id)`
+pub fn seal_psk<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plain_txt: &[u8],
+    psk: &[u8],
+    psk_id: &[u8],
+) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+    check_mode(hpke, Mode::Psk)?;
+    hpke.seal(pk_r, info, aad, plain_txt, Some(psk), Some(psk_id), None)
+}
+
+/// `OpenPSK(enc, skR, info, aad, ct, psk, psk_id)`
+#[allow(clippy::too_many_arguments)]
+pub fn open_psk<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    aad: &[u8],
+    ct: &Ciphertext,
+    psk: &[u8],
+    psk_id: &[u8],
+) -> Result<Plaintext, HpkeError> {
+    check_mode(hpke, Mode::Psk)?;
+    hpke.open(enc, sk_r, info, aad, ct, Some(psk), Some(psk_id), None)
+}
+
+/// `SealAuth(pkR, info, aad, pt, skS)`
+pub fn seal_auth<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plain_txt: &[u8],
+    sk_s: &HpkePrivateKey,
+) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+    check_mode(hpke, Mode::Auth)?;
+    hpke.seal(pk_r, info, aad, plain_txt, None, None, Some(sk_s))
+}
+
+/// `OpenAuth(enc, skR, info, aad, ct, pkS)`
+pub fn open_auth<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    aad: &[u8],
+    ct: &Ciphertext,
+    pk_s: &HpkePublicKey,
+) -> Result<Plaintext, HpkeError> {
+    check_mode(hpke, Mode::Auth)?;
+    hpke.open(enc, sk_r, info, aad, ct, None, None, Some(pk_s))
+}
+
+/// `SealAuthPSK(pkR, info, aad, pt, psk, psk_id, skS)`
+#[allow(clippy::too_many_arguments)]
+pub fn seal_auth_psk<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    aad: &[u8],
+    plain_txt: &[u8],
+    psk: &[u8],
+    psk_id: &[u8],
+    sk_s: &HpkePrivateKey,
+) -> Result<(EncapsulatedSecret, Ciphertext), HpkeError> {
+    check_mode(hpke, Mode::AuthPsk)?;
+    hpke.seal(
+        pk_r,
+        info,
+        aad,
+        plain_txt,
+        Some(psk),
+        Some(psk_id),
+        Some(sk_s),
+    )
+}
+
+/// `OpenAuthPSK(enc, skR, info, aad, ct, psk, psk_id, pkS)`
+#[allow(clippy::too_many_arguments)]
+pub fn open_auth_psk<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    aad: &[u8],
+    ct: &Ciphertext,
+    psk: &[u8],
+    psk_id: &[u8],
+    pk_s: &HpkePublicKey,
+) -> Result<Plaintext, HpkeError> {
+    check_mode(hpke, Mode::AuthPsk)?;
+    hpke.open(
+        enc,
+        sk_r,
+        info,
+        aad,
+        ct,
+        Some(psk),
+        Some(psk_id),
+        Some(pk_s),
+    )
+}
+
+/// `SendExportBase(pkR, info, exporter_context, L)`
+pub fn send_export_base<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+) -> Result<(EncapsulatedSecret, Vec<u8>), HpkeError> {
+    check_mode(hpke, Mode::Base)?;
+    hpke.send_export(pk_r, info, None, None, None, exporter_context, length)
+}
+
+/// `ReceiveExportBase(enc, skR, info, exporter_context, L)`
+pub fn receive_export_base<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, HpkeError> {
+    check_mode(hpke, Mode::Base)?;
+    hpke.receiver_export(enc, sk_r, info, None, None, None, exporter_context, length)
+}
+
+/// `SendExportPSK(pkR, info, exporter_context, L, psk, psk_id)`
+pub fn send_export_psk<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    psk: &[u8],
+    psk_id: &[u8],
+) -> Result<(EncapsulatedSecret, Vec<u8>), HpkeError> {
+    check_mode(hpke, Mode::Psk)?;
+    hpke.send_export(
+        pk_r,
+        info,
+        Some(psk),
+        Some(psk_id),
+        None,
+        exporter_context,
+        length,
+    )
+}
+
+/// `ReceiveExportPSK(enc, skR, info, exporter_context, L, psk, psk_id)`
+#[allow(clippy::too_many_arguments)]
+pub fn receive_export_psk<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    psk: &[u8],
+    psk_id: &[u8],
+) -> Result<Vec<u8>, HpkeError> {
+    check_mode(hpke, Mode::Psk)?;
+    hpke.receiver_export(
+        enc,
+        sk_r,
+        info,
+        Some(psk),
+        Some(psk_id),
+        None,
+        exporter_context,
+        length,
+    )
+}
+
+/// `SendExportAuth(pkR, info, exporter_context, L, skS)`
+pub fn send_export_auth<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    sk_s: &HpkePrivateKey,
+) -> Result<(EncapsulatedSecret, Vec<u8>), HpkeError> {
+    check_mode(hpke, Mode::Auth)?;
+    hpke.send_export(pk_r, info, None, None, Some(sk_s), exporter_context, length)
+}
+
+/// `ReceiveExportAuth(enc, skR, info, exporter_context, L, pkS)`
+pub fn receive_export_auth<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    pk_s: &HpkePublicKey,
+) -> Result<Vec<u8>, HpkeError> {
+    check_mode(hpke, Mode::Auth)?;
+    hpke.receiver_export(
+        enc,
+        sk_r,
+        info,
+        None,
+        None,
+        Some(pk_s),
+        exporter_context,
+        length,
+    )
+}
+
+/// `SendExportAuthPSK(pkR, info, exporter_context, L, psk, psk_id, skS)`
+#[allow(clippy::too_many_arguments)]
+pub fn send_export_auth_psk<Crypto: HpkeCrypto>(
+    hpke: &mut Hpke<Crypto>,
+    pk_r: &HpkePublicKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    psk: &[u8],
+    psk_id: &[u8],
+    sk_s: &HpkePrivateKey,
+) -> Result<(EncapsulatedSecret, Vec<u8>), HpkeError> {
+    check_mode(hpke, Mode::AuthPsk)?;
+    hpke.send_export(
+        pk_r,
+        info,
+        Some(psk),
+        Some(psk_id),
+        Some(sk_s),
+        exporter_context,
+        length,
+    )
+}
+
+/// `ReceiveExportAuthPSK(enc, skR, info, exporter_context, L, psk, psk_id, pkS)`
+#[allow(clippy::too_many_arguments)]
+pub fn receive_export_auth_psk<Crypto: HpkeCrypto>(
+    hpke: &Hpke<Crypto>,
+    enc: &EncapsulatedSecret,
+    sk_r: &HpkePrivateKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    length: usize,
+    psk: &[u8],
+    psk_id: &[u8],
+    pk_s: &HpkePublicKey,
+) -> Result<Vec<u8>, HpkeError> {
+    check_mode(hpke, Mode::AuthPsk)?;
+    hpke.receiver_export(
+        enc,
+        sk_r,
+        info,
+        Some(psk),
+        Some(psk_id),
+        Some(pk_s),
+        exporter_context,
+        length,
+    )
+}
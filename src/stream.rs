@@ -0,0 +1,217 @@
+//! # Streaming Encryption
+//!
+//! A [`ChunkSealer`] encrypts a payload of arbitrary length as a sequence of
+//! AEAD chunks, such that the payload never has to be held in memory as a
+//! whole.
+//! The [`ChunkOpener`] decrypts the chunks in the same order.
+//!
+//! Every chunk is sealed with the next sequence number of the context, i.e.
+//! with its own nonce.
+//! All chunks but the last carry exactly `chunk_size` bytes of plain text.
+//! The last chunk carries less than `chunk_size` bytes, possibly none, and is
+//! marked as end of the stream in its associated data:
+//!
+//! ```text
+//! chunk_aad = flag || aad
+//! ```
+//!
+//! where `flag` is `0x01` for the last chunk and `0x00` otherwise.
+//! Reordered, dropped, or truncated chunks therefore fail to open.
+//! A stream is only complete once [`ChunkOpener::finish`] succeeded.
+//!
+//! Chunks are not [padded](crate::padding), because the chunk lengths
+//! identify the last chunk.
+//! Disable the padding of the context with
+//! [`SenderContext::set_padding`] and [`ReceiverContext::set_padding`]
+//! before turning it into a stream.
+//!
+//! ```
+//! use hpke_rs::{hpke_types::*, Hpke, Mode};
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! let mut hpke = Hpke::<HpkeRustCrypto>::new(
+//!     Mode::Base,
+//!     KemAlgorithm::DhKem25519,
+//!     KdfAlgorithm::HkdfSha256,
+//!     AeadAlgorithm::ChaCha20Poly1305,
+//! );
+//! let (sk_r, pk_r) = hpke.generate_key_pair()?.into_keys();
+//! let (enc, sender) = hpke.setup_sender(&pk_r, b"info", None, None, None)?;
+//!
+//! let payload = [0x2a; 100];
+//! let mut chunks = payload.chunks_exact(32);
+//! let mut sealer = sender.into_chunk_sealer(b"aad", 32)?;
+//! let mut stream = Vec::new();
+//! for chunk in &mut chunks {
+//!     stream.push(sealer.seal_chunk(chunk)?);
+//! }
+//! stream.push(sealer.finish(chunks.remainder())?);
+//!
+//! let receiver = hpke.setup_receiver(&enc, &sk_r, b"info", None, None, None)?;
+//! let mut opener = receiver.into_chunk_opener(b"aad", 32)?;
+//! let (last, full) = stream.split_last().unwrap();
+//! let mut opened = Vec::new();
+//! for chunk in full {
+//!     opened.extend(opener.open_chunk(chunk)?);
+//! }
+//! opened.extend(opener.finish(last)?);
+//! assert_eq!(opened, payload);
+//! # Ok::<(), hpke_rs::HpkeError>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::HpkeCrypto;
+
+use crate::{util, Ciphertext, Context, HpkeError, Plaintext, ReceiverContext, SenderContext};
+
+const FLAG_CHUNK: u8 = 0x00;
+const FLAG_LAST: u8 = 0x01;
+
+/// The associated data of a chunk with the `flag`.
+fn chunk_aad(flag: u8, aad: &[u8]) -> Vec<u8> {
+    util::concat(&[&[flag], aad])
+}
+
+/// Returns an [`HpkeError::InvalidInput`] for an empty `chunk_size`, an
+/// [`HpkeError::ExportOnlyMode`] for export-only contexts, and an
+/// [`HpkeError::InvalidConfig`] if padding is enabled.
+fn check_stream<Crypto: HpkeCrypto>(
+    context: &Context<Crypto>,
+    chunk_size: usize,
+) -> Result<(), HpkeError> {
+    context.check_aead()?;
+    context.check_no_padding()?;
+    if chunk_size == 0 {
+        return Err(HpkeError::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Seals a stream in chunks of a fixed size.
+#[derive(Debug)]
+pub struct ChunkSealer<Crypto: 'static + HpkeCrypto> {
+    context: SenderContext<Crypto>,
+    aad: Vec<u8>,
+    chunk_size: usize,
+}
+
+/// Opens a stream that was sealed by a [`ChunkSealer`].
+#[derive(Debug)]
+pub struct ChunkOpener<Crypto: 'static + HpkeCrypto> {
+    context: ReceiverContext<Crypto>,
+    aad: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl<Crypto: HpkeCrypto> SenderContext<Crypto> {
+    /// Turn this context into a [`ChunkSealer`] for chunks of `chunk_size`
+    /// bytes, authenticating the `aad` with every chunk.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `chunk_size` is `0`, an
+    /// [`HpkeError::ExportOnlyMode`] for export-only contexts, and an
+    /// [`HpkeError::InvalidConfig`] if [padding](Self::set_padding) is
+    /// enabled.
+    pub fn into_chunk_sealer(
+        self,
+        aad: &[u8],
+        chunk_size: usize,
+    ) -> Result<ChunkSealer<Crypto>, HpkeError> {
+        check_stream(&self, chunk_size)?;
+        Ok(ChunkSealer {
+            context: self,
+            aad: aad.to_vec(),
+            chunk_size,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> ReceiverContext<Crypto> {
+    /// Turn this context into a [`ChunkOpener`] for chunks of `chunk_size`
+    /// bytes of plain text, authenticating the `aad` with every chunk.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `chunk_size` is `0`, an
+    /// [`HpkeError::ExportOnlyMode`] for export-only contexts, and an
+    /// [`HpkeError::InvalidConfig`] if [padding](Self::set_padding) is
+    /// enabled.
+    pub fn into_chunk_opener(
+        self,
+        aad: &[u8],
+        chunk_size: usize,
+    ) -> Result<ChunkOpener<Crypto>, HpkeError> {
+        check_stream(&self, chunk_size)?;
+        Ok(ChunkOpener {
+            context: self,
+            aad: aad.to_vec(),
+            chunk_size,
+        })
+    }
+}
+
+impl<Crypto: HpkeCrypto> ChunkSealer<Crypto> {
+    /// Get the plain text size of all chunks but the last.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Seal the next `chunk`, which must be exactly
+    /// [`chunk_size`](Self::chunk_size) bytes long.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if it isn't.
+    pub fn seal_chunk(&mut self, chunk: &[u8]) -> Result<Ciphertext, HpkeError> {
+        if chunk.len() != self.chunk_size {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.context.seal(&chunk_aad(FLAG_CHUNK, &self.aad), chunk)
+    }
+
+    /// Seal the last `chunk` of the stream, which must be shorter than
+    /// [`chunk_size`](Self::chunk_size) and may be empty.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if it isn't shorter.
+    pub fn finish(mut self, chunk: &[u8]) -> Result<Ciphertext, HpkeError> {
+        if chunk.len() >= self.chunk_size {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.context.seal(&chunk_aad(FLAG_LAST, &self.aad), chunk)
+    }
+}
+
+impl<Crypto: HpkeCrypto> ChunkOpener<Crypto> {
+    /// Get the plain text size of all chunks but the last.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns `true` if `cipher_txt` is a full chunk, and therefore not the
+    /// last chunk of the stream.
+    pub fn is_full_chunk(&self, cipher_txt: &Ciphertext) -> bool {
+        cipher_txt.plaintext_len() == self.chunk_size
+    }
+
+    /// Open the next chunk `cipher_txt`, which must not be the last chunk of
+    /// the stream.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `cipher_txt` is not a full
+    /// chunk, and an error if it can't be opened.
+    pub fn open_chunk(&mut self, cipher_txt: &Ciphertext) -> Result<Plaintext, HpkeError> {
+        if !self.is_full_chunk(cipher_txt) {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.context
+            .open(&chunk_aad(FLAG_CHUNK, &self.aad), cipher_txt)
+    }
+
+    /// Open the last chunk `cipher_txt` of the stream.
+    ///
+    /// Returns an [`HpkeError::InvalidInput`] if `cipher_txt` is a full
+    /// chunk, and an error if it can't be opened, e.g. because the stream was
+    /// truncated.
+    pub fn finish(mut self, cipher_txt: &Ciphertext) -> Result<Plaintext, HpkeError> {
+        if cipher_txt.plaintext_len() >= self.chunk_size {
+            return Err(HpkeError::InvalidInput);
+        }
+        self.context
+            .open(&chunk_aad(FLAG_LAST, &self.aad), cipher_txt)
+    }
+}
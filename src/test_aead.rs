@@ -21,11 +21,71 @@ fn test_aes_gcm_128_self() {
         HpkeRustCrypto::aead_open(AeadAlgorithm::Aes128Gcm, &key, &nonce, &aad, &ctxt).unwrap();
     assert_eq!(&ptxt, msg);
 
-    // assert error on Libcrux provider
-    HpkeLibcrux::aead_seal(AeadAlgorithm::Aes128Gcm, &key, &nonce, &aad, msg)
-        .expect_err("Unsupported algorithm");
-    HpkeLibcrux::aead_open(AeadAlgorithm::Aes128Gcm, &key, &nonce, &aad, &ctxt)
-        .expect_err("Unsupported algorithm");
+    // test libcrux provider
+    let libcrux_ctxt =
+        HpkeLibcrux::aead_seal(AeadAlgorithm::Aes128Gcm, &key, &nonce, &aad, msg).unwrap();
+    assert_eq!(libcrux_ctxt, ctxt);
+    let ptxt = HpkeLibcrux::aead_open(AeadAlgorithm::Aes128Gcm, &key, &nonce, &aad, &ctxt).unwrap();
+    assert_eq!(&ptxt, msg);
+    HpkeLibcrux::aead_open(AeadAlgorithm::Aes256Gcm, &[0u8; 32], &nonce, &aad, &ctxt)
+        .expect_err("Wrong key");
+}
+
+// Test cases 4 and 16 of the GCM specification (McGrew and Viega) for the
+// RustCrypto AES-GCM fallback of the libcrux provider.
+#[test]
+fn test_aes_gcm_libcrux_kat() {
+    let key = [
+        0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c, 0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83,
+        0x08,
+    ];
+    let nonce = [
+        0xca, 0xfe, 0xba, 0xbe, 0xfa, 0xce, 0xdb, 0xad, 0xde, 0xca, 0xf8, 0x88,
+    ];
+    let aad = [
+        0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe, 0xef, 0xfe, 0xed, 0xfa, 0xce, 0xde, 0xad, 0xbe,
+        0xef, 0xab, 0xad, 0xda, 0xd2,
+    ];
+    let msg = [
+        0xd9, 0x31, 0x32, 0x25, 0xf8, 0x84, 0x06, 0xe5, 0xa5, 0x59, 0x09, 0xc5, 0xaf, 0xf5, 0x26,
+        0x9a, 0x86, 0xa7, 0xa9, 0x53, 0x15, 0x34, 0xf7, 0xda, 0x2e, 0x4c, 0x30, 0x3d, 0x8a, 0x31,
+        0x8a, 0x72, 0x1c, 0x3c, 0x0c, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2f, 0xcf, 0x0e, 0x24, 0x49,
+        0xa6, 0xb5, 0x25, 0xb1, 0x6a, 0xed, 0xf5, 0xaa, 0x0d, 0xe6, 0x57, 0xba, 0x63, 0x7b, 0x39,
+    ];
+
+    for (alg, key, expected) in [
+        (
+            AeadAlgorithm::Aes128Gcm,
+            key.to_vec(),
+            [
+                0x42, 0x83, 0x1e, 0xc2, 0x21, 0x77, 0x74, 0x24, 0x4b, 0x72, 0x21, 0xb7, 0x84, 0xd0,
+                0xd4, 0x9c, 0xe3, 0xaa, 0x21, 0x2f, 0x2c, 0x02, 0xa4, 0xe0, 0x35, 0xc1, 0x7e, 0x23,
+                0x29, 0xac, 0xa1, 0x2e, 0x21, 0xd5, 0x14, 0xb2, 0x54, 0x66, 0x93, 0x1c, 0x7d, 0x8f,
+                0x6a, 0x5a, 0xac, 0x84, 0xaa, 0x05, 0x1b, 0xa3, 0x0b, 0x39, 0x6a, 0x0a, 0xac, 0x97,
+                0x3d, 0x58, 0xe0, 0x91, 0x5b, 0xc9, 0x4f, 0xbc, 0x32, 0x21, 0xa5, 0xdb, 0x94, 0xfa,
+                0xe9, 0x5a, 0xe7, 0x12, 0x1a, 0x47,
+            ],
+        ),
+        (
+            AeadAlgorithm::Aes256Gcm,
+            [key, key].concat(),
+            [
+                0x52, 0x2d, 0xc1, 0xf0, 0x99, 0x56, 0x7d, 0x07, 0xf4, 0x7f, 0x37, 0xa3, 0x2a, 0x84,
+                0x42, 0x7d, 0x64, 0x3a, 0x8c, 0xdc, 0xbf, 0xe5, 0xc0, 0xc9, 0x75, 0x98, 0xa2, 0xbd,
+                0x25, 0x55, 0xd1, 0xaa, 0x8c, 0xb0, 0x8e, 0x48, 0x59, 0x0d, 0xbb, 0x3d, 0xa7, 0xb0,
+                0x8b, 0x10, 0x56, 0x82, 0x88, 0x38, 0xc5, 0xf6, 0x1e, 0x63, 0x93, 0xba, 0x7a, 0x0a,
+                0xbc, 0xc9, 0xf6, 0x62, 0x76, 0xfc, 0x6e, 0xce, 0x0f, 0x4e, 0x17, 0x68, 0xcd, 0xdf,
+                0x88, 0x53, 0xbb, 0x2d, 0x55, 0x1b,
+            ],
+        ),
+    ] {
+        HpkeLibcrux::supports_aead(alg).unwrap();
+        let ctxt = HpkeLibcrux::aead_seal(alg, &key, &nonce, &aad, &msg).unwrap();
+        assert_eq!(ctxt, expected);
+        let ptxt = HpkeLibcrux::aead_open(alg, &key, &nonce, &aad, &ctxt).unwrap();
+        assert_eq!(ptxt, msg);
+        HpkeLibcrux::aead_open(alg, &key, &nonce, &aad[1..], &ctxt).expect_err("Wrong aad");
+    }
 }
 
 #[test]
@@ -99,3 +159,70 @@ fn test_chacha20poly1305_self_error() {
     )
     .expect_err("Should fail due to incorrect key length");
 }
+
+// Test vector 1 of draft-irtf-cfrg-aegis-aead for each variant.
+#[test]
+fn test_aegis_kat() {
+    let mut key = [0u8; 32];
+    key[..2].copy_from_slice(&[0x10, 0x01]);
+    let mut nonce = [0u8; 32];
+    nonce[..3].copy_from_slice(&[0x10, 0x00, 0x02]);
+    let msg = [0u8; 16];
+
+    for (alg, expected) in [
+        (
+            AeadAlgorithm::Aegis128L,
+            [
+                0xc1, 0xc0, 0xe5, 0x8b, 0xd9, 0x13, 0x00, 0x6f, 0xeb, 0xa0, 0x0f, 0x4b, 0x3c, 0xc3,
+                0x59, 0x4e, 0xab, 0xe0, 0xec, 0xe8, 0x0c, 0x24, 0x86, 0x8a, 0x22, 0x6a, 0x35, 0xd1,
+                0x6b, 0xda, 0xe3, 0x7a,
+            ],
+        ),
+        (
+            AeadAlgorithm::Aegis256,
+            [
+                0x75, 0x4f, 0xc3, 0xd8, 0xc9, 0x73, 0x24, 0x6d, 0xcc, 0x6d, 0x74, 0x14, 0x12, 0xa4,
+                0xb2, 0x36, 0x3f, 0xe9, 0x19, 0x94, 0x76, 0x8b, 0x33, 0x2e, 0xd7, 0xf5, 0x70, 0xa1,
+                0x9e, 0xc5, 0x89, 0x6e,
+            ],
+        ),
+    ] {
+        let key = &key[..alg.key_length()];
+        let nonce = &nonce[..alg.nonce_length()];
+        let ctxt = HpkeRustCrypto::aead_seal(alg, key, nonce, &[], &msg).unwrap();
+        assert_eq!(ctxt, expected);
+        let ptxt = HpkeRustCrypto::aead_open(alg, key, nonce, &[], &ctxt).unwrap();
+        assert_eq!(ptxt, msg);
+
+        let mut tampered = ctxt.clone();
+        tampered[0] ^= 1;
+        HpkeRustCrypto::aead_open(alg, key, nonce, &[], &tampered).expect_err("Invalid tag");
+        HpkeRustCrypto::aead_open(alg, key, &nonce[1..], &[], &ctxt).expect_err("Wrong nonce");
+    }
+}
+
+#[test]
+fn test_xchacha20poly1305_self() {
+    let key = [0x42u8; 32];
+    let nonce: Vec<u8> = (0..24).collect();
+    let aad = [0x03, 0x04, 0x05];
+    let msg = b"test message";
+
+    let ctxt = HpkeRustCrypto::aead_seal(AeadAlgorithm::XChaCha20Poly1305, &key, &nonce, &aad, msg)
+        .unwrap();
+    assert_eq!(ctxt.len(), msg.len() + 16);
+    let ptxt =
+        HpkeRustCrypto::aead_open(AeadAlgorithm::XChaCha20Poly1305, &key, &nonce, &aad, &ctxt)
+            .unwrap();
+    assert_eq!(&ptxt, msg);
+
+    // The ChaCha20Poly1305 nonce length is rejected.
+    HpkeRustCrypto::aead_seal(
+        AeadAlgorithm::XChaCha20Poly1305,
+        &key,
+        &nonce[..12],
+        &aad,
+        msg,
+    )
+    .expect_err("Should fail due to incorrect nonce length");
+}
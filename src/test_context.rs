@@ -0,0 +1,98 @@
+use alloc::vec;
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm};
+use hpke_rs_rust_crypto::HpkeRustCrypto;
+
+use crate::{Context, HpkeError, HpkeSuite, Mode};
+
+fn context(nonce_len: usize, sequence_number: u32) -> Context<HpkeRustCrypto> {
+    Context {
+        key: vec![0u8; 32],
+        nonce: vec![0u8; nonce_len],
+        exporter_secret: vec![0u8; 32],
+        sequence_number,
+        message_limit: u64::MAX,
+        suite: HpkeSuite::new(
+            Mode::Base,
+            KemAlgorithm::DhKem25519,
+            KdfAlgorithm::HkdfSha256,
+            AeadAlgorithm::ChaCha20Poly1305,
+        ),
+        transcript: None,
+        padding: None,
+        phantom: PhantomData,
+    }
+}
+
+#[test]
+fn test_compute_nonce_short_nonce() {
+    // Sequence numbers that fit into a short nonce are fine.
+    assert_eq!(context(2, 0x0102).compute_nonce(), Ok(vec![0x01, 0x02]));
+    assert_eq!(
+        context(2, 0x010203).compute_nonce(),
+        Err(HpkeError::MessageLimitReached)
+    );
+    assert_eq!(context(0, 0).compute_nonce(), Ok(vec![]));
+}
+
+#[test]
+fn test_increment_seq_limit() {
+    let mut ctx = context(12, u32::MAX - 1);
+    assert_eq!(
+        ctx.increment_seq_with(&PhantomData::<HpkeRustCrypto>),
+        Ok(())
+    );
+    assert_eq!(
+        ctx.increment_seq_with(&PhantomData::<HpkeRustCrypto>),
+        Err(HpkeError::MessageLimitReached)
+    );
+    assert_eq!(ctx.sequence_number, u32::MAX);
+}
+
+#[test]
+fn test_export_invalid_length() {
+    let ctx = context(12, 0);
+    assert!(ctx.export(b"", usize::from(u16::MAX) + 1).is_err());
+    assert!(ctx.export(b"", usize::MAX).is_err());
+}
+
+#[test]
+fn test_check_lengths() {
+    let ctx = context(12, 0);
+    let max_ptxt = usize::try_from(AeadAlgorithm::ChaCha20Poly1305.max_plaintext_length())
+        .unwrap_or(usize::MAX);
+    assert_eq!(ctx.check_lengths(0, 0), Ok(()));
+    assert_eq!(ctx.check_lengths(usize::MAX, max_ptxt), Ok(()));
+    if max_ptxt < usize::MAX {
+        assert_eq!(
+            ctx.check_lengths(0, max_ptxt + 1),
+            Err(HpkeError::InputTooLong)
+        );
+    }
+
+    let mut ctx = context(12, 0);
+    ctx.suite.aead = AeadAlgorithm::Aes128Gcm;
+    assert_eq!(
+        ctx.check_lengths(0, (1 << 36) - 31),
+        Err(HpkeError::InputTooLong)
+    );
+    assert_eq!(ctx.check_lengths(0, (1 << 36) - 32), Ok(()));
+    assert_eq!(ctx.check_lengths(1 << 61, 0), Err(HpkeError::InputTooLong));
+}
+
+#[test]
+fn test_message_limit() {
+    let mut ctx = context(12, 2);
+    assert_eq!(ctx.set_message_limit(3), Ok(()));
+    assert!(ctx.compute_nonce().is_ok());
+    ctx.sequence_number = 3;
+    assert_eq!(ctx.compute_nonce(), Err(HpkeError::MessageLimitReached));
+
+    ctx.suite.aead = AeadAlgorithm::Aes128Gcm;
+    assert_eq!(ctx.set_message_limit(1 << 25), Err(HpkeError::InvalidInput));
+    assert_eq!(
+        ctx.set_message_limit(AeadAlgorithm::Aes128Gcm.max_messages()),
+        Ok(())
+    );
+}
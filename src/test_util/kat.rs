@@ -0,0 +1,356 @@
+//! # RFC 9180 Known-Answer Tests
+//!
+//! Run the test vectors of RFC 9180, e.g. `tests/test_vectors.json` of this
+//! repository, against a crypto provider.
+//!
+//! ```
+//! use hpke_rs::{
+//!     hpke_types::{AeadAlgorithm, KemAlgorithm},
+//!     test_util::kat::{kat, test_vectors},
+//! };
+//! use hpke_rs_rust_crypto::HpkeRustCrypto;
+//!
+//! // The vectors of DHKEM(X25519, HKDF-SHA256) with ChaCha20Poly1305.
+//! for test in test_vectors(include_str!("../../tests/test_vectors.json"))
+//!     .iter()
+//!     .filter(|test| test.suite().kem == KemAlgorithm::DhKem25519)
+//!     .filter(|test| test.suite().aead == AeadAlgorithm::ChaCha20Poly1305)
+//! {
+//!     kat::<HpkeRustCrypto>(test);
+//! }
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use hpke_rs_crypto::{types::AeadAlgorithm, HpkeCrypto, HpkeCryptoInstance};
+use serde::Deserialize;
+
+use super::{hex_to_bytes, hex_to_bytes_option, vec_to_option_slice};
+use crate::{
+    instance::HpkeInstance, Ciphertext, EncapsulatedSecret, Hpke, HpkePrivateKey, HpkePublicKey,
+    HpkeSuite, Mode,
+};
+
+/// A test vector of RFC 9180.
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+pub struct HpkeTestVector {
+    mode: u8,
+    kem_id: u16,
+    kdf_id: u16,
+    aead_id: u16,
+    info: String,
+    ikmR: String,
+    ikmS: Option<String>,
+    ikmE: String,
+    skRm: String,
+    skSm: Option<String>,
+    skEm: String,
+    psk: Option<String>,
+    psk_id: Option<String>,
+    pkRm: String,
+    pkSm: Option<String>,
+    pkEm: String,
+    enc: String,
+    shared_secret: String,
+    key: String,
+    base_nonce: String,
+    exporter_secret: String,
+    encryptions: Vec<CiphertextKat>,
+    exports: Vec<ExportKat>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CiphertextKat {
+    aad: String,
+    ct: String,
+    pt: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+struct ExportKat {
+    exporter_context: String,
+    L: usize,
+    exported_value: String,
+}
+
+impl HpkeTestVector {
+    /// Get the ciphersuite of the test vector.
+    pub fn suite(&self) -> HpkeSuite {
+        HpkeSuite::new(
+            self.mode.try_into().unwrap(),
+            self.kem_id.try_into().unwrap(),
+            self.kdf_id.try_into().unwrap(),
+            self.aead_id.try_into().unwrap(),
+        )
+    }
+
+    /// Get the serialized private key of the receiver (`skRm`).
+    pub fn receiver_private_key(&self) -> Vec<u8> {
+        hex_to_bytes(&self.skRm)
+    }
+
+    /// Get the encoded public key of the receiver (`pkRm`).
+    pub fn receiver_public_key(&self) -> Vec<u8> {
+        hex_to_bytes(&self.pkRm)
+    }
+
+    /// Returns `true` if the `Crypto` provider supports the ciphersuite.
+    fn is_supported<Crypto: HpkeCrypto>(&self) -> bool {
+        let suite = self.suite();
+        let supported = Crypto::supports_kem(suite.kem).is_ok()
+            && Crypto::supports_kdf(suite.kdf).is_ok()
+            && Crypto::supports_aead(suite.aead).is_ok();
+        if !supported {
+            log::trace!(" > {:?} not implemented for {}", suite, Crypto::name());
+        }
+        supported
+    }
+}
+
+/// Parse the JSON encoded list of test vectors.
+pub fn test_vectors(json: &str) -> Vec<HpkeTestVector> {
+    serde_json::from_str(json).unwrap()
+}
+
+/// Run the test vector `test` against the `Crypto` provider.
+///
+/// Test vectors for ciphersuites the provider doesn't support are skipped.
+pub fn kat<Crypto: HpkeCrypto + 'static>(test: &HpkeTestVector) {
+    if !test.is_supported::<Crypto>() {
+        return;
+    }
+    let HpkeSuite {
+        mode,
+        kem: kem_id,
+        kdf: kdf_id,
+        aead: aead_id,
+        ..
+    } = test.suite();
+    log::trace!(
+        "Testing mode {:?} with ciphersuite {:?}_{:?}_{:?}",
+        mode,
+        kem_id,
+        kdf_id,
+        aead_id
+    );
+
+    // Init HPKE with the given mode and ciphersuite.
+    let mut hpke = Hpke::<Crypto>::new(mode, kem_id, kdf_id, aead_id);
+
+    // Set up sender and receiver.
+    let pk_rm = HpkePublicKey::new(hex_to_bytes(&test.pkRm));
+    let sk_rm = HpkePrivateKey::new(hex_to_bytes(&test.skRm));
+    let pk_em = HpkePublicKey::new(hex_to_bytes(&test.pkEm));
+    let sk_em = HpkePrivateKey::new(hex_to_bytes(&test.skEm));
+    let pk_sm = hex_to_bytes_option(test.pkSm.clone());
+    let pk_sm = if pk_sm.is_empty() {
+        None
+    } else {
+        Some(HpkePublicKey::new(pk_sm))
+    };
+    let pk_sm = pk_sm.as_ref();
+    let sk_sm = hex_to_bytes_option(test.skSm.clone());
+    let sk_sm = if sk_sm.is_empty() {
+        None
+    } else {
+        Some(HpkePrivateKey::new(sk_sm))
+    };
+    let sk_sm = sk_sm.as_ref();
+    let info = hex_to_bytes(&test.info);
+    let psk = hex_to_bytes_option(test.psk.clone());
+    let psk = vec_to_option_slice(&psk);
+    let psk_id = hex_to_bytes_option(test.psk_id.clone());
+    let psk_id = vec_to_option_slice(&psk_id);
+    let shared_secret = hex_to_bytes(&test.shared_secret);
+    let key = hex_to_bytes(&test.key);
+    let nonce = hex_to_bytes(&test.base_nonce);
+    let exporter_secret = hex_to_bytes(&test.exporter_secret);
+
+    // Input key material.
+    let ikm_r = hex_to_bytes(&test.ikmR);
+    let ikm_e = hex_to_bytes(&test.ikmE);
+    let ikm_s = hex_to_bytes_option(test.ikmS.clone());
+
+    // Use internal `key_schedule` function for KAT.
+    let mut direct_ctx = hpke
+        .key_schedule(
+            &shared_secret,
+            &info,
+            psk.unwrap_or_default(),
+            psk_id.unwrap_or_default(),
+        )
+        .unwrap();
+
+    // Check setup info
+    // Note that key and nonce are empty for exporter only key derivation.
+    assert_eq!(direct_ctx.key(), key);
+    assert_eq!(direct_ctx.nonce(), nonce);
+    assert_eq!(direct_ctx.exporter_secret(), exporter_secret);
+    assert_eq!(direct_ctx.sequence_number(), 0);
+
+    // Test key pair derivation.
+    let (my_sk_r, my_pk_r) = hpke.derive_key_pair(&ikm_r).unwrap().into_keys();
+    assert_eq!(sk_rm, my_sk_r);
+    assert_eq!(pk_rm, my_pk_r);
+    let (my_sk_e, my_pk_e) = hpke.derive_key_pair(&ikm_e).unwrap().into_keys();
+    assert_eq!(sk_em, my_sk_e);
+    assert_eq!(pk_em, my_pk_e);
+    if let (Some(sk_sm), Some(pk_sm)) = (sk_sm, pk_sm) {
+        let (my_sk_s, my_pk_s) = hpke.derive_key_pair(&ikm_s).unwrap().into_keys();
+        assert_eq!(sk_sm, &my_sk_s);
+        assert_eq!(pk_sm, &my_pk_s);
+    }
+
+    // Setup KAT receiver.
+    let kat_enc = EncapsulatedSecret::new(kem_id, hex_to_bytes(&test.enc)).unwrap();
+    #[allow(unused_mut)]
+    let mut receiver_context_kat = hpke
+        .setup_receiver(&kat_enc, &sk_rm, &info, psk, psk_id, pk_sm)
+        .unwrap();
+
+    // Setup sender and receiver with KAT randomness.
+    // We first have to inject the randomness (ikmE).
+
+    #[cfg(feature = "hpke-test-prng")]
+    {
+        log::trace!("Testing with known ikmE ...");
+        let mut hpke_sender = Hpke::<Crypto>::new(mode, kem_id, kdf_id, aead_id);
+        // This only works when seeding the PRNG with ikmE.
+        hpke_sender.seed(&ikm_e).unwrap();
+        let (enc, _sender_context_kat) = hpke_sender
+            .setup_sender(&pk_rm, &info, psk, psk_id, sk_sm)
+            .unwrap();
+        let receiver_context = hpke
+            .setup_receiver(&enc, &sk_rm, &info, psk, psk_id, pk_sm)
+            .unwrap();
+        assert_eq!(enc, kat_enc);
+        assert_eq!(receiver_context.key(), receiver_context_kat.key());
+        assert_eq!(receiver_context.nonce(), receiver_context_kat.nonce());
+        assert_eq!(
+            receiver_context.exporter_secret(),
+            receiver_context_kat.exporter_secret()
+        );
+        receiver_context_kat = receiver_context;
+        assert_eq!(receiver_context_kat.key(), key);
+        assert_eq!(receiver_context_kat.nonce(), nonce);
+        assert_eq!(receiver_context_kat.exporter_secret(), exporter_secret);
+        assert_eq!(receiver_context_kat.sequence_number(), 0);
+    }
+
+    // Setup sender and receiver for self tests.
+    let (enc, mut sender_context) = hpke
+        .setup_sender(&pk_rm, &info, psk, psk_id, sk_sm)
+        .unwrap();
+    let mut receiver_context = hpke
+        .setup_receiver(&enc, &sk_rm, &info, psk, psk_id, pk_sm)
+        .unwrap();
+
+    // Encrypt
+    for (i, encryption) in test.encryptions.iter().enumerate() {
+        // Cloning the Hpke object renews the test PRNG.
+        hpke = hpke.clone();
+        let aad = hex_to_bytes(&encryption.aad);
+        let ptxt = hex_to_bytes(&encryption.pt);
+        let ctxt_kat = Ciphertext::new(aead_id, hex_to_bytes(&encryption.ct)).unwrap();
+
+        // Test context API self-test
+        let ctxt_out = sender_context.seal(&aad, &ptxt).unwrap();
+        let ptxt_out = receiver_context.open(&aad, &ctxt_out).unwrap();
+        assert_eq!(ptxt_out, ptxt);
+
+        // Test single-shot API self-test
+        let (enc, ct) = hpke
+            .seal(&pk_rm, &info, &aad, &ptxt, psk, psk_id, sk_sm)
+            .unwrap();
+        let ptxt_out = hpke
+            .open(&enc, &sk_rm, &info, &aad, &ct, psk, psk_id, pk_sm)
+            .unwrap();
+        assert_eq!(ptxt_out, ptxt);
+
+        // Test KAT receiver context open
+        let ptxt_out = receiver_context_kat.open(&aad, &ctxt_kat).unwrap();
+        assert_eq!(ptxt_out, ptxt);
+
+        // Test KAT seal and open with an explicit nonce.
+        // Note that the nonces in the K-256 vectors are off by one, so
+        // the nonce is computed from the base nonce.
+        let seq = (i as u32).to_be_bytes();
+        let mut kat_nonce = nonce.clone();
+        for (n, s) in kat_nonce.iter_mut().rev().zip(seq.iter().rev()) {
+            *n ^= s;
+        }
+        let ct = direct_ctx.seal_with_nonce(&kat_nonce, &aad, &ptxt).unwrap();
+        assert_eq!(ctxt_kat, ct);
+        let ptxt_out = direct_ctx
+            .open_with_nonce(&kat_nonce, &aad, &ctxt_kat)
+            .unwrap();
+        assert_eq!(ptxt_out, ptxt);
+
+        // Test KAT seal on direct_ctx
+        let ct = direct_ctx.seal(&aad, &ptxt).unwrap();
+        assert_eq!(ctxt_kat, ct);
+    }
+
+    // Test KAT on direct_ctx for exporters
+    for export in test.exports.iter() {
+        let export_context = hex_to_bytes(&export.exporter_context);
+        let export_value = hex_to_bytes(&export.exported_value);
+        let exported_secret = direct_ctx.export(&export_context, export.L).unwrap();
+        assert_eq!(export_value, exported_secret);
+    }
+}
+
+/// Run the receiver side of the test vector `test` against the `provider`,
+/// with the receiver's private key `sk_r`.
+///
+/// This is meant for providers that hold the private key, e.g. in a key
+/// store, such that `sk_r` is a handle of the imported
+/// [`HpkeTestVector::receiver_private_key`].
+/// The ciphertexts and exports of the test vector are checked.
+pub fn kat_receiver<P: HpkeCryptoInstance>(
+    provider: P,
+    test: &HpkeTestVector,
+    sk_r: &HpkePrivateKey,
+) {
+    let suite = test.suite();
+    let hpke = HpkeInstance::new(provider, suite.mode, suite.kem, suite.kdf, suite.aead).unwrap();
+    let enc = EncapsulatedSecret::new(suite.kem, hex_to_bytes(&test.enc)).unwrap();
+    let info = hex_to_bytes(&test.info);
+    let psk = hex_to_bytes_option(test.psk.clone());
+    let psk_id = hex_to_bytes_option(test.psk_id.clone());
+    let pk_s = match suite.mode {
+        Mode::Auth | Mode::AuthPsk => {
+            Some(HpkePublicKey::new(hex_to_bytes_option(test.pkSm.clone())))
+        }
+        Mode::Base | Mode::Psk => None,
+    };
+    let mut context = hpke
+        .setup_receiver(
+            &enc,
+            sk_r,
+            &info,
+            vec_to_option_slice(&psk),
+            vec_to_option_slice(&psk_id),
+            pk_s.as_ref(),
+        )
+        .unwrap();
+
+    if suite.aead != AeadAlgorithm::HpkeExport {
+        for encryption in test.encryptions.iter() {
+            let aad = hex_to_bytes(&encryption.aad);
+            let ct = Ciphertext::new(suite.aead, hex_to_bytes(&encryption.ct)).unwrap();
+            assert_eq!(
+                context.open(&aad, &ct).unwrap(),
+                hex_to_bytes(&encryption.pt)
+            );
+        }
+    }
+    for export in test.exports.iter() {
+        let exported_secret = context
+            .export(&hex_to_bytes(&export.exporter_context), export.L)
+            .unwrap();
+        assert_eq!(exported_secret, hex_to_bytes(&export.exported_value));
+    }
+}
@@ -0,0 +1,181 @@
+//! # Transcript Hashing
+//!
+//! An opt-in running hash over the messages processed by a [`Context`].
+//!
+//! When the transcript is enabled, every message sealed or opened on the
+//! context is recorded with its sequence number, the length of its associated
+//! data, and the length of its ciphertext:
+//!
+//! ```text
+//! transcript_0 = LabeledExtract("", "transcript", "")
+//! entry_n      = I2OSP(seq, 4) || I2OSP(len(aad), 8) || I2OSP(len(ct), 8)
+//! transcript_n = LabeledExtract(transcript_{n-1}, "transcript", entry_n)
+//! ```
+//!
+//! The transcript doesn't depend on any secret and can be recomputed from an
+//! audit log of [`TranscriptEntry`]s with [`transcript_hash`].
+//!
+//! Sender and receiver that processed the same messages end up with the same
+//! transcript.
+//! [`Context::transcript_commitment`] binds the transcript to the session
+//! secret such that both sides can compare commitments to check the integrity
+//! of the whole session.
+//!
+//! ```text
+//! commitment = Export("hpke-rs transcript" || transcript_n, Nh)
+//! ```
+
+use alloc::vec::Vec;
+
+use core::marker::PhantomData;
+
+use hpke_rs_crypto::{HpkeCrypto, HpkeCryptoInstance};
+
+use crate::{kdf::labeled_extract_with, util, Context, HpkeError, HpkeSuite};
+
+const TRANSCRIPT_LABEL: &[u8] = b"hpke-rs transcript";
+
+/// A message recorded in a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    /// The sequence number of the message.
+    pub sequence_number: u32,
+
+    /// The length of the associated data in bytes.
+    pub aad_len: usize,
+
+    /// The length of the ciphertext in bytes.
+    pub ct_len: usize,
+}
+
+impl TranscriptEntry {
+    /// Encode the entry as `I2OSP(seq, 4) || I2OSP(aad_len, 8) || I2OSP(ct_len, 8)`.
+    fn encode(&self) -> Result<Vec<u8>, HpkeError> {
+        let aad_len = u64::try_from(self.aad_len).map_err(|_| HpkeError::InvalidInput)?;
+        let ct_len = u64::try_from(self.ct_len).map_err(|_| HpkeError::InvalidInput)?;
+        Ok(util::concat(&[
+            &self.sequence_number.to_be_bytes(),
+            &aad_len.to_be_bytes(),
+            &ct_len.to_be_bytes(),
+        ]))
+    }
+}
+
+/// The initial transcript of the `suite`.
+fn initial(crypto: &impl HpkeCryptoInstance, suite: &HpkeSuite) -> Result<Vec<u8>, HpkeError> {
+    Ok(labeled_extract_with(
+        crypto,
+        suite.kdf,
+        &[],
+        &suite.suite_id(),
+        "transcript",
+        &[],
+    )?)
+}
+
+/// Append the `entry` to the `transcript`.
+fn append(
+    crypto: &impl HpkeCryptoInstance,
+    suite: &HpkeSuite,
+    transcript: &[u8],
+    entry: &TranscriptEntry,
+) -> Result<Vec<u8>, HpkeError> {
+    Ok(labeled_extract_with(
+        crypto,
+        suite.kdf,
+        transcript,
+        &suite.suite_id(),
+        "transcript",
+        &entry.encode()?,
+    )?)
+}
+
+/// Compute the transcript of the `entries` in the `suite`.
+///
+/// This is the transcript a [`Context`] with the transcript enabled holds
+/// after processing the messages described by `entries`, in order.
+pub fn transcript_hash<Crypto: HpkeCrypto>(
+    suite: &HpkeSuite,
+    entries: &[TranscriptEntry],
+) -> Result<Vec<u8>, HpkeError> {
+    let crypto = PhantomData::<Crypto>;
+    let mut transcript = initial(&crypto, suite)?;
+    for entry in entries {
+        transcript = append(&crypto, suite, &transcript, entry)?;
+    }
+    Ok(transcript)
+}
+
+impl<Crypto: HpkeCrypto> Context<Crypto> {
+    /// Enable the transcript for this context.
+    ///
+    /// The transcript has to cover the entire session and can therefore only
+    /// be enabled before the first message is processed.
+    /// Returns an [`HpkeError::InvalidConfig`] otherwise.
+    /// Enabling an enabled transcript doesn't change it.
+    pub fn enable_transcript(&mut self) -> Result<(), HpkeError> {
+        self.enable_transcript_with(&PhantomData::<Crypto>)
+    }
+
+    /// Commit to the current transcript with the secret of this context.
+    ///
+    /// Returns an [`HpkeError::InvalidConfig`] if the transcript is not
+    /// enabled.
+    pub fn transcript_commitment(&self) -> Result<Vec<u8>, HpkeError> {
+        self.transcript_commitment_with(&PhantomData::<Crypto>)
+    }
+}
+
+impl<Crypto> Context<Crypto> {
+    /// Get the current transcript, or `None` if the transcript is not enabled.
+    pub fn transcript(&self) -> Option<&[u8]> {
+        self.transcript.as_deref()
+    }
+
+    /// [`Context::enable_transcript`] with the provider instance `crypto`.
+    pub(crate) fn enable_transcript_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+    ) -> Result<(), HpkeError> {
+        if self.transcript.is_some() {
+            return Ok(());
+        }
+        if self.sequence_number != 0 {
+            return Err(HpkeError::InvalidConfig);
+        }
+        self.transcript = Some(initial(crypto, &self.suite)?);
+        Ok(())
+    }
+
+    /// [`Context::transcript_commitment`] with the provider instance `crypto`.
+    pub(crate) fn transcript_commitment_with(
+        &self,
+        crypto: &impl HpkeCryptoInstance,
+    ) -> Result<Vec<u8>, HpkeError> {
+        let transcript = self.transcript.as_ref().ok_or(HpkeError::InvalidConfig)?;
+        self.export_with(
+            crypto,
+            &util::concat(&[TRANSCRIPT_LABEL, transcript]),
+            crypto.kdf_digest_length(self.suite.kdf),
+        )
+    }
+
+    /// Record a message with the current sequence number in the transcript,
+    /// if it is enabled.
+    pub(crate) fn record_transcript_with(
+        &mut self,
+        crypto: &impl HpkeCryptoInstance,
+        aad_len: usize,
+        ct_len: usize,
+    ) -> Result<(), HpkeError> {
+        if let Some(transcript) = &self.transcript {
+            let entry = TranscriptEntry {
+                sequence_number: self.sequence_number,
+                aad_len,
+                ct_len,
+            };
+            self.transcript = Some(append(crypto, &self.suite, transcript, &entry)?);
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,93 @@
 use alloc::vec::Vec;
 
+use crate::HpkeError;
+
 #[inline]
 pub(crate) fn concat(values: &[&[u8]]) -> Vec<u8> {
     values.join(&[][..])
 }
 
+/// Returns `1` if `x` is non-zero and `0` otherwise, without branching on `x`.
+#[inline]
+pub(crate) fn ct_is_nonzero(x: usize) -> u8 {
+    ((x | x.wrapping_neg()) >> (usize::BITS - 1)) as u8
+}
+
+/// Returns `1` if `a < b` and `0` otherwise, without branching on the inputs.
 #[inline]
-pub(crate) fn xor_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
-    assert_eq!(a.len(), b.len());
-    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+pub(crate) fn ct_lt(a: usize, b: usize) -> u8 {
+    (((!a & b) | ((!a | b) & a.wrapping_sub(b))) >> (usize::BITS - 1)) as u8
+}
+
+/// XOR the big endian sequence number `seq` into the end of `base_nonce`.
+///
+/// Returns `None` if `seq` doesn't fit into the nonce.
+pub(crate) fn xor_nonce(base_nonce: &[u8], seq: u32) -> Option<Vec<u8>> {
+    let seq = seq.to_be_bytes();
+    let truncated = seq.len().saturating_sub(base_nonce.len());
+    if seq.iter().take(truncated).any(|&b| b != 0) {
+        return None;
+    }
+    let mut nonce = base_nonce.to_vec();
+    for (n, s) in nonce.iter_mut().rev().zip(seq.iter().rev()) {
+        *n ^= s;
+    }
+    Some(nonce)
+}
+
+/// Split `len` bytes off the front of `bytes`.
+pub(crate) fn split(bytes: &[u8], len: usize) -> Result<(&[u8], &[u8]), HpkeError> {
+    bytes.split_at_checked(len).ok_or(HpkeError::InvalidInput)
+}
+
+/// Read a big-endian `u16` from the front of `bytes`.
+pub(crate) fn read_u16(bytes: &[u8]) -> Result<(u16, &[u8]), HpkeError> {
+    let (value, rest) = split(bytes, 2)?;
+    let value = value.try_into().map_err(|_| HpkeError::InvalidInput)?;
+    Ok((u16::from_be_bytes(value), rest))
+}
+
+/// Read a vector with a `u16` length from the front of `bytes`.
+pub(crate) fn read_vec16(bytes: &[u8]) -> Result<(&[u8], &[u8]), HpkeError> {
+    let (len, rest) = read_u16(bytes)?;
+    split(rest, len.into())
+}
+
+/// Write `value` with a `u16` length to `out`.
+///
+/// Returns an [`HpkeError::InvalidInput`] if `value` is too long.
+pub(crate) fn write_vec16(out: &mut Vec<u8>, value: &[u8]) -> Result<(), HpkeError> {
+    let len = u16::try_from(value.len()).map_err(|_| HpkeError::InvalidInput)?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(value);
+    Ok(())
+}
+
+#[test]
+fn test_ct_helpers() {
+    assert_eq!(ct_is_nonzero(0), 0);
+    assert_eq!(ct_is_nonzero(1), 1);
+    assert_eq!(ct_is_nonzero(usize::MAX), 1);
+    assert_eq!(ct_lt(0, 32), 1);
+    assert_eq!(ct_lt(31, 32), 1);
+    assert_eq!(ct_lt(32, 32), 0);
+    assert_eq!(ct_lt(33, 32), 0);
+    assert_eq!(ct_lt(usize::MAX, 32), 0);
+    assert_eq!(ct_lt(32, usize::MAX), 1);
+}
+
+#[test]
+fn test_xor_nonce() {
+    // The sequence number goes into the last bytes of nonces of any length,
+    // e.g. the 24 byte nonces of XChaCha20Poly1305.
+    let base_nonce = [0xffu8; 24];
+    let nonce = xor_nonce(&base_nonce, 0x0102_0304).unwrap();
+    assert_eq!(nonce[..20], base_nonce[..20]);
+    assert_eq!(nonce[20..], [0xfe, 0xfd, 0xfc, 0xfb]);
+
+    // Sequence numbers must fit into short nonces.
+    assert_eq!(xor_nonce(&[0u8; 2], 0xffff).unwrap(), [0xff, 0xff]);
+    assert!(xor_nonce(&[0u8; 2], 0x1_0000).is_none());
 }
 
 #[test]
@@ -21,3 +100,20 @@ fn test_concat() {
         concat(&[a.as_bytes(), b.as_bytes()])[..]
     )
 }
+
+#[test]
+fn test_vec16() {
+    let mut out = Vec::new();
+    write_vec16(&mut out, b"abc").unwrap();
+    assert_eq!(out, [0, 3, b'a', b'b', b'c']);
+    out.push(0xff);
+    assert_eq!(read_vec16(&out).unwrap(), (&b"abc"[..], &[0xff][..]));
+
+    // Too long and truncated vectors are rejected with the same error.
+    assert_eq!(
+        write_vec16(&mut out, &[0; 0x1_0000]),
+        Err(HpkeError::InvalidInput)
+    );
+    assert_eq!(read_vec16(&[0, 4, 1, 2, 3]), Err(HpkeError::InvalidInput));
+    assert_eq!(read_u16(&[0]), Err(HpkeError::InvalidInput));
+}
@@ -0,0 +1,157 @@
+//! X25519Kyber768Draft00 as described in draft-westerbaan-cfrg-hpke-xyber768d00.
+//!
+//! The hybrid of DHKEM(X25519, HKDF-SHA256) and Kyber768.
+//! Keys, encapsulations and shared secrets are the concatenations of the
+//! X25519 and the Kyber768 values, in this order.
+//!
+//! The DHKEM is computed here with the DH functions of the crypto provider,
+//! the provider implements Kyber768 in its KEM functions.
+
+use alloc::vec::Vec;
+
+use hpke_rs_crypto::{
+    error::Error,
+    types::{KemAlgorithm, PrivateKeyRef},
+    HpkeCryptoInstance,
+};
+use zeroize::Zeroize;
+
+use crate::util::*;
+use crate::{
+    dh_kem,
+    kdf::{labeled_expand_with, labeled_extract_with},
+    kem::*,
+};
+
+const ALG: KemAlgorithm = KemAlgorithm::X25519Kyber768Draft00;
+const DH_ALG: KemAlgorithm = KemAlgorithm::DhKem25519;
+
+/// The length of the X25519 keys and encapsulation.
+const X25519_LEN: usize = 32;
+
+/// The length of the Kyber768 key generation seed.
+const KYBER_SEED_LEN: usize = 64;
+
+/// Derive the key pair from the input key material `ikm`.
+pub(super) fn derive_key_pair(
+    crypto: &impl HpkeCryptoInstance,
+    ikm: &[u8],
+) -> Result<(PublicKey, PrivateKey), Error> {
+    let suite_id = ciphersuite(ALG);
+    let mut dkp_prk = labeled_extract_with(crypto, ALG.into(), &[], &suite_id, "dkp_prk", ikm)?;
+    let seed = labeled_expand_with(
+        crypto,
+        ALG.into(),
+        &dkp_prk,
+        &suite_id,
+        "sk",
+        &[],
+        X25519_LEN.saturating_add(KYBER_SEED_LEN),
+    );
+    dkp_prk.zeroize();
+    let mut seed = seed?;
+    let (seed_x, seed_k) = seed
+        .split_at_checked(X25519_LEN)
+        .ok_or(Error::KemInvalidSecretKey)?;
+
+    let key_pair = dh_kem::derive_key_pair(crypto, DH_ALG, &ciphersuite(DH_ALG), seed_x).and_then(
+        |(pk_x, sk_x)| {
+            let (pk_k, sk_k) = crypto.kem_key_gen_derand(ALG, seed_k)?;
+            Ok((concat(&[&pk_x, &pk_k]), concat(&[&sk_x, &sk_k])))
+        },
+    );
+    seed.zeroize();
+    key_pair
+}
+
+/// Encapsulation to `pk_r` with the `randomness` for the ephemeral X25519
+/// key and the `prng` for Kyber768.
+///
+/// Returns (shared secret, encapsulated secret).
+pub(super) fn encaps<Crypto: HpkeCryptoInstance>(
+    crypto: &Crypto,
+    pk_r: &[u8],
+    randomness: &[u8],
+    prng: &mut Crypto::HpkePrng,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    encaps_with(crypto, pk_r, randomness, |pk_k| {
+        crypto.kem_encaps(ALG, pk_k, prng)
+    })
+}
+
+/// Encapsulation to `pk_r` with the `randomness` for the ephemeral X25519
+/// key, followed by the randomness for Kyber768.
+///
+/// Returns (shared secret, encapsulated secret).
+pub(super) fn encaps_derand(
+    crypto: &impl HpkeCryptoInstance,
+    pk_r: &[u8],
+    randomness: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (randomness_x, randomness_k) = randomness
+        .split_at_checked(X25519_LEN)
+        .ok_or(Error::InsufficientRandomness)?;
+    encaps_with(crypto, pk_r, randomness_x, |pk_k| {
+        crypto.kem_encaps_derand(ALG, pk_k, randomness_k)
+    })
+}
+
+/// Encapsulation to `pk_r` with the `randomness` for the ephemeral X25519
+/// key and `encaps_k` for Kyber768.
+fn encaps_with(
+    crypto: &impl HpkeCryptoInstance,
+    pk_r: &[u8],
+    randomness: &[u8],
+    encaps_k: impl FnOnce(&[u8]) -> Result<(Vec<u8>, Vec<u8>), Error>,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    if pk_r.len() != ALG.public_key_len() {
+        return Err(Error::KemInvalidPublicKey);
+    }
+    let (pk_x, pk_k) = pk_r
+        .split_at_checked(X25519_LEN)
+        .ok_or(Error::KemInvalidPublicKey)?;
+    let (mut ss_x, enc_x) = dh_kem::encaps(crypto, DH_ALG, pk_x, &ciphersuite(DH_ALG), randomness)?;
+    let (mut ss_k, ct_k) = encaps_k(pk_k)?;
+    let ss = concat(&[&ss_x, &ss_k]);
+    ss_x.zeroize();
+    ss_k.zeroize();
+    Ok((ss, concat(&[&enc_x, &ct_k])))
+}
+
+/// Decapsulation of `enc` with `sk_r`.
+///
+/// Failures depending on `enc` are reported as
+/// [`Error::KemInvalidCiphertext`].
+pub(super) fn decaps(
+    crypto: &impl HpkeCryptoInstance,
+    enc: &[u8],
+    sk_r: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if sk_r.len() != ALG.private_key_len() {
+        return Err(Error::KemInvalidSecretKey);
+    }
+    if enc.len() != ALG.encapsulated_key_len() {
+        return Err(Error::KemInvalidCiphertext);
+    }
+    let (sk_x, sk_k) = sk_r
+        .split_at_checked(X25519_LEN)
+        .ok_or(Error::KemInvalidSecretKey)?;
+    let (enc_x, ct_k) = enc
+        .split_at_checked(X25519_LEN)
+        .ok_or(Error::KemInvalidCiphertext)?;
+    let mut ss_x = dh_kem::decaps(
+        crypto,
+        DH_ALG,
+        enc_x,
+        PrivateKeyRef::Bytes(sk_x),
+        &ciphersuite(DH_ALG),
+    )?;
+    // Kyber768 decapsulation rejects implicitly.
+    let mut ss_k = crypto
+        .kem_decaps(ALG, ct_k, sk_k)
+        .map_err(|_| Error::KemInvalidCiphertext)?;
+    let ss = concat(&[&ss_x, &ss_k]);
+    ss_x.zeroize();
+    ss_k.zeroize();
+    Ok(ss)
+}
@@ -2,304 +2,82 @@ extern crate hpke_rs as hpke;
 
 use hpke_rs_rust_crypto::HpkeRustCrypto;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use serde::{self, Deserialize, Serialize};
-use std::convert::TryInto;
-use std::fs::File;
-use std::io::BufReader;
 use std::time::Instant;
 
 use hpke::prelude::*;
-use hpke::test_util::{hex_to_bytes, hex_to_bytes_option, vec_to_option_slice};
+use hpke::test_util::kat::{kat, test_vectors};
 use hpke_rs_crypto::{types::*, HpkeCrypto};
 use hpke_rs_libcrux::HpkeLibcrux;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[allow(non_snake_case)]
-struct HpkeTestVector {
-    mode: u8,
-    kem_id: u16,
-    kdf_id: u16,
-    aead_id: u16,
-    info: String,
-    ikmR: String,
-    ikmS: Option<String>,
-    ikmE: String,
-    skRm: String,
-    skSm: Option<String>,
-    skEm: String,
-    psk: Option<String>,
-    psk_id: Option<String>,
-    pkRm: String,
-    pkSm: Option<String>,
-    pkEm: String,
-    enc: String,
-    shared_secret: String,
-    key_schedule_context: String,
-    secret: String,
-    key: String,
-    base_nonce: String,
-    exporter_secret: String,
-    encryptions: Vec<CiphertextKAT>,
-    exports: Vec<ExportsKAT>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[allow(non_snake_case)]
-struct CiphertextKAT {
-    aad: String,
-    ct: String,
-    nonce: String,
-    pt: String,
+#[test]
+fn kats_rust_crypto() {
+    run::<HpkeRustCrypto>();
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[allow(non_snake_case)]
-struct ExportsKAT {
-    exporter_context: String,
-    L: usize,
-    exported_value: String,
+#[test]
+fn kats_libcrux() {
+    run::<HpkeLibcrux>();
 }
 
-fn kat<Crypto: HpkeCrypto + 'static>(tests: Vec<HpkeTestVector>) {
-    // Replace into_par_iter() with into_iter() to run tests sequentially.
-    tests.into_par_iter().for_each(|test| {
-        println!(
-            "Testing mode {:?} with ciphersuite {:?}_{:?}_{:?}",
-            test.mode, test.kem_id, test.kdf_id, test.aead_id
-        );
-        let mode: HpkeMode = test.mode.try_into().unwrap();
-        let kem_id: KemAlgorithm = test.kem_id.try_into().unwrap();
-        let kdf_id: KdfAlgorithm = test.kdf_id.try_into().unwrap();
-        let aead_id: AeadAlgorithm = test.aead_id.try_into().unwrap();
-
-        if Crypto::supports_kem(kem_id).is_err() {
-            log::trace!(
-                " > KEM {:?} not implemented yet for {}",
-                kem_id,
-                Crypto::name()
-            );
-            return;
+#[test]
+fn kats_keystore() {
+    use hpke::test_util::kat::kat_receiver;
+    use hpke_rs_crypto::{
+        error::Error,
+        keystore::{KeyStore, KeyStoreProvider},
+    };
+
+    /// A key store holding the receiver's private key in memory.
+    #[derive(Debug)]
+    struct MemoryStore(Vec<u8>);
+
+    impl KeyStore for MemoryStore {
+        fn name(&self) -> String {
+            "Memory".to_string()
         }
-
-        if Crypto::supports_aead(aead_id).is_err() {
-            log::trace!(
-                " > AEAD {:?} not implemented yet for {}",
-                aead_id,
-                Crypto::name()
-            );
-            return;
+        fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+            HpkeRustCrypto::supports_kem(alg)
         }
-
-        if Crypto::supports_kdf(kdf_id).is_err() {
-            log::trace!(
-                " > KDF {:?} not implemented yet for {}",
-                kdf_id,
-                Crypto::name()
-            );
-            return;
+        fn dh(&self, alg: KemAlgorithm, pk: &[u8], _handle: &[u8]) -> Result<Vec<u8>, Error> {
+            HpkeRustCrypto::dh(alg, pk, &self.0)
         }
-
-        log::trace!(
-            "Testing mode {:?} with ciphersuite {:?}_{:?}_{:?}",
-            mode,
-            kem_id,
-            kdf_id,
-            aead_id
-        );
-
-        // Init HPKE with the given mode and ciphersuite.
-        let mut hpke = Hpke::<Crypto>::new(mode, kem_id, kdf_id, aead_id);
-
-        // Set up sender and receiver.
-        let pk_rm = HpkePublicKey::new(hex_to_bytes(&test.pkRm));
-        let sk_rm = HpkePrivateKey::new(hex_to_bytes(&test.skRm));
-        let pk_em = HpkePublicKey::new(hex_to_bytes(&test.pkEm));
-        let sk_em = HpkePrivateKey::new(hex_to_bytes(&test.skEm));
-        let pk_sm = hex_to_bytes_option(test.pkSm);
-        let pk_sm = if pk_sm.is_empty() {
-            None
-        } else {
-            Some(HpkePublicKey::new(pk_sm))
-        };
-        let pk_sm = pk_sm.as_ref();
-        let sk_sm = hex_to_bytes_option(test.skSm);
-        let sk_sm = if sk_sm.is_empty() {
-            None
-        } else {
-            Some(HpkePrivateKey::new(sk_sm))
-        };
-        let sk_sm = sk_sm.as_ref();
-        let info = hex_to_bytes(&test.info);
-        let psk = hex_to_bytes_option(test.psk);
-        let psk = vec_to_option_slice(&psk);
-        let psk_id = hex_to_bytes_option(test.psk_id);
-        let psk_id = vec_to_option_slice(&psk_id);
-        let shared_secret = hex_to_bytes(&test.shared_secret);
-        let key = hex_to_bytes(&test.key);
-        let nonce = hex_to_bytes(&test.base_nonce);
-        let exporter_secret = hex_to_bytes(&test.exporter_secret);
-
-        // Input key material.
-        let ikm_r = hex_to_bytes(&test.ikmR);
-        let ikm_e = hex_to_bytes(&test.ikmE);
-        let ikm_s = hex_to_bytes_option(test.ikmS);
-
-        // Use internal `key_schedule` function for KAT.
-        let mut direct_ctx = hpke
-            .key_schedule(
-                &shared_secret,
-                &info,
-                psk.unwrap_or_default(),
-                psk_id.unwrap_or_default(),
-            )
-            .unwrap();
-
-        // Check setup info
-        // Note that key and nonce are empty for exporter only key derivation.
-        assert_eq!(direct_ctx.key(), key);
-        assert_eq!(direct_ctx.nonce(), nonce);
-        assert_eq!(direct_ctx.exporter_secret(), exporter_secret);
-        assert_eq!(direct_ctx.sequence_number(), 0);
-
-        // Test key pair derivation.
-        let (my_sk_r, my_pk_r) = hpke.derive_key_pair(&ikm_r).unwrap().into_keys();
-        assert_eq!(sk_rm, my_sk_r);
-        assert_eq!(pk_rm, my_pk_r);
-        let (my_sk_e, my_pk_e) = hpke.derive_key_pair(&ikm_e).unwrap().into_keys();
-        assert_eq!(sk_em, my_sk_e);
-        assert_eq!(pk_em, my_pk_e);
-        if let (Some(sk_sm), Some(pk_sm)) = (sk_sm, pk_sm) {
-            let (my_sk_s, my_pk_s) = hpke.derive_key_pair(&ikm_s).unwrap().into_keys();
-            assert_eq!(sk_sm, &my_sk_s);
-            assert_eq!(pk_sm, &my_pk_s);
+        fn public_key(&self, alg: KemAlgorithm, _handle: &[u8]) -> Result<Vec<u8>, Error> {
+            HpkeRustCrypto::secret_to_public(alg, &self.0)
         }
+    }
 
-        // Setup KAT receiver.
-        let kat_enc = hex_to_bytes(&test.enc);
-        let mut receiver_context_kat = hpke
-            .setup_receiver(&kat_enc, &sk_rm, &info, psk, psk_id, pk_sm)
-            .unwrap();
-
-        // Setup sender and receiver with KAT randomness.
-        // We first have to inject the randomness (ikmE).
-
-        #[cfg(feature = "hpke-test-prng")]
+    let tests = test_vectors(include_str!("test_vectors.json"));
+    tests.into_par_iter().for_each(|test| {
+        let suite = test.suite();
+        if HpkeRustCrypto::supports_kem(suite.kem).is_err()
+            || HpkeRustCrypto::supports_kdf(suite.kdf).is_err()
+            || HpkeRustCrypto::supports_aead(suite.aead).is_err()
         {
-            log::trace!("Testing with known ikmE ...");
-            let mut hpke_sender = Hpke::<Crypto>::new(mode, kem_id, kdf_id, aead_id);
-            // This only works when seeding the PRNG with ikmE.
-            hpke_sender.seed(&ikm_e).expect("Error injecting ikm_e");
-            let (enc, _sender_context_kat) = hpke_sender
-                .setup_sender(&pk_rm, &info, psk, psk_id, sk_sm)
-                .unwrap();
-            let receiver_context = hpke
-                .setup_receiver(&enc, &sk_rm, &info, psk, psk_id, pk_sm)
-                .unwrap();
-            assert_eq!(enc, kat_enc);
-            assert_eq!(receiver_context.key(), receiver_context_kat.key());
-            assert_eq!(receiver_context.nonce(), receiver_context_kat.nonce());
-            assert_eq!(
-                receiver_context.exporter_secret(),
-                receiver_context_kat.exporter_secret()
-            );
-            receiver_context_kat = receiver_context;
-            assert_eq!(receiver_context_kat.key(), key);
-            assert_eq!(receiver_context_kat.nonce(), nonce);
-            assert_eq!(receiver_context_kat.exporter_secret(), exporter_secret);
-            assert_eq!(receiver_context_kat.sequence_number(), 0);
-        }
-
-        // Setup sender and receiver for self tests.
-        let (enc, mut sender_context) = hpke
-            .setup_sender(&pk_rm, &info, psk, psk_id, sk_sm)
-            .unwrap();
-        let mut receiver_context = hpke
-            .setup_receiver(&enc, &sk_rm, &info, psk, psk_id, pk_sm)
-            .unwrap();
-
-        // Encrypt
-        log::trace!(
-            "Testing encryptions for mode {:?} with ciphersuite {:?}_{:?}_{:?}",
-            mode,
-            kem_id,
-            kdf_id,
-            aead_id
-        );
-        for encryption in test.encryptions.iter() {
-            // Cloning the Hpke object renews the test PRNG.
-            hpke = hpke.clone();
-            let aad = hex_to_bytes(&encryption.aad);
-            let ptxt = hex_to_bytes(&encryption.pt);
-            let ctxt_kat = hex_to_bytes(&encryption.ct);
-
-            // Test context API self-test
-            let ctxt_out = sender_context.seal(&aad, &ptxt).unwrap();
-            let ptxt_out = receiver_context.open(&aad, &ctxt_out).unwrap();
-            assert_eq!(ptxt_out, ptxt);
-
-            // Test single-shot API self-test
-            let (enc, ct) = hpke
-                .seal(&pk_rm, &info, &aad, &ptxt, psk, psk_id, sk_sm)
-                .unwrap();
-            let ptxt_out = hpke
-                .open(&enc, &sk_rm, &info, &aad, &ct, psk, psk_id, pk_sm)
-                .unwrap();
-            assert_eq!(ptxt_out, ptxt);
-
-            // Test KAT receiver context open
-            let ptxt_out = receiver_context_kat.open(&aad, &ctxt_kat).unwrap();
-            assert_eq!(ptxt_out, ptxt);
-
-            // Test KAT seal on direct_ctx
-            let ct = direct_ctx.seal(&aad, &ptxt).unwrap();
-            assert_eq!(ctxt_kat, ct);
+            return;
         }
-
-        // Test KAT on direct_ctx for exporters
-        log::trace!(
-            "Testing exporter for mode {:?} with ciphersuite {:?}_{:?}_{:?}",
-            mode,
-            kem_id,
-            kdf_id,
-            aead_id
+        let store = MemoryStore(test.receiver_private_key());
+        let sk_r = HpkePrivateKey::from_handle(suite.kem, b"receiver".to_vec());
+        kat_receiver(
+            KeyStoreProvider::<HpkeRustCrypto, _>::new(store),
+            &test,
+            &sk_r,
         );
-        for export in test.exports.iter() {
-            let export_context = hex_to_bytes(&export.exporter_context);
-            let export_value = hex_to_bytes(&export.exported_value);
-            let length = export.L;
-
-            let exported_secret = direct_ctx.export(&export_context, length).unwrap();
-            assert_eq!(export_value, exported_secret);
-        }
     });
 }
 
-#[test]
-fn kats_rust_crypto() {
-    run::<HpkeRustCrypto>();
-}
-
-#[test]
-fn kats_libcrux() {
-    run::<HpkeLibcrux>();
-}
-
 fn run<Crypto: HpkeCrypto + 'static>() {
     let _ = pretty_env_logger::try_init();
     let files = vec!["tests/test_vectors.json", "tests/test_vectors_k256.json"];
     for file in files {
-        let file = match File::open(file) {
-            Ok(f) => f,
+        let json = match std::fs::read_to_string(file) {
+            Ok(json) => json,
             Err(_) => panic!("Couldn't open file {}.", file),
         };
-        let reader = BufReader::new(file);
-        let tests: Vec<HpkeTestVector> = match serde_json::from_reader(reader) {
-            Ok(r) => r,
-            Err(e) => panic!("Error reading file.\n{:?}", e),
-        };
+        let tests = test_vectors(&json);
 
         let now = Instant::now();
-        kat::<Crypto>(tests.clone());
+        // Replace into_par_iter() with into_iter() to run tests sequentially.
+        tests.into_par_iter().for_each(|test| kat::<Crypto>(&test));
         let time = now.elapsed();
         log::info!(
             "Test vectors with {} took: {}s",
@@ -311,6 +89,50 @@ fn run<Crypto: HpkeCrypto + 'static>() {
 
 #[cfg(feature = "serialization")]
 #[cfg(feature = "hazmat")]
+/// Check the X-Wing key generation and decapsulation of the `Crypto` provider
+/// against the first test vector of draft-connolly-cfrg-xwing-kem-06.
+///
+/// Returns the vector's public key, encapsulation randomness, ciphertext, and
+/// shared secret.
+fn kat_xwing<Crypto: HpkeCrypto>() -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
+    use hpke::test_util::hex_to_bytes;
+
+    let kat: serde_json::Value =
+        serde_json::from_str(include_str!("test_vectors_xwing.json")).unwrap();
+    let field = |name: &str| hex_to_bytes(kat[name].as_str().unwrap());
+    let (seed, pk, eseed, ct, ss) = (
+        field("seed"),
+        field("pk"),
+        field("eseed"),
+        field("ct"),
+        field("ss"),
+    );
+
+    let (pk_r, sk_r) = Crypto::kem_key_gen_derand(KemAlgorithm::XWingDraft06, &seed).unwrap();
+    assert_eq!(pk_r, pk);
+    // The private key is the seed.
+    assert_eq!(sk_r, seed);
+    assert_eq!(
+        Crypto::kem_decaps(KemAlgorithm::XWingDraft06, &ct, &sk_r).unwrap(),
+        ss
+    );
+    (pk, eseed, ct, ss)
+}
+
+#[test]
+fn kat_xwing_rust_crypto() {
+    let (pk, eseed, ct, ss) = kat_xwing::<HpkeRustCrypto>();
+    assert_eq!(
+        HpkeRustCrypto::kem_encaps_derand(KemAlgorithm::XWingDraft06, &pk, &eseed).unwrap(),
+        (ss, ct)
+    );
+}
+
+#[test]
+fn kat_xwing_libcrux() {
+    kat_xwing::<HpkeLibcrux>();
+}
+
 #[test]
 fn test_serialization() {
     use hpke::HpkeKeyPair;
@@ -361,3 +183,169 @@ fn test_serialization() {
     // let aead_id: aead::Mode = aead::Mode::AesGcm128;
     // let hpke = Hpke::new(mode, kem_id, kdf_id, aead_id);
 }
+
+#[cfg(feature = "serialization")]
+#[test]
+fn test_strict_key_deserialization() {
+    use hpke::HpkePrivateKey;
+    use tls_codec::Serialize;
+
+    let kem_mode = KemAlgorithm::DhKem25519;
+    let mut hpke = Hpke::<HpkeRustCrypto>::new(
+        HpkeMode::Base,
+        kem_mode,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::ChaCha20Poly1305,
+    );
+    let (sk, pk) = hpke.generate_key_pair().unwrap().into_keys();
+
+    // TLS
+    let mut encoded = pk.tls_serialize_detached().unwrap();
+    assert_eq!(
+        HpkePublicKey::tls_deserialize_strict(kem_mode, &encoded).unwrap(),
+        pk
+    );
+    assert!(HpkePublicKey::tls_deserialize_strict(KemAlgorithm::DhKemP256, &encoded).is_err());
+    encoded.push(0);
+    assert_eq!(
+        HpkePublicKey::tls_deserialize_strict(kem_mode, &encoded).unwrap_err(),
+        tls_codec::Error::TrailingData
+    );
+
+    // JSON
+    let serialized_pk = serde_json::to_string(&pk).unwrap();
+    let mut deserializer = serde_json::Deserializer::from_str(&serialized_pk);
+    assert_eq!(
+        HpkePublicKey::deserialize_strict(kem_mode, &mut deserializer).unwrap(),
+        pk
+    );
+    let mut deserializer = serde_json::Deserializer::from_str(&serialized_pk);
+    assert!(HpkePublicKey::deserialize_strict(KemAlgorithm::DhKem448, &mut deserializer).is_err());
+
+    let serialized_sk = serde_json::to_string(&sk).unwrap();
+    let mut deserializer = serde_json::Deserializer::from_str(&serialized_sk);
+    assert!(HpkePrivateKey::deserialize_strict(kem_mode, &mut deserializer).is_ok());
+    let mut deserializer = serde_json::Deserializer::from_str(&serialized_sk);
+    assert!(
+        HpkePrivateKey::deserialize_strict(KemAlgorithm::DhKemP384, &mut deserializer).is_err()
+    );
+
+    // Constructors
+    assert_eq!(
+        HpkePublicKey::new_checked(kem_mode, vec![0u8; 33]).unwrap_err(),
+        HpkeError::InvalidKeyLength {
+            expected: 32,
+            got: 33
+        }
+    );
+    assert!(HpkePrivateKey::new_checked(kem_mode, vec![0u8; 32]).is_ok());
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn test_error_serialization() {
+    let errors = [
+        HpkeError::OpenError,
+        HpkeError::CryptoError("Crypto error: test".to_string()),
+        HpkeError::InvalidKeyLength {
+            expected: 32,
+            got: 31,
+        },
+        HpkeError::UnsupportedAlgorithm {
+            kind: hpke::AlgorithmKind::Aead,
+            id: 0x0001,
+        },
+        HpkeError::UnsupportedByProvider {
+            kind: hpke::AlgorithmKind::Kdf,
+        },
+    ];
+    for error in errors {
+        let serialized = serde_json::to_string(&error).unwrap();
+        let deserialized: HpkeError = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(error, deserialized);
+        assert_eq!(error.code(), deserialized.code());
+    }
+
+    assert_eq!(HpkeError::OpenError.code(), 1);
+    assert_eq!(HpkeError::CryptoError(String::new()).code(), 9);
+    assert_eq!(
+        HpkeError::UnsupportedAlgorithm {
+            kind: hpke::AlgorithmKind::Kem,
+            id: 0
+        }
+        .code(),
+        17
+    );
+    assert_eq!(
+        HpkeError::UnsupportedByProvider {
+            kind: hpke::AlgorithmKind::Kem
+        }
+        .code(),
+        24
+    );
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn test_message_key_hint() {
+    use hpke::message::{HpkeMessage, KeyHint};
+    use std::collections::HashMap;
+    use tls_codec::Serialize;
+
+    let mut hpke = Hpke::<HpkeRustCrypto>::new(
+        HpkeMode::Base,
+        KemAlgorithm::DhKem25519,
+        KdfAlgorithm::HkdfSha256,
+        AeadAlgorithm::ChaCha20Poly1305,
+    );
+    let mut keys: HashMap<KeyHint, _> = HashMap::new();
+    let mut public_keys = Vec::new();
+    for _ in 0..4 {
+        let (sk, pk) = hpke.generate_key_pair().unwrap().into_keys();
+        keys.insert(hpke.key_hint(&pk).unwrap(), sk);
+        public_keys.push(pk);
+    }
+
+    // Messages without hint
+    let (enc, ct) = hpke
+        .seal(
+            &public_keys[2],
+            b"info",
+            b"aad",
+            b"no hint",
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    let message = HpkeMessage::new(enc, ct);
+    let encoded = message.tls_serialize_detached().unwrap();
+    let decoded = HpkeMessage::tls_deserialize_with(hpke.suite(), &mut encoded.as_slice()).unwrap();
+    assert_eq!(decoded, message);
+    assert!(decoded.key_hint().is_none());
+
+    // Messages with hint
+    let (enc, ct) = hpke
+        .seal(&public_keys[1], b"info", b"aad", b"hint", None, None, None)
+        .unwrap();
+    let hint = hpke.key_hint(&public_keys[1]).unwrap();
+    let message = HpkeMessage::new(enc, ct).with_key_hint(hint);
+    let encoded = message.tls_serialize_detached().unwrap();
+    let decoded = HpkeMessage::tls_deserialize_with(hpke.suite(), &mut encoded.as_slice()).unwrap();
+    assert_eq!(decoded, message);
+
+    let sk_r = &keys[decoded.key_hint().unwrap()];
+    let ptxt = hpke
+        .open(
+            decoded.enc(),
+            sk_r,
+            b"info",
+            b"aad",
+            decoded.ciphertext(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(ptxt, b"hint");
+}
@@ -0,0 +1,120 @@
+//! # Private Key Stores
+//!
+//! A [`KeyStore`] holds private keys in secure hardware, e.g. an HSM or the
+//! platform keystore, and performs the Diffie-Hellman operations with them,
+//! such that the keys never leave the hardware.
+//! The keys are identified by opaque handles, see [`PrivateKeyRef::Handle`].
+//!
+//! [`KeyStoreProvider`] combines a key store with an [`HpkeCrypto`] provider
+//! for all other operations, i.e. ephemeral keys, the KDF, and the AEAD, which
+//! don't involve long-term secrets.
+
+use alloc::{format, string::String, vec::Vec};
+use core::marker::PhantomData;
+
+use crate::{
+    error::Error,
+    types::{self, AeadAlgorithm, KemAlgorithm, PrivateKeyRef},
+    HpkeCrypto, HpkeCryptoInstance,
+};
+
+/// A store of private keys for DH KEMs that are referenced by handles.
+pub trait KeyStore: core::fmt::Debug + Send + Sync {
+    /// The name of the key store.
+    fn name(&self) -> String;
+
+    /// Returns an error if the key store doesn't support keys for the DH KEM
+    /// `alg`.
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error>;
+
+    /// Diffie-Hellman of the encoded public key `pk` with the private key
+    /// `handle`.
+    ///
+    /// See [`HpkeCrypto::dh`] for the requirements, in particular the
+    /// rejection of invalid public keys.
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], handle: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Get the encoded public key of the private key `handle`.
+    fn public_key(&self, alg: KemAlgorithm, handle: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// A crypto provider that uses the `Store` for private keys given as handles
+/// and the `Crypto` provider for everything else.
+#[derive(Debug)]
+pub struct KeyStoreProvider<Crypto, Store> {
+    store: Store,
+    phantom: PhantomData<Crypto>,
+}
+
+impl<Crypto: HpkeCrypto, Store: KeyStore> KeyStoreProvider<Crypto, Store> {
+    /// Create a provider for the key `store`.
+    pub fn new(store: Store) -> Self {
+        Self {
+            store,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Get the key store.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+}
+
+/// All operations but the ones with private keys are forwarded to the
+/// `Crypto` provider.
+impl<Crypto: HpkeCrypto, Store: KeyStore> HpkeCryptoInstance for KeyStoreProvider<Crypto, Store> {
+    crate::forward_to_hpke_crypto!(@operations Crypto);
+
+    fn name(&self) -> String {
+        format!(
+            "{} with {}",
+            self.store.name(),
+            <Crypto as HpkeCrypto>::name()
+        )
+    }
+
+    /// Both the key store and the `Crypto` provider, for the ephemeral keys,
+    /// have to support the KEM.
+    fn supports_kem(&self, alg: KemAlgorithm) -> Result<(), Error> {
+        self.store.supports_kem(alg)?;
+        <Crypto as HpkeCrypto>::supports_kem(alg)
+    }
+
+    fn dh_with_key(
+        &self,
+        alg: KemAlgorithm,
+        pk: &[u8],
+        sk: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => <Crypto as HpkeCrypto>::dh(alg, pk, sk),
+            PrivateKeyRef::Handle(handle) => self.store.dh(alg, pk, handle),
+        }
+    }
+
+    fn secret_to_public_with_key(
+        &self,
+        alg: KemAlgorithm,
+        sk: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => <Crypto as HpkeCrypto>::secret_to_public(alg, sk),
+            PrivateKeyRef::Handle(handle) => self.store.public_key(alg, handle),
+        }
+    }
+
+    /// Key stores only hold keys of DH KEMs, which don't decapsulate with
+    /// [`HpkeCryptoInstance::kem_decaps`].
+    fn kem_decaps_with_key(
+        &self,
+        alg: KemAlgorithm,
+        ct: &[u8],
+        sk_r: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk_r {
+            PrivateKeyRef::Bytes(sk_r) => <Crypto as HpkeCrypto>::kem_decaps(alg, ct, sk_r),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+}
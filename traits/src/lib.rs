@@ -7,11 +7,15 @@ extern crate std;
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 
 use error::Error;
-use types::{AeadAlgorithm, KemAlgorithm};
+use types::{AeadAlgorithm, KemAlgorithm, PrivateKeyRef};
 
 pub mod error;
+pub mod keystore;
+#[cfg(feature = "rand_core_06")]
+pub mod rng;
 pub mod types;
 
 // re-export trait
@@ -40,13 +44,11 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
     fn prng() -> Self::HpkePrng;
 
     /// Get the length of the output digest.
+    ///
+    /// Defaults to [`types::KdfAlgorithm::digest_len`].
     #[inline(always)]
     fn kdf_digest_length(alg: types::KdfAlgorithm) -> usize {
-        match alg {
-            types::KdfAlgorithm::HkdfSha256 => 32,
-            types::KdfAlgorithm::HkdfSha384 => 48,
-            types::KdfAlgorithm::HkdfSha512 => 64,
-        }
+        alg.digest_len()
     }
 
     /// KDF Extract
@@ -61,6 +63,13 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
     ) -> Result<Vec<u8>, Error>;
 
     /// Diffie-Hellman
+    ///
+    /// Implementations must return an error for invalid public keys, e.g.
+    /// points that are not on the curve or low order points, and must not
+    /// panic.
+    /// Errors for the public key must be returned after the same amount of
+    /// work on the private key as a successful operation, because the DH KEM
+    /// decapsulation doesn't reveal why it failed.
     fn dh(alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error>;
 
     /// Diffie-Hellman with the base (generate public key for secret key `sk`).
@@ -83,13 +92,105 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
         prng: &mut Self::HpkePrng,
     ) -> Result<(Vec<u8>, Vec<u8>), Error>;
 
+    /// KEM encapsulation to `pk_r` (shared secret, ciphertext) with the given
+    /// `randomness` of length [`KemAlgorithm::encaps_randomness_len`].
+    ///
+    /// The default implementation returns an
+    /// [`Error::UnsupportedKemOperation`].
+    fn kem_encaps_derand(
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let _ = (alg, pk_r, randomness);
+        Err(Error::UnsupportedKemOperation)
+    }
+
     /// KEM decapsulation with `sk_r`.
     /// Returns the shared secret.
+    ///
+    /// Implementations are required to be IND-CCA2 secure, i.e. to implement
+    /// implicit rejection where the KEM defines it (ML-KEM, X-Wing):
+    /// an invalid ciphertext of the correct length must yield a pseudorandom
+    /// shared secret in constant time, not an error.
+    /// Errors must only be returned for malformed inputs, e.g. wrong lengths,
+    /// and must not depend on secret data.
+    /// HPKE maps all errors of this function to the same generic error.
     fn kem_decaps(alg: KemAlgorithm, ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error>;
 
     /// Validate a secret key for its correctness.
     fn dh_validate_sk(alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
 
+    /// Validate a public key of the DH KEM `alg` for its correctness.
+    ///
+    /// Implementations must return an [`Error::KemInvalidPublicKey`] for
+    /// encodings that are not valid public keys, e.g. points that are not on
+    /// the curve or the identity for NIST curves, and the all-zero key for
+    /// X25519.
+    ///
+    /// The default implementation only checks the encoding, see
+    /// [`validate_pk_encoding`].
+    /// Providers should override it to check that the point is on the curve.
+    fn dh_validate_pk(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        validate_pk_encoding(alg, pk)
+    }
+
+    /// Encode the SEC1 public key `pk` of the DH KEM `alg`, which may be
+    /// compressed or uncompressed, in the `compressed` or uncompressed form.
+    ///
+    /// The default implementation returns an
+    /// [`Error::UnsupportedKemOperation`].
+    /// Providers should override it if the underlying library supports point
+    /// compression.
+    fn dh_encode_pk(alg: KemAlgorithm, pk: &[u8], compressed: bool) -> Result<Vec<u8>, Error> {
+        let _ = (alg, pk, compressed);
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    /// Diffie-Hellman with the private key `sk`, which may be a handle.
+    ///
+    /// The default implementation uses [`HpkeCrypto::dh`] for private keys
+    /// given as bytes and returns an [`Error::UnsupportedKemOperation`] for
+    /// handles.
+    /// Providers that hold private keys should override it.
+    fn dh_with_key(alg: KemAlgorithm, pk: &[u8], sk: PrivateKeyRef<'_>) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => Self::dh(alg, pk, sk),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
+    /// Compute the public key of the private key `sk`, which may be a handle.
+    ///
+    /// The default implementation uses [`HpkeCrypto::secret_to_public`] for
+    /// private keys given as bytes and returns an
+    /// [`Error::UnsupportedKemOperation`] for handles.
+    fn secret_to_public_with_key(
+        alg: KemAlgorithm,
+        sk: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => Self::secret_to_public(alg, sk),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
+    /// KEM decapsulation with the private key `sk_r`, which may be a handle.
+    ///
+    /// The default implementation uses [`HpkeCrypto::kem_decaps`] for
+    /// private keys given as bytes and returns an
+    /// [`Error::UnsupportedKemOperation`] for handles.
+    fn kem_decaps_with_key(
+        alg: KemAlgorithm,
+        ct: &[u8],
+        sk_r: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk_r {
+            PrivateKeyRef::Bytes(sk_r) => Self::kem_decaps(alg, ct, sk_r),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
     /// AEAD encrypt.
     fn aead_seal(
         alg: AeadAlgorithm,
@@ -108,6 +209,55 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
         msg: &[u8],
     ) -> Result<Vec<u8>, Error>;
 
+    /// AEAD encrypt `buffer` in place.
+    ///
+    /// Returns the detached tag.
+    ///
+    /// The default implementation copies through [`HpkeCrypto::aead_seal`].
+    /// Providers should override it if the underlying library supports
+    /// in-place encryption.
+    fn aead_seal_in_place_detached(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut ctxt = Self::aead_seal(alg, key, nonce, aad, buffer)?;
+        if ctxt.len() < buffer.len() {
+            return Err(Error::CryptoLibraryError("Invalid AEAD output".into()));
+        }
+        let tag = ctxt.split_off(buffer.len());
+        buffer.copy_from_slice(&ctxt);
+        Ok(tag)
+    }
+
+    /// AEAD decrypt `buffer` in place with the detached `tag`.
+    ///
+    /// `buffer` is only changed if decryption succeeds.
+    ///
+    /// The default implementation copies through [`HpkeCrypto::aead_open`].
+    /// Providers should override it if the underlying library supports
+    /// in-place decryption.
+    fn aead_open_in_place_detached(
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        let mut ctxt = Vec::with_capacity(buffer.len().saturating_add(tag.len()));
+        ctxt.extend_from_slice(buffer);
+        ctxt.extend_from_slice(tag);
+        let ptxt = Self::aead_open(alg, key, nonce, aad, &ctxt)?;
+        if ptxt.len() != buffer.len() {
+            return Err(Error::AeadInvalidCiphertext);
+        }
+        buffer.copy_from_slice(&ptxt);
+        Ok(())
+    }
+
     /// Get key length for AEAD.
     ///
     /// Note that this function returns `0` for export only keys of unknown size.
@@ -116,7 +266,10 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
             AeadAlgorithm::Aes128Gcm => 16,
             AeadAlgorithm::Aes256Gcm => 32,
             AeadAlgorithm::ChaCha20Poly1305 => 32,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 32,
+            AeadAlgorithm::XChaCha20Poly1305 => 32,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
         }
     }
 
@@ -128,7 +281,10 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
             AeadAlgorithm::Aes128Gcm => 12,
             AeadAlgorithm::Aes256Gcm => 12,
             AeadAlgorithm::ChaCha20Poly1305 => 12,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 32,
+            AeadAlgorithm::XChaCha20Poly1305 => 24,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
         }
     }
 
@@ -140,9 +296,464 @@ pub trait HpkeCrypto: core::fmt::Debug + Send + Sync {
             AeadAlgorithm::Aes128Gcm => 16,
             AeadAlgorithm::Aes256Gcm => 16,
             AeadAlgorithm::ChaCha20Poly1305 => 16,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 16,
+            AeadAlgorithm::XChaCha20Poly1305 => 16,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
+        }
+    }
+}
+
+/// An instance-based version of [`HpkeCrypto`].
+///
+/// All functions of [`HpkeCrypto`] are associated functions, such that a
+/// provider can't hold any state.
+/// The methods of this trait take `&self` instead, such that providers can
+/// carry configuration or handles, e.g. to an HSM or a remote KMS, and are
+/// passed into HPKE as values.
+///
+/// Every [`HpkeCrypto`] implements this trait by forwarding to its
+/// associated functions, and so does `PhantomData<Crypto>` for code that has
+/// the provider as type only.
+/// See [`HpkeCrypto`] for the documentation of the functions.
+pub trait HpkeCryptoInstance: core::fmt::Debug + Send + Sync {
+    /// The PRNG implementation returned in [`HpkeCryptoInstance::prng()`].
+    type HpkePrng: RngCore + CryptoRng;
+
+    /// The name of the implementation.
+    fn name(&self) -> String;
+
+    /// Returns an error if the KDF algorithm is not supported by this crypto provider.
+    fn supports_kdf(&self, alg: types::KdfAlgorithm) -> Result<(), Error>;
+
+    /// Returns an error if the KEM algorithm is not supported by this crypto provider.
+    fn supports_kem(&self, alg: types::KemAlgorithm) -> Result<(), Error>;
+
+    /// Returns an error if the AEAD algorithm is not supported by this crypto provider.
+    fn supports_aead(&self, alg: types::AeadAlgorithm) -> Result<(), Error>;
+
+    /// Get a stateful PRNG.
+    fn prng(&self) -> Self::HpkePrng;
+
+    /// Get the length of the output digest.
+    fn kdf_digest_length(&self, alg: types::KdfAlgorithm) -> usize {
+        alg.digest_len()
+    }
+
+    /// KDF Extract
+    fn kdf_extract(
+        &self,
+        alg: types::KdfAlgorithm,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// KDF Expand
+    fn kdf_expand(
+        &self,
+        alg: types::KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Diffie-Hellman, see [`HpkeCrypto::dh`].
+    fn dh(&self, alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Diffie-Hellman with the base (generate public key for secret key `sk`).
+    fn secret_to_public(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// KEM key pair generation (encapsulation key, decapsulation key).
+    fn kem_key_gen(
+        &self,
+        alg: KemAlgorithm,
+        prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error>;
+
+    /// KEM key pair generation (encapsulation key, decapsulation key) based
+    /// on the `seed`.
+    fn kem_key_gen_derand(
+        &self,
+        alg: KemAlgorithm,
+        seed: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error>;
+
+    /// KEM encapsulation to `pk_r` (shared secret, ciphertext).
+    fn kem_encaps(
+        &self,
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        prng: &mut Self::HpkePrng,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error>;
+
+    /// KEM encapsulation with the given `randomness`, see
+    /// [`HpkeCrypto::kem_encaps_derand`].
+    fn kem_encaps_derand(
+        &self,
+        alg: KemAlgorithm,
+        pk_r: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let _ = (alg, pk_r, randomness);
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    /// KEM decapsulation with `sk_r`, see [`HpkeCrypto::kem_decaps`].
+    fn kem_decaps(&self, alg: KemAlgorithm, ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Validate a secret key for its correctness.
+    fn dh_validate_sk(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Validate a public key of the DH KEM `alg` for its correctness.
+    ///
+    /// See [`HpkeCrypto::dh_validate_pk`] for the default implementation.
+    fn dh_validate_pk(&self, alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+        validate_pk_encoding(alg, pk)
+    }
+
+    /// Encode the SEC1 public key `pk` of the DH KEM `alg`, see
+    /// [`HpkeCrypto::dh_encode_pk`].
+    ///
+    /// The default implementation returns an
+    /// [`Error::UnsupportedKemOperation`].
+    fn dh_encode_pk(
+        &self,
+        alg: KemAlgorithm,
+        pk: &[u8],
+        compressed: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let _ = (alg, pk, compressed);
+        Err(Error::UnsupportedKemOperation)
+    }
+
+    /// See [`HpkeCrypto::dh_with_key`].
+    fn dh_with_key(
+        &self,
+        alg: KemAlgorithm,
+        pk: &[u8],
+        sk: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => self.dh(alg, pk, sk),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
         }
     }
+
+    /// See [`HpkeCrypto::secret_to_public_with_key`].
+    fn secret_to_public_with_key(
+        &self,
+        alg: KemAlgorithm,
+        sk: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk {
+            PrivateKeyRef::Bytes(sk) => self.secret_to_public(alg, sk),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
+    /// See [`HpkeCrypto::kem_decaps_with_key`].
+    fn kem_decaps_with_key(
+        &self,
+        alg: KemAlgorithm,
+        ct: &[u8],
+        sk_r: PrivateKeyRef<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        match sk_r {
+            PrivateKeyRef::Bytes(sk_r) => self.kem_decaps(alg, ct, sk_r),
+            PrivateKeyRef::Handle(_) => Err(Error::UnsupportedKemOperation),
+        }
+    }
+
+    /// AEAD encrypt.
+    fn aead_seal(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// AEAD decrypt.
+    fn aead_open(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// AEAD encrypt `buffer` in place, see
+    /// [`HpkeCrypto::aead_seal_in_place_detached`].
+    ///
+    /// The default implementation copies through
+    /// [`HpkeCryptoInstance::aead_seal`].
+    fn aead_seal_in_place_detached(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut ctxt = self.aead_seal(alg, key, nonce, aad, buffer)?;
+        if ctxt.len() < buffer.len() {
+            return Err(Error::CryptoLibraryError("Invalid AEAD output".into()));
+        }
+        let tag = ctxt.split_off(buffer.len());
+        buffer.copy_from_slice(&ctxt);
+        Ok(tag)
+    }
+
+    /// AEAD decrypt `buffer` in place with the detached `tag`, see
+    /// [`HpkeCrypto::aead_open_in_place_detached`].
+    ///
+    /// The default implementation copies through
+    /// [`HpkeCryptoInstance::aead_open`].
+    fn aead_open_in_place_detached(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), Error> {
+        let mut ctxt = Vec::with_capacity(buffer.len().saturating_add(tag.len()));
+        ctxt.extend_from_slice(buffer);
+        ctxt.extend_from_slice(tag);
+        let ptxt = self.aead_open(alg, key, nonce, aad, &ctxt)?;
+        if ptxt.len() != buffer.len() {
+            return Err(Error::AeadInvalidCiphertext);
+        }
+        buffer.copy_from_slice(&ptxt);
+        Ok(())
+    }
+
+    /// Get key length for AEAD.
+    ///
+    /// Note that this function returns `0` for export only keys of unknown size.
+    fn aead_key_length(&self, alg: AeadAlgorithm) -> usize {
+        alg.key_length()
+    }
+
+    /// Get nonce length for AEAD.
+    ///
+    /// Note that this function returns `0` for export only nonces of unknown size.
+    fn aead_nonce_length(&self, alg: AeadAlgorithm) -> usize {
+        alg.nonce_length()
+    }
+}
+
+/// Implement [`HpkeCryptoInstance`] by forwarding to the [`HpkeCrypto`]
+/// provider `$crypto`.
+///
+/// `@operations` forwards all methods but the name, the KEM support, and the
+/// `*_with_key` methods, for instances that use their own private keys.
+macro_rules! forward_to_hpke_crypto {
+    ($crypto:ident) => {
+        fn name(&self) -> String {
+            <$crypto as HpkeCrypto>::name()
+        }
+
+        fn supports_kem(&self, alg: types::KemAlgorithm) -> Result<(), Error> {
+            <$crypto as HpkeCrypto>::supports_kem(alg)
+        }
+
+        fn dh_with_key(
+            &self,
+            alg: KemAlgorithm,
+            pk: &[u8],
+            sk: PrivateKeyRef<'_>,
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::dh_with_key(alg, pk, sk)
+        }
+
+        fn secret_to_public_with_key(
+            &self,
+            alg: KemAlgorithm,
+            sk: PrivateKeyRef<'_>,
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::secret_to_public_with_key(alg, sk)
+        }
+
+        fn kem_decaps_with_key(
+            &self,
+            alg: KemAlgorithm,
+            ct: &[u8],
+            sk_r: PrivateKeyRef<'_>,
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::kem_decaps_with_key(alg, ct, sk_r)
+        }
+
+        $crate::forward_to_hpke_crypto!(@operations $crypto);
+    };
+    (@operations $crypto:ident) => {
+        type HpkePrng = <$crypto as HpkeCrypto>::HpkePrng;
+
+        fn supports_kdf(&self, alg: types::KdfAlgorithm) -> Result<(), Error> {
+            <$crypto as HpkeCrypto>::supports_kdf(alg)
+        }
+
+        fn supports_aead(&self, alg: types::AeadAlgorithm) -> Result<(), Error> {
+            <$crypto as HpkeCrypto>::supports_aead(alg)
+        }
+
+        fn prng(&self) -> Self::HpkePrng {
+            <$crypto as HpkeCrypto>::prng()
+        }
+
+        fn kdf_digest_length(&self, alg: types::KdfAlgorithm) -> usize {
+            <$crypto as HpkeCrypto>::kdf_digest_length(alg)
+        }
+
+        fn kdf_extract(
+            &self,
+            alg: types::KdfAlgorithm,
+            salt: &[u8],
+            ikm: &[u8],
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::kdf_extract(alg, salt, ikm)
+        }
+
+        fn kdf_expand(
+            &self,
+            alg: types::KdfAlgorithm,
+            prk: &[u8],
+            info: &[u8],
+            output_size: usize,
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::kdf_expand(alg, prk, info, output_size)
+        }
+
+        fn dh(&self, alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::dh(alg, pk, sk)
+        }
+
+        fn secret_to_public(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::secret_to_public(alg, sk)
+        }
+
+        fn kem_key_gen(
+            &self,
+            alg: KemAlgorithm,
+            prng: &mut Self::HpkePrng,
+        ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+            <$crypto as HpkeCrypto>::kem_key_gen(alg, prng)
+        }
+
+        fn kem_key_gen_derand(
+            &self,
+            alg: KemAlgorithm,
+            seed: &[u8],
+        ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+            <$crypto as HpkeCrypto>::kem_key_gen_derand(alg, seed)
+        }
+
+        fn kem_encaps(
+            &self,
+            alg: KemAlgorithm,
+            pk_r: &[u8],
+            prng: &mut Self::HpkePrng,
+        ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+            <$crypto as HpkeCrypto>::kem_encaps(alg, pk_r, prng)
+        }
+
+        fn kem_encaps_derand(
+            &self,
+            alg: KemAlgorithm,
+            pk_r: &[u8],
+            randomness: &[u8],
+        ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+            <$crypto as HpkeCrypto>::kem_encaps_derand(alg, pk_r, randomness)
+        }
+
+        fn kem_decaps(&self, alg: KemAlgorithm, ct: &[u8], sk_r: &[u8]) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::kem_decaps(alg, ct, sk_r)
+        }
+
+        fn dh_validate_sk(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::dh_validate_sk(alg, sk)
+        }
+
+        fn dh_validate_pk(&self, alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+            <$crypto as HpkeCrypto>::dh_validate_pk(alg, pk)
+        }
+
+        fn dh_encode_pk(
+            &self,
+            alg: KemAlgorithm,
+            pk: &[u8],
+            compressed: bool,
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::dh_encode_pk(alg, pk, compressed)
+        }
+
+        fn aead_seal(
+            &self,
+            alg: AeadAlgorithm,
+            key: &[u8],
+            nonce: &[u8],
+            aad: &[u8],
+            msg: &[u8],
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::aead_seal(alg, key, nonce, aad, msg)
+        }
+
+        fn aead_open(
+            &self,
+            alg: AeadAlgorithm,
+            key: &[u8],
+            nonce: &[u8],
+            aad: &[u8],
+            msg: &[u8],
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::aead_open(alg, key, nonce, aad, msg)
+        }
+
+        fn aead_seal_in_place_detached(
+            &self,
+            alg: AeadAlgorithm,
+            key: &[u8],
+            nonce: &[u8],
+            aad: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<Vec<u8>, Error> {
+            <$crypto as HpkeCrypto>::aead_seal_in_place_detached(alg, key, nonce, aad, buffer)
+        }
+
+        fn aead_open_in_place_detached(
+            &self,
+            alg: AeadAlgorithm,
+            key: &[u8],
+            nonce: &[u8],
+            aad: &[u8],
+            buffer: &mut [u8],
+            tag: &[u8],
+        ) -> Result<(), Error> {
+            <$crypto as HpkeCrypto>::aead_open_in_place_detached(alg, key, nonce, aad, buffer, tag)
+        }
+
+        fn aead_key_length(&self, alg: AeadAlgorithm) -> usize {
+            <$crypto as HpkeCrypto>::aead_key_length(alg)
+        }
+
+        fn aead_nonce_length(&self, alg: AeadAlgorithm) -> usize {
+            <$crypto as HpkeCrypto>::aead_nonce_length(alg)
+        }
+    };
+}
+pub(crate) use forward_to_hpke_crypto;
+
+impl<Crypto: HpkeCrypto> HpkeCryptoInstance for Crypto {
+    forward_to_hpke_crypto!(Crypto);
+}
+
+/// The [`HpkeCrypto`] provider `Crypto` as a value, for code that has the
+/// provider as type only.
+impl<Crypto: HpkeCrypto> HpkeCryptoInstance for PhantomData<Crypto> {
+    forward_to_hpke_crypto!(Crypto);
 }
 
 /// PRNG extension for testing that is supposed to return pre-configured bytes.
@@ -155,3 +766,36 @@ pub trait HpkeTestRng {
     /// Set the randomness state of this test PRNG.
     fn seed(&mut self, seed: &[u8]);
 }
+
+/// Check the encoding of the public key `pk` of the DH KEM `alg`.
+///
+/// This checks the length, the uncompressed SEC1 form `0x04 || x || y` for
+/// NIST curves and secp256k1, and rejects the all-zero key for X25519 and
+/// X448.
+/// It doesn't check that the point is on the curve.
+///
+/// Returns an [`Error::KemInvalidPublicKey`] for invalid encodings, and an
+/// [`Error::UnsupportedKemOperation`] for KEMs that are not DH KEMs.
+pub fn validate_pk_encoding(alg: KemAlgorithm, pk: &[u8]) -> Result<(), Error> {
+    let sec1 = match alg {
+        KemAlgorithm::DhKemP256
+        | KemAlgorithm::DhKemP384
+        | KemAlgorithm::DhKemP521
+        | KemAlgorithm::DhKemK256 => true,
+        KemAlgorithm::DhKem25519 | KemAlgorithm::DhKem448 => false,
+        _ => return Err(Error::UnsupportedKemOperation),
+    };
+    if pk.len() != alg.public_key_len() {
+        return Err(Error::KemInvalidPublicKey);
+    }
+    let valid = if sec1 {
+        pk.first() == Some(&0x04)
+    } else {
+        pk.iter().any(|&b| b != 0)
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::KemInvalidPublicKey)
+    }
+}
@@ -0,0 +1,62 @@
+//! # RNG Adapters
+//!
+//! The ecosystem is split between the `rand_core` 0.6 and 0.9 traits.
+//! This crate, and the APIs of hpke-rs that take an RNG, use `rand_core` 0.9.
+//!
+//! - [`Rand06`] adapts an RNG implementing the `rand_core` 0.6 traits, e.g.
+//!   from RustCrypto crates, to the `rand_core` 0.9 traits.
+//! - [`Rand09`] adapts an RNG implementing the `rand_core` 0.9 traits, e.g. a
+//!   provider PRNG, to the `rand_core` 0.6 traits.
+//!
+//! Both adapters wrap the RNG by value or by mutable reference.
+
+use crate::{CryptoRng, RngCore};
+
+/// An RNG implementing the `rand_core` 0.6 traits, usable as `rand_core` 0.9
+/// RNG.
+///
+/// Errors of the wrapped RNG panic, as with `rand_core_06::RngCore::fill_bytes`.
+#[derive(Debug, Clone, Default)]
+pub struct Rand06<R>(pub R);
+
+impl<R: rand_core_06::RngCore> RngCore for Rand06<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+}
+
+impl<R: rand_core_06::RngCore + rand_core_06::CryptoRng> CryptoRng for Rand06<R> {}
+
+/// An RNG implementing the `rand_core` 0.9 traits, usable as `rand_core` 0.6
+/// RNG.
+#[derive(Debug, Clone, Default)]
+pub struct Rand09<R>(pub R);
+
+impl<R: RngCore> rand_core_06::RngCore for Rand09<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core_06::Error> {
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng> rand_core_06::CryptoRng for Rand09<R> {}
@@ -5,33 +5,118 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use alloc::string::ToString;
+
 use crate::error;
 
+/// Find the value in `values` with the variant or IANA `name`, ignoring
+/// ASCII case.
+fn parse_name<T: Copy + core::fmt::Display>(
+    values: &[T],
+    iana_name: fn(&T) -> &'static str,
+    name: &str,
+) -> Option<T> {
+    values.iter().copied().find(|value| {
+        value.to_string().eq_ignore_ascii_case(name) || iana_name(value).eq_ignore_ascii_case(name)
+    })
+}
+
+/// Parse the name of an [`UnknownId`], e.g. `Unknown(0x00ff)`, ignoring
+/// ASCII case.
+fn parse_unknown_id(name: &str) -> Option<u16> {
+    const PREFIX: &str = "Unknown(0x";
+    let (prefix, rest) = name.split_at_checked(PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(PREFIX) {
+        return None;
+    }
+    u16::from_str_radix(rest.strip_suffix(')')?, 16).ok()
+}
+
+/// Deserialize an [`UnknownId`], rejecting identifiers for which `unknown`
+/// returns `None`.
+#[cfg(feature = "serde")]
+fn deserialize_unknown_id<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+    unknown: fn(u16) -> Option<UnknownId>,
+) -> Result<UnknownId, D::Error> {
+    let id = u16::deserialize(deserializer)?;
+    unknown(id).ok_or_else(|| serde::de::Error::custom("implemented algorithm identifier"))
+}
+
+/// The identifier of an algorithm that is not implemented by this crate.
+///
+/// It is only created by the `from_id` functions, e.g.
+/// [`KemAlgorithm::from_id`], such that an implemented algorithm is never
+/// represented as unknown.
+#[cfg_attr(feature = "serde", derive(Serialize), serde(transparent))]
+#[derive(PartialEq, Eq, Copy, Clone, Hash)]
+pub struct UnknownId(u16);
+
+impl UnknownId {
+    /// Get the IANA identifier.
+    pub const fn get(&self) -> u16 {
+        self.0
+    }
+}
+
+impl core::fmt::Debug for UnknownId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:#06x}", self.0)
+    }
+}
+
 /// KEM Modes
+///
+/// Identifiers that this crate doesn't implement are represented as
+/// [`KemAlgorithm::Unknown`], see [`KemAlgorithm::from_id`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(PartialEq, Copy, Clone, Debug)]
-#[repr(u16)]
+#[non_exhaustive]
 pub enum KemAlgorithm {
     /// DH KEM on P256
-    DhKemP256 = 0x0010,
+    DhKemP256,
 
     /// DH KEM on P384
-    DhKemP384 = 0x0011,
+    DhKemP384,
 
     /// DH KEM on P521
-    DhKemP521 = 0x0012,
+    DhKemP521,
 
     /// DH KEM on secp256k1
-    DhKemK256 = 0x0016,
+    DhKemK256,
 
     /// DH KEM on x25519
-    DhKem25519 = 0x0020,
+    DhKem25519,
 
     /// DH KEM on x448
-    DhKem448 = 0x0021,
+    DhKem448,
 
     /// X-WING
-    XWingDraft06 = 0x004D,
+    XWingDraft06,
+
+    /// X25519Kyber768Draft00, the legacy hybrid of DHKEM(X25519) and Kyber768
+    ///
+    /// Crypto providers implement the Kyber768 component in the KEM functions.
+    /// The DHKEM(X25519) component is computed with the DH functions.
+    X25519Kyber768Draft00,
+
+    /// ML-KEM-512
+    MlKem512,
+
+    /// ML-KEM-768
+    MlKem768,
+
+    /// ML-KEM-1024
+    MlKem1024,
+
+    /// A KEM identifier that is not implemented by this crate.
+    Unknown(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "KemAlgorithm::deserialize_unknown")
+        )]
+        UnknownId,
+    ),
 }
 
 impl core::fmt::Display for KemAlgorithm {
@@ -42,22 +127,132 @@ impl core::fmt::Display for KemAlgorithm {
 
 impl core::convert::TryFrom<u16> for KemAlgorithm {
     type Error = error::Error;
+
+    /// Returns an error for identifiers that are not implemented by this
+    /// crate.
     fn try_from(x: u16) -> Result<KemAlgorithm, Self::Error> {
-        match x {
-            0x0010 => Ok(KemAlgorithm::DhKemP256),
-            0x0011 => Ok(KemAlgorithm::DhKemP384),
-            0x0012 => Ok(KemAlgorithm::DhKemP521),
-            0x0016 => Ok(KemAlgorithm::DhKemK256),
-            0x0020 => Ok(KemAlgorithm::DhKem25519),
-            0x0021 => Ok(KemAlgorithm::DhKem448),
-            0x004D => Ok(KemAlgorithm::XWingDraft06),
-            _ => Err(Self::Error::UnknownKemAlgorithm),
+        match Self::from_id(x) {
+            KemAlgorithm::Unknown(_) => Err(Self::Error::UnknownKemAlgorithm),
+            alg => Ok(alg),
         }
     }
 }
 
+impl From<KemAlgorithm> for u16 {
+    fn from(alg: KemAlgorithm) -> u16 {
+        alg.id()
+    }
+}
+
+impl KemAlgorithm {
+    /// Get the KEM for the IANA identifier `id`.
+    ///
+    /// Unlike [`TryFrom<u16>`], identifiers that are not implemented by this
+    /// crate are returned as [`KemAlgorithm::Unknown`], e.g. to skip them when
+    /// parsing a list of supported algorithms.
+    pub const fn from_id(id: u16) -> Self {
+        match id {
+            0x0010 => KemAlgorithm::DhKemP256,
+            0x0011 => KemAlgorithm::DhKemP384,
+            0x0012 => KemAlgorithm::DhKemP521,
+            0x0016 => KemAlgorithm::DhKemK256,
+            0x0020 => KemAlgorithm::DhKem25519,
+            0x0021 => KemAlgorithm::DhKem448,
+            0x004D => KemAlgorithm::XWingDraft06,
+            0x0030 => KemAlgorithm::X25519Kyber768Draft00,
+            0x0040 => KemAlgorithm::MlKem512,
+            0x0041 => KemAlgorithm::MlKem768,
+            0x0042 => KemAlgorithm::MlKem1024,
+            _ => KemAlgorithm::Unknown(UnknownId(id)),
+        }
+    }
+
+    /// Get the IANA identifier of the KEM.
+    pub const fn id(&self) -> u16 {
+        match self {
+            KemAlgorithm::DhKemP256 => 0x0010,
+            KemAlgorithm::DhKemP384 => 0x0011,
+            KemAlgorithm::DhKemP521 => 0x0012,
+            KemAlgorithm::DhKemK256 => 0x0016,
+            KemAlgorithm::DhKem25519 => 0x0020,
+            KemAlgorithm::DhKem448 => 0x0021,
+            KemAlgorithm::XWingDraft06 => 0x004D,
+            KemAlgorithm::X25519Kyber768Draft00 => 0x0030,
+            KemAlgorithm::MlKem512 => 0x0040,
+            KemAlgorithm::MlKem768 => 0x0041,
+            KemAlgorithm::MlKem1024 => 0x0042,
+            KemAlgorithm::Unknown(id) => id.0,
+        }
+    }
+
+    /// Deserialize the identifier of an unknown KEM.
+    #[cfg(feature = "serde")]
+    fn deserialize_unknown<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<UnknownId, D::Error> {
+        deserialize_unknown_id(deserializer, |id| match Self::from_id(id) {
+            Self::Unknown(id) => Some(id),
+            _ => None,
+        })
+    }
+}
+
+impl core::str::FromStr for KemAlgorithm {
+    type Err = error::Error;
+
+    /// Parse the variant name, e.g. `DhKem25519`, or the IANA name, e.g.
+    /// `DHKEM(X25519, HKDF-SHA256)`, ignoring ASCII case.
+    /// Unknown identifiers are parsed from their [`UnknownId`] name, e.g.
+    /// `Unknown(0x00ff)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_name(&Self::ALL, Self::iana_name, s)
+            .or_else(|| parse_unknown_id(s).map(Self::from_id))
+            .ok_or(Self::Err::UnknownKemAlgorithm)
+    }
+}
+
 impl KemAlgorithm {
+    /// All KEM algorithms.
+    const ALL: [Self; 11] = [
+        KemAlgorithm::DhKemP256,
+        KemAlgorithm::DhKemP384,
+        KemAlgorithm::DhKemP521,
+        KemAlgorithm::DhKemK256,
+        KemAlgorithm::DhKem25519,
+        KemAlgorithm::DhKem448,
+        KemAlgorithm::XWingDraft06,
+        KemAlgorithm::X25519Kyber768Draft00,
+        KemAlgorithm::MlKem512,
+        KemAlgorithm::MlKem768,
+        KemAlgorithm::MlKem1024,
+    ];
+
+    /// Get the name of the KEM in the IANA HPKE registry.
+    pub const fn iana_name(&self) -> &'static str {
+        match self {
+            KemAlgorithm::DhKemP256 => "DHKEM(P-256, HKDF-SHA256)",
+            KemAlgorithm::DhKemP384 => "DHKEM(P-384, HKDF-SHA384)",
+            KemAlgorithm::DhKemP521 => "DHKEM(P-521, HKDF-SHA512)",
+            KemAlgorithm::DhKemK256 => "DHKEM(secp256k1, HKDF-SHA256)",
+            KemAlgorithm::DhKem25519 => "DHKEM(X25519, HKDF-SHA256)",
+            KemAlgorithm::DhKem448 => "DHKEM(X448, HKDF-SHA512)",
+            KemAlgorithm::XWingDraft06 => "X-Wing",
+            KemAlgorithm::X25519Kyber768Draft00 => "X25519Kyber768Draft00",
+            KemAlgorithm::MlKem512 => "ML-KEM-512",
+            KemAlgorithm::MlKem768 => "ML-KEM-768",
+            KemAlgorithm::MlKem1024 => "ML-KEM-1024",
+            KemAlgorithm::Unknown(_) => "Unknown",
+        }
+    }
+
     /// Get the length of the private key for the KEM in bytes.
+    ///
+    /// For ML-KEM this is the length of the expanded decapsulation key that
+    /// key generation returns.
+    /// Private keys that are stored as their key generation seed have the
+    /// [`KemAlgorithm::seed_len`] instead.
+    ///
+    /// Returns `0` for [`KemAlgorithm::Unknown`].
     pub const fn private_key_len(&self) -> usize {
         match self {
             KemAlgorithm::DhKemP256 => 32,
@@ -67,10 +262,113 @@ impl KemAlgorithm {
             KemAlgorithm::DhKem25519 => 32,
             KemAlgorithm::DhKem448 => 56,
             KemAlgorithm::XWingDraft06 => 32,
+            KemAlgorithm::X25519Kyber768Draft00 => 2432,
+            KemAlgorithm::MlKem512 => 1632,
+            KemAlgorithm::MlKem768 => 2400,
+            KemAlgorithm::MlKem1024 => 3168,
+            KemAlgorithm::Unknown(_) => 0,
+        }
+    }
+
+    /// Get the length of an encoded public key (`Npk`) for the KEM in bytes.
+    ///
+    /// Returns `0` for [`KemAlgorithm::Unknown`].
+    pub const fn public_key_len(&self) -> usize {
+        match self {
+            KemAlgorithm::XWingDraft06 | KemAlgorithm::X25519Kyber768Draft00 => 1216,
+            KemAlgorithm::MlKem512 => 800,
+            KemAlgorithm::MlKem768 => 1184,
+            KemAlgorithm::MlKem1024 => 1568,
+            // For DH KEMs the encapsulated key is an encoded public key.
+            _ => self.encapsulated_key_len(),
+        }
+    }
+
+    /// Get the length of a compressed SEC1 public key for the KEM in bytes.
+    ///
+    /// Returns `None` for KEMs without compressed public keys.
+    pub const fn compressed_public_key_len(&self) -> Option<usize> {
+        match self {
+            KemAlgorithm::DhKemP256 | KemAlgorithm::DhKemK256 => Some(33),
+            KemAlgorithm::DhKemP384 => Some(49),
+            KemAlgorithm::DhKemP521 => Some(67),
+            KemAlgorithm::DhKem25519
+            | KemAlgorithm::DhKem448
+            | KemAlgorithm::XWingDraft06
+            | KemAlgorithm::X25519Kyber768Draft00
+            | KemAlgorithm::MlKem512
+            | KemAlgorithm::MlKem768
+            | KemAlgorithm::MlKem1024
+            | KemAlgorithm::Unknown(_) => None,
+        }
+    }
+
+    /// Get the length of an encapsulated key (`Nenc`) for the KEM in bytes.
+    ///
+    /// Returns `0` for [`KemAlgorithm::Unknown`].
+    pub const fn encapsulated_key_len(&self) -> usize {
+        match self {
+            KemAlgorithm::DhKemP256 => 65,
+            KemAlgorithm::DhKemP384 => 97,
+            KemAlgorithm::DhKemP521 => 133,
+            KemAlgorithm::DhKemK256 => 65,
+            KemAlgorithm::DhKem25519 => 32,
+            KemAlgorithm::DhKem448 => 56,
+            KemAlgorithm::XWingDraft06 | KemAlgorithm::X25519Kyber768Draft00 => 1120,
+            KemAlgorithm::MlKem512 => 768,
+            KemAlgorithm::MlKem768 => 1088,
+            KemAlgorithm::MlKem1024 => 1568,
+            KemAlgorithm::Unknown(_) => 0,
+        }
+    }
+
+    /// Get the length of the key generation seed for the KEM in bytes.
+    ///
+    /// Private keys of KEMs with a seed can be stored as the seed and are
+    /// expanded with [`crate::HpkeCrypto::kem_key_gen_derand`] on use.
+    /// Returns `None` for KEMs whose private keys can't be stored as a seed,
+    /// and for X-Wing, whose private key always is the seed.
+    pub const fn seed_len(&self) -> Option<usize> {
+        match self {
+            KemAlgorithm::DhKemP256
+            | KemAlgorithm::DhKemP384
+            | KemAlgorithm::DhKemP521
+            | KemAlgorithm::DhKemK256
+            | KemAlgorithm::DhKem25519
+            | KemAlgorithm::DhKem448
+            | KemAlgorithm::XWingDraft06
+            | KemAlgorithm::X25519Kyber768Draft00
+            | KemAlgorithm::Unknown(_) => None,
+            KemAlgorithm::MlKem512 | KemAlgorithm::MlKem768 | KemAlgorithm::MlKem1024 => Some(64),
+        }
+    }
+
+    /// Get the length of the randomness of a KEM encapsulation in bytes, for
+    /// KEMs that encapsulate with [`crate::HpkeCrypto::kem_encaps_derand`].
+    ///
+    /// For X25519Kyber768Draft00 this is the randomness of the Kyber768
+    /// component, HPKE computes the X25519 component.
+    /// Returns `None` for DH KEMs, whose ephemeral keys are derived by HPKE.
+    pub const fn encaps_randomness_len(&self) -> Option<usize> {
+        match self {
+            KemAlgorithm::DhKemP256
+            | KemAlgorithm::DhKemP384
+            | KemAlgorithm::DhKemP521
+            | KemAlgorithm::DhKemK256
+            | KemAlgorithm::DhKem25519
+            | KemAlgorithm::DhKem448
+            | KemAlgorithm::Unknown(_) => None,
+            KemAlgorithm::XWingDraft06 => Some(64),
+            KemAlgorithm::MlKem512
+            | KemAlgorithm::MlKem768
+            | KemAlgorithm::MlKem1024
+            | KemAlgorithm::X25519Kyber768Draft00 => Some(32),
         }
     }
 
     /// Get the length of the shared secret for the KEM in bytes.
+    ///
+    /// Returns `0` for [`KemAlgorithm::Unknown`].
     pub const fn shared_secret_len(&self) -> usize {
         match self {
             KemAlgorithm::DhKemP256 => 32,
@@ -80,26 +378,51 @@ impl KemAlgorithm {
             KemAlgorithm::DhKem25519 => 32,
             KemAlgorithm::DhKem448 => 64,
             KemAlgorithm::XWingDraft06 => 32,
+            KemAlgorithm::X25519Kyber768Draft00 => 64,
+            KemAlgorithm::MlKem512 | KemAlgorithm::MlKem768 | KemAlgorithm::MlKem1024 => 32,
+            KemAlgorithm::Unknown(_) => 0,
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-#[repr(u16)]
+#[non_exhaustive]
 /// AEAD types
+///
+/// Identifiers that this crate doesn't implement are represented as
+/// [`AeadAlgorithm::Unknown`], see [`AeadAlgorithm::from_id`].
 pub enum AeadAlgorithm {
     /// AES GCM 128
-    Aes128Gcm = 0x0001,
+    Aes128Gcm,
 
     /// AES GCM 256
-    Aes256Gcm = 0x0002,
+    Aes256Gcm,
 
     /// ChaCha20 Poly1305
-    ChaCha20Poly1305 = 0x0003,
+    ChaCha20Poly1305,
+
+    /// AEGIS-128L with 128 bit tags (private-use identifier `0xFF01`)
+    Aegis128L,
+
+    /// AEGIS-256 with 128 bit tags (private-use identifier `0xFF02`)
+    Aegis256,
+
+    /// XChaCha20 Poly1305 with 24 byte nonces (private-use identifier
+    /// `0xFF03`)
+    XChaCha20Poly1305,
 
     /// HPKE Export-only
-    HpkeExport = 0xFFFF,
+    HpkeExport,
+
+    /// An AEAD identifier that is not implemented by this crate.
+    Unknown(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "AeadAlgorithm::deserialize_unknown")
+        )]
+        UnknownId,
+    ),
 }
 
 impl core::fmt::Display for AeadAlgorithm {
@@ -110,48 +433,146 @@ impl core::fmt::Display for AeadAlgorithm {
 
 impl core::convert::TryFrom<u16> for AeadAlgorithm {
     type Error = error::Error;
+
+    /// Returns an error for identifiers that are not implemented by this
+    /// crate.
     fn try_from(x: u16) -> Result<AeadAlgorithm, Self::Error> {
-        match x {
-            0x0001 => Ok(AeadAlgorithm::Aes128Gcm),
-            0x0002 => Ok(AeadAlgorithm::Aes256Gcm),
-            0x0003 => Ok(AeadAlgorithm::ChaCha20Poly1305),
-            0xFFFF => Ok(AeadAlgorithm::HpkeExport),
-            _ => Err(Self::Error::UnknownAeadAlgorithm),
+        match Self::from_id(x) {
+            AeadAlgorithm::Unknown(_) => Err(Self::Error::UnknownAeadAlgorithm),
+            alg => Ok(alg),
         }
     }
 }
 
+impl From<AeadAlgorithm> for u16 {
+    fn from(alg: AeadAlgorithm) -> u16 {
+        alg.id()
+    }
+}
+
 impl AeadAlgorithm {
+    /// Get the AEAD for the IANA identifier `id`.
+    ///
+    /// Unlike [`TryFrom<u16>`], identifiers that are not implemented by this
+    /// crate are returned as [`AeadAlgorithm::Unknown`], e.g. to skip them when
+    /// parsing a list of supported algorithms.
+    pub const fn from_id(id: u16) -> Self {
+        match id {
+            0x0001 => AeadAlgorithm::Aes128Gcm,
+            0x0002 => AeadAlgorithm::Aes256Gcm,
+            0x0003 => AeadAlgorithm::ChaCha20Poly1305,
+            0xFF01 => AeadAlgorithm::Aegis128L,
+            0xFF02 => AeadAlgorithm::Aegis256,
+            0xFF03 => AeadAlgorithm::XChaCha20Poly1305,
+            0xFFFF => AeadAlgorithm::HpkeExport,
+            _ => AeadAlgorithm::Unknown(UnknownId(id)),
+        }
+    }
+
+    /// Get the IANA identifier of the AEAD.
+    pub const fn id(&self) -> u16 {
+        match self {
+            AeadAlgorithm::Aes128Gcm => 0x0001,
+            AeadAlgorithm::Aes256Gcm => 0x0002,
+            AeadAlgorithm::ChaCha20Poly1305 => 0x0003,
+            AeadAlgorithm::Aegis128L => 0xFF01,
+            AeadAlgorithm::Aegis256 => 0xFF02,
+            AeadAlgorithm::XChaCha20Poly1305 => 0xFF03,
+            AeadAlgorithm::HpkeExport => 0xFFFF,
+            AeadAlgorithm::Unknown(id) => id.0,
+        }
+    }
+
+    /// Deserialize the identifier of an unknown AEAD.
+    #[cfg(feature = "serde")]
+    fn deserialize_unknown<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<UnknownId, D::Error> {
+        deserialize_unknown_id(deserializer, |id| match Self::from_id(id) {
+            Self::Unknown(id) => Some(id),
+            _ => None,
+        })
+    }
+}
+
+impl core::str::FromStr for AeadAlgorithm {
+    type Err = error::Error;
+
+    /// Parse the variant name, e.g. `Aes128Gcm`, or the IANA name, e.g.
+    /// `AES-128-GCM`, ignoring ASCII case.
+    /// Unknown identifiers are parsed from their [`UnknownId`] name, e.g.
+    /// `Unknown(0x00ff)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_name(&Self::ALL, Self::iana_name, s)
+            .or_else(|| parse_unknown_id(s).map(Self::from_id))
+            .ok_or(Self::Err::UnknownAeadAlgorithm)
+    }
+}
+
+impl AeadAlgorithm {
+    /// All AEAD algorithms.
+    const ALL: [Self; 7] = [
+        AeadAlgorithm::Aes128Gcm,
+        AeadAlgorithm::Aes256Gcm,
+        AeadAlgorithm::ChaCha20Poly1305,
+        AeadAlgorithm::Aegis128L,
+        AeadAlgorithm::Aegis256,
+        AeadAlgorithm::XChaCha20Poly1305,
+        AeadAlgorithm::HpkeExport,
+    ];
+
+    /// Get the name of the AEAD in the IANA HPKE registry.
+    ///
+    /// AEADs with private-use identifiers use the name of the algorithm.
+    pub const fn iana_name(&self) -> &'static str {
+        match self {
+            AeadAlgorithm::Aes128Gcm => "AES-128-GCM",
+            AeadAlgorithm::Aes256Gcm => "AES-256-GCM",
+            AeadAlgorithm::ChaCha20Poly1305 => "ChaCha20Poly1305",
+            AeadAlgorithm::Aegis128L => "AEGIS-128L",
+            AeadAlgorithm::Aegis256 => "AEGIS-256",
+            AeadAlgorithm::XChaCha20Poly1305 => "XChaCha20Poly1305",
+            AeadAlgorithm::HpkeExport => "Export-only",
+            AeadAlgorithm::Unknown(_) => "Unknown",
+        }
+    }
+
     /// Get the tag size of the [`AeadAlgorithm`] in bytes.
     ///
     /// Note that the function returns `0` for unknown lengths such as the
-    /// [`AeadAlgorithm::HpkeExport`] type.
+    /// [`AeadAlgorithm::HpkeExport`] and [`AeadAlgorithm::Unknown`] types.
     pub const fn tag_length(&self) -> usize {
         match self {
             AeadAlgorithm::Aes128Gcm => 16,
             AeadAlgorithm::Aes256Gcm => 16,
             AeadAlgorithm::ChaCha20Poly1305 => 16,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 16,
+            AeadAlgorithm::XChaCha20Poly1305 => 16,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
         }
     }
 
     /// Get the key size of the [`AeadAlgorithm`] in bytes.
     ///
     /// Note that the function returns `0` for unknown lengths such as the
-    /// [`AeadAlgorithm::HpkeExport`] type.
+    /// [`AeadAlgorithm::HpkeExport`] and [`AeadAlgorithm::Unknown`] types.
     pub const fn key_length(&self) -> usize {
         match self {
             AeadAlgorithm::Aes128Gcm => 16,
             AeadAlgorithm::Aes256Gcm => 32,
             AeadAlgorithm::ChaCha20Poly1305 => 32,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 32,
+            AeadAlgorithm::XChaCha20Poly1305 => 32,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
         }
     }
 
     /// Get the nonce size of the [`AeadAlgorithm`] in bytes.
     ///
     /// Note that the function returns `0` for unknown lengths such as the
-    /// [`AeadAlgorithm::HpkeExport`] type.
+    /// [`AeadAlgorithm::HpkeExport`] and [`AeadAlgorithm::Unknown`] types.
     ///
     /// Further note that while the AEAD mechanisms generally allow for different
     /// nonce lengths, this HPKE implementation expects the most common nonce size.
@@ -160,27 +581,110 @@ impl AeadAlgorithm {
             AeadAlgorithm::Aes128Gcm => 12,
             AeadAlgorithm::Aes256Gcm => 12,
             AeadAlgorithm::ChaCha20Poly1305 => 12,
-            AeadAlgorithm::HpkeExport => 0,
+            AeadAlgorithm::Aegis128L => 16,
+            AeadAlgorithm::Aegis256 => 32,
+            AeadAlgorithm::XChaCha20Poly1305 => 24,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
+        }
+    }
+
+    /// Get the maximum plain text size of a single [`AeadAlgorithm`]
+    /// invocation in bytes.
+    ///
+    /// This is `2^36 - 32` for AES-GCM (NIST SP 800-38D), `2^38 - 64` for
+    /// ChaCha20Poly1305 (RFC 8439) and XChaCha20Poly1305, and `2^61 - 1` for
+    /// AEGIS.
+    /// Note that the function returns `0` for the [`AeadAlgorithm::HpkeExport`]
+    /// and [`AeadAlgorithm::Unknown`] types.
+    pub const fn max_plaintext_length(&self) -> u64 {
+        match self {
+            AeadAlgorithm::Aes128Gcm | AeadAlgorithm::Aes256Gcm => (1 << 36) - 32,
+            AeadAlgorithm::ChaCha20Poly1305 | AeadAlgorithm::XChaCha20Poly1305 => (1 << 38) - 64,
+            AeadAlgorithm::Aegis128L | AeadAlgorithm::Aegis256 => (1 << 61) - 1,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
+        }
+    }
+
+    /// Get the maximum associated data size of a single [`AeadAlgorithm`]
+    /// invocation in bytes.
+    ///
+    /// This is `2^61 - 1` for AES-GCM (NIST SP 800-38D) and AEGIS, and
+    /// `2^64 - 1` for ChaCha20Poly1305 (RFC 8439) and XChaCha20Poly1305.
+    /// Note that the function returns `0` for the [`AeadAlgorithm::HpkeExport`]
+    /// and [`AeadAlgorithm::Unknown`] types.
+    pub const fn max_aad_length(&self) -> u64 {
+        match self {
+            AeadAlgorithm::Aes128Gcm
+            | AeadAlgorithm::Aes256Gcm
+            | AeadAlgorithm::Aegis128L
+            | AeadAlgorithm::Aegis256 => (1 << 61) - 1,
+            AeadAlgorithm::ChaCha20Poly1305 | AeadAlgorithm::XChaCha20Poly1305 => u64::MAX,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
+        }
+    }
+
+    /// Get the maximum number of messages that should be protected with a
+    /// single key.
+    ///
+    /// This is the confidentiality limit of `2^24.5` messages for AES-GCM
+    /// (RFC 8446, Section 5.5).
+    /// ChaCha20Poly1305, XChaCha20Poly1305, and AEGIS have no practical limit
+    /// and return `2^64 - 1`.
+    /// Note that the function returns `0` for the [`AeadAlgorithm::HpkeExport`]
+    /// and [`AeadAlgorithm::Unknown`] types.
+    pub const fn max_messages(&self) -> u64 {
+        match self {
+            AeadAlgorithm::Aes128Gcm | AeadAlgorithm::Aes256Gcm => 23_726_566,
+            AeadAlgorithm::ChaCha20Poly1305
+            | AeadAlgorithm::XChaCha20Poly1305
+            | AeadAlgorithm::Aegis128L
+            | AeadAlgorithm::Aegis256 => u64::MAX,
+            AeadAlgorithm::HpkeExport | AeadAlgorithm::Unknown(_) => 0,
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-#[repr(u16)]
+#[non_exhaustive]
 /// KDF types
 /// Value are taken from the HPKE RFC (not published yet)
 /// TODO: update when HPKE has been published and values have been registered with
 ///       IANA.
+///
+/// Identifiers that this crate doesn't implement are represented as
+/// [`KdfAlgorithm::Unknown`], see [`KdfAlgorithm::from_id`].
 pub enum KdfAlgorithm {
     /// HKDF SHA 256
-    HkdfSha256 = 0x0001,
+    HkdfSha256,
 
     /// HKDF SHA 384
-    HkdfSha384 = 0x0002,
+    HkdfSha384,
 
     /// HKDF SHA 512
-    HkdfSha512 = 0x0003,
+    HkdfSha512,
+
+    /// KMAC256 (NIST SP 800-185) with 64 byte extract outputs (private-use
+    /// identifier `0xFF01`)
+    ///
+    /// `Extract(salt, ikm)` is `KMAC256(salt, ikm, 512, "HPKE Extract")` and
+    /// `Expand(prk, info, L)` is `KMAC256(prk, info, 8 * L, "HPKE Expand")`.
+    ///
+    /// **Note** that the private-use identifier is not interoperable.
+    /// Other HPKE implementations may use `0xFF01` for a different KDF, and
+    /// it is the identifier of [`AeadAlgorithm::Aegis128L`] among the AEADs.
+    /// Only use it between peers that both use hpke-rs, see
+    /// [`KdfAlgorithm::from_id`].
+    Kmac256,
+
+    /// A KDF identifier that is not implemented by this crate.
+    Unknown(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "KdfAlgorithm::deserialize_unknown")
+        )]
+        UnknownId,
+    ),
 }
 
 impl core::fmt::Display for KdfAlgorithm {
@@ -191,12 +695,112 @@ impl core::fmt::Display for KdfAlgorithm {
 
 impl core::convert::TryFrom<u16> for KdfAlgorithm {
     type Error = error::Error;
+
+    /// Returns an error for identifiers that are not implemented by this
+    /// crate.
     fn try_from(x: u16) -> Result<KdfAlgorithm, Self::Error> {
-        match x {
-            0x0001 => Ok(KdfAlgorithm::HkdfSha256),
-            0x0002 => Ok(KdfAlgorithm::HkdfSha384),
-            0x0003 => Ok(KdfAlgorithm::HkdfSha512),
-            _ => Err(Self::Error::UnknownKdfAlgorithm),
+        match Self::from_id(x) {
+            KdfAlgorithm::Unknown(_) => Err(Self::Error::UnknownKdfAlgorithm),
+            alg => Ok(alg),
+        }
+    }
+}
+
+impl From<KdfAlgorithm> for u16 {
+    fn from(alg: KdfAlgorithm) -> u16 {
+        alg.id()
+    }
+}
+
+impl KdfAlgorithm {
+    /// Get the KDF for the IANA identifier `id`.
+    ///
+    /// Unlike [`TryFrom<u16>`], identifiers that are not implemented by this
+    /// crate are returned as [`KdfAlgorithm::Unknown`], e.g. to skip them when
+    /// parsing a list of supported algorithms.
+    ///
+    /// The private-use identifier `0xFF01` is mapped to
+    /// [`KdfAlgorithm::Kmac256`], which only hpke-rs peers agree on.
+    /// Skip it when parsing the algorithms of other peers.
+    pub const fn from_id(id: u16) -> Self {
+        match id {
+            0x0001 => KdfAlgorithm::HkdfSha256,
+            0x0002 => KdfAlgorithm::HkdfSha384,
+            0x0003 => KdfAlgorithm::HkdfSha512,
+            0xFF01 => KdfAlgorithm::Kmac256,
+            _ => KdfAlgorithm::Unknown(UnknownId(id)),
+        }
+    }
+
+    /// Get the IANA identifier of the KDF.
+    pub const fn id(&self) -> u16 {
+        match self {
+            KdfAlgorithm::HkdfSha256 => 0x0001,
+            KdfAlgorithm::HkdfSha384 => 0x0002,
+            KdfAlgorithm::HkdfSha512 => 0x0003,
+            KdfAlgorithm::Kmac256 => 0xFF01,
+            KdfAlgorithm::Unknown(id) => id.0,
+        }
+    }
+
+    /// Deserialize the identifier of an unknown KDF.
+    #[cfg(feature = "serde")]
+    fn deserialize_unknown<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<UnknownId, D::Error> {
+        deserialize_unknown_id(deserializer, |id| match Self::from_id(id) {
+            Self::Unknown(id) => Some(id),
+            _ => None,
+        })
+    }
+
+    /// Get the length of the KDF output (`Nh`) in bytes.
+    ///
+    /// Returns `0` for [`KdfAlgorithm::Unknown`].
+    pub const fn digest_len(&self) -> usize {
+        match self {
+            KdfAlgorithm::HkdfSha256 => 32,
+            KdfAlgorithm::HkdfSha384 => 48,
+            KdfAlgorithm::HkdfSha512 => 64,
+            KdfAlgorithm::Kmac256 => 64,
+            KdfAlgorithm::Unknown(_) => 0,
+        }
+    }
+}
+
+impl core::str::FromStr for KdfAlgorithm {
+    type Err = error::Error;
+
+    /// Parse the variant name, e.g. `HkdfSha256`, or the IANA name, e.g.
+    /// `HKDF-SHA256`, ignoring ASCII case.
+    /// Unknown identifiers are parsed from their [`UnknownId`] name, e.g.
+    /// `Unknown(0x00ff)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_name(&Self::ALL, Self::iana_name, s)
+            .or_else(|| parse_unknown_id(s).map(Self::from_id))
+            .ok_or(Self::Err::UnknownKdfAlgorithm)
+    }
+}
+
+impl KdfAlgorithm {
+    /// All KDF algorithms.
+    const ALL: [Self; 4] = [
+        KdfAlgorithm::HkdfSha256,
+        KdfAlgorithm::HkdfSha384,
+        KdfAlgorithm::HkdfSha512,
+        KdfAlgorithm::Kmac256,
+    ];
+
+    /// Get the name of the KDF in the IANA HPKE registry.
+    ///
+    /// KDFs with private-use identifiers use the name of the algorithm.
+    pub const fn iana_name(&self) -> &'static str {
+        match self {
+            KdfAlgorithm::HkdfSha256 => "HKDF-SHA256",
+            KdfAlgorithm::HkdfSha384 => "HKDF-SHA384",
+            KdfAlgorithm::HkdfSha512 => "HKDF-SHA512",
+            KdfAlgorithm::Kmac256 => "KMAC256",
+            KdfAlgorithm::Unknown(_) => "Unknown",
         }
     }
 }
@@ -211,6 +815,36 @@ impl From<KemAlgorithm> for KdfAlgorithm {
             KemAlgorithm::DhKem25519 => KdfAlgorithm::HkdfSha256,
             KemAlgorithm::DhKem448 => KdfAlgorithm::HkdfSha512,
             KemAlgorithm::XWingDraft06 => KdfAlgorithm::HkdfSha512,
+            KemAlgorithm::X25519Kyber768Draft00 => KdfAlgorithm::HkdfSha256,
+            KemAlgorithm::MlKem512 => KdfAlgorithm::HkdfSha256,
+            KemAlgorithm::MlKem768 => KdfAlgorithm::HkdfSha256,
+            KemAlgorithm::MlKem1024 => KdfAlgorithm::HkdfSha384,
+            // The reserved KDF identifier, an unknown KEM has no KDF.
+            KemAlgorithm::Unknown(_) => KdfAlgorithm::from_id(0x0000),
+        }
+    }
+}
+
+/// A reference to a private key for the KEM operations of a crypto provider.
+///
+/// Providers that keep private keys in secure hardware, e.g. an HSM or the
+/// platform keystore, identify them with opaque handles rather than the key
+/// bytes, which never leave the hardware.
+#[derive(Clone, Copy)]
+pub enum PrivateKeyRef<'a> {
+    /// The encoded private key.
+    Bytes(&'a [u8]),
+
+    /// An opaque handle of a private key that is held by the crypto provider.
+    /// The encoding of the handle is defined by the provider.
+    Handle(&'a [u8]),
+}
+
+impl core::fmt::Debug for PrivateKeyRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bytes(_) => f.write_str("Bytes(***)"),
+            Self::Handle(handle) => f.debug_tuple("Handle").field(handle).finish(),
         }
     }
 }
@@ -0,0 +1,303 @@
+//! # Asynchronous HPKE
+//!
+//! HPKE in the base mode with an [`HpkeCryptoAsync`] provider.
+
+use hpke_rs_crypto::{
+    error::Error,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+};
+
+use crate::HpkeCryptoAsync;
+
+const HPKE_VERSION: &[u8] = b"HPKE-v1";
+
+/// The identifier of the base mode.
+const MODE_BASE: u8 = 0x00;
+
+/// An HPKE configuration with an asynchronous crypto provider.
+///
+/// Only the base mode and DH KEMs are supported.
+#[derive(Debug)]
+pub struct Hpke<Crypto: HpkeCryptoAsync> {
+    crypto: Crypto,
+    kem: KemAlgorithm,
+    kdf: KdfAlgorithm,
+    aead: AeadAlgorithm,
+}
+
+/// The HPKE context of a sender or receiver.
+pub struct Context<'a, Crypto: HpkeCryptoAsync> {
+    hpke: &'a Hpke<Crypto>,
+    key: Vec<u8>,
+    base_nonce: Vec<u8>,
+    exporter_secret: Vec<u8>,
+    sequence_number: u64,
+}
+
+impl<Crypto: HpkeCryptoAsync> Hpke<Crypto> {
+    /// Create an HPKE configuration for the ciphersuite.
+    ///
+    /// Returns an error if the `crypto` provider doesn't support it.
+    pub fn new(
+        crypto: Crypto,
+        kem: KemAlgorithm,
+        kdf: KdfAlgorithm,
+        aead: AeadAlgorithm,
+    ) -> Result<Self, Error> {
+        Crypto::supports_kem(kem)?;
+        Crypto::supports_kdf(KdfAlgorithm::from(kem))?;
+        Crypto::supports_kdf(kdf)?;
+        Crypto::supports_aead(aead)?;
+        Ok(Self {
+            crypto,
+            kem,
+            kdf,
+            aead,
+        })
+    }
+
+    /// Generate a key pair (public key, private key) for the KEM.
+    pub async fn generate_key_pair(&self) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        self.crypto.kem_key_gen(self.kem).await
+    }
+
+    /// Set up a sender context for the receiver's public key `pk_r`.
+    ///
+    /// Returns the encapsulated key and the context.
+    pub async fn setup_sender(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+    ) -> Result<(Vec<u8>, Context<'_, Crypto>), Error> {
+        let (pk_e, sk_e) = self.crypto.kem_key_gen(self.kem).await?;
+        let dh = self.crypto.dh(self.kem, pk_r, &sk_e).await?;
+        let shared_secret = self.extract_and_expand(&dh, &pk_e, pk_r).await?;
+        let context = self.key_schedule(&shared_secret, info).await?;
+        Ok((pk_e, context))
+    }
+
+    /// Set up a receiver context for the encapsulated key `enc` with the
+    /// receiver's private key `sk_r`.
+    pub async fn setup_receiver(
+        &self,
+        enc: &[u8],
+        sk_r: &[u8],
+        info: &[u8],
+    ) -> Result<Context<'_, Crypto>, Error> {
+        let dh = self.crypto.dh(self.kem, enc, sk_r).await?;
+        let pk_r = self.crypto.secret_to_public(self.kem, sk_r).await?;
+        let shared_secret = self.extract_and_expand(&dh, enc, &pk_r).await?;
+        self.key_schedule(&shared_secret, info).await
+    }
+
+    /// Encrypt the `plain_txt` to the receiver's public key `pk_r` in a
+    /// single shot.
+    ///
+    /// Returns the encapsulated key and the ciphertext.
+    pub async fn seal(
+        &self,
+        pk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        plain_txt: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let (enc, mut context) = self.setup_sender(pk_r, info).await?;
+        let ct = context.seal(aad, plain_txt).await?;
+        Ok((enc, ct))
+    }
+
+    /// Decrypt the `cipher_txt` for the encapsulated key `enc` with the
+    /// receiver's private key `sk_r` in a single shot.
+    pub async fn open(
+        &self,
+        enc: &[u8],
+        sk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        cipher_txt: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut context = self.setup_receiver(enc, sk_r, info).await?;
+        context.open(aad, cipher_txt).await
+    }
+
+    fn suite_id(&self) -> Vec<u8> {
+        [
+            &b"HPKE"[..],
+            &self.kem.id().to_be_bytes(),
+            &self.kdf.id().to_be_bytes(),
+            &self.aead.id().to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    async fn labeled_extract(
+        &self,
+        alg: KdfAlgorithm,
+        salt: &[u8],
+        suite_id: &[u8],
+        label: &str,
+        ikm: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let labeled_ikm = [HPKE_VERSION, suite_id, label.as_bytes(), ikm].concat();
+        self.crypto.kdf_extract(alg, salt, &labeled_ikm).await
+    }
+
+    async fn labeled_expand(
+        &self,
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        suite_id: &[u8],
+        label: &str,
+        info: &[u8],
+        len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let len_bytes = u16::try_from(len)
+            .map_err(|_| Error::HpkeInvalidOutputLength)?
+            .to_be_bytes();
+        let labeled_info = [&len_bytes, HPKE_VERSION, suite_id, label.as_bytes(), info].concat();
+        self.crypto.kdf_expand(alg, prk, &labeled_info, len).await
+    }
+
+    /// The `ExtractAndExpand` of the DH KEM.
+    async fn extract_and_expand(
+        &self,
+        dh: &[u8],
+        enc: &[u8],
+        pk_r: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let kdf = KdfAlgorithm::from(self.kem);
+        let suite_id = [&b"KEM"[..], &self.kem.id().to_be_bytes()].concat();
+        let kem_context = [enc, pk_r].concat();
+        let eae_prk = self
+            .labeled_extract(kdf, &[], &suite_id, "eae_prk", dh)
+            .await?;
+        self.labeled_expand(
+            kdf,
+            &eae_prk,
+            &suite_id,
+            "shared_secret",
+            &kem_context,
+            self.kem.shared_secret_len(),
+        )
+        .await
+    }
+
+    async fn key_schedule(
+        &self,
+        shared_secret: &[u8],
+        info: &[u8],
+    ) -> Result<Context<'_, Crypto>, Error> {
+        let suite_id = self.suite_id();
+        let psk_id_hash = self
+            .labeled_extract(self.kdf, &[], &suite_id, "psk_id_hash", &[])
+            .await?;
+        let info_hash = self
+            .labeled_extract(self.kdf, &[], &suite_id, "info_hash", info)
+            .await?;
+        let key_schedule_context = [&[MODE_BASE][..], &psk_id_hash, &info_hash].concat();
+        let secret = self
+            .labeled_extract(self.kdf, shared_secret, &suite_id, "secret", &[])
+            .await?;
+
+        // The export-only AEAD has neither key nor nonce.
+        let (key, base_nonce) = if self.aead == AeadAlgorithm::HpkeExport {
+            (Vec::new(), Vec::new())
+        } else {
+            let key = self
+                .labeled_expand(
+                    self.kdf,
+                    &secret,
+                    &suite_id,
+                    "key",
+                    &key_schedule_context,
+                    self.aead.key_length(),
+                )
+                .await?;
+            let base_nonce = self
+                .labeled_expand(
+                    self.kdf,
+                    &secret,
+                    &suite_id,
+                    "base_nonce",
+                    &key_schedule_context,
+                    self.aead.nonce_length(),
+                )
+                .await?;
+            (key, base_nonce)
+        };
+        let exporter_secret = self
+            .labeled_expand(
+                self.kdf,
+                &secret,
+                &suite_id,
+                "exp",
+                &key_schedule_context,
+                self.kdf.digest_len(),
+            )
+            .await?;
+
+        Ok(Context {
+            hpke: self,
+            key,
+            base_nonce,
+            exporter_secret,
+            sequence_number: 0,
+        })
+    }
+}
+
+impl<Crypto: HpkeCryptoAsync> Context<'_, Crypto> {
+    /// Encrypt the `plain_txt` with the `aad`.
+    pub async fn seal(&mut self, aad: &[u8], plain_txt: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = self.compute_nonce()?;
+        let ct = self
+            .hpke
+            .crypto
+            .aead_seal(self.hpke.aead, &self.key, &nonce, aad, plain_txt)
+            .await?;
+        self.sequence_number += 1;
+        Ok(ct)
+    }
+
+    /// Decrypt the `cipher_txt` with the `aad`.
+    pub async fn open(&mut self, aad: &[u8], cipher_txt: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = self.compute_nonce()?;
+        let pt = self
+            .hpke
+            .crypto
+            .aead_open(self.hpke.aead, &self.key, &nonce, aad, cipher_txt)
+            .await?;
+        self.sequence_number += 1;
+        Ok(pt)
+    }
+
+    /// Export a secret of `length` bytes for the `exporter_context`.
+    pub async fn export(&self, exporter_context: &[u8], length: usize) -> Result<Vec<u8>, Error> {
+        self.hpke
+            .labeled_expand(
+                self.hpke.kdf,
+                &self.exporter_secret,
+                &self.hpke.suite_id(),
+                "sec",
+                exporter_context,
+                length,
+            )
+            .await
+    }
+
+    /// The nonce for the current sequence number.
+    ///
+    /// Returns an [`Error::AeadInvalidNonce`] when the message limit of the
+    /// AEAD is reached.
+    fn compute_nonce(&self) -> Result<Vec<u8>, Error> {
+        if self.sequence_number >= self.hpke.aead.max_messages() {
+            return Err(Error::AeadInvalidNonce);
+        }
+        let mut nonce = self.base_nonce.clone();
+        let seq = self.sequence_number.to_be_bytes();
+        for (n, s) in nonce.iter_mut().rev().zip(seq.iter().rev()) {
+            *n ^= s;
+        }
+        Ok(nonce)
+    }
+}
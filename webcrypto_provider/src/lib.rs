@@ -0,0 +1,395 @@
+#![doc = include_str!("../Readme.md")]
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use hpke_rs_crypto::{
+    error::Error,
+    types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm},
+};
+use js_sys::{Array, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Crypto, CryptoKey, CryptoKeyPair, SubtleCrypto};
+
+mod hpke;
+
+pub use hpke::{Context, Hpke};
+
+/// The asynchronous counterpart of the [`HpkeCrypto`](hpke_rs_crypto::HpkeCrypto)
+/// trait for crypto libraries that only have asynchronous APIs.
+///
+/// It has the functions needed for the DH KEMs.
+/// Keys are generated with the random generator of the provider.
+#[allow(async_fn_in_trait)]
+pub trait HpkeCryptoAsync {
+    /// The name of the implementation.
+    fn name() -> String;
+
+    /// Returns an error if the KDF algorithm is not supported by this crypto provider.
+    fn supports_kdf(alg: KdfAlgorithm) -> Result<(), Error>;
+
+    /// Returns an error if the KEM algorithm is not supported by this crypto provider.
+    fn supports_kem(alg: KemAlgorithm) -> Result<(), Error>;
+
+    /// Returns an error if the AEAD algorithm is not supported by this crypto provider.
+    fn supports_aead(alg: AeadAlgorithm) -> Result<(), Error>;
+
+    /// KDF Extract
+    async fn kdf_extract(
+        &self,
+        alg: KdfAlgorithm,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// KDF Expand
+    async fn kdf_expand(
+        &self,
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Diffie-Hellman
+    ///
+    /// See [`HpkeCrypto::dh`](hpke_rs_crypto::HpkeCrypto::dh) for the
+    /// requirements.
+    async fn dh(&self, alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Diffie-Hellman with the base (generate public key for secret key `sk`).
+    async fn secret_to_public(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// KEM key pair generation (encapsulation key, decapsulation key).
+    async fn kem_key_gen(&self, alg: KemAlgorithm) -> Result<(Vec<u8>, Vec<u8>), Error>;
+
+    /// AEAD encrypt.
+    async fn aead_seal(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// AEAD decrypt.
+    async fn aead_open(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// The PKCS#8 encoding of a P-256 private key without the scalar and the
+/// optional public key, which browsers derive on import.
+const P256_PKCS8_PREFIX: [u8; 35] = [
+    0x30, 0x41, 0x02, 0x01, 0x00, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+    0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x04, 0x27, 0x30, 0x25, 0x02, 0x01,
+    0x01, 0x04, 0x20,
+];
+
+/// The WebCrypto HPKE Provider
+#[derive(Debug, Clone)]
+pub struct HpkeWebCrypto {
+    subtle: SubtleCrypto,
+}
+
+impl HpkeWebCrypto {
+    /// Create a provider with the `crypto.subtle` object of the global scope,
+    /// i.e. of the window or the worker.
+    pub fn new() -> Result<Self, Error> {
+        let crypto = Reflect::get(&js_sys::global(), &"crypto".into())
+            .ok()
+            .and_then(|crypto| crypto.dyn_into::<Crypto>().ok())
+            .ok_or_else(|| Error::CryptoLibraryError("WebCrypto is not available".into()))?;
+        Ok(Self::from_subtle(crypto.subtle()))
+    }
+
+    /// Create a provider with the `subtle` crypto object.
+    pub fn from_subtle(subtle: SubtleCrypto) -> Self {
+        Self { subtle }
+    }
+
+    async fn import_key(
+        &self,
+        format: &str,
+        data: &Object,
+        alg: &Object,
+        extractable: bool,
+        usages: &[&str],
+    ) -> Result<CryptoKey, JsValue> {
+        let usages: Array = usages.iter().copied().map(JsValue::from_str).collect();
+        let key =
+            resolve(
+                self.subtle
+                    .import_key_with_object(format, data, alg, extractable, &usages),
+            )
+            .await?;
+        key.dyn_into()
+    }
+
+    /// Import the P-256 private key `sk` for ECDH.
+    async fn import_sk(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<CryptoKey, Error> {
+        let params = ecdh_params(alg)?;
+        if sk.len() != alg.private_key_len() {
+            return Err(Error::KemInvalidSecretKey);
+        }
+        let pkcs8 = [&P256_PKCS8_PREFIX[..], sk].concat();
+        // Browsers reject scalars that are zero or not less than the order.
+        self.import_key(
+            "pkcs8",
+            &Uint8Array::from(&pkcs8[..]),
+            &params,
+            true,
+            &["deriveBits"],
+        )
+        .await
+        .map_err(|_| Error::KemInvalidSecretKey)
+    }
+
+    /// Import the uncompressed P-256 public key `pk` for ECDH.
+    async fn import_pk(&self, alg: KemAlgorithm, pk: &[u8]) -> Result<CryptoKey, Error> {
+        let params = ecdh_params(alg)?;
+        // Browsers reject points that are not on the curve.
+        self.import_key("raw", &Uint8Array::from(pk), &params, true, &[])
+            .await
+            .map_err(|_| Error::KemInvalidPublicKey)
+    }
+
+    /// Export the P-256 private `key` as (public key, private key).
+    async fn export_key_pair(&self, key: &CryptoKey) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let jwk = resolve(self.subtle.export_key("jwk", key))
+            .await
+            .map_err(js_error)?;
+        let x = jwk_coordinate(&jwk, "x")?;
+        let y = jwk_coordinate(&jwk, "y")?;
+        let d = jwk_coordinate(&jwk, "d")?;
+        Ok(([&[0x04], &x[..], &y[..]].concat(), d))
+    }
+
+    /// HMAC with the `hash` of the `data` with the `key`.
+    async fn hmac(&self, hash: &str, key: &[u8], data: &[u8]) -> Result<Vec<u8>, Error> {
+        let params = object(&[("name", "HMAC".into()), ("hash", hash.into())])?;
+        let key = self
+            .import_key("raw", &Uint8Array::from(key), &params, false, &["sign"])
+            .await
+            .map_err(js_error)?;
+        let tag = resolve(self.subtle.sign_with_str_and_u8_array("HMAC", &key, data))
+            .await
+            .map_err(js_error)?;
+        Ok(Uint8Array::new(&tag).to_vec())
+    }
+
+    /// Import the AEAD `key` of the `alg` for the `usage` and get the
+    /// parameters for the `nonce` and `aad`.
+    async fn aead_key(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        usage: &str,
+    ) -> Result<(CryptoKey, Object), Error> {
+        let name = aead_name(alg)?;
+        if key.len() != alg.key_length() {
+            return Err(Error::CryptoLibraryError("Invalid AEAD key length".into()));
+        }
+        if nonce.len() != alg.nonce_length() {
+            return Err(Error::AeadInvalidNonce);
+        }
+        let key = self
+            .import_key(
+                "raw",
+                &Uint8Array::from(key),
+                &object(&[("name", name.into())])?,
+                false,
+                &[usage],
+            )
+            .await
+            .map_err(js_error)?;
+        let params = object(&[
+            ("name", name.into()),
+            ("iv", Uint8Array::from(nonce).into()),
+            ("additionalData", Uint8Array::from(aad).into()),
+        ])?;
+        Ok((key, params))
+    }
+}
+
+impl HpkeCryptoAsync for HpkeWebCrypto {
+    fn name() -> String {
+        "WebCrypto".into()
+    }
+
+    async fn kdf_extract(
+        &self,
+        alg: KdfAlgorithm,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let hash = hash_name(alg)?;
+        // An empty salt is a string of zeros of the hash length (RFC 5869),
+        // which yields the same HMAC but is a valid WebCrypto key.
+        let zeros = vec![0u8; alg.digest_len()];
+        let salt = if salt.is_empty() { &zeros } else { salt };
+        self.hmac(hash, salt, ikm).await
+    }
+
+    async fn kdf_expand(
+        &self,
+        alg: KdfAlgorithm,
+        prk: &[u8],
+        info: &[u8],
+        output_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let hash = hash_name(alg)?;
+        let digest_len = alg.digest_len();
+        if output_size > 255 * digest_len {
+            return Err(Error::HpkeInvalidOutputLength);
+        }
+
+        // The HKDF of WebCrypto only does extract and expand in one step.
+        // T(i) = HMAC(PRK, T(i - 1) | info | i)
+        let mut okm = Vec::with_capacity(output_size + digest_len);
+        let mut t = Vec::new();
+        let mut counter = 1u8;
+        while okm.len() < output_size {
+            let mut input = t;
+            input.extend_from_slice(info);
+            input.push(counter);
+            t = self.hmac(hash, prk, &input).await?;
+            okm.extend_from_slice(&t);
+            counter = counter.wrapping_add(1);
+        }
+        okm.truncate(output_size);
+        Ok(okm)
+    }
+
+    async fn dh(&self, alg: KemAlgorithm, pk: &[u8], sk: &[u8]) -> Result<Vec<u8>, Error> {
+        let sk = self.import_sk(alg, sk).await?;
+        let pk = self.import_pk(alg, pk).await?;
+        let params = object(&[("name", "ECDH".into()), ("public", pk.into())])?;
+        let bits = 8 * alg.shared_secret_len() as u32;
+        let dh = resolve(self.subtle.derive_bits_with_object(&params, &sk, bits))
+            .await
+            .map_err(js_error)?;
+        Ok(Uint8Array::new(&dh).to_vec())
+    }
+
+    async fn secret_to_public(&self, alg: KemAlgorithm, sk: &[u8]) -> Result<Vec<u8>, Error> {
+        let sk = self.import_sk(alg, sk).await?;
+        self.export_key_pair(&sk).await.map(|(pk, _)| pk)
+    }
+
+    async fn kem_key_gen(&self, alg: KemAlgorithm) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let params = ecdh_params(alg)?;
+        let usages: Array = [JsValue::from_str("deriveBits")].into_iter().collect();
+        let key_pair: CryptoKeyPair =
+            resolve(self.subtle.generate_key_with_object(&params, true, &usages))
+                .await
+                .and_then(JsCast::dyn_into)
+                .map_err(js_error)?;
+        self.export_key_pair(&key_pair.get_private_key()).await
+    }
+
+    async fn aead_seal(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let (key, params) = self.aead_key(alg, key, nonce, aad, "encrypt").await?;
+        let ct = resolve(
+            self.subtle
+                .encrypt_with_object_and_u8_array(&params, &key, msg),
+        )
+        .await
+        .map_err(js_error)?;
+        Ok(Uint8Array::new(&ct).to_vec())
+    }
+
+    async fn aead_open(
+        &self,
+        alg: AeadAlgorithm,
+        key: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let (key, params) = self.aead_key(alg, key, nonce, aad, "decrypt").await?;
+        if msg.len() < alg.tag_length() {
+            return Err(Error::AeadInvalidCiphertext);
+        }
+        let pt = resolve(
+            self.subtle
+                .decrypt_with_object_and_u8_array(&params, &key, msg),
+        )
+        .await
+        .map_err(|_| Error::AeadOpenError)?;
+        Ok(Uint8Array::new(&pt).to_vec())
+    }
+
+    hpke_rs_provider_macros::supports! {
+        kdf: [HkdfSha256, HkdfSha384, HkdfSha512],
+        kem: [DhKemP256],
+        aead: [Aes128Gcm, Aes256Gcm, HpkeExport],
+    }
+}
+
+/// Wait for the `promise` of a SubtleCrypto call.
+async fn resolve(promise: Result<Promise, JsValue>) -> Result<JsValue, JsValue> {
+    JsFuture::from(promise?).await
+}
+
+/// Build a JavaScript object with the `properties`.
+fn object(properties: &[(&str, JsValue)]) -> Result<Object, Error> {
+    let object = Object::new();
+    for (key, value) in properties {
+        Reflect::set(&object, &JsValue::from_str(key), value).map_err(js_error)?;
+    }
+    Ok(object)
+}
+
+/// The parameters for importing and generating ECDH keys of the `alg`.
+fn ecdh_params(alg: KemAlgorithm) -> Result<Object, Error> {
+    object(&[
+        ("name", "ECDH".into()),
+        ("namedCurve", curve_name(alg)?.into()),
+    ])
+}
+
+/// Decode the base64url encoded P-256 coordinate or scalar `name` of the `jwk`.
+fn jwk_coordinate(jwk: &JsValue, name: &str) -> Result<Vec<u8>, Error> {
+    Reflect::get(jwk, &JsValue::from_str(name))
+        .ok()
+        .and_then(|value| value.as_string())
+        .and_then(|value| Base64UrlUnpadded::decode_vec(&value).ok())
+        .filter(|value| value.len() == KemAlgorithm::DhKemP256.private_key_len())
+        .ok_or_else(|| Error::CryptoLibraryError(format!("Invalid JWK parameter {name}")))
+}
+
+hpke_rs_provider_macros::algorithm_map! {
+    fn hash_name(KdfAlgorithm) -> &'static str, else UnknownKdfAlgorithm {
+        HkdfSha256 => "SHA-256",
+        HkdfSha384 => "SHA-384",
+        HkdfSha512 => "SHA-512",
+    }
+
+    fn curve_name(KemAlgorithm) -> &'static str, else UnknownKemAlgorithm {
+        DhKemP256 => "P-256",
+    }
+
+    fn aead_name(AeadAlgorithm) -> &'static str, else UnknownAeadAlgorithm {
+        Aes128Gcm => "AES-GCM",
+        Aes256Gcm => "AES-GCM",
+    }
+}
+
+hpke_rs_provider_macros::error_map! {
+    fn js_error(JsValue) => CryptoLibraryError("WebCrypto error");
+}
@@ -0,0 +1,102 @@
+//! RFC 9180 known-answer tests in the browser.
+//!
+//! ```text
+//! wasm-pack test --headless --firefox
+//! ```
+//!
+//! `Hpke` only implements the base mode and can't inject the ephemeral key of
+//! the sender, such that the ciphertexts of the base mode test vectors are
+//! opened and their exports are checked.
+#![cfg(target_arch = "wasm32")]
+
+use hpke_rs_crypto::types::{AeadAlgorithm, KdfAlgorithm, KemAlgorithm};
+use hpke_rs_webcrypto::{Hpke, HpkeWebCrypto};
+use serde::Deserialize;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// The fields of an RFC 9180 test vector that are used for the receiver.
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct HpkeTestVector {
+    mode: u8,
+    kem_id: u16,
+    kdf_id: u16,
+    aead_id: u16,
+    info: String,
+    skRm: String,
+    enc: String,
+    encryptions: Vec<CiphertextKat>,
+    exports: Vec<ExportKat>,
+}
+
+#[derive(Deserialize)]
+struct CiphertextKat {
+    aad: String,
+    ct: String,
+    pt: String,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct ExportKat {
+    exporter_context: String,
+    L: usize,
+    exported_value: String,
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[wasm_bindgen_test]
+async fn kat_webcrypto() {
+    let tests: Vec<HpkeTestVector> =
+        serde_json::from_str(include_str!("../../tests/test_vectors.json")).unwrap();
+    let mut count = 0;
+    for test in tests.iter().filter(|test| test.mode == 0) {
+        let aead = AeadAlgorithm::from_id(test.aead_id);
+        let Ok(hpke) = Hpke::new(
+            HpkeWebCrypto::new().unwrap(),
+            KemAlgorithm::from_id(test.kem_id),
+            KdfAlgorithm::from_id(test.kdf_id),
+            aead,
+        ) else {
+            continue;
+        };
+
+        let mut context = hpke
+            .setup_receiver(
+                &hex_to_bytes(&test.enc),
+                &hex_to_bytes(&test.skRm),
+                &hex_to_bytes(&test.info),
+            )
+            .await
+            .unwrap();
+        if aead != AeadAlgorithm::HpkeExport {
+            for encryption in test.encryptions.iter() {
+                let pt = context
+                    .open(
+                        &hex_to_bytes(&encryption.aad),
+                        &hex_to_bytes(&encryption.ct),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(pt, hex_to_bytes(&encryption.pt));
+            }
+        }
+        for export in test.exports.iter() {
+            let exported = context
+                .export(&hex_to_bytes(&export.exporter_context), export.L)
+                .await
+                .unwrap();
+            assert_eq!(exported, hex_to_bytes(&export.exported_value));
+        }
+        count += 1;
+    }
+    assert!(count > 0);
+}